@@ -0,0 +1,78 @@
+// Đo hiệu năng các truy vấn đồ thị trên một mạng lưới tổng hợp (mặc định 100 000 doanh nghiệp)
+// Chạy bằng: cargo bench --bench graph [-- <số doanh nghiệp>]
+
+// Khai báo các thư viện cần thiết
+use std::env;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use rim::peer::graph::Graph;
+
+// Số kết nối trung bình của mỗi doanh nghiệp trong mạng lưới tổng hợp
+const DEGREE: usize = 10;
+
+// Số lần lặp của mỗi truy vấn
+const ROUNDS: usize = 100;
+
+// Hàm main tạo mạng lưới và in thời gian của từng thao tác
+fn main() {
+    let nodes: usize = env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(100_000);
+
+    // Mạng lưới rỗng thì không có cạnh hay cặp nào để đo
+    if nodes == 0 {
+        eprintln!("graph bench needs at least one business");
+        return;
+    }
+
+    // Sinh các cạnh ngẫu nhiên có thể lặp lại bằng xorshift
+    let mut seed = 0x9e37_79b9_7f4a_7c15_u64;
+    let mut next = move |bound: usize| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (seed % bound as u64) as usize
+    };
+    let name = |i: usize| format!("b{:06}", i);
+    let edges: Vec<(String, String)> = (0..nodes * DEGREE / 2)
+        .map(|i| (name(i % nodes), name(next(nodes))))
+        .collect();
+
+    // Xây dựng đồ thị
+    let start = Instant::now();
+    let graph = Graph::build(&edges);
+    report("build", start.elapsed(), 1);
+    println!("{} nodes, {} edges", graph.len(), graph.size());
+
+    // Các truy vấn giữa những cặp doanh nghiệp ngẫu nhiên
+    let pairs: Vec<(String, String)> = (0..ROUNDS)
+        .map(|_| (name(next(nodes)), name(next(nodes))))
+        .collect();
+    let start = Instant::now();
+    for (a, b) in &pairs {
+        black_box(graph.mutual(a, b));
+    }
+    report("mutual", start.elapsed(), ROUNDS);
+    let start = Instant::now();
+    for (a, b) in &pairs {
+        black_box(graph.path(a, b));
+    }
+    report("path", start.elapsed(), ROUNDS);
+    let start = Instant::now();
+    for (a, _) in &pairs {
+        black_box(graph.within(a, 2));
+    }
+    report("within 2", start.elapsed(), ROUNDS);
+    let start = Instant::now();
+    for (a, _) in &pairs {
+        black_box(graph.suggest(a, 10));
+    }
+    report("suggest", start.elapsed(), ROUNDS);
+}
+
+// Hàm report in thời gian trung bình của một thao tác
+fn report(name: &str, elapsed: Duration, rounds: usize) {
+    println!("{:<10} {:>12.3?} / op", name, elapsed / rounds as u32);
+}
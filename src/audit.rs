@@ -0,0 +1,352 @@
+// Khai báo các thư viện cần thiết
+use std::fmt;
+use std::io;
+use std::sync::Mutex;
+
+use crate::auth::Principal;
+use crate::clock;
+use crate::crypto::{hex, sha256};
+use crate::social::message::{escape, unescape};
+use crate::storage::Storage;
+
+// Khai báo tên nhật ký kiểm toán trong đối tượng lưu trữ
+const LOG: &str = "audit.log";
+
+// Khai báo giá trị băm đứng trước mục đầu tiên của chuỗi
+const GENESIS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+// Khai báo một struct để đại diện cho một mục trong nhật ký kiểm toán
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub seq: u64,         // Số thứ tự (bắt đầu từ 1)
+    pub time: u64,        // Thời điểm ghi
+    pub actor: String,    // Người thực hiện dạng `tên@doanh nghiệp`
+    pub action: String,   // Hành động (ví dụ: set_access, login, transfer)
+    pub resource: String, // Đối tượng bị tác động
+    pub before: String,   // Giá trị trước khi thay đổi (rỗng nếu không có)
+    pub after: String,    // Giá trị sau khi thay đổi (rỗng nếu không có)
+    pub prev: String,     // Giá trị băm của mục trước
+    pub hash: String,     // Giá trị băm của mục này
+}
+
+// Khai báo các phương thức cho struct Entry
+impl Entry {
+    // Phương thức body trả về phần nội dung được băm của mục (mọi trường trừ hai giá trị băm)
+    fn body(&self) -> String {
+        [
+            self.seq.to_string(),
+            self.time.to_string(),
+            escape(&self.actor),
+            escape(&self.action),
+            escape(&self.resource),
+            escape(&self.before),
+            escape(&self.after),
+        ]
+        .join("\t")
+    }
+
+    // Phương thức digest tính HMAC-SHA256 của mục bằng khoá kiểm toán, từ giá trị băm của mục trước và nội dung
+    fn digest(&self, key: &[u8; 32]) -> String {
+        hex(&sha256::hmac(
+            key,
+            format!("{}\n{}", self.prev, self.body()).as_bytes(),
+        ))
+    }
+
+    // Phương thức render để ghi mục thành một dòng của nhật ký
+    fn render(&self) -> String {
+        format!("{}\t{}\t{}\n", self.body(), self.prev, self.hash)
+    }
+
+    // Phương thức parse để đọc lại một dòng do render tạo ra
+    fn parse(line: &str) -> Option<Entry> {
+        let fields: Vec<String> = line.split('\t').map(unescape).collect();
+        if fields.len() != 9 {
+            return None;
+        }
+        Some(Entry {
+            seq: fields[0].parse().ok()?,
+            time: fields[1].parse().ok()?,
+            actor: fields[2].clone(),
+            action: fields[3].clone(),
+            resource: fields[4].clone(),
+            before: fields[5].clone(),
+            after: fields[6].clone(),
+            prev: fields[7].clone(),
+            hash: fields[8].clone(),
+        })
+    }
+}
+
+// Khai báo một struct để chứa đầu chuỗi (được bảo vệ bởi khóa)
+#[derive(Debug)]
+struct Head {
+    seq: u64,     // Số thứ tự của mục cuối cùng
+    hash: String, // Giá trị băm của mục cuối cùng
+}
+
+// Khai báo một struct để ghi nhật ký kiểm toán chỉ ghi thêm, nối các mục bằng chuỗi HMAC-SHA256
+//
+// Mỗi mục chứa giá trị băm của mục trước nên sửa, xoá hay chèn một mục ở giữa đều làm đứt chuỗi.
+// Giá trị băm được tính bằng khoá kiểm toán do người vận hành giữ ngoài đối tượng lưu trữ, nên người
+// sửa được tập tin nhật ký cũng không tính lại được cả chuỗi. Cắt bớt các mục cuối thì chỉ phát hiện
+// được khi so với đầu chuỗi mà dịch vụ đang giữ.
+pub struct Audit {
+    key: [u8; 32],           // Khoá kiểm toán dùng để tính HMAC
+    storage: Mutex<Storage>, // Nơi lưu nhật ký kiểm toán
+    head: Mutex<Head>,       // Đầu chuỗi hiện tại
+}
+
+// Khai báo cách hiển thị Audit khi gỡ lỗi (không in khoá kiểm toán)
+impl fmt::Debug for Audit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Audit")
+            .field("storage", &self.storage)
+            .field("head", &self.head)
+            .finish_non_exhaustive()
+    }
+}
+
+// Khai báo các phương thức cho struct Audit
+impl Audit {
+    // Phương thức open để mở nhật ký kiểm toán bằng khoá kiểm toán; nhật ký có chuỗi bị đứt thì không
+    // được ghi tiếp
+    pub fn open(mut storage: Storage, key: &[u8; 32]) -> io::Result<Audit> {
+        storage.serve()?;
+        let entries = if storage.list()?.iter().any(|name| name == LOG) {
+            verify(&storage.view(LOG)?, key)?
+        } else {
+            Vec::new()
+        };
+        let head = match entries.last() {
+            Some(last) => Head {
+                seq: last.seq,
+                hash: last.hash.clone(),
+            },
+            None => Head {
+                seq: 0,
+                hash: GENESIS.to_string(),
+            },
+        };
+        Ok(Audit {
+            key: *key,
+            storage: Mutex::new(storage),
+            head: Mutex::new(head),
+        })
+    }
+
+    // Phương thức record để ghi một mục mới vào cuối chuỗi và trả về số thứ tự của nó
+    pub fn record(
+        &self,
+        actor: &str,
+        action: &str,
+        resource: &str,
+        before: &str,
+        after: &str,
+    ) -> io::Result<u64> {
+        let mut head = self.head.lock().unwrap();
+        let mut entry = Entry {
+            seq: head.seq + 1,
+            time: clock::now(),
+            actor: actor.to_string(),
+            action: action.to_string(),
+            resource: resource.to_string(),
+            before: before.to_string(),
+            after: after.to_string(),
+            prev: head.hash.clone(),
+            hash: String::new(),
+        };
+        entry.hash = entry.digest(&self.key);
+        self.storage
+            .lock()
+            .unwrap()
+            .append(LOG, entry.render().as_bytes())?;
+        head.seq = entry.seq;
+        head.hash = entry.hash;
+        Ok(entry.seq)
+    }
+
+    // Phương thức entries trả về các mục đã ghi sau khi kiểm tra chuỗi
+    pub fn entries(&self) -> io::Result<Vec<Entry>> {
+        let head = self.head.lock().unwrap();
+        let storage = self.storage.lock().unwrap();
+        if head.seq == 0 {
+            return Ok(Vec::new());
+        }
+        let entries = verify(&storage.view(LOG)?, &self.key)?;
+
+        // Chuỗi hợp lệ nhưng ngắn hơn đầu chuỗi đang giữ nghĩa là các mục cuối đã bị cắt
+        if entries.last().map(|last| (last.seq, &last.hash)) != Some((head.seq, &head.hash)) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Audit log was truncated before entry {}", head.seq),
+            ));
+        }
+        Ok(entries)
+    }
+
+    // Phương thức verify để kiểm tra toàn bộ nhật ký và trả về số mục
+    pub fn verify(&self) -> io::Result<u64> {
+        Ok(self.entries()?.len() as u64)
+    }
+}
+
+// Hàm actor tạo tên người thực hiện từ người dùng đã xác thực
+pub fn actor(principal: &Principal) -> String {
+    format!("{}@{}", principal.name(), principal.business())
+}
+
+// Hàm verify kiểm tra một nhật ký kiểm toán (ví dụ: bản sao đã xuất ra) bằng khoá kiểm toán và trả về các
+// mục nếu chuỗi nguyên vẹn
+pub fn verify(log: &str, key: &[u8; 32]) -> io::Result<Vec<Entry>> {
+    let broken = |seq: usize, reason: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Audit chain broken at entry {}: {}", seq, reason),
+        )
+    };
+    let mut entries: Vec<Entry> = Vec::new();
+    for (index, line) in log.lines().enumerate() {
+        let seq = index + 1;
+        let entry = Entry::parse(line).ok_or_else(|| broken(seq, "unreadable entry"))?;
+        if entry.seq != seq as u64 {
+            return Err(broken(seq, "entry out of sequence"));
+        }
+        let prev = entries.last().map_or(GENESIS, |last| &last.hash);
+        if entry.prev != prev {
+            return Err(broken(seq, "previous hash does not match"));
+        }
+        if entry.hash != entry.digest(key) {
+            return Err(broken(seq, "entry hash does not match its content"));
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+pub(crate) mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::storage::tests::{create_temp_storage, reopen};
+    use crate::storage::Access;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    // Khai báo khoá kiểm toán dùng trong unit test
+    const KEY: [u8; 32] = [5; 32];
+
+    // Khai báo một hàm để tạo nhật ký kiểm toán tạm thời dùng trong các unit test của các module khác
+    pub(crate) fn audit() -> (Arc<Audit>, PathBuf) {
+        let storage = create_temp_storage("audit", Access::Private).unwrap();
+        let path = storage.path().to_path_buf();
+        (Arc::new(Audit::open(storage, &KEY).unwrap()), path)
+    }
+
+    // Khai báo một hàm để kiểm tra việc ghi, mở lại và kiểm tra chuỗi
+    #[test]
+    fn test_chain() {
+        let (audit, path) = audit();
+        assert_eq!(audit.verify().unwrap(), 0);
+        audit
+            .record("an@alpha", "set_access", "storage x", "Private", "Public")
+            .unwrap();
+        audit
+            .record("an@alpha", "delete", "storage x/a\tb.txt", "4 bytes", "")
+            .unwrap();
+        let entries = audit.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].prev, GENESIS);
+        assert_eq!(entries[1].prev, entries[0].hash);
+        assert_eq!(entries[1].resource, "storage x/a\tb.txt");
+
+        // Mở lại thì tiếp tục chuỗi cũ
+        drop(audit);
+        let audit = Audit::open(reopen(&path).unwrap(), &KEY).unwrap();
+        assert_eq!(
+            audit
+                .record("binh@alpha", "login", "user 2", "", "")
+                .unwrap(),
+            3
+        );
+        assert_eq!(audit.verify().unwrap(), 3);
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra việc phát hiện chuỗi bị sửa, xoá, chèn hoặc cắt bớt
+    #[test]
+    fn test_tamper() {
+        let (audit, path) = audit();
+        for i in 0..4 {
+            audit
+                .record(
+                    "an@alpha",
+                    "transfer",
+                    "account 1 -> account 2",
+                    &i.to_string(),
+                    "",
+                )
+                .unwrap();
+        }
+        let log = fs::read_to_string(path.join(LOG)).unwrap();
+        assert_eq!(verify(&log, &KEY).unwrap().len(), 4);
+        let lines: Vec<&str> = log.lines().collect();
+
+        // Sửa nội dung một mục
+        let edited = log.replacen("\t2\t\t", "\t9\t\t", 1);
+        let error = verify(&edited, &KEY).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Audit chain broken at entry 3: entry hash does not match its content"
+        );
+
+        // Sửa nội dung và tính lại giá trị băm của mục đó thì mục sau bị đứt
+        let mut entry = Entry::parse(lines[1]).unwrap();
+        entry.after = "forged".to_string();
+        entry.hash = entry.digest(&KEY);
+        let forged = [lines[0], entry.render().trim_end(), lines[2], lines[3]].join("\n");
+        let error = verify(&forged, &KEY).unwrap_err();
+        assert!(error.to_string().contains("entry 3: previous hash"));
+
+        // Không có khoá kiểm toán thì không tính lại được chuỗi, kể cả khi tính lại mọi mục
+        let mut prev = GENESIS.to_string();
+        let mut rebuilt = String::new();
+        for line in &lines {
+            let mut entry = Entry::parse(line).unwrap();
+            entry.after = "forged".to_string();
+            entry.prev = prev;
+            entry.hash = entry.digest(&[6; 32]);
+            prev = entry.hash.clone();
+            rebuilt += &entry.render();
+        }
+        assert!(verify(&rebuilt, &[6; 32]).is_ok());
+        let error = verify(&rebuilt, &KEY).unwrap_err();
+        assert!(error.to_string().contains("entry 1: entry hash"));
+        assert!(verify(&log, &[6; 32]).is_err());
+
+        // Xoá hoặc đổi chỗ một mục
+        let removed = [lines[0], lines[2], lines[3]].join("\n");
+        assert!(verify(&removed, &KEY)
+            .unwrap_err()
+            .to_string()
+            .contains("entry 2"));
+        let swapped = [lines[1], lines[0], lines[2], lines[3]].join("\n");
+        assert!(verify(&swapped, &KEY).is_err());
+
+        // Cắt bớt các mục cuối: chuỗi còn lại hợp lệ nhưng không khớp đầu chuỗi đang giữ
+        let truncated = format!("{}\n", [lines[0], lines[1]].join("\n"));
+        fs::write(path.join(LOG), &truncated).unwrap();
+        assert!(verify(&truncated, &KEY).is_ok());
+        let error = audit.verify().unwrap_err();
+        assert!(error.to_string().contains("truncated"));
+
+        // Nhật ký bị đứt thì không mở được để ghi tiếp
+        fs::write(path.join(LOG), edited).unwrap();
+        assert!(Audit::open(reopen(&path).unwrap(), &KEY).is_err());
+
+        fs::remove_dir_all(path).unwrap();
+    }
+}
@@ -0,0 +1,969 @@
+// Khai báo các module con của xác thực
+pub mod role;
+pub mod session;
+
+// Khai báo các thư viện cần thiết
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, RwLock};
+
+use crate::audit::{self, Audit};
+use crate::clock::DAY;
+use crate::crypto::{self, hex, sha256, unhex};
+use crate::event::{Bus, Event};
+use crate::social::message::{escape, unescape};
+use crate::storage::Storage;
+use role::{Action, Role};
+use session::Sessions;
+
+// Khai báo số vòng lặp PBKDF2 mặc định khi băm mật khẩu
+pub const ITERATIONS: u32 = 100_000;
+
+// Khai báo số lần đăng nhập sai liên tiếp trước khi khoá tài khoản
+pub const ATTEMPTS: u32 = 5;
+
+// Khai báo thời gian khoá tài khoản (tính bằng giây)
+pub const LOCKOUT: u64 = 15 * 60;
+
+// Khai báo thời gian sống tối đa của một phiên đăng nhập (tính bằng giây)
+pub const SESSION: u64 = DAY / 2;
+
+// Khai báo độ dài tối thiểu của mật khẩu
+pub const MINIMUM: usize = 8;
+
+// Khai báo tên nhật ký người dùng trong đối tượng lưu trữ
+const LOG: &str = "users.log";
+
+// Khai báo một struct để đại diện cho người dùng đã xác thực (chỉ module auth tạo được)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    user: u64,        // Mã người dùng
+    name: String,     // Tên đăng nhập
+    business: String, // Doanh nghiệp của người dùng
+    role: Role,       // Vai trò của người dùng trong doanh nghiệp
+    expires: u64,     // Thời điểm phiên đăng nhập hết hạn
+}
+
+// Khai báo các phương thức cho struct Principal
+impl Principal {
+    // Phương thức new để tạo người dùng đã xác thực (chỉ dùng trong crate)
+    pub(crate) fn new(
+        user: u64,
+        name: &str,
+        business: &str,
+        role: Role,
+        expires: u64,
+    ) -> Principal {
+        Principal {
+            user,
+            name: name.to_string(),
+            business: business.to_string(),
+            role,
+            expires,
+        }
+    }
+
+    // Phương thức service để tạo tài khoản dịch vụ không hết hạn của doanh nghiệp, dùng khi các dịch vụ
+    // (phiên, kiểm toán, tin nhắn, khoá, liên kết, danh bạ) tự đăng nhập vào đối tượng lưu trữ của mình
+    pub(crate) fn service(business: &str) -> Principal {
+        Principal::new(0, "service", business, Role::Owner, u64::MAX)
+    }
+
+    // Phương thức until trả về bản sao của người dùng đã xác thực với thời điểm hết hạn khác
+    fn until(&self, expires: u64) -> Principal {
+        Principal {
+            expires,
+            ..self.clone()
+        }
+    }
+
+    // Phương thức user trả về mã người dùng
+    pub fn user(&self) -> u64 {
+        self.user
+    }
+
+    // Phương thức name trả về tên đăng nhập
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Phương thức business trả về doanh nghiệp của người dùng
+    pub fn business(&self) -> &str {
+        &self.business
+    }
+
+    // Phương thức role trả về vai trò của người dùng
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    // Phương thức expires trả về thời điểm hết hạn
+    pub fn expires(&self) -> u64 {
+        self.expires
+    }
+
+    // Phương thức active kiểm tra phiên đăng nhập còn hiệu lực tại thời điểm `now`
+    pub fn active(&self, now: u64) -> bool {
+        now < self.expires
+    }
+}
+
+// Khai báo một struct để đại diện cho mật khẩu đã băm (có muối và số vòng lặp)
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Hash {
+    salt: [u8; 16],            // Muối ngẫu nhiên
+    iterations: u32,           // Số vòng lặp PBKDF2
+    digest: [u8; sha256::LEN], // Kết quả dẫn xuất
+}
+
+// Khai báo các phương thức cho struct Hash
+impl Hash {
+    // Phương thức new để băm mật khẩu với muối mới
+    pub(crate) fn new(password: &str, iterations: u32) -> io::Result<Hash> {
+        let mut salt = [0; 16];
+        crypto::random(&mut salt)?;
+        let mut digest = [0; sha256::LEN];
+        sha256::pbkdf2(password.as_bytes(), &salt, iterations, &mut digest);
+        Ok(Hash {
+            salt,
+            iterations,
+            digest,
+        })
+    }
+
+    // Phương thức verify để kiểm tra mật khẩu
+    pub(crate) fn verify(&self, password: &str) -> bool {
+        let mut digest = [0; sha256::LEN];
+        sha256::pbkdf2(
+            password.as_bytes(),
+            &self.salt,
+            self.iterations,
+            &mut digest,
+        );
+        crypto::equal(&digest, &self.digest)
+    }
+}
+
+// Khai báo cách hiển thị mật khẩu đã băm thành chuỗi `<số vòng lặp>:<muối>:<kết quả>` để lưu lại
+impl fmt::Display for Hash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}",
+            self.iterations,
+            hex(&self.salt),
+            hex(&self.digest)
+        )
+    }
+}
+
+// Khai báo cách đọc mật khẩu đã băm từ chuỗi do Display tạo ra
+impl FromStr for Hash {
+    type Err = io::Error;
+
+    fn from_str(text: &str) -> io::Result<Hash> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Invalid password hash");
+        let mut parts = text.split(':');
+        let (Some(iterations), Some(salt), Some(digest), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        Ok(Hash {
+            iterations: iterations.parse().map_err(|_| invalid())?,
+            salt: unhex(salt)?.try_into().map_err(|_| invalid())?,
+            digest: unhex(digest)?.try_into().map_err(|_| invalid())?,
+        })
+    }
+}
+
+// Khai báo một struct để đại diện cho một người dùng thuộc một doanh nghiệp
+#[derive(Debug, Clone, PartialEq)]
+pub struct User {
+    pub id: u64,             // Mã người dùng
+    pub business: String,    // Doanh nghiệp
+    pub name: String,        // Tên đăng nhập (duy nhất trong doanh nghiệp)
+    pub role: Role,          // Vai trò trong doanh nghiệp
+    hash: Hash,              // Mật khẩu đã băm
+    pub failures: u32,       // Số lần đăng nhập sai liên tiếp
+    pub locked: Option<u64>, // Thời điểm hết khoá (nếu đang bị khoá)
+}
+
+// Khai báo các phương thức cho struct User
+impl User {
+    // Phương thức render để ghi trạng thái hiện tại của người dùng thành một dòng nhật ký
+    fn render(&self) -> String {
+        let locked = self
+            .locked
+            .map(|until| until.to_string())
+            .unwrap_or_default();
+        format!(
+            "user\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+            self.id,
+            escape(&self.business),
+            escape(&self.name),
+            self.role,
+            self.hash,
+            self.failures,
+            locked
+        )
+    }
+
+    // Phương thức parse để đọc lại một dòng do render tạo ra
+    fn parse(line: &str) -> Option<User> {
+        let fields: Vec<String> = line.split('\t').map(unescape).collect();
+        let ["user", id, business, name, role, hash, failures, locked] =
+            fields.iter().map(String::as_str).collect::<Vec<_>>()[..]
+        else {
+            return None;
+        };
+        Some(User {
+            id: id.parse().ok()?,
+            business: business.to_string(),
+            name: name.to_string(),
+            role: role.parse().ok()?,
+            hash: hash.parse().ok()?,
+            failures: failures.parse().ok()?,
+            locked: match locked {
+                "" => None,
+                until => Some(until.parse().ok()?),
+            },
+        })
+    }
+}
+
+// Khai báo một struct để đại diện cho một phiên đăng nhập
+#[derive(Debug, Clone, PartialEq)]
+pub struct Session {
+    pub token: String,        // Mã phiên trao cho client
+    pub refresh: String,      // Mã làm mới để lấy phiên mới khi phiên này hết hạn
+    pub principal: Principal, // Người dùng đã xác thực
+}
+
+// Khai báo một struct để chứa người dùng (được bảo vệ bởi khóa)
+#[derive(Debug, Default)]
+struct Data {
+    users: BTreeMap<u64, User>, // Người dùng theo mã
+    next: u64,                  // Mã người dùng tiếp theo
+}
+
+// Khai báo một struct để quản lý người dùng, đăng nhập và phiên
+//
+// Mỗi thay đổi của người dùng (tạo mới, mật khẩu, vai trò, đếm lần sai, khoá) được ghi thêm thành một
+// dòng chứa toàn bộ trạng thái vào nhật ký người dùng; khi mở lại, dòng sau cùng của mỗi người thắng.
+#[derive(Debug)]
+pub struct Auth {
+    bus: Arc<Bus>,             // Kênh sự kiện
+    sessions: Arc<Sessions>,   // Dịch vụ phiên
+    iterations: u32,           // Số vòng lặp PBKDF2 cho mật khẩu mới
+    storage: Mutex<Storage>,   // Nơi lưu nhật ký người dùng
+    data: RwLock<Data>,        // Người dùng
+    audit: Option<Arc<Audit>>, // Nhật ký kiểm toán ghi lại đăng nhập, khoá tài khoản và đổi vai trò (nếu có)
+}
+
+// Khai báo các phương thức cho struct Auth
+impl Auth {
+    // Phương thức open để mở dịch vụ xác thực với số vòng lặp mặc định và đọc lại nhật ký người dùng
+    pub fn open(storage: Storage, bus: Arc<Bus>, sessions: Arc<Sessions>) -> io::Result<Auth> {
+        Auth::with_iterations(storage, bus, sessions, ITERATIONS)
+    }
+
+    // Phương thức with_iterations để mở dịch vụ xác thực với số vòng lặp cho trước
+    pub fn with_iterations(
+        mut storage: Storage,
+        bus: Arc<Bus>,
+        sessions: Arc<Sessions>,
+        iterations: u32,
+    ) -> io::Result<Auth> {
+        storage.serve()?;
+        let mut data = Data::default();
+        if storage.list()?.iter().any(|name| name == LOG) {
+            for (number, line) in storage.view(LOG)?.lines().enumerate() {
+                let user = User::parse(line).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Invalid record in {} at line {}", LOG, number + 1),
+                    )
+                })?;
+                data.next = data.next.max(user.id);
+                data.users.insert(user.id, user);
+            }
+        }
+        Ok(Auth {
+            bus,
+            sessions,
+            iterations: iterations.max(1),
+            storage: Mutex::new(storage),
+            data: RwLock::new(data),
+            audit: None,
+        })
+    }
+
+    // Phương thức with_audit để ghi các thao tác liên quan đến bảo mật vào nhật ký kiểm toán
+    pub fn with_audit(mut self, audit: Arc<Audit>) -> Auth {
+        self.audit = Some(audit);
+        self
+    }
+
+    // Phương thức register để thêm người dùng vào doanh nghiệp (người đầu tiên là chủ, sau đó là nhân viên)
+    //
+    // Người đầu tiên tự đăng ký nên `by` có thể là None; từ người thứ hai, `by` phải là người dùng của
+    // chính doanh nghiệp đó và được phép quản lý vai trò.
+    pub fn register(
+        &self,
+        by: Option<&Principal>,
+        business: &str,
+        name: &str,
+        password: &str,
+    ) -> io::Result<u64> {
+        if password.chars().count() < MINIMUM {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Password must have at least {} characters", MINIMUM),
+            ));
+        }
+        let hash = Hash::new(password, self.iterations)?;
+
+        let mut data = self.data.write().unwrap();
+        if find(&data, business, name).is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("User {} already exists in {}", name, business),
+            ));
+        }
+        let role = if data.users.values().any(|user| user.business == business) {
+            let by = by.filter(|by| by.business == business).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!("Only a user of {} may add users to it", business),
+                )
+            })?;
+            authorize(by, Action::ManageRoles, &format!("business {}", business))?;
+            Role::Staff
+        } else {
+            Role::Owner
+        };
+        let id = data.next + 1;
+        self.save(
+            &mut data,
+            User {
+                id,
+                business: business.to_string(),
+                name: name.to_string(),
+                role,
+                hash,
+                failures: 0,
+                locked: None,
+            },
+        )?;
+        data.next = id;
+        Ok(id)
+    }
+
+    // Phương thức user trả về thông tin người dùng
+    pub fn user(&self, id: u64) -> io::Result<User> {
+        self.data
+            .read()
+            .unwrap()
+            .users
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, format!("User {} not found", id))
+            })
+    }
+
+    // Phương thức users trả về người dùng của một doanh nghiệp
+    pub fn users(&self, business: &str) -> Vec<User> {
+        let data = self.data.read().unwrap();
+        data.users
+            .values()
+            .filter(|user| user.business == business)
+            .cloned()
+            .collect()
+    }
+
+    // Phương thức login để đăng nhập và mở phiên; sai nhiều lần liên tiếp sẽ khoá tài khoản
+    pub fn login(
+        &self,
+        business: &str,
+        name: &str,
+        password: &str,
+        now: u64,
+    ) -> io::Result<Session> {
+        let denied = || io::Error::new(io::ErrorKind::PermissionDenied, "Invalid credentials");
+
+        // Băm mật khẩu ngoài khoá để không chặn các yêu cầu khác
+        let user = find(&self.data.read().unwrap(), business, name).cloned();
+        let Some(user) = user else {
+            // Vẫn băm để thời gian trả lời không tiết lộ người dùng có tồn tại hay không
+            let _ = Hash::new(password, self.iterations)?;
+            self.failed(business, name, &format!("user {}", name), "unknown user")?;
+            return Err(denied());
+        };
+        if let Some(until) = user.locked.filter(|until| now < *until) {
+            let actor = format!("{}@{}", name, business);
+            let resource = format!("user {}", user.id);
+            self.record(&actor, "login_failed", &resource, "", "account locked")?;
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("Account {} is locked until {}", name, until),
+            ));
+        }
+        let valid = user.hash.verify(password);
+
+        let mut data = self.data.write().unwrap();
+        let Some(mut user) = data.users.get(&user.id).cloned() else {
+            return Err(denied());
+        };
+        if !valid {
+            user.failures += 1;
+            let actor = format!("{}@{}", name, business);
+            let resource = format!("user {}", user.id);
+            let locked = user.failures >= ATTEMPTS;
+            if locked {
+                user.failures = 0;
+                user.locked = Some(now + LOCKOUT);
+            }
+            self.save(&mut data, user)?;
+            drop(data);
+            if locked {
+                self.bus.publish(Event::AccountLocked {
+                    business: business.to_string(),
+                    user: name.to_string(),
+                });
+                let until = format!("locked until {}", now + LOCKOUT);
+                self.record(&actor, "lockout", &resource, "", &until)?;
+            }
+            self.failed(business, name, &resource, "invalid password")?;
+            return Err(denied());
+        }
+        let principal = Principal::new(user.id, name, business, user.role, 0);
+        if user.failures != 0 || user.locked.is_some() {
+            user.failures = 0;
+            user.locked = None;
+            self.save(&mut data, user)?;
+        }
+        drop(data);
+
+        let session = self.sessions.start(&principal, now)?;
+        self.bus.publish(Event::LoggedIn {
+            business: business.to_string(),
+            user: name.to_string(),
+        });
+        let resource = format!("user {}", principal.user);
+        self.record(&audit::actor(&principal), "login", &resource, "", "")?;
+        Ok(session)
+    }
+
+    // Phương thức session trả về người dùng của một phiên còn hiệu lực
+    pub fn session(&self, token: &str, now: u64) -> io::Result<Principal> {
+        self.sessions.validate(token, now)
+    }
+
+    // Phương thức refresh để đổi mã làm mới lấy phiên mới
+    pub fn refresh(&self, token: &str, now: u64) -> io::Result<Session> {
+        self.sessions.refresh(token, now)
+    }
+
+    // Phương thức logout để kết thúc phiên trên một thiết bị
+    pub fn logout(&self, token: &str) -> io::Result<()> {
+        self.sessions.revoke(token)
+    }
+
+    // Phương thức logout_all để kết thúc mọi phiên của người dùng trên mọi thiết bị
+    pub fn logout_all(&self, principal: &Principal) -> io::Result<usize> {
+        let ended = self.sessions.revoke_all(principal.user)?;
+        let resource = format!("user {}", principal.user);
+        let after = format!("{} sessions ended", ended);
+        self.record(
+            &audit::actor(principal),
+            "logout_all",
+            &resource,
+            "",
+            &after,
+        )?;
+        Ok(ended)
+    }
+
+    // Phương thức password để người dùng đổi mật khẩu (mọi phiên của họ bị kết thúc)
+    pub fn password(&self, principal: &Principal, old: &str, new: &str) -> io::Result<()> {
+        let user = self.user(principal.user)?;
+        if !user.hash.verify(old) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "Invalid credentials",
+            ));
+        }
+        if new.chars().count() < MINIMUM {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Password must have at least {} characters", MINIMUM),
+            ));
+        }
+        let hash = Hash::new(new, self.iterations)?;
+        let mut data = self.data.write().unwrap();
+        if let Some(mut user) = data.users.get(&principal.user).cloned() {
+            user.hash = hash;
+            self.save(&mut data, user)?;
+        }
+        drop(data);
+        let ended = self.sessions.revoke_all(principal.user)?;
+        let resource = format!("user {}", principal.user);
+        let after = format!("{} sessions ended", ended);
+        self.record(&audit::actor(principal), "password", &resource, "", &after)
+    }
+
+    // Phương thức unlock để người quản lý vai trò mở khoá tài khoản cùng doanh nghiệp trước thời hạn
+    pub fn unlock(&self, by: &Principal, id: u64) -> io::Result<()> {
+        let resource = format!("user {}", id);
+        authorize(by, Action::ManageRoles, &resource)?;
+
+        let mut data = self.data.write().unwrap();
+        let mut user = data
+            .users
+            .get(&id)
+            .filter(|user| user.business == by.business)
+            .cloned()
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, format!("User {} not found", id))
+            })?;
+        let before = user
+            .locked
+            .map(|until| format!("locked until {}", until))
+            .unwrap_or_default();
+        user.failures = 0;
+        user.locked = None;
+        self.save(&mut data, user)?;
+        drop(data);
+        self.record(&audit::actor(by), "unlock", &resource, &before, "")
+    }
+
+    // Phương thức assign để chỉ định vai trò cho người dùng cùng doanh nghiệp
+    //
+    // Chỉ chủ doanh nghiệp mới chỉ định hoặc thay đổi vai trò chủ, và doanh nghiệp luôn còn ít nhất một
+    // chủ. Vai trò mới có hiệu lực ngay với các phiên đang mở của người dùng.
+    pub fn assign(&self, by: &Principal, id: u64, role: Role) -> io::Result<()> {
+        authorize(by, Action::ManageRoles, &format!("user {}", id))?;
+
+        let mut data = self.data.write().unwrap();
+        let user = data
+            .users
+            .get(&id)
+            .filter(|user| user.business == by.business)
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, format!("User {} not found", id))
+            })?;
+        if (role == Role::Owner || user.role == Role::Owner) && by.role != Role::Owner {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("Only an owner may change the owner role of user {}", id),
+            ));
+        }
+        let owners = data
+            .users
+            .values()
+            .filter(|other| other.business == by.business && other.role == Role::Owner)
+            .count();
+        if user.role == Role::Owner && role != Role::Owner && owners == 1 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} must keep at least one owner", by.business),
+            ));
+        }
+
+        let mut user = user.clone();
+        let name = user.name.clone();
+        let before = user.role;
+        user.role = role;
+        self.save(&mut data, user)?;
+        drop(data);
+        self.sessions.assign(id, role)?;
+        self.bus.publish(Event::RoleChanged {
+            business: by.business.clone(),
+            user: name,
+            role,
+        });
+        let resource = format!("user {}", id);
+        let (before, after) = (before.to_string(), role.to_string());
+        self.record(&audit::actor(by), "assign", &resource, &before, &after)
+    }
+
+    // Phương thức save để ghi trạng thái mới của người dùng vào nhật ký rồi cập nhật trong bộ nhớ
+    fn save(&self, data: &mut Data, user: User) -> io::Result<()> {
+        let line = user.render();
+        self.storage.lock().unwrap().append(LOG, line.as_bytes())?;
+        data.users.insert(user.id, user);
+        Ok(())
+    }
+
+    // Phương thức failed để công bố và ghi lại một lần đăng nhập thất bại
+    fn failed(&self, business: &str, name: &str, resource: &str, reason: &str) -> io::Result<()> {
+        self.bus.publish(Event::LoginFailed {
+            business: business.to_string(),
+            user: name.to_string(),
+        });
+        let actor = format!("{}@{}", name, business);
+        self.record(&actor, "login_failed", resource, "", reason)
+    }
+
+    // Phương thức record để ghi một thao tác vào nhật ký kiểm toán (nếu đã gắn)
+    fn record(
+        &self,
+        actor: &str,
+        action: &str,
+        resource: &str,
+        before: &str,
+        after: &str,
+    ) -> io::Result<()> {
+        if let Some(audit) = &self.audit {
+            audit.record(actor, action, resource, before, after)?;
+        }
+        Ok(())
+    }
+}
+
+// Hàm authorize kiểm tra vai trò của người dùng đã xác thực có cho phép hành động trên tài nguyên
+//
+// Đây là điểm kiểm tra quyền duy nhất: lưu trữ, ngân hàng và cửa hàng đều gọi hàm này trước khi thực
+// hiện thao tác. `resource` mô tả đối tượng bị tác động và chỉ dùng trong thông báo lỗi; việc tài nguyên
+// có thuộc doanh nghiệp của người dùng hay không do từng module kiểm tra.
+pub fn authorize(principal: &Principal, action: Action, resource: &str) -> io::Result<()> {
+    if principal.role.allows(action) {
+        return Ok(());
+    }
+    Err(io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        format!(
+            "{} ({} of {}) may not {} on {}",
+            principal.name, principal.role, principal.business, action, resource
+        ),
+    ))
+}
+
+// Hàm find tìm người dùng theo doanh nghiệp và tên đăng nhập
+fn find<'a>(data: &'a Data, business: &str, name: &str) -> Option<&'a User> {
+    data.users
+        .values()
+        .find(|user| user.business == business && user.name == name)
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+pub(crate) mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::storage::tests::{create_temp_storage, reopen};
+    use crate::storage::Access;
+    use std::fs;
+    use std::path::PathBuf;
+
+    // Khai báo một hàm để tạo người dùng đã xác thực dùng trong các unit test của các module khác
+    pub(crate) fn principal(business: &str) -> Principal {
+        Principal::new(0, "tester", business, Role::Owner, u64::MAX)
+    }
+
+    // Khai báo một hàm để tạo dịch vụ xác thực với số vòng lặp nhỏ cho unit test
+    // (trả về đường dẫn của đối tượng lưu trữ phiên và của đối tượng lưu trữ người dùng)
+    fn auth() -> (Auth, Arc<Bus>, [PathBuf; 2]) {
+        let bus = Arc::new(Bus::new());
+        let storage = create_temp_storage("auth", Access::Private).unwrap();
+        let paths = [
+            storage.path().to_path_buf(),
+            create_temp_storage("auth", Access::Private)
+                .unwrap()
+                .path()
+                .to_path_buf(),
+        ];
+        let sessions = Arc::new(Sessions::open(storage, bus.clone()).unwrap());
+        let users = reopen(&paths[1]).unwrap();
+        let auth = Auth::with_iterations(users, bus.clone(), sessions, 10).unwrap();
+        (auth, bus, paths)
+    }
+
+    // Khai báo một hàm để kiểm tra đăng ký, đăng nhập, phiên và đăng xuất
+    #[test]
+    fn test_login() {
+        let (auth, _, paths) = auth();
+        let id = auth.register(None, "alpha", "an", "correct horse").unwrap();
+        assert!(auth
+            .register(Some(&principal("alpha")), "alpha", "an", "another one")
+            .is_err());
+        assert!(auth
+            .register(Some(&principal("alpha")), "alpha", "binh", "short")
+            .is_err());
+        auth.register(None, "beta", "an", "battery staple").unwrap();
+
+        // Cùng tên nhưng khác doanh nghiệp là hai người dùng khác nhau
+        assert!(auth.login("beta", "an", "correct horse", 0).is_err());
+        let session = auth.login("alpha", "an", "correct horse", 100).unwrap();
+        assert_eq!(session.principal.user(), id);
+        assert_eq!(session.principal.business(), "alpha");
+
+        // Phiên hết hạn sau SESSION giây và kết thúc khi đăng xuất
+        assert_eq!(
+            auth.session(&session.token, 100).unwrap(),
+            session.principal
+        );
+        assert!(auth.session(&session.token, 100 + SESSION).is_err());
+        auth.logout(&session.token).unwrap();
+        assert!(auth.session(&session.token, 100).is_err());
+        assert!(auth.logout(&session.token).is_err());
+
+        // Đổi mật khẩu kết thúc mọi phiên của người dùng
+        let first = auth.login("alpha", "an", "correct horse", 0).unwrap();
+        let second = auth.login("alpha", "an", "correct horse", 0).unwrap();
+        let other = auth.login("beta", "an", "battery staple", 0).unwrap();
+        assert!(auth
+            .password(&first.principal, "wrong", "new password")
+            .is_err());
+        auth.password(&first.principal, "correct horse", "new password")
+            .unwrap();
+        assert!(auth.session(&first.token, 0).is_err());
+        assert!(auth.session(&second.token, 0).is_err());
+        assert!(auth.session(&other.token, 0).is_ok());
+        assert!(auth.login("alpha", "an", "correct horse", 0).is_err());
+        let session = auth.login("alpha", "an", "new password", 0).unwrap();
+
+        // Làm mới phiên, rồi đăng xuất mọi thiết bị
+        let renewed = auth.refresh(&session.refresh, 10).unwrap();
+        assert_eq!(renewed.principal.user(), id);
+        assert_eq!(auth.logout_all(&renewed.principal).unwrap(), 1);
+        assert!(auth.session(&renewed.token, 10).is_err());
+
+        paths
+            .iter()
+            .for_each(|path| fs::remove_dir_all(path).unwrap());
+    }
+
+    // Khai báo một hàm để kiểm tra khoá tài khoản sau nhiều lần đăng nhập sai
+    #[test]
+    fn test_lockout() {
+        let (auth, bus, paths) = auth();
+        let events = bus.subscribe();
+        let id = auth.register(None, "alpha", "an", "correct horse").unwrap();
+
+        // Sai ATTEMPTS lần liên tiếp thì bị khoá, kể cả khi sau đó nhập đúng
+        for _ in 0..ATTEMPTS {
+            assert!(auth.login("alpha", "an", "wrong guess", 1000).is_err());
+        }
+        assert_eq!(auth.user(id).unwrap().locked, Some(1000 + LOCKOUT));
+        let error = auth
+            .login("alpha", "an", "correct horse", 1000)
+            .unwrap_err();
+        assert!(error.to_string().contains("locked"));
+
+        // Hết thời gian khoá thì đăng nhập lại được; đăng nhập đúng đặt lại bộ đếm
+        auth.login("alpha", "an", "correct horse", 1000 + LOCKOUT)
+            .unwrap();
+        assert!(auth.login("alpha", "an", "wrong guess", 2000).is_err());
+        auth.login("alpha", "an", "correct horse", 2000).unwrap();
+        assert_eq!(auth.user(id).unwrap().failures, 0);
+
+        // Mở khoá trước thời hạn
+        for _ in 0..ATTEMPTS {
+            assert!(auth.login("alpha", "an", "wrong guess", 3000).is_err());
+        }
+        assert!(auth.unlock(&principal("beta"), id).is_err());
+        auth.unlock(&principal("alpha"), id).unwrap();
+        auth.login("alpha", "an", "correct horse", 3000).unwrap();
+
+        // Người dùng không tồn tại trả về cùng một lỗi
+        let error = auth.login("alpha", "ghost", "whatever1", 0).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+
+        let events: Vec<Event> = events.try_iter().collect();
+        let locked = Event::AccountLocked {
+            business: "alpha".to_string(),
+            user: "an".to_string(),
+        };
+        assert_eq!(events.iter().filter(|e| **e == locked).count(), 2);
+
+        paths
+            .iter()
+            .for_each(|path| fs::remove_dir_all(path).unwrap());
+    }
+
+    // Khai báo một hàm để kiểm tra việc đọc lại người dùng, mật khẩu, bộ đếm và khoá từ nhật ký
+    #[test]
+    fn test_persist() {
+        let (auth, bus, paths) = auth();
+        let owner = auth.register(None, "alpha", "an", "correct horse").unwrap();
+        let binh = auth
+            .register(Some(&principal("alpha")), "alpha", "binh", "correct horse")
+            .unwrap();
+        let boss = auth.login("alpha", "an", "correct horse", 0).unwrap();
+        auth.assign(&boss.principal, binh, Role::Accountant)
+            .unwrap();
+        auth.password(&boss.principal, "correct horse", "battery staple")
+            .unwrap();
+        for _ in 0..ATTEMPTS {
+            assert!(auth.login("alpha", "binh", "wrong guess", 500).is_err());
+        }
+        assert!(auth.login("alpha", "an", "wrong guess", 500).is_err());
+        let before = auth.users("alpha");
+
+        // Mở lại từ nhật ký cho ra đúng người dùng, và mã mới không dùng lại mã cũ
+        drop(auth);
+        let sessions = Arc::new(Sessions::open(reopen(&paths[0]).unwrap(), bus.clone()).unwrap());
+        let auth = Auth::with_iterations(reopen(&paths[1]).unwrap(), bus, sessions, 10).unwrap();
+        assert_eq!(auth.users("alpha"), before);
+        assert_eq!(auth.user(binh).unwrap().role, Role::Accountant);
+        assert_eq!(auth.user(binh).unwrap().locked, Some(500 + LOCKOUT));
+        assert_eq!(auth.user(owner).unwrap().failures, 1);
+        assert!(auth.login("alpha", "an", "correct horse", 600).is_err());
+        auth.login("alpha", "an", "battery staple", 600).unwrap();
+        assert_eq!(auth.user(owner).unwrap().failures, 0);
+        let chi = auth
+            .register(Some(&principal("alpha")), "alpha", "chi", "correct horse")
+            .unwrap();
+        assert_eq!(chi, binh + 1);
+
+        paths
+            .iter()
+            .for_each(|path| fs::remove_dir_all(path).unwrap());
+    }
+
+    // Khai báo một hàm để kiểm tra việc chỉ định vai trò
+    #[test]
+    fn test_roles() {
+        let (auth, bus, paths) = auth();
+        let events = bus.subscribe();
+
+        // Người đầu tiên của doanh nghiệp là chủ, những người sau là nhân viên
+        let owner = auth.register(None, "alpha", "an", "correct horse").unwrap();
+        let binh = auth
+            .register(Some(&principal("alpha")), "alpha", "binh", "correct horse")
+            .unwrap();
+        let chi = auth
+            .register(Some(&principal("alpha")), "alpha", "chi", "correct horse")
+            .unwrap();
+        let other = auth
+            .register(None, "beta", "dung", "correct horse")
+            .unwrap();
+
+        // Từ người thứ hai, chỉ người quản lý vai trò của chính doanh nghiệp mới thêm được người dùng
+        assert!(auth
+            .register(None, "alpha", "dao", "correct horse")
+            .is_err());
+        let outsider = principal("beta");
+        assert!(auth
+            .register(Some(&outsider), "alpha", "dao", "correct horse")
+            .is_err());
+        assert_eq!(auth.user(owner).unwrap().role, Role::Owner);
+        assert_eq!(auth.user(binh).unwrap().role, Role::Staff);
+        assert_eq!(auth.user(other).unwrap().role, Role::Owner);
+
+        // Nhân viên không được chỉ định vai trò
+        let staff = auth.login("alpha", "binh", "correct horse", 0).unwrap();
+        assert!(auth
+            .register(Some(&staff.principal), "alpha", "dao", "correct horse")
+            .is_err());
+        assert!(auth.unlock(&staff.principal, chi).is_err());
+        let error = auth.assign(&staff.principal, chi, Role::Admin).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+
+        // Vai trò mới có hiệu lực ngay với phiên đang mở
+        let boss = auth.login("alpha", "an", "correct horse", 0).unwrap();
+        auth.assign(&boss.principal, binh, Role::Admin).unwrap();
+        let admin = auth.session(&staff.token, 10).unwrap();
+        assert_eq!(admin.role(), Role::Admin);
+
+        // Quản trị viên chỉ định được vai trò thường, nhưng không được đụng tới vai trò chủ
+        auth.assign(&admin, chi, Role::Accountant).unwrap();
+        assert!(auth.assign(&admin, chi, Role::Owner).is_err());
+        assert!(auth.assign(&admin, owner, Role::Staff).is_err());
+
+        // Không chỉ định được người dùng của doanh nghiệp khác, và doanh nghiệp luôn còn một chủ
+        assert!(auth.assign(&boss.principal, other, Role::Staff).is_err());
+        assert!(auth.assign(&boss.principal, owner, Role::Admin).is_err());
+        auth.assign(&boss.principal, chi, Role::Owner).unwrap();
+        auth.assign(&boss.principal, owner, Role::Admin).unwrap();
+
+        // Hàm authorize dùng bảng quyền của vai trò
+        let accountant = Principal::new(chi, "chi", "alpha", Role::Accountant, u64::MAX);
+        authorize(&accountant, Action::BankTransfer, "account 1").unwrap();
+        let error = authorize(&accountant, Action::ShopManage, "shop 1").unwrap_err();
+        assert!(error.to_string().contains("may not shop manage on shop 1"));
+
+        let changed = Event::RoleChanged {
+            business: "alpha".to_string(),
+            user: "binh".to_string(),
+            role: Role::Admin,
+        };
+        assert!(events.try_iter().any(|event| event == changed));
+
+        paths
+            .iter()
+            .for_each(|path| fs::remove_dir_all(path).unwrap());
+    }
+
+    // Khai báo một hàm để kiểm tra việc ghi đăng nhập, khoá tài khoản và đổi vai trò vào nhật ký kiểm toán
+    #[test]
+    fn test_audit() {
+        let (audit, audit_path) = crate::audit::tests::audit();
+        let (auth, _, paths) = auth();
+        let auth = auth.with_audit(audit.clone());
+        auth.register(None, "alpha", "an", "correct horse").unwrap();
+        let binh = auth
+            .register(Some(&principal("alpha")), "alpha", "binh", "correct horse")
+            .unwrap();
+
+        // Đăng nhập sai đến khi bị khoá, rồi đăng nhập đúng khi vẫn còn bị khoá
+        for _ in 0..ATTEMPTS {
+            assert!(auth.login("alpha", "binh", "wrong", 0).is_err());
+        }
+        assert!(auth.login("alpha", "binh", "correct horse", 0).is_err());
+        assert!(auth.login("alpha", "nobody", "wrong", 0).is_err());
+
+        // Đăng nhập, đổi vai trò, đổi mật khẩu và đăng xuất khỏi mọi thiết bị
+        let boss = auth.login("alpha", "an", "correct horse", 0).unwrap();
+        auth.assign(&boss.principal, binh, Role::Accountant)
+            .unwrap();
+        auth.unlock(&boss.principal, binh).unwrap();
+        auth.password(&boss.principal, "correct horse", "battery staple")
+            .unwrap();
+        auth.logout_all(&boss.principal).unwrap();
+
+        let entries = audit.entries().unwrap();
+        let actions: Vec<&str> = entries.iter().map(|entry| entry.action.as_str()).collect();
+        let mut expected = vec!["login_failed"; ATTEMPTS as usize];
+        expected.insert(ATTEMPTS as usize - 1, "lockout");
+        expected.extend([
+            "login_failed",
+            "login_failed",
+            "login",
+            "assign",
+            "unlock",
+            "password",
+        ]);
+        expected.push("logout_all");
+        assert_eq!(actions, expected);
+        assert_eq!(entries[0].actor, "binh@alpha");
+        assert_eq!(
+            entries[ATTEMPTS as usize - 1].after,
+            format!("locked until {}", LOCKOUT)
+        );
+        assert_eq!(entries[ATTEMPTS as usize + 1].after, "account locked");
+        assert_eq!(entries[ATTEMPTS as usize + 2].resource, "user nobody");
+        let assign = &entries[ATTEMPTS as usize + 4];
+        assert_eq!(assign.actor, "an@alpha");
+        assert_eq!(assign.resource, format!("user {}", binh));
+        assert_eq!(
+            (assign.before.as_str(), assign.after.as_str()),
+            ("staff", "accountant")
+        );
+        assert_eq!(
+            entries[ATTEMPTS as usize + 5].before,
+            format!("locked until {}", LOCKOUT)
+        );
+        assert_eq!(audit.verify().unwrap(), entries.len() as u64);
+
+        paths
+            .iter()
+            .for_each(|path| fs::remove_dir_all(path).unwrap());
+        fs::remove_dir_all(audit_path).unwrap();
+    }
+}
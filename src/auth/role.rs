@@ -0,0 +1,140 @@
+// Khai báo các thư viện cần thiết
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+// Khai báo một enum để đại diện cho vai trò của người dùng trong doanh nghiệp
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Role {
+    Owner,      // Chủ doanh nghiệp: mọi quyền, kể cả chỉ định chủ khác
+    Admin,      // Quản trị viên: mọi quyền nghiệp vụ và quản lý vai trò (trừ vai trò chủ)
+    Accountant, // Kế toán: tập tin và chuyển tiền
+    Staff,      // Nhân viên: đọc tập tin và quản lý cửa hàng
+}
+
+// Khai báo một enum để đại diện cho các hành động cần được cấp quyền
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    StorageRead,  // Đọc tập tin trong đối tượng lưu trữ
+    StorageWrite, // Ghi, xoá, chia sẻ tập tin và đổi quyền truy cập
+    BankTransfer, // Nạp, rút và chuyển tiền trên sổ cái
+    ShopManage,   // Quản lý cửa hàng, sản phẩm và giá
+    ManageRoles,  // Chỉ định vai trò cho người dùng của doanh nghiệp
+}
+
+// Khai báo bảng quyền của từng vai trò
+const PERMISSIONS: [(Role, &[Action]); 4] = [
+    (Role::Owner, &Action::ALL),
+    (Role::Admin, &Action::ALL),
+    (
+        Role::Accountant,
+        &[
+            Action::StorageRead,
+            Action::StorageWrite,
+            Action::BankTransfer,
+        ],
+    ),
+    (Role::Staff, &[Action::StorageRead, Action::ShopManage]),
+];
+
+// Khai báo các phương thức cho enum Role
+impl Role {
+    // Khai báo danh sách tất cả vai trò
+    pub const ALL: [Role; 4] = [Role::Owner, Role::Admin, Role::Accountant, Role::Staff];
+
+    // Phương thức permissions trả về các hành động mà vai trò được phép làm
+    pub fn permissions(&self) -> &'static [Action] {
+        PERMISSIONS
+            .iter()
+            .find(|(role, _)| role == self)
+            .map(|(_, actions)| *actions)
+            .unwrap_or(&[])
+    }
+
+    // Phương thức allows kiểm tra vai trò có được phép làm một hành động hay không
+    pub fn allows(&self, action: Action) -> bool {
+        self.permissions().contains(&action)
+    }
+}
+
+// Khai báo các phương thức cho enum Action
+impl Action {
+    // Khai báo danh sách tất cả hành động
+    pub const ALL: [Action; 5] = [
+        Action::StorageRead,
+        Action::StorageWrite,
+        Action::BankTransfer,
+        Action::ShopManage,
+        Action::ManageRoles,
+    ];
+}
+
+// Khai báo cách hiển thị vai trò thành chuỗi
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Role::Owner => "owner",
+            Role::Admin => "admin",
+            Role::Accountant => "accountant",
+            Role::Staff => "staff",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Khai báo cách đọc vai trò từ chuỗi do Display tạo ra
+impl FromStr for Role {
+    type Err = io::Error;
+
+    fn from_str(text: &str) -> io::Result<Role> {
+        Role::ALL
+            .into_iter()
+            .find(|role| role.to_string() == text)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown role: {}", text),
+                )
+            })
+    }
+}
+
+// Khai báo cách hiển thị hành động thành chuỗi
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Action::StorageRead => "storage read",
+            Action::StorageWrite => "storage write",
+            Action::BankTransfer => "bank transfer",
+            Action::ShopManage => "shop manage",
+            Action::ManageRoles => "manage roles",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+
+    // Khai báo một hàm để kiểm tra bảng quyền
+    #[test]
+    fn test_permissions() {
+        for action in Action::ALL {
+            assert!(Role::Owner.allows(action));
+        }
+        assert!(Role::Accountant.allows(Action::BankTransfer));
+        assert!(!Role::Accountant.allows(Action::ShopManage));
+        assert!(Role::Staff.allows(Action::ShopManage));
+        assert!(!Role::Staff.allows(Action::BankTransfer));
+        assert!(!Role::Staff.allows(Action::StorageWrite));
+        assert!(!Role::Staff.allows(Action::ManageRoles));
+
+        for role in Role::ALL {
+            assert_eq!(role.to_string().parse::<Role>().unwrap(), role);
+        }
+        assert!("boss".parse::<Role>().is_err());
+    }
+}
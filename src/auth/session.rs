@@ -0,0 +1,637 @@
+// Khai báo các thư viện cần thiết
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+use super::role::Role;
+use super::{Principal, Session, SESSION};
+use crate::clock::DAY;
+use crate::crypto::{self, hex, sha256, unhex};
+use crate::event::{Bus, Event};
+use crate::social::message::{escape, unescape};
+use crate::storage::{Access, Storage};
+
+// Khai báo thời gian tối đa giữa hai lần dùng phiên trước khi phiên hết hạn (tính bằng giây)
+pub const IDLE: u64 = 30 * 60;
+
+// Khai báo thời gian sống của một chuỗi mã làm mới, tính từ lần đăng nhập (tính bằng giây)
+pub const REFRESH: u64 = 30 * DAY;
+
+// Khai báo khoảng thời gian tối thiểu giữa hai lần ghi thời điểm dùng phiên vào nhật ký
+const TOUCH: u64 = 60;
+
+// Khai báo tên nhật ký phiên và tên tập tin chứa khoá ký trong đối tượng lưu trữ
+const LOG: &str = "sessions.log";
+const SECRET: &str = "sessions.key";
+
+// Khai báo loại mã để mã phiên không dùng được làm mã làm mới và ngược lại
+const ACCESS: &str = "access";
+const RENEW: &str = "refresh";
+
+// Khai báo một enum để đại diện cho một bản ghi trong nhật ký phiên
+//
+// Nhật ký chỉ lưu giá trị băm của mã nên người đọc được đối tượng lưu trữ cũng không dùng được phiên.
+#[derive(Debug, Clone, PartialEq)]
+enum Record {
+    Family(u64, Principal),        // Chuỗi phiên mới sau khi đăng nhập
+    Access(String, u64, u64, u64), // Phiên mới: băm mã, chuỗi, lúc tạo, lúc hết hạn
+    Refresh(u64, String),          // Mã làm mới mới của chuỗi (mã cũ thành đã dùng)
+    Touch(String, u64),            // Thời điểm dùng phiên gần nhất
+    Revoke(String, u64),           // Thu hồi phiên (giữ trong danh sách thu hồi tới khi hết hạn)
+    End(u64),                      // Kết thúc cả chuỗi phiên
+    Role(u64, Role),               // Vai trò mới của người dùng
+}
+
+// Khai báo các phương thức cho enum Record
+impl Record {
+    // Phương thức render để ghi bản ghi thành một dòng, các trường cách nhau bằng tab
+    fn render(&self) -> String {
+        let fields: Vec<String> = match self {
+            Record::Family(family, principal) => vec![
+                "family".into(),
+                family.to_string(),
+                principal.user.to_string(),
+                escape(&principal.name),
+                escape(&principal.business),
+                principal.role.to_string(),
+                principal.expires.to_string(),
+            ],
+            Record::Access(key, family, created, expires) => vec![
+                "access".into(),
+                key.clone(),
+                family.to_string(),
+                created.to_string(),
+                expires.to_string(),
+            ],
+            Record::Refresh(family, key) => vec!["refresh".into(), family.to_string(), key.clone()],
+            Record::Touch(key, time) => vec!["touch".into(), key.clone(), time.to_string()],
+            Record::Revoke(key, expires) => vec!["revoke".into(), key.clone(), expires.to_string()],
+            Record::End(family) => vec!["end".into(), family.to_string()],
+            Record::Role(user, role) => vec!["role".into(), user.to_string(), role.to_string()],
+        };
+        format!("{}\n", fields.join("\t"))
+    }
+
+    // Phương thức parse để đọc lại một dòng do render tạo ra
+    fn parse(line: &str) -> Option<Record> {
+        let fields: Vec<String> = line.split('\t').map(unescape).collect();
+        let number = |i: usize| fields.get(i)?.parse::<u64>().ok();
+        let text = |i: usize| fields.get(i).cloned();
+        let record = match fields[0].as_str() {
+            "family" => Record::Family(
+                number(1)?,
+                Principal::new(
+                    number(2)?,
+                    &text(3)?,
+                    &text(4)?,
+                    text(5)?.parse().ok()?,
+                    number(6)?,
+                ),
+            ),
+            "access" => Record::Access(text(1)?, number(2)?, number(3)?, number(4)?),
+            "refresh" => Record::Refresh(number(1)?, text(2)?),
+            "touch" => Record::Touch(text(1)?, number(2)?),
+            "revoke" => Record::Revoke(text(1)?, number(2)?),
+            "end" => Record::End(number(1)?),
+            "role" => Record::Role(number(1)?, text(2)?.parse().ok()?),
+            _ => return None,
+        };
+        Some(record)
+    }
+}
+
+// Khai báo một struct để đại diện cho một phiên đang mở
+#[derive(Debug)]
+struct Entry {
+    family: u64,     // Chuỗi phiên chứa phiên này
+    created: u64,    // Thời điểm tạo
+    expires: u64,    // Thời điểm hết hạn tuyệt đối
+    seen: AtomicU64, // Thời điểm dùng gần nhất (cập nhật được khi chỉ giữ khoá đọc)
+}
+
+// Khai báo một struct để đại diện cho một chuỗi phiên bắt đầu từ một lần đăng nhập
+#[derive(Debug, Clone, PartialEq)]
+struct Family {
+    principal: Principal,   // Người dùng (expires là hạn của mã làm mới)
+    refresh: String,        // Băm của mã làm mới hiện tại
+    used: BTreeSet<String>, // Băm của các mã làm mới đã dùng
+    ended: bool,            // Chuỗi đã bị kết thúc
+}
+
+// Khai báo một struct để chứa các phiên (được bảo vệ bởi khóa)
+#[derive(Debug, Default)]
+struct Data {
+    sessions: BTreeMap<String, Entry>, // Phiên đang mở theo băm của mã
+    families: BTreeMap<u64, Family>,   // Chuỗi phiên theo mã
+    revoked: BTreeMap<String, u64>,    // Danh sách thu hồi: băm của mã và thời điểm hết hạn
+    next: u64,                         // Mã chuỗi tiếp theo
+}
+
+// Khai báo một struct để cấp, kiểm tra, làm mới và thu hồi mã phiên
+//
+// Mã trao cho client có dạng `<id>.<chữ ký>`: id là 32 byte ngẫu nhiên, chữ ký là HMAC-SHA256 của
+// loại mã và id bằng khoá ký lưu trong đối tượng lưu trữ. Mã giả bị loại trước khi chạm tới khoá dữ
+// liệu; kiểm tra mã thật chỉ cần khoá đọc nên nhiều luồng có thể kiểm tra cùng lúc.
+#[derive(Debug)]
+pub struct Sessions {
+    secret: [u8; 32],        // Khoá ký mã
+    bus: Arc<Bus>,           // Kênh sự kiện
+    storage: Mutex<Storage>, // Nơi lưu khoá ký và nhật ký phiên
+    data: RwLock<Data>,      // Các phiên
+}
+
+// Khai báo các phương thức cho struct Sessions
+impl Sessions {
+    // Phương thức open để mở dịch vụ phiên, tạo khoá ký nếu chưa có và đọc lại nhật ký phiên
+    pub fn open(mut storage: Storage, bus: Arc<Bus>) -> io::Result<Sessions> {
+        storage.serve()?;
+        let files = storage.list()?;
+        let secret = if files.iter().any(|name| name == SECRET) {
+            unhex(storage.view(SECRET)?.trim())?
+                .try_into()
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid session key"))?
+        } else {
+            let secret = crypto::key()?;
+            storage.put(SECRET, hex(&secret).as_bytes())?;
+            storage.set_file_access(SECRET, Access::Private)?;
+            secret
+        };
+
+        let mut data = Data::default();
+        if files.iter().any(|name| name == LOG) {
+            for (number, line) in storage.view(LOG)?.lines().enumerate() {
+                let record = Record::parse(line).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Invalid record in {} at line {}", LOG, number + 1),
+                    )
+                })?;
+                apply(&mut data, record);
+            }
+        }
+        Ok(Sessions {
+            secret,
+            bus,
+            storage: Mutex::new(storage),
+            data: RwLock::new(data),
+        })
+    }
+
+    // Phương thức start để mở chuỗi phiên mới cho người dùng vừa đăng nhập
+    pub fn start(&self, principal: &Principal, now: u64) -> io::Result<Session> {
+        let mut data = self.data.write().unwrap();
+        data.next += 1;
+        let family = data.next;
+        let principal = principal.until(now + REFRESH);
+        self.record(&mut data, Record::Family(family, principal))?;
+        self.issue(&mut data, family, now)
+    }
+
+    // Phương thức validate trả về người dùng của một mã phiên còn hiệu lực và ghi nhận lần dùng
+    pub fn validate(&self, token: &str, now: u64) -> io::Result<Principal> {
+        let key = self.verify(token, ACCESS)?;
+
+        let data = self.data.read().unwrap();
+        if data.revoked.contains_key(&key) {
+            return Err(denied("Session revoked"));
+        }
+        let entry = data
+            .sessions
+            .get(&key)
+            .ok_or_else(|| denied("Session expired or not found"))?;
+        let family = &data.families[&entry.family];
+        let seen = entry.seen.load(Ordering::Relaxed);
+        if family.ended || now >= entry.expires || now >= seen.max(entry.created) + IDLE {
+            return Err(denied("Session expired or not found"));
+        }
+
+        // Chỉ luồng đầu tiên vượt qua mốc TOUCH mới ghi vào nhật ký
+        let last = entry.seen.fetch_max(now, Ordering::Relaxed);
+        if now / TOUCH > last / TOUCH {
+            self.storage
+                .lock()
+                .unwrap()
+                .append(LOG, Record::Touch(key, now).render().as_bytes())?;
+        }
+
+        Ok(family.principal.until(entry.expires.min(now + IDLE)))
+    }
+
+    // Phương thức refresh để đổi mã làm mới lấy phiên mới và mã làm mới mới
+    //
+    // Mỗi mã làm mới chỉ dùng được một lần. Nếu một mã đã dùng bị đưa ra lần nữa thì mã đã bị lộ:
+    // cả chuỗi phiên bị kết thúc để kẻ lấy được mã và người dùng thật đều phải đăng nhập lại.
+    pub fn refresh(&self, token: &str, now: u64) -> io::Result<Session> {
+        let key = self.verify(token, RENEW)?;
+
+        let mut data = self.data.write().unwrap();
+        let found = data.families.iter().find_map(|(id, family)| {
+            if family.refresh == key {
+                Some((*id, false))
+            } else if family.used.contains(&key) {
+                Some((*id, true))
+            } else {
+                None
+            }
+        });
+        let Some((id, reused)) = found else {
+            return Err(denied("Refresh token not found"));
+        };
+        let family = &data.families[&id];
+        if family.ended || !family.principal.active(now) {
+            return Err(denied("Refresh token expired or revoked"));
+        }
+        if reused {
+            let principal = family.principal.clone();
+            self.end(&mut data, id)?;
+            self.bus.publish(Event::RefreshReused {
+                business: principal.business,
+                user: principal.name,
+            });
+            return Err(denied("Refresh token reuse detected"));
+        }
+        self.issue(&mut data, id, now)
+    }
+
+    // Phương thức revoke để thu hồi một mã phiên (đăng xuất khỏi một thiết bị)
+    pub fn revoke(&self, token: &str) -> io::Result<()> {
+        let key = self
+            .verify(token, ACCESS)
+            .map_err(|_| io::Error::new(io::ErrorKind::NotFound, "Session not found"))?;
+        let mut data = self.data.write().unwrap();
+        let Some(entry) = data.sessions.get(&key) else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "Session not found"));
+        };
+        let family = entry.family;
+        self.end(&mut data, family)
+    }
+
+    // Phương thức revoke_all để thu hồi mọi phiên và mã làm mới của người dùng (đăng xuất mọi thiết bị)
+    pub fn revoke_all(&self, user: u64) -> io::Result<usize> {
+        let mut data = self.data.write().unwrap();
+        let families: Vec<u64> = data
+            .families
+            .iter()
+            .filter(|(_, family)| family.principal.user == user && !family.ended)
+            .map(|(id, _)| *id)
+            .collect();
+        for family in &families {
+            self.end(&mut data, *family)?;
+        }
+        Ok(families.len())
+    }
+
+    // Phương thức assign để áp dụng vai trò mới cho mọi chuỗi phiên của người dùng
+    pub fn assign(&self, user: u64, role: Role) -> io::Result<()> {
+        let mut data = self.data.write().unwrap();
+        self.record(&mut data, Record::Role(user, role))
+    }
+
+    // Phương thức active trả về số phiên còn hiệu lực của người dùng tại thời điểm `now`
+    pub fn active(&self, user: u64, now: u64) -> usize {
+        let data = self.data.read().unwrap();
+        data.sessions
+            .values()
+            .filter(|entry| {
+                let family = &data.families[&entry.family];
+                let seen = entry.seen.load(Ordering::Relaxed).max(entry.created);
+                family.principal.user == user
+                    && !family.ended
+                    && now < entry.expires
+                    && now < seen + IDLE
+            })
+            .count()
+    }
+
+    // Phương thức purge để bỏ các phiên, chuỗi phiên và mục thu hồi đã hết hạn rồi viết gọn nhật ký
+    pub fn purge(&self, now: u64) -> io::Result<()> {
+        let mut data = self.data.write().unwrap();
+        data.families
+            .retain(|_, family| family.principal.active(now));
+        let Data {
+            sessions,
+            families,
+            revoked,
+            ..
+        } = &mut *data;
+        sessions.retain(|_, entry| families.contains_key(&entry.family) && now < entry.expires);
+        revoked.retain(|_, expires| now < *expires);
+
+        // Viết lại nhật ký từ trạng thái còn lại
+        let mut log = String::new();
+        for (id, family) in &data.families {
+            log += &Record::Family(*id, family.principal.clone()).render();
+            for key in family.used.iter().chain([&family.refresh]) {
+                log += &Record::Refresh(*id, key.clone()).render();
+            }
+            if family.ended {
+                log += &Record::End(*id).render();
+            }
+        }
+        for (key, entry) in &data.sessions {
+            log +=
+                &Record::Access(key.clone(), entry.family, entry.created, entry.expires).render();
+            log += &Record::Touch(key.clone(), entry.seen.load(Ordering::Relaxed)).render();
+        }
+        for (key, expires) in &data.revoked {
+            log += &Record::Revoke(key.clone(), *expires).render();
+        }
+        let mut storage = self.storage.lock().unwrap();
+        if storage.list()?.iter().any(|name| name == LOG) {
+            storage.edit(LOG, &log)
+        } else {
+            storage.put(LOG, log.as_bytes())
+        }
+    }
+
+    // Phương thức issue để cấp phiên và mã làm mới mới trong một chuỗi phiên
+    fn issue(&self, data: &mut Data, family: u64, now: u64) -> io::Result<Session> {
+        let (access, access_key) = self.token(ACCESS)?;
+        let (refresh, refresh_key) = self.token(RENEW)?;
+        let expires = (now + SESSION).min(data.families[&family].principal.expires);
+        self.record(data, Record::Access(access_key, family, now, expires))?;
+        self.record(data, Record::Refresh(family, refresh_key))?;
+        Ok(Session {
+            token: access,
+            refresh,
+            principal: data.families[&family]
+                .principal
+                .until(expires.min(now + IDLE)),
+        })
+    }
+
+    // Phương thức end để kết thúc một chuỗi phiên và đưa các phiên của nó vào danh sách thu hồi
+    fn end(&self, data: &mut Data, family: u64) -> io::Result<()> {
+        let keys: Vec<(String, u64)> = data
+            .sessions
+            .iter()
+            .filter(|(_, entry)| entry.family == family)
+            .map(|(key, entry)| (key.clone(), entry.expires))
+            .collect();
+        for (key, expires) in keys {
+            self.record(data, Record::Revoke(key, expires))?;
+        }
+        self.record(data, Record::End(family))
+    }
+
+    // Phương thức token để tạo một mã mới đã ký, trả về mã và băm của nó
+    fn token(&self, kind: &str) -> io::Result<(String, String)> {
+        let id = hex(&crypto::key()?);
+        let signature = hex(&sha256::hmac(
+            &self.secret,
+            format!("{}:{}", kind, id).as_bytes(),
+        ));
+        Ok((
+            format!("{}.{}", id, signature),
+            hex(&sha256::digest(id.as_bytes())),
+        ))
+    }
+
+    // Phương thức verify để kiểm tra chữ ký của mã và trả về băm của nó
+    fn verify(&self, token: &str, kind: &str) -> io::Result<String> {
+        let invalid = || denied("Invalid session token");
+        let (id, signature) = token.split_once('.').ok_or_else(invalid)?;
+        let expected = sha256::hmac(&self.secret, format!("{}:{}", kind, id).as_bytes());
+        if !crypto::equal(&unhex(signature).map_err(|_| invalid())?, &expected) {
+            return Err(invalid());
+        }
+        Ok(hex(&sha256::digest(id.as_bytes())))
+    }
+
+    // Phương thức record để ghi bản ghi vào nhật ký rồi áp dụng nó vào trạng thái trong bộ nhớ
+    fn record(&self, data: &mut Data, record: Record) -> io::Result<()> {
+        self.storage
+            .lock()
+            .unwrap()
+            .append(LOG, record.render().as_bytes())?;
+        apply(data, record);
+        Ok(())
+    }
+}
+
+// Hàm apply áp dụng một bản ghi vào trạng thái của các phiên
+fn apply(data: &mut Data, record: Record) {
+    match record {
+        Record::Family(id, principal) => {
+            data.next = data.next.max(id);
+            data.families.insert(
+                id,
+                Family {
+                    principal,
+                    refresh: String::new(),
+                    used: BTreeSet::new(),
+                    ended: false,
+                },
+            );
+        }
+        Record::Access(key, family, created, expires) => {
+            data.sessions.insert(
+                key,
+                Entry {
+                    family,
+                    created,
+                    expires,
+                    seen: AtomicU64::new(created),
+                },
+            );
+        }
+        Record::Refresh(id, key) => {
+            if let Some(family) = data.families.get_mut(&id) {
+                let old = std::mem::replace(&mut family.refresh, key);
+                if !old.is_empty() {
+                    family.used.insert(old);
+                }
+            }
+        }
+        Record::Touch(key, time) => {
+            if let Some(entry) = data.sessions.get(&key) {
+                entry.seen.fetch_max(time, Ordering::Relaxed);
+            }
+        }
+        Record::Revoke(key, expires) => {
+            data.sessions.remove(&key);
+            data.revoked.insert(key, expires);
+        }
+        Record::End(id) => {
+            if let Some(family) = data.families.get_mut(&id) {
+                family.ended = true;
+            }
+        }
+        Record::Role(user, role) => {
+            for family in data.families.values_mut() {
+                if family.principal.user == user {
+                    family.principal.role = role;
+                }
+            }
+        }
+    }
+}
+
+// Hàm denied tạo lỗi từ chối truy cập
+fn denied(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::PermissionDenied, message.to_string())
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::storage::tests::{create_temp_storage, reopen};
+    use std::fs;
+    use std::thread;
+
+    // Khai báo một hàm để tạo người dùng đã xác thực làm đầu vào cho start
+    fn user(id: u64, name: &str, business: &str) -> Principal {
+        Principal::new(id, name, business, Role::Staff, 0)
+    }
+
+    // Khai báo một hàm để kiểm tra hạn của phiên, thu hồi và việc mở lại từ nhật ký
+    #[test]
+    fn test_sessions() {
+        let storage = create_temp_storage("sessions", Access::Private).unwrap();
+        let path = storage.path().to_path_buf();
+        let bus = Arc::new(Bus::new());
+        let sessions = Sessions::open(storage, bus.clone()).unwrap();
+
+        // Mã giả, mã bị sửa và mã làm mới không dùng được làm mã phiên
+        let session = sessions.start(&user(3, "chi", "alpha"), 1000).unwrap();
+        assert_eq!(sessions.validate(&session.token, 1000).unwrap().user(), 3);
+        let (id, _) = session.token.split_once('.').unwrap();
+        let forged = format!("{}.{}", id, "00".repeat(32));
+        assert!(sessions.validate(&forged, 1000).is_err());
+        assert!(sessions.validate("garbage", 1000).is_err());
+        assert!(sessions.validate(&session.refresh, 1000).is_err());
+
+        // Phiên hết hạn khi không dùng quá IDLE giây, và không quá SESSION giây kể từ khi tạo
+        assert!(sessions.validate(&session.token, 1000 + IDLE).is_err());
+        let mut now = 1000;
+        while now + IDLE / 2 < 1000 + SESSION {
+            now += IDLE / 2;
+            sessions.validate(&session.token, now).unwrap();
+        }
+        assert!(sessions.validate(&session.token, 1000 + SESSION).is_err());
+
+        // Thu hồi một phiên không ảnh hưởng phiên khác của cùng người dùng
+        let phone = sessions.start(&user(1, "an", "alpha"), 2000).unwrap();
+        let laptop = sessions.start(&user(1, "an", "alpha"), 2000).unwrap();
+        let other = sessions.start(&user(2, "binh", "alpha"), 2000).unwrap();
+        sessions.revoke(&phone.token).unwrap();
+        let error = sessions.validate(&phone.token, 2000).unwrap_err();
+        assert_eq!(error.to_string(), "Session revoked");
+        assert!(sessions.refresh(&phone.refresh, 2000).is_err());
+        assert!(sessions.revoke(&phone.token).is_err());
+        sessions.validate(&laptop.token, 2100).unwrap();
+        assert_eq!(sessions.active(1, 2100), 1);
+
+        // Đăng xuất mọi thiết bị
+        let tablet = sessions.start(&user(1, "an", "alpha"), 2100).unwrap();
+        assert_eq!(sessions.revoke_all(1).unwrap(), 2);
+        assert!(sessions.validate(&laptop.token, 2100).is_err());
+        assert!(sessions.validate(&tablet.token, 2100).is_err());
+        assert!(sessions.refresh(&tablet.refresh, 2100).is_err());
+        assert_eq!(sessions.active(1, 2100), 0);
+
+        // Mở lại từ nhật ký: phiên còn hiệu lực vẫn dùng được, phiên đã thu hồi vẫn bị từ chối
+        drop(sessions);
+        let sessions = Sessions::open(reopen(&path).unwrap(), bus.clone()).unwrap();
+        assert_eq!(
+            sessions.validate(&other.token, 2100).unwrap().name(),
+            "binh"
+        );
+        assert!(sessions.validate(&laptop.token, 2100).is_err());
+
+        // Thời điểm dùng gần nhất cũng được ghi lại nên phiên không hết hạn sai sau khi mở lại
+        sessions.validate(&other.token, 2000 + IDLE - 10).unwrap();
+        drop(sessions);
+        let sessions = Sessions::open(reopen(&path).unwrap(), bus).unwrap();
+        sessions.validate(&other.token, 2000 + IDLE + 10).unwrap();
+
+        // Dọn dẹp bỏ mục đã hết hạn và viết gọn nhật ký mà không làm mất phiên còn hiệu lực
+        let now = 2000 + IDLE + 10;
+        sessions.purge(now).unwrap();
+        sessions.validate(&other.token, now).unwrap();
+        assert!(sessions.validate(&laptop.token, now).is_err());
+        drop(sessions);
+        let sessions = Sessions::open(reopen(&path).unwrap(), Arc::new(Bus::new())).unwrap();
+        sessions.validate(&other.token, now).unwrap();
+        sessions.purge(now + REFRESH).unwrap();
+        assert!(sessions.validate(&other.token, now).is_err());
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra việc làm mới phiên và phát hiện mã làm mới bị dùng lại
+    #[test]
+    fn test_refresh() {
+        let storage = create_temp_storage("refresh", Access::Private).unwrap();
+        let path = storage.path().to_path_buf();
+        let bus = Arc::new(Bus::new());
+        let events = bus.subscribe();
+        let sessions = Sessions::open(storage, bus).unwrap();
+
+        // Làm mới cho phiên mới và mã làm mới mới; phiên cũ vẫn còn tới khi hết hạn
+        let first = sessions.start(&user(1, "an", "alpha"), 0).unwrap();
+        assert!(sessions.refresh(&first.token, 0).is_err());
+        let second = sessions.refresh(&first.refresh, 100).unwrap();
+        assert_ne!(second.token, first.token);
+        assert_ne!(second.refresh, first.refresh);
+        sessions.validate(&second.token, 100).unwrap();
+        let third = sessions.refresh(&second.refresh, 200).unwrap();
+
+        // Dùng lại mã làm mới cũ thì cả chuỗi phiên bị kết thúc
+        let error = sessions.refresh(&first.refresh, 300).unwrap_err();
+        assert_eq!(error.to_string(), "Refresh token reuse detected");
+        assert!(sessions.validate(&third.token, 300).is_err());
+        assert!(sessions.refresh(&third.refresh, 300).is_err());
+        assert_eq!(
+            events.try_iter().collect::<Vec<_>>(),
+            [Event::RefreshReused {
+                business: "alpha".to_string(),
+                user: "an".to_string(),
+            }]
+        );
+
+        // Chuỗi phiên không làm mới được sau REFRESH giây kể từ khi đăng nhập
+        let session = sessions.start(&user(1, "an", "alpha"), 0).unwrap();
+        assert!(sessions.refresh(&session.refresh, REFRESH).is_err());
+
+        // Việc dùng lại vẫn bị phát hiện sau khi mở lại từ nhật ký
+        let session = sessions.start(&user(1, "an", "alpha"), 0).unwrap();
+        let renewed = sessions.refresh(&session.refresh, 10).unwrap();
+        drop(sessions);
+        let sessions = Sessions::open(reopen(&path).unwrap(), Arc::new(Bus::new())).unwrap();
+        assert!(sessions.refresh(&session.refresh, 20).is_err());
+        assert!(sessions.validate(&renewed.token, 20).is_err());
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra nhiều luồng cùng kiểm tra phiên
+    #[test]
+    fn test_threads() {
+        let storage = create_temp_storage("threads", Access::Private).unwrap();
+        let path = storage.path().to_path_buf();
+        let sessions = Arc::new(Sessions::open(storage, Arc::new(Bus::new())).unwrap());
+        let session = sessions.start(&user(1, "an", "alpha"), 0).unwrap();
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let sessions = sessions.clone();
+                let token = session.token.clone();
+                thread::spawn(move || {
+                    for now in 0..200 {
+                        sessions.validate(&token, i * 200 + now).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(sessions.active(1, 1599), 1);
+
+        fs::remove_dir_all(path).unwrap();
+    }
+}
@@ -0,0 +1,513 @@
+// Khai báo các module con của ngân hàng
+pub mod invoice;
+pub mod statement;
+
+// Khai báo các thư viện cần thiết
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use crate::audit::{self, Audit};
+use crate::auth::role::Action;
+use crate::auth::{self, Principal};
+use crate::clock;
+
+// Mã tài khoản đại diện cho thế giới bên ngoài sổ cái (nạp và rút tiền)
+pub const EXTERNAL: u64 = 0;
+
+// Khai báo một enum để đại diện cho các loại tiền tệ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Currency {
+    Vnd, // Việt Nam đồng (không có đơn vị lẻ)
+    Usd, // Đô la Mỹ (2 chữ số lẻ)
+    Eur, // Euro (2 chữ số lẻ)
+}
+
+// Khai báo các phương thức cho enum Currency
+impl Currency {
+    // Phương thức code trả về mã ISO 4217 của tiền tệ
+    pub fn code(&self) -> &'static str {
+        match self {
+            Currency::Vnd => "VND",
+            Currency::Usd => "USD",
+            Currency::Eur => "EUR",
+        }
+    }
+
+    // Phương thức scale trả về số chữ số lẻ của tiền tệ
+    pub fn scale(&self) -> u32 {
+        match self {
+            Currency::Vnd => 0,
+            Currency::Usd | Currency::Eur => 2,
+        }
+    }
+}
+
+// Khai báo cách hiển thị enum Currency thành chuỗi
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+// Khai báo một struct để đại diện cho một khoản tiền (tính bằng đơn vị nhỏ nhất của tiền tệ)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Money {
+    pub amount: i64,        // Số tiền tính bằng đơn vị nhỏ nhất (ví dụ: cent)
+    pub currency: Currency, // Loại tiền tệ
+}
+
+// Khai báo các phương thức cho struct Money
+impl Money {
+    // Phương thức new để tạo một khoản tiền mới
+    pub fn new(amount: i64, currency: Currency) -> Money {
+        Money { amount, currency }
+    }
+
+    // Phương thức zero để tạo một khoản tiền bằng 0
+    pub fn zero(currency: Currency) -> Money {
+        Money::new(0, currency)
+    }
+
+    // Phương thức plus để cộng hai khoản tiền cùng loại tiền tệ
+    pub fn plus(&self, other: Money) -> io::Result<Money> {
+        // Kiểm tra xem hai khoản tiền có cùng loại tiền tệ hay không
+        self.check(other)?;
+
+        // Cộng có kiểm tra tràn số
+        let amount = self.amount.checked_add(other.amount).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "Money overflow".to_string())
+        })?;
+
+        Ok(Money::new(amount, self.currency))
+    }
+
+    // Phương thức minus để trừ hai khoản tiền cùng loại tiền tệ
+    pub fn minus(&self, other: Money) -> io::Result<Money> {
+        // Kiểm tra xem hai khoản tiền có cùng loại tiền tệ hay không
+        self.check(other)?;
+
+        // Trừ có kiểm tra tràn số
+        let amount = self.amount.checked_sub(other.amount).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "Money overflow".to_string())
+        })?;
+
+        Ok(Money::new(amount, self.currency))
+    }
+
+    // Phương thức decimal trả về số tiền dạng thập phân không kèm mã tiền tệ (ví dụ: "-12.50")
+    pub fn decimal(&self) -> String {
+        // Lấy ra số chữ số lẻ của tiền tệ
+        let scale = self.currency.scale();
+
+        // Nếu không có đơn vị lẻ, trả về số nguyên
+        if scale == 0 {
+            return self.amount.to_string();
+        }
+
+        // Tách phần nguyên và phần lẻ
+        let unit = 10u64.pow(scale);
+        let sign = if self.amount < 0 { "-" } else { "" };
+        let whole = self.amount.unsigned_abs() / unit;
+        let frac = self.amount.unsigned_abs() % unit;
+
+        format!("{}{}.{:0width$}", sign, whole, frac, width = scale as usize)
+    }
+
+    // Phương thức check để kiểm tra hai khoản tiền có cùng loại tiền tệ hay không
+    pub fn check(&self, other: Money) -> io::Result<()> {
+        if self.currency != other.currency {
+            // Nếu khác loại tiền tệ, trả về một lỗi io với thông báo
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Currency mismatch: {} and {}",
+                    self.currency, other.currency
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+// Khai báo cách hiển thị struct Money thành chuỗi (ví dụ: "12.50 USD")
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.decimal(), self.currency)
+    }
+}
+
+// Khai báo một struct để đại diện cho một tài khoản ngân hàng của doanh nghiệp
+#[derive(Debug, Clone, PartialEq)]
+pub struct Account {
+    pub id: u64,            // Mã của tài khoản
+    pub owner: String,      // Tên doanh nghiệp sở hữu tài khoản
+    pub currency: Currency, // Loại tiền tệ của tài khoản
+    pub balance: i64,       // Số dư (tính bằng đơn vị nhỏ nhất của tiền tệ)
+}
+
+// Khai báo một struct để đại diện cho một bút toán trong sổ cái
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub id: u64,       // Mã của bút toán
+    pub time: u64,     // Thời điểm ghi sổ (giây kể từ mốc UNIX)
+    pub from: u64,     // Tài khoản bị ghi nợ (EXTERNAL nếu là nạp tiền)
+    pub to: u64,       // Tài khoản được ghi có (EXTERNAL nếu là rút tiền)
+    pub amount: Money, // Số tiền của bút toán
+    pub memo: String,  // Nội dung của bút toán
+}
+
+// Khai báo một struct để chứa dữ liệu của sổ cái (được bảo vệ bởi khóa)
+#[derive(Debug, Default)]
+struct Book {
+    accounts: HashMap<u64, Account>, // Danh sách các tài khoản theo mã
+    entries: Vec<Entry>,             // Danh sách các bút toán theo thứ tự ghi sổ
+    next: u64,                       // Mã tiếp theo cho tài khoản và bút toán
+}
+
+// Khai báo một struct để đại diện cho sổ cái ngân hàng (an toàn khi dùng chung giữa các luồng)
+#[derive(Debug, Default)]
+pub struct Ledger {
+    book: Mutex<Book>,         // Dữ liệu của sổ cái
+    audit: Option<Arc<Audit>>, // Nhật ký kiểm toán ghi lại mọi bút toán (nếu có)
+}
+
+// Khai báo các phương thức cho struct Ledger
+impl Ledger {
+    // Phương thức new để tạo một sổ cái rỗng
+    pub fn new() -> Ledger {
+        Ledger::default()
+    }
+
+    // Phương thức with_audit để ghi mọi lần nạp, rút và chuyển tiền vào nhật ký kiểm toán
+    pub fn with_audit(mut self, audit: Arc<Audit>) -> Ledger {
+        self.audit = Some(audit);
+        self
+    }
+
+    // Phương thức open để mở một tài khoản mới cho doanh nghiệp và trả về mã tài khoản
+    pub fn open(&self, owner: &str, currency: Currency) -> u64 {
+        // Lấy khóa ghi của sổ cái
+        let mut book = self.book.lock().unwrap();
+
+        // Cấp mã mới cho tài khoản (mã 0 dành cho EXTERNAL)
+        book.next += 1;
+        let id = book.next;
+
+        // Thêm tài khoản mới vào sổ cái
+        book.accounts.insert(
+            id,
+            Account {
+                id,
+                owner: owner.to_string(),
+                currency,
+                balance: 0,
+            },
+        );
+
+        id
+    }
+
+    // Phương thức account để lấy thông tin của một tài khoản
+    pub fn account(&self, id: u64) -> io::Result<Account> {
+        self.book
+            .lock()
+            .unwrap()
+            .accounts
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| missing(id))
+    }
+
+    // Phương thức balance để lấy số dư của một tài khoản
+    pub fn balance(&self, id: u64) -> io::Result<Money> {
+        let account = self.account(id)?;
+        Ok(Money::new(account.balance, account.currency))
+    }
+
+    // Phương thức deposit để nạp tiền từ bên ngoài vào một tài khoản của doanh nghiệp người dùng
+    pub fn deposit(
+        &self,
+        principal: &Principal,
+        id: u64,
+        amount: Money,
+        memo: &str,
+    ) -> io::Result<u64> {
+        self.authorize(principal, id)?;
+        self.post(principal, EXTERNAL, id, amount, memo)
+    }
+
+    // Phương thức withdraw để rút tiền từ một tài khoản của doanh nghiệp người dùng ra bên ngoài
+    pub fn withdraw(
+        &self,
+        principal: &Principal,
+        id: u64,
+        amount: Money,
+        memo: &str,
+    ) -> io::Result<u64> {
+        self.authorize(principal, id)?;
+        self.post(principal, id, EXTERNAL, amount, memo)
+    }
+
+    // Phương thức transfer để chuyển tiền từ một tài khoản của doanh nghiệp người dùng sang tài khoản khác
+    pub fn transfer(
+        &self,
+        principal: &Principal,
+        from: u64,
+        to: u64,
+        amount: Money,
+        memo: &str,
+    ) -> io::Result<u64> {
+        // Hai tài khoản phải khác nhau và đều nằm trong sổ cái
+        if from == to || from == EXTERNAL || to == EXTERNAL {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid transfer from {} to {}", from, to),
+            ));
+        }
+        self.authorize(principal, from)?;
+        self.post(principal, from, to, amount, memo)
+    }
+
+    // Phương thức history để lấy danh sách các bút toán liên quan tới một tài khoản
+    pub fn history(&self, id: u64) -> Vec<Entry> {
+        self.book
+            .lock()
+            .unwrap()
+            .entries
+            .iter()
+            .filter(|entry| entry.from == id || entry.to == id)
+            .cloned()
+            .collect()
+    }
+
+    // Phương thức authorize để kiểm tra người dùng được chuyển tiền và tài khoản thuộc doanh nghiệp của họ
+    fn authorize(&self, principal: &Principal, id: u64) -> io::Result<()> {
+        auth::authorize(principal, Action::BankTransfer, &format!("account {}", id))?;
+        if self.account(id)?.owner != principal.business() {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("Account {} does not belong to {}", id, principal.business()),
+            ));
+        }
+        Ok(())
+    }
+
+    // Phương thức post để ghi một bút toán và cập nhật số dư (toàn bộ thực hiện dưới một khóa,
+    // kể cả mục kiểm toán để thứ tự trong nhật ký trùng với thứ tự ghi sổ)
+    fn post(
+        &self,
+        principal: &Principal,
+        from: u64,
+        to: u64,
+        amount: Money,
+        memo: &str,
+    ) -> io::Result<u64> {
+        // Số tiền phải dương
+        if amount.amount <= 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Amount must be positive: {}", amount),
+            ));
+        }
+
+        // Lấy khóa ghi của sổ cái
+        let mut book = self.book.lock().unwrap();
+
+        // Kiểm tra các tài khoản tham gia bút toán
+        for id in [from, to] {
+            if id == EXTERNAL {
+                continue;
+            }
+            let account = book.accounts.get(&id).ok_or_else(|| missing(id))?;
+            if account.currency != amount.currency {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Account {} holds {}, not {}",
+                        id, account.currency, amount.currency
+                    ),
+                ));
+            }
+        }
+
+        // Kiểm tra số dư của tài khoản bị ghi nợ
+        if from != EXTERNAL && book.accounts[&from].balance < amount.amount {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Insufficient funds in account {}", from),
+            ));
+        }
+
+        // Ghi bút toán vào nhật ký kiểm toán cùng số dư trước và sau (trước khi cập nhật,
+        // để bút toán không được ghi sổ nếu không ghi được nhật ký)
+        if let Some(audit) = &self.audit {
+            let action = match (from, to) {
+                (EXTERNAL, _) => "deposit",
+                (_, EXTERNAL) => "withdraw",
+                _ => "transfer",
+            };
+            let resource = format!("account {} -> account {}: {}", from, to, amount);
+            let before = balances(&book, from, to, 0);
+            let after = balances(&book, from, to, amount.amount);
+            audit.record(&audit::actor(principal), action, &resource, &before, &after)?;
+        }
+
+        // Cập nhật số dư của hai tài khoản
+        if let Some(account) = book.accounts.get_mut(&from) {
+            account.balance -= amount.amount;
+        }
+        if let Some(account) = book.accounts.get_mut(&to) {
+            account.balance += amount.amount;
+        }
+
+        // Ghi bút toán vào sổ cái
+        book.next += 1;
+        let id = book.next;
+        book.entries.push(Entry {
+            id,
+            time: clock::now(),
+            from,
+            to,
+            amount,
+            memo: memo.to_string(),
+        });
+
+        Ok(id)
+    }
+}
+
+// Hàm balances mô tả số dư của các tài khoản tham gia một bút toán sau khi chuyển một số tiền
+// (0 cho số dư hiện tại), bỏ qua EXTERNAL
+fn balances(book: &Book, from: u64, to: u64, amount: i64) -> String {
+    [(from, -amount), (to, amount)]
+        .iter()
+        .filter_map(|(id, delta)| book.accounts.get(id).map(|account| (account, delta)))
+        .map(|(account, delta)| {
+            let balance = Money::new(account.balance + delta, account.currency);
+            format!("account {}: {}", account.id, balance)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Hàm missing tạo lỗi io cho tài khoản không tồn tại
+fn missing(id: u64) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("Account {} not found", id))
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::auth::role::Role;
+    use crate::auth::tests::principal;
+    use std::fs;
+
+    // Khai báo một hàm để kiểm tra cách hiển thị của struct Money
+    #[test]
+    fn test_money() {
+        assert_eq!(Money::new(1250, Currency::Usd).to_string(), "12.50 USD");
+        assert_eq!(Money::new(-5, Currency::Eur).to_string(), "-0.05 EUR");
+        assert_eq!(Money::new(20000, Currency::Vnd).to_string(), "20000 VND");
+
+        // Cộng hai khoản tiền khác loại tiền tệ bị từ chối
+        assert!(Money::zero(Currency::Usd)
+            .plus(Money::zero(Currency::Eur))
+            .is_err());
+    }
+
+    // Khai báo một hàm để kiểm tra phương thức transfer của struct Ledger
+    #[test]
+    fn test_transfer() {
+        // Tạo sổ cái với hai tài khoản
+        let ledger = Ledger::new();
+        let alpha = ledger.open("alpha", Currency::Usd);
+        let beta = ledger.open("beta", Currency::Usd);
+        let owner = principal("alpha");
+
+        // Nạp tiền vào tài khoản thứ nhất rồi chuyển sang tài khoản thứ hai
+        ledger
+            .deposit(&owner, alpha, Money::new(1000, Currency::Usd), "seed")
+            .unwrap();
+        ledger
+            .transfer(&owner, alpha, beta, Money::new(400, Currency::Usd), "pay")
+            .unwrap();
+
+        // Kiểm tra số dư và lịch sử giao dịch
+        assert_eq!(ledger.balance(alpha).unwrap().amount, 600);
+        assert_eq!(ledger.balance(beta).unwrap().amount, 400);
+        assert_eq!(ledger.history(alpha).len(), 2);
+        assert_eq!(ledger.history(beta).len(), 1);
+
+        // Chuyển quá số dư hoặc sai loại tiền tệ bị từ chối
+        assert!(ledger
+            .transfer(&owner, alpha, beta, Money::new(601, Currency::Usd), "x")
+            .is_err());
+        assert!(ledger
+            .transfer(&owner, alpha, beta, Money::new(1, Currency::Eur), "x")
+            .is_err());
+        assert_eq!(ledger.balance(alpha).unwrap().amount, 600);
+
+        // Không được chuyển tiền từ tài khoản của doanh nghiệp khác, hoặc khi vai trò không cho phép
+        let money = Money::new(1, Currency::Usd);
+        let error = ledger
+            .transfer(&principal("beta"), alpha, beta, money, "x")
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        let staff = Principal::new(1, "staff", "alpha", Role::Staff, u64::MAX);
+        assert!(ledger.transfer(&staff, alpha, beta, money, "x").is_err());
+        assert!(ledger.withdraw(&staff, alpha, money, "x").is_err());
+        let accountant = Principal::new(2, "accountant", "alpha", Role::Accountant, u64::MAX);
+        ledger.withdraw(&accountant, alpha, money, "x").unwrap();
+        assert_eq!(ledger.balance(alpha).unwrap().amount, 599);
+    }
+
+    // Khai báo một hàm để kiểm tra việc ghi các bút toán vào nhật ký kiểm toán
+    #[test]
+    fn test_audit() {
+        let (audit, path) = crate::audit::tests::audit();
+        let ledger = Ledger::new().with_audit(audit.clone());
+        let alpha = ledger.open("alpha", Currency::Usd);
+        let beta = ledger.open("beta", Currency::Usd);
+        let owner = principal("alpha");
+
+        // Nạp, chuyển và rút tiền đều được ghi cùng số dư trước và sau
+        ledger
+            .deposit(&owner, alpha, Money::new(1000, Currency::Usd), "seed")
+            .unwrap();
+        ledger
+            .transfer(&owner, alpha, beta, Money::new(400, Currency::Usd), "pay")
+            .unwrap();
+        ledger
+            .withdraw(&owner, alpha, Money::new(100, Currency::Usd), "cash")
+            .unwrap();
+
+        // Bút toán bị từ chối thì không được ghi
+        assert!(ledger
+            .transfer(&owner, alpha, beta, Money::new(9999, Currency::Usd), "x")
+            .is_err());
+
+        let entries = audit.entries().unwrap();
+        let actions: Vec<&str> = entries.iter().map(|entry| entry.action.as_str()).collect();
+        assert_eq!(actions, ["deposit", "transfer", "withdraw"]);
+        assert_eq!(entries[1].actor, "tester@alpha");
+        assert_eq!(
+            entries[1].resource,
+            format!("account {} -> account {}: 4.00 USD", alpha, beta)
+        );
+        assert_eq!(
+            entries[1].before,
+            format!("account {}: 10.00 USD, account {}: 0.00 USD", alpha, beta)
+        );
+        assert_eq!(
+            entries[1].after,
+            format!("account {}: 6.00 USD, account {}: 4.00 USD", alpha, beta)
+        );
+        assert_eq!(entries[2].after, format!("account {}: 5.00 USD", alpha));
+
+        fs::remove_dir_all(path).unwrap();
+    }
+}
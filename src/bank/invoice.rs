@@ -0,0 +1,613 @@
+// Khai báo các thư viện cần thiết
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use super::{Currency, Ledger, Money};
+use crate::auth::role::Action;
+use crate::auth::{self, Principal};
+use crate::clock;
+use crate::event::{Bus, Event};
+use crate::storage::Storage;
+
+// Khai báo một struct để đại diện cho một dòng hàng trên hóa đơn
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    pub description: String, // Mô tả hàng hóa hoặc dịch vụ
+    pub quantity: u32,       // Số lượng
+    pub price: Money,        // Đơn giá
+    pub tax: u32,            // Thuế suất tính bằng phần vạn (1000 = 10%)
+}
+
+// Khai báo các phương thức cho struct Line
+impl Line {
+    // Phương thức amount trả về thành tiền trước thuế của dòng hàng (lỗi nếu tràn số)
+    pub fn amount(&self) -> io::Result<i64> {
+        self.price
+            .amount
+            .checked_mul(self.quantity as i64)
+            .ok_or_else(overflow)
+    }
+
+    // Phương thức levy trả về tiền thuế của dòng hàng (làm tròn nửa lên, lỗi nếu tràn số)
+    pub fn levy(&self) -> io::Result<i64> {
+        self.amount()?
+            .checked_mul(self.tax as i64)
+            .and_then(|levy| levy.checked_add(5_000))
+            .map(|levy| levy / 10_000)
+            .ok_or_else(overflow)
+    }
+}
+
+// Khai báo một enum để đại diện cho các trạng thái của hóa đơn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum State {
+    Draft,   // Đang soạn, còn có thể thêm dòng hàng
+    Issued,  // Đã xuất và gửi cho khách hàng
+    Partial, // Đã thanh toán một phần
+    Paid,    // Đã thanh toán đủ
+    Void,    // Đã huỷ
+}
+
+// Khai báo một struct để đại diện cho một hóa đơn giữa hai doanh nghiệp
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invoice {
+    pub id: u64,             // Mã của hóa đơn
+    pub issuer: String,      // Doanh nghiệp xuất hóa đơn
+    pub customer: String,    // Doanh nghiệp nhận hóa đơn
+    pub currency: Currency,  // Loại tiền tệ của hóa đơn
+    pub lines: Vec<Line>,    // Danh sách các dòng hàng
+    pub due: u64,            // Hạn thanh toán (giây kể từ mốc UNIX)
+    pub issued: Option<u64>, // Thời điểm xuất hóa đơn
+    pub paid: i64,           // Số tiền đã thanh toán
+    pub state: State,        // Trạng thái của hóa đơn
+}
+
+// Khai báo các phương thức cho struct Invoice
+impl Invoice {
+    // Phương thức subtotal trả về tổng tiền trước thuế
+    pub fn subtotal(&self) -> io::Result<Money> {
+        self.sum(Line::amount)
+    }
+
+    // Phương thức tax trả về tổng tiền thuế
+    pub fn tax(&self) -> io::Result<Money> {
+        self.sum(Line::levy)
+    }
+
+    // Phương thức total trả về tổng tiền phải thanh toán
+    pub fn total(&self) -> io::Result<Money> {
+        self.subtotal()?.plus(self.tax()?)
+    }
+
+    // Phương thức outstanding trả về số tiền còn nợ
+    pub fn outstanding(&self) -> io::Result<Money> {
+        self.total()?.minus(Money::new(self.paid, self.currency))
+    }
+
+    // Phương thức sum cộng một đại lượng của các dòng hàng có kiểm tra tràn số
+    fn sum(&self, value: fn(&Line) -> io::Result<i64>) -> io::Result<Money> {
+        self.lines
+            .iter()
+            .try_fold(Money::zero(self.currency), |sum, line| {
+                sum.plus(Money::new(value(line)?, self.currency))
+            })
+    }
+
+    // Phương thức overdue kiểm tra hóa đơn đã quá hạn tại thời điểm cho trước hay chưa
+    pub fn overdue(&self, now: u64) -> bool {
+        matches!(self.state, State::Issued | State::Partial) && now > self.due
+    }
+
+    // Phương thức file trả về tên tập tin của bản in hóa đơn trong đối tượng lưu trữ
+    pub fn file(&self) -> String {
+        format!("invoice-{}.txt", self.id)
+    }
+
+    // Phương thức render để tạo bản in dạng văn bản của hóa đơn
+    pub fn render(&self) -> io::Result<String> {
+        // Tạo một chuỗi để lưu trữ bản in
+        let mut text = String::new();
+
+        // Thêm phần đầu của hóa đơn
+        text.push_str(&format!("Invoice: {}\n", self.id));
+        text.push_str(&format!("Issuer: {}\n", self.issuer));
+        text.push_str(&format!("Customer: {}\n", self.customer));
+        text.push_str(&format!("Due: {}\n", self.due));
+
+        // Thêm từng dòng hàng
+        for line in &self.lines {
+            text.push_str(&format!(
+                "- {} x{} @ {} = {} (tax {})\n",
+                line.description,
+                line.quantity,
+                line.price,
+                Money::new(line.amount()?, self.currency),
+                Money::new(line.levy()?, self.currency),
+            ));
+        }
+
+        // Thêm phần tổng cộng
+        text.push_str(&format!("Subtotal: {}\n", self.subtotal()?));
+        text.push_str(&format!("Tax: {}\n", self.tax()?));
+        text.push_str(&format!("Total: {}\n", self.total()?));
+
+        Ok(text)
+    }
+}
+
+// Khai báo một struct để quản lý hóa đơn, kết nối với sổ cái và kênh sự kiện
+#[derive(Debug)]
+pub struct Biller {
+    ledger: Arc<Ledger>,                    // Sổ cái dùng để ghi các khoản thanh toán
+    bus: Arc<Bus>,                          // Kênh sự kiện để công bố thay đổi của hóa đơn
+    invoices: Mutex<HashMap<u64, Invoice>>, // Danh sách các hóa đơn theo mã
+}
+
+// Khai báo các phương thức cho struct Biller
+impl Biller {
+    // Phương thức new để tạo một bộ quản lý hóa đơn mới
+    pub fn new(ledger: Arc<Ledger>, bus: Arc<Bus>) -> Biller {
+        Biller {
+            ledger,
+            bus,
+            invoices: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Phương thức request để yêu cầu một doanh nghiệp khác xuất hóa đơn
+    pub fn request(&self, customer: &str, issuer: &str, memo: &str) {
+        self.bus.publish(Event::InvoiceRequested {
+            customer: customer.to_string(),
+            issuer: issuer.to_string(),
+            memo: memo.to_string(),
+        });
+    }
+
+    // Phương thức draft để người dùng của bên xuất tạo một hóa đơn nháp mới và trả về mã của nó
+    pub fn draft(
+        &self,
+        principal: &Principal,
+        customer: &str,
+        currency: Currency,
+        due: u64,
+    ) -> io::Result<u64> {
+        // Hóa đơn luôn được xuất bởi doanh nghiệp của người dùng
+        let issuer = principal.business();
+        authorize(principal, issuer)?;
+
+        // Lấy khóa của danh sách hóa đơn
+        let mut invoices = self.invoices.lock().unwrap();
+
+        // Cấp mã mới cho hóa đơn
+        let id = invoices.len() as u64 + 1;
+
+        // Thêm hóa đơn nháp vào danh sách
+        invoices.insert(
+            id,
+            Invoice {
+                id,
+                issuer: issuer.to_string(),
+                customer: customer.to_string(),
+                currency,
+                lines: Vec::new(),
+                due,
+                issued: None,
+                paid: 0,
+                state: State::Draft,
+            },
+        );
+
+        Ok(id)
+    }
+
+    // Phương thức get để lấy một bản sao của hóa đơn
+    pub fn get(&self, id: u64) -> io::Result<Invoice> {
+        self.invoices
+            .lock()
+            .unwrap()
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| missing(id))
+    }
+
+    // Phương thức add để người dùng của bên xuất thêm một dòng hàng vào hóa đơn nháp
+    pub fn add(&self, principal: &Principal, id: u64, line: Line) -> io::Result<()> {
+        // Lấy khóa của danh sách hóa đơn
+        let mut invoices = self.invoices.lock().unwrap();
+        let invoice = invoices.get_mut(&id).ok_or_else(|| missing(id))?;
+        authorize(principal, &invoice.issuer)?;
+
+        // Chỉ có thể thêm dòng hàng khi hóa đơn còn là bản nháp
+        expect(invoice, &[State::Draft])?;
+
+        // Đơn giá phải cùng loại tiền tệ với hóa đơn và không được âm (thuế suất không âm nên tiền thuế cũng vậy)
+        Money::zero(invoice.currency).check(line.price)?;
+        if line.price.amount < 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Price of {} is negative", line.description),
+            ));
+        }
+
+        // Tổng tiền của hóa đơn sau khi thêm dòng hàng không được tràn số
+        invoice.lines.push(line);
+        if let Err(error) = invoice.total() {
+            invoice.lines.pop();
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    // Phương thức issue để xuất hóa đơn: lưu bản in vào đối tượng lưu trữ của bên xuất và chia sẻ cho khách hàng.
+    // Gọi lại sau một lần xuất bị lỗi giữa chừng sẽ tiếp tục từ bước còn dở (bản in đã lưu hoặc đã chia sẻ được giữ)
+    pub fn issue(
+        &self,
+        principal: &Principal,
+        id: u64,
+        storage: &mut Storage,
+        customer: &mut Storage,
+    ) -> io::Result<()> {
+        // Lấy khóa của danh sách hóa đơn
+        let mut invoices = self.invoices.lock().unwrap();
+        let invoice = invoices.get_mut(&id).ok_or_else(|| missing(id))?;
+        authorize(principal, &invoice.issuer)?;
+
+        // Bản in chỉ được lưu vào đối tượng lưu trữ của bên xuất và chia sẻ cho đúng khách hàng
+        for (target, owner) in [
+            (&*storage, &invoice.issuer),
+            (&*customer, &invoice.customer),
+        ] {
+            if target.owner() != owner {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!("Storage {} does not belong to {}", target.name(), owner),
+                ));
+            }
+        }
+
+        // Chỉ có thể xuất hóa đơn nháp có ít nhất một dòng hàng
+        expect(invoice, &[State::Draft])?;
+        if invoice.lines.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invoice {} has no lines", id),
+            ));
+        }
+
+        // Lưu bản in vào đối tượng lưu trữ của bên xuất (ghi đè bản in cũ nếu khác) và chia sẻ cho khách hàng
+        let file = invoice.file();
+        let text = invoice.render()?;
+        if !storage.list()?.contains(&file) {
+            storage.put(&file, text.as_bytes())?;
+        } else if storage.view(&file)? != text {
+            storage.edit(&file, &text)?;
+        }
+        if !customer.list()?.contains(&file) {
+            storage.share(&file, customer)?;
+        } else if customer.view(&file)? != text {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("Customer already has a different {}", file),
+            ));
+        }
+
+        // Cập nhật trạng thái của hóa đơn
+        invoice.issued = Some(clock::now());
+        invoice.state = State::Issued;
+
+        // Công bố sự kiện hóa đơn đã được xuất
+        self.bus.publish(Event::InvoiceIssued {
+            invoice: id,
+            issuer: invoice.issuer.clone(),
+            customer: invoice.customer.clone(),
+            total: invoice.total()?,
+        });
+
+        Ok(())
+    }
+
+    // Phương thức pay để người dùng của khách hàng thanh toán (một phần hoặc toàn bộ) hóa đơn bằng
+    // chuyển khoản trên sổ cái
+    pub fn pay(
+        &self,
+        principal: &Principal,
+        id: u64,
+        from: u64,
+        to: u64,
+        amount: Money,
+    ) -> io::Result<u64> {
+        // Lấy khóa của danh sách hóa đơn
+        let mut invoices = self.invoices.lock().unwrap();
+        let invoice = invoices.get_mut(&id).ok_or_else(|| missing(id))?;
+
+        // Chỉ có thể thanh toán hóa đơn đã xuất hoặc đã thanh toán một phần
+        expect(invoice, &[State::Issued, State::Partial])?;
+
+        // Tiền phải đi từ tài khoản của khách hàng tới tài khoản của bên xuất hóa đơn
+        for (account, owner) in [(from, &invoice.customer), (to, &invoice.issuer)] {
+            if &self.ledger.account(account)?.owner != owner {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!("Account {} does not belong to {}", account, owner),
+                ));
+            }
+        }
+
+        // Số tiền thanh toán không được vượt quá số tiền còn nợ
+        let outstanding = invoice.outstanding()?;
+        let remaining = outstanding.minus(amount)?;
+        if remaining.amount < 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Payment {} exceeds outstanding {}", amount, outstanding),
+            ));
+        }
+
+        // Ghi khoản chuyển tiền vào sổ cái
+        let entry =
+            self.ledger
+                .transfer(principal, from, to, amount, &format!("Invoice {}", id))?;
+
+        // Cập nhật số tiền đã thanh toán và trạng thái của hóa đơn
+        invoice.paid += amount.amount;
+        invoice.state = if remaining.amount == 0 {
+            State::Paid
+        } else {
+            State::Partial
+        };
+
+        // Công bố sự kiện hóa đơn nhận được thanh toán
+        self.bus.publish(Event::InvoicePaid {
+            invoice: id,
+            amount,
+            remaining,
+        });
+
+        Ok(entry)
+    }
+
+    // Phương thức void để người dùng của bên xuất huỷ một hóa đơn chưa nhận khoản thanh toán nào
+    pub fn void(&self, principal: &Principal, id: u64) -> io::Result<()> {
+        // Lấy khóa của danh sách hóa đơn
+        let mut invoices = self.invoices.lock().unwrap();
+        let invoice = invoices.get_mut(&id).ok_or_else(|| missing(id))?;
+        authorize(principal, &invoice.issuer)?;
+
+        // Chỉ có thể huỷ hóa đơn nháp hoặc đã xuất nhưng chưa thanh toán
+        expect(invoice, &[State::Draft, State::Issued])?;
+        invoice.state = State::Void;
+
+        // Công bố sự kiện hóa đơn bị huỷ
+        self.bus.publish(Event::InvoiceVoided { invoice: id });
+
+        Ok(())
+    }
+}
+
+// Hàm authorize kiểm tra người dùng được chi tiền và thuộc doanh nghiệp xuất hóa đơn
+fn authorize(principal: &Principal, issuer: &str) -> io::Result<()> {
+    auth::authorize(
+        principal,
+        Action::BankTransfer,
+        &format!("invoices of {}", issuer),
+    )?;
+    if principal.business() != issuer {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} may not bill for {}", principal.business(), issuer),
+        ));
+    }
+    Ok(())
+}
+
+// Hàm expect kiểm tra hóa đơn đang ở một trong các trạng thái cho phép
+fn expect(invoice: &Invoice, states: &[State]) -> io::Result<()> {
+    if !states.contains(&invoice.state) {
+        // Nếu không, trả về một lỗi io với thông báo
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invoice {} is {:?}", invoice.id, invoice.state),
+        ));
+    }
+    Ok(())
+}
+
+// Hàm overflow tạo lỗi io cho số tiền vượt quá giới hạn
+fn overflow() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "Money overflow".to_string())
+}
+
+// Hàm missing tạo lỗi io cho hóa đơn không tồn tại
+fn missing(id: u64) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("Invoice {} not found", id))
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::auth::role::Role;
+    use crate::auth::tests::principal;
+    use crate::storage::tests::create_temp_storage;
+    use crate::storage::Access;
+    use std::fs;
+
+    // Khai báo một hàm để tạo dòng hàng với đơn giá USD cho trước
+    fn line(quantity: u32, price: i64, tax: u32) -> Line {
+        Line {
+            description: "Widget".to_string(),
+            quantity,
+            price: Money::new(price, Currency::Usd),
+            tax,
+        }
+    }
+
+    // Khai báo một hàm để kiểm tra cách tính tổng tiền của hóa đơn
+    #[test]
+    fn test_total() {
+        // Tạo bộ quản lý hóa đơn và một hóa đơn nháp
+        let biller = Biller::new(Arc::new(Ledger::new()), Arc::new(Bus::new()));
+        let issuer = principal("alpha");
+        let id = biller.draft(&issuer, "beta", Currency::Usd, 0).unwrap();
+
+        // Thêm hai dòng hàng với thuế suất 10% và 5%
+        biller.add(&issuer, id, line(3, 1000, 1000)).unwrap();
+        biller.add(&issuer, id, line(1, 999, 500)).unwrap();
+
+        // Kiểm tra tổng tiền trước thuế, tiền thuế và tổng cộng
+        let invoice = biller.get(id).unwrap();
+        assert_eq!(invoice.subtotal().unwrap().amount, 3999);
+        assert_eq!(invoice.tax().unwrap().amount, 300 + 50);
+        assert_eq!(invoice.total().unwrap().amount, 4349);
+
+        // Dòng hàng khác loại tiền tệ bị từ chối
+        let mut other = line(1, 1, 0);
+        other.price.currency = Currency::Eur;
+        assert!(biller.add(&issuer, id, other).is_err());
+
+        // Đơn giá âm bị từ chối
+        assert!(biller.add(&issuer, id, line(1, -1, 1000)).is_err());
+
+        // Dòng hàng làm tràn tổng tiền bị từ chối và hóa đơn giữ nguyên
+        assert!(line(u32::MAX, i64::MAX / 2, 0).amount().is_err());
+        assert!(biller
+            .add(&issuer, id, line(2, i64::MAX / 2, 1000))
+            .is_err());
+        assert!(biller
+            .add(&issuer, id, line(1, i64::MAX - 4000, 0))
+            .is_err());
+        assert_eq!(biller.get(id).unwrap().lines.len(), 2);
+    }
+
+    // Khai báo một hàm để kiểm tra chỉ người dùng của bên xuất mới được soạn, xuất và huỷ hóa đơn
+    #[test]
+    fn test_issuer() {
+        let biller = Biller::new(Arc::new(Ledger::new()), Arc::new(Bus::new()));
+        let issuer = principal("alpha");
+        let id = biller.draft(&issuer, "beta", Currency::Usd, 0).unwrap();
+        biller.add(&issuer, id, line(1, 100, 0)).unwrap();
+
+        // Doanh nghiệp khác và nhân viên không có quyền chi tiền đều bị từ chối
+        let other = principal("beta");
+        let staff = Principal::new(1, "staff", "alpha", Role::Staff, u64::MAX);
+        assert!(biller.draft(&staff, "beta", Currency::Usd, 0).is_err());
+        assert!(biller.add(&other, id, line(1, 100, 0)).is_err());
+        assert!(biller.add(&staff, id, line(1, 100, 0)).is_err());
+        assert!(biller.void(&other, id).is_err());
+
+        // Bản in không được lưu vào đối tượng lưu trữ của doanh nghiệp khác
+        let mut storage = create_temp_storage("alpha", Access::Private).unwrap();
+        let mut customer = create_temp_storage("beta", Access::Private).unwrap();
+        let mut stranger = create_temp_storage("gamma", Access::Private).unwrap();
+        assert!(biller
+            .issue(&other, id, &mut customer, &mut storage)
+            .is_err());
+        let wrong = biller.issue(&issuer, id, &mut storage, &mut stranger);
+        assert_eq!(wrong.unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+        assert!(stranger.list().unwrap().is_empty());
+        assert_eq!(biller.get(id).unwrap().state, State::Draft);
+        biller.void(&issuer, id).unwrap();
+
+        // Xoá các đối tượng lưu trữ khỏi hệ thống tập tin
+        fs::remove_dir_all(storage.path()).unwrap();
+        fs::remove_dir_all(customer.path()).unwrap();
+        fs::remove_dir_all(stranger.path()).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra việc xuất và thanh toán hóa đơn
+    #[test]
+    fn test_pay() {
+        // Tạo sổ cái với hai tài khoản và nạp tiền cho khách hàng
+        let ledger = Arc::new(Ledger::new());
+        let seller = ledger.open("alpha", Currency::Usd);
+        let buyer = ledger.open("beta", Currency::Usd);
+        let payer = principal("beta");
+        ledger
+            .deposit(&payer, buyer, Money::new(10_000, Currency::Usd), "seed")
+            .unwrap();
+
+        // Tạo kênh sự kiện và đăng ký một người nhận
+        let bus = Arc::new(Bus::new());
+        let events = bus.subscribe();
+
+        // Tạo hai đối tượng lưu trữ cho bên xuất và khách hàng
+        let mut storage = create_temp_storage("alpha", Access::Private).unwrap();
+        let mut customer = create_temp_storage("beta", Access::Private).unwrap();
+
+        // Tạo và xuất một hóa đơn
+        let biller = Biller::new(ledger.clone(), bus);
+        let issuer = principal("alpha");
+        let id = biller.draft(&issuer, "beta", Currency::Usd, 0).unwrap();
+        assert!(biller
+            .issue(&issuer, id, &mut storage, &mut customer)
+            .is_err());
+        biller.add(&issuer, id, line(2, 2500, 0)).unwrap();
+        biller
+            .issue(&issuer, id, &mut storage, &mut customer)
+            .unwrap();
+
+        // Bản in của hóa đơn được lưu và chia sẻ cho khách hàng
+        assert!(customer
+            .view("invoice-1.txt")
+            .unwrap()
+            .contains("Total: 50.00 USD"));
+
+        // Hóa đơn đã xuất thì không thể thêm dòng hàng
+        assert!(biller.add(&issuer, id, line(1, 1, 0)).is_err());
+
+        // Tiền phải đi từ tài khoản của khách hàng tới tài khoản của bên xuất
+        let elsewhere = ledger.open("gamma", Currency::Usd);
+        let wrong = biller.pay(&payer, id, buyer, elsewhere, Money::new(100, Currency::Usd));
+        assert_eq!(wrong.unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+
+        // Thanh toán một phần rồi toàn bộ
+        biller
+            .pay(&payer, id, buyer, seller, Money::new(2000, Currency::Usd))
+            .unwrap();
+        assert_eq!(biller.get(id).unwrap().state, State::Partial);
+        assert!(biller
+            .pay(&payer, id, buyer, seller, Money::new(3001, Currency::Usd))
+            .is_err());
+        biller
+            .pay(&payer, id, buyer, seller, Money::new(3000, Currency::Usd))
+            .unwrap();
+        assert_eq!(biller.get(id).unwrap().state, State::Paid);
+        assert_eq!(ledger.balance(seller).unwrap().amount, 5000);
+
+        // Hóa đơn đã thanh toán thì không thể huỷ
+        assert!(biller.void(&issuer, id).is_err());
+
+        // Xuất lại sau khi lần trước dừng giữa chừng (bản in đã lưu nhưng chưa chia sẻ)
+        let second = biller.draft(&issuer, "beta", Currency::Usd, 0).unwrap();
+        biller.add(&issuer, second, line(1, 100, 0)).unwrap();
+        let invoice = biller.get(second).unwrap();
+        storage
+            .put(&invoice.file(), invoice.render().unwrap().as_bytes())
+            .unwrap();
+        biller
+            .issue(&issuer, second, &mut storage, &mut customer)
+            .unwrap();
+        assert!(customer
+            .view("invoice-2.txt")
+            .unwrap()
+            .contains("Total: 1.00 USD"));
+
+        // Kiểm tra các sự kiện đã được công bố
+        let events: Vec<Event> = events.try_iter().collect();
+        assert!(matches!(events[0], Event::InvoiceIssued { invoice: 1, .. }));
+        assert_eq!(
+            events[2],
+            Event::InvoicePaid {
+                invoice: 1,
+                amount: Money::new(3000, Currency::Usd),
+                remaining: Money::zero(Currency::Usd),
+            }
+        );
+
+        // Xoá hai đối tượng lưu trữ khỏi hệ thống tập tin
+        fs::remove_dir_all(storage.path()).unwrap();
+        fs::remove_dir_all(customer.path()).unwrap();
+    }
+}
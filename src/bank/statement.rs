@@ -0,0 +1,419 @@
+// Khai báo các thư viện cần thiết
+use std::io;
+
+use super::{Account, Ledger, Money, EXTERNAL};
+use crate::auth::Principal;
+use crate::clock;
+use crate::storage::{Access, Storage};
+use crate::xml::Writer;
+
+// Không gian tên của định dạng ISO 20022 camt.053
+const CAMT: &str = "urn:iso:std:iso:20022:tech:xsd:camt.053.001.02";
+
+// Khai báo một enum để đại diện cho các định dạng xuất sao kê
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Csv,  // Bảng tính CSV
+    Camt, // Tài liệu XML theo ISO 20022 camt.053
+}
+
+// Khai báo các phương thức cho enum Format
+impl Format {
+    // Phương thức extension trả về phần mở rộng của tập tin
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::Camt => "xml",
+        }
+    }
+}
+
+// Khai báo một struct để đại diện cho một dòng trong sao kê
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    pub id: u64,              // Mã bút toán trong sổ cái
+    pub time: u64,            // Thời điểm ghi sổ
+    pub amount: i64,          // Số tiền (dương là ghi có, âm là ghi nợ)
+    pub counterparty: String, // Doanh nghiệp đối ứng (hoặc EXTERNAL)
+    pub memo: String,         // Nội dung của bút toán
+}
+
+// Khai báo một struct để đại diện cho sao kê của một tài khoản trong khoảng thời gian [from, to)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statement {
+    pub account: Account, // Tài khoản được sao kê
+    pub from: u64,        // Thời điểm bắt đầu (bao gồm)
+    pub to: u64,          // Thời điểm kết thúc (không bao gồm)
+    pub opening: Money,   // Số dư đầu kỳ
+    pub closing: Money,   // Số dư cuối kỳ
+    pub items: Vec<Item>, // Các giao dịch trong kỳ
+}
+
+// Khai báo các phương thức cho struct Statement
+impl Statement {
+    // Phương thức build để người dùng của doanh nghiệp sở hữu tài khoản lập sao kê của tài khoản đó từ sổ cái
+    pub fn build(
+        ledger: &Ledger,
+        principal: &Principal,
+        id: u64,
+        from: u64,
+        to: u64,
+    ) -> io::Result<Statement> {
+        // Kiểm tra quyền giống như khi chuyển tiền từ tài khoản
+        ledger.authorize(principal, id)?;
+
+        // Khoảng thời gian phải hợp lệ
+        if from > to {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid range {}..{}", from, to),
+            ));
+        }
+
+        // Lấy thông tin tài khoản và lịch sử giao dịch
+        let account = ledger.account(id)?;
+        let mut opening = 0;
+        let mut items = Vec::new();
+
+        // Duyệt qua lịch sử giao dịch theo thứ tự ghi sổ
+        for entry in ledger.history(id) {
+            // Xác định chiều của giao dịch và doanh nghiệp đối ứng
+            let (amount, other) = if entry.to == id {
+                (entry.amount.amount, entry.from)
+            } else {
+                (-entry.amount.amount, entry.to)
+            };
+
+            // Giao dịch trước kỳ được cộng vào số dư đầu kỳ
+            if entry.time < from {
+                opening += amount;
+            } else if entry.time < to {
+                items.push(Item {
+                    id: entry.id,
+                    time: entry.time,
+                    amount,
+                    counterparty: owner(ledger, other),
+                    memo: entry.memo,
+                });
+            }
+        }
+
+        // Số dư cuối kỳ bằng số dư đầu kỳ cộng các giao dịch trong kỳ
+        let closing = opening + items.iter().map(|item| item.amount).sum::<i64>();
+
+        Ok(Statement {
+            opening: Money::new(opening, account.currency),
+            closing: Money::new(closing, account.currency),
+            account,
+            from,
+            to,
+            items,
+        })
+    }
+
+    // Phương thức file trả về tên tập tin của sao kê theo định dạng cho trước (lần xuất thứ hai trở đi của cùng
+    // kỳ sao kê được thêm số thứ tự, ví dụ: `statement-1-2026-01-01-2026-02-01-2.csv`)
+    pub fn file(&self, format: Format, seq: u32) -> String {
+        let suffix = if seq > 1 {
+            format!("-{}", seq)
+        } else {
+            String::new()
+        };
+        format!(
+            "statement-{}-{}-{}{}.{}",
+            self.account.id,
+            clock::date(self.from),
+            clock::date(self.to),
+            suffix,
+            format.extension()
+        )
+    }
+
+    // Phương thức csv để xuất sao kê dạng CSV (mỗi giao dịch một dòng)
+    pub fn csv(&self) -> String {
+        // Dòng tiêu đề
+        let mut out = String::from("id,time,direction,amount,currency,counterparty,memo\n");
+
+        // Thêm từng giao dịch
+        for item in &self.items {
+            out.push_str(&format!(
+                "{},{},{},{},{},{},{}\n",
+                item.id,
+                clock::stamp(item.time),
+                indicator(item.amount),
+                Money::new(item.amount.abs(), self.account.currency).decimal(),
+                self.account.currency,
+                quote(&item.counterparty),
+                quote(&item.memo),
+            ));
+        }
+
+        out
+    }
+
+    // Phương thức camt để xuất sao kê dạng XML theo ISO 20022 camt.053
+    pub fn camt(&self) -> String {
+        // Lấy thời điểm tạo tài liệu và mã của sao kê
+        let now = clock::stamp(clock::now());
+        let id = format!("{}-{}-{}", self.account.id, self.from, self.to);
+        let currency = self.account.currency.code();
+
+        // Mở tài liệu và phần đầu của thông điệp
+        let mut xml = Writer::new();
+        xml.open("Document", &[("xmlns", CAMT)])
+            .open("BkToCstmrStmt", &[])
+            .open("GrpHdr", &[])
+            .leaf("MsgId", &[], &id)
+            .leaf("CreDtTm", &[], &now)
+            .close();
+
+        // Phần thông tin của sao kê và tài khoản
+        xml.open("Stmt", &[])
+            .leaf("Id", &[], &id)
+            .leaf("CreDtTm", &[], &now)
+            .open("FrToDt", &[])
+            .leaf("FrDtTm", &[], &clock::stamp(self.from))
+            .leaf("ToDtTm", &[], &clock::stamp(self.to))
+            .close()
+            .open("Acct", &[])
+            .open("Id", &[])
+            .open("Othr", &[])
+            .leaf("Id", &[], &self.account.id.to_string())
+            .close()
+            .close()
+            .leaf("Ccy", &[], currency)
+            .open("Ownr", &[])
+            .leaf("Nm", &[], &self.account.owner)
+            .close()
+            .close();
+
+        // Số dư đầu kỳ (OPBD) và cuối kỳ (CLBD)
+        balance(&mut xml, "OPBD", self.opening, self.from);
+        balance(&mut xml, "CLBD", self.closing, self.to);
+
+        // Từng giao dịch trong kỳ
+        for item in &self.items {
+            xml.open("Ntry", &[])
+                .leaf("NtryRef", &[], &item.id.to_string())
+                .leaf(
+                    "Amt",
+                    &[("Ccy", currency)],
+                    &Money::new(item.amount.abs(), self.account.currency).decimal(),
+                )
+                .leaf("CdtDbtInd", &[], indicator(item.amount))
+                .open("Sts", &[])
+                .leaf("Cd", &[], "BOOK")
+                .close()
+                .open("BookgDt", &[])
+                .leaf("DtTm", &[], &clock::stamp(item.time))
+                .close()
+                .open("ValDt", &[])
+                .leaf("Dt", &[], &clock::date(item.time))
+                .close()
+                .open("NtryDtls", &[])
+                .open("TxDtls", &[])
+                .open("RltdPties", &[])
+                .open(if item.amount < 0 { "Cdtr" } else { "Dbtr" }, &[])
+                .leaf("Nm", &[], &item.counterparty)
+                .close()
+                .close()
+                .close()
+                .close()
+                .leaf("AddtlNtryInf", &[], &item.memo)
+                .close();
+        }
+
+        xml.finish()
+    }
+
+    // Phương thức export để lưu sao kê vào đối tượng lưu trữ với quyền truy cập Private
+    pub fn export(&self, storage: &mut Storage, format: Format) -> io::Result<String> {
+        self.save(storage, format, Access::Private)
+    }
+
+    // Phương thức save để lưu sao kê vào đối tượng lưu trữ với quyền truy cập cho trước
+    pub fn save(
+        &self,
+        storage: &mut Storage,
+        format: Format,
+        access: Access,
+    ) -> io::Result<String> {
+        // Tạo nội dung theo định dạng
+        let content = match format {
+            Format::Csv => self.csv(),
+            Format::Camt => self.camt(),
+        };
+
+        // Ghi tập tin với tên chưa dùng (không ghi đè lần xuất trước) cùng với quyền truy cập của nó
+        let existing = storage.list()?;
+        let name = (1..)
+            .map(|seq| self.file(format, seq))
+            .find(|name| !existing.contains(name))
+            .unwrap();
+        storage.put_with_access(&name, content.as_bytes(), access)?;
+
+        Ok(name)
+    }
+}
+
+// Hàm balance ghi một phần số dư (Bal) của camt.053
+fn balance(xml: &mut Writer, code: &str, amount: Money, time: u64) {
+    xml.open("Bal", &[])
+        .open("Tp", &[])
+        .open("CdOrPrtry", &[])
+        .leaf("Cd", &[], code)
+        .close()
+        .close()
+        .leaf(
+            "Amt",
+            &[("Ccy", amount.currency.code())],
+            &Money::new(amount.amount.abs(), amount.currency).decimal(),
+        )
+        .leaf("CdtDbtInd", &[], indicator(amount.amount))
+        .open("Dt", &[])
+        .leaf("Dt", &[], &clock::date(time))
+        .close()
+        .close();
+}
+
+// Hàm indicator trả về mã ghi có (CRDT) hoặc ghi nợ (DBIT) theo dấu của số tiền
+fn indicator(amount: i64) -> &'static str {
+    if amount < 0 {
+        "DBIT"
+    } else {
+        "CRDT"
+    }
+}
+
+// Hàm owner trả về tên doanh nghiệp sở hữu tài khoản (hoặc EXTERNAL)
+fn owner(ledger: &Ledger, id: u64) -> String {
+    if id == EXTERNAL {
+        return "EXTERNAL".to_string();
+    }
+    ledger
+        .account(id)
+        .map(|account| account.owner)
+        .unwrap_or_else(|_| id.to_string())
+}
+
+// Hàm quote đặt một trường CSV trong dấu nháy kép khi cần
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::auth::tests::principal;
+    use crate::bank::Currency;
+    use crate::storage::tests::create_temp_storage;
+    use std::fs;
+
+    // Khai báo một hàm để tạo sổ cái có vài giao dịch
+    fn ledger() -> (Ledger, u64) {
+        let ledger = Ledger::new();
+        let alpha = ledger.open("alpha", Currency::Usd);
+        let beta = ledger.open("beta, ltd", Currency::Usd);
+        let owner = principal("alpha");
+        ledger
+            .deposit(&owner, alpha, Money::new(10_000, Currency::Usd), "seed")
+            .unwrap();
+        ledger
+            .transfer(
+                &owner,
+                alpha,
+                beta,
+                Money::new(2_550, Currency::Usd),
+                "order \"7\"",
+            )
+            .unwrap();
+        (ledger, alpha)
+    }
+
+    // Khai báo một hàm để kiểm tra việc lập sao kê theo khoảng thời gian
+    #[test]
+    fn test_build() {
+        let (ledger, alpha) = ledger();
+        let owner = principal("alpha");
+        let now = clock::now();
+
+        // Sao kê bao gồm toàn bộ giao dịch
+        let statement = Statement::build(&ledger, &owner, alpha, 0, now + 1).unwrap();
+        assert_eq!(statement.opening.amount, 0);
+        assert_eq!(statement.closing.amount, 7_450);
+        assert_eq!(statement.items.len(), 2);
+        assert_eq!(statement.items[1].amount, -2_550);
+        assert_eq!(statement.items[1].counterparty, "beta, ltd");
+
+        // Sao kê sau các giao dịch chỉ có số dư đầu kỳ
+        let later = Statement::build(&ledger, &owner, alpha, now + 1, now + clock::DAY).unwrap();
+        assert_eq!(later.opening.amount, 7_450);
+        assert_eq!(later.closing.amount, 7_450);
+        assert!(later.items.is_empty());
+
+        // Khoảng thời gian ngược bị từ chối
+        assert!(Statement::build(&ledger, &owner, alpha, 2, 1).is_err());
+
+        // Người dùng của doanh nghiệp khác không được lập sao kê
+        let other = Statement::build(&ledger, &principal("beta"), alpha, 0, now + 1);
+        assert_eq!(other.unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+    }
+
+    // Khai báo một hàm để kiểm tra các định dạng xuất và việc lưu sao kê
+    #[test]
+    fn test_export() {
+        let (ledger, alpha) = ledger();
+        let owner = principal("alpha");
+        let statement = Statement::build(&ledger, &owner, alpha, 0, clock::now() + 1).unwrap();
+
+        // Kiểm tra nội dung CSV (các trường đặc biệt được đặt trong dấu nháy kép)
+        let csv = statement.csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].contains(",CRDT,100.00,USD,EXTERNAL,seed"));
+        assert!(lines[2].ends_with(",DBIT,25.50,USD,\"beta, ltd\",\"order \"\"7\"\"\""));
+
+        // Kiểm tra nội dung camt.053
+        let camt = statement.camt();
+        assert!(
+            camt.contains("<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:camt.053.001.02\">")
+        );
+        assert!(camt.contains("<Cd>CLBD</Cd>"));
+        assert!(camt.contains("<Amt Ccy=\"USD\">74.50</Amt>"));
+        assert!(camt.contains("<AddtlNtryInf>order &quot;7&quot;</AddtlNtryInf>"));
+
+        // Lưu sao kê vào đối tượng lưu trữ Public: tập tin vẫn có quyền Private
+        let mut storage = create_temp_storage("statement", Access::Public).unwrap();
+        let name = statement.export(&mut storage, Format::Camt).unwrap();
+        assert!(name.ends_with(".xml"));
+        assert!(storage
+            .view(&name)
+            .unwrap()
+            .contains("<Amt Ccy=\"USD\">74.50</Amt>"));
+        assert_eq!(storage.file_access(&name).unwrap(), Access::Private);
+
+        // Lưu với quyền truy cập chỉ định
+        let name = statement
+            .save(&mut storage, Format::Csv, Access::Shared)
+            .unwrap();
+        assert_eq!(storage.view(&name).unwrap(), csv);
+        assert_eq!(storage.file_access(&name).unwrap(), Access::Shared);
+
+        // Xuất lại cùng kỳ tạo tập tin mới thay vì ghi đè
+        let again = statement
+            .save(&mut storage, Format::Csv, Access::Private)
+            .unwrap();
+        assert_eq!(again, statement.file(Format::Csv, 2));
+        assert_ne!(again, name);
+        assert_eq!(storage.file_access(&name).unwrap(), Access::Shared);
+
+        // Xoá đối tượng lưu trữ khỏi hệ thống tập tin
+        fs::remove_dir_all(storage.path()).unwrap();
+    }
+}
@@ -0,0 +1,71 @@
+// Khai báo các thư viện cần thiết
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Số giây trong một ngày
+pub const DAY: u64 = 86_400;
+
+// Hàm now trả về thời điểm hiện tại tính bằng giây kể từ mốc UNIX
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// Hàm civil chuyển số giây kể từ mốc UNIX thành (năm, tháng, ngày, giờ, phút, giây) theo lịch Gregory (UTC)
+pub fn civil(secs: u64) -> (i64, u32, u32, u32, u32, u32) {
+    // Tách phần ngày và phần giây trong ngày
+    let days = (secs / DAY) as i64;
+    let rest = secs % DAY;
+
+    // Thuật toán chuyển số ngày thành ngày dương lịch (theo chu kỳ 400 năm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (
+        year,
+        month,
+        day,
+        (rest / 3_600) as u32,
+        (rest % 3_600 / 60) as u32,
+        (rest % 60) as u32,
+    )
+}
+
+// Hàm date định dạng thời điểm thành ngày ISO 8601 (ví dụ: "2024-02-29")
+pub fn date(secs: u64) -> String {
+    let (year, month, day, ..) = civil(secs);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// Hàm stamp định dạng thời điểm thành ngày giờ ISO 8601 (ví dụ: "2024-02-29T13:05:00")
+pub fn stamp(secs: u64) -> String {
+    let (year, month, day, hour, minute, second) = civil(secs);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year, month, day, hour, minute, second
+    )
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+
+    // Khai báo một hàm để kiểm tra cách định dạng ngày giờ
+    #[test]
+    fn test_stamp() {
+        assert_eq!(stamp(0), "1970-01-01T00:00:00");
+        assert_eq!(date(951_782_400), "2000-02-29");
+        assert_eq!(stamp(1_709_211_900), "2024-02-29T13:05:00");
+        assert_eq!(date(4_102_444_799), "2099-12-31");
+    }
+}
@@ -0,0 +1,66 @@
+// Khai báo các module con của mật mã (cài đặt trong crate vì dự án chỉ dùng thư viện chuẩn)
+pub mod chacha;
+pub mod sha256;
+pub mod x25519;
+
+// Khai báo các thư viện cần thiết
+use std::fs::File;
+use std::io::{self, Read};
+
+// Hàm random điền các byte ngẫu nhiên an toàn từ hệ điều hành vào bộ đệm
+pub fn random(buf: &mut [u8]) -> io::Result<()> {
+    File::open("/dev/urandom")?.read_exact(buf)
+}
+
+// Hàm key tạo một khoá 32 byte ngẫu nhiên
+pub fn key() -> io::Result<[u8; 32]> {
+    let mut key = [0; 32];
+    random(&mut key)?;
+    Ok(key)
+}
+
+// Hàm equal so sánh hai dãy byte trong thời gian không phụ thuộc vào nội dung
+pub fn equal(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// Hàm hex biểu diễn các byte dưới dạng chuỗi thập lục phân chữ thường
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// Hàm unhex đọc lại chuỗi thập lục phân
+pub fn unhex(text: &str) -> io::Result<Vec<u8>> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("Invalid hex: {}", text));
+    if !text.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| {
+            text.get(i..i + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(invalid)
+        })
+        .collect()
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+
+    // Khai báo một hàm để kiểm tra các hàm tiện ích
+    #[test]
+    fn test_helpers() {
+        assert_eq!(hex(&[0, 0xab, 0x10]), "00ab10");
+        assert_eq!(unhex("00ab10").unwrap(), [0, 0xab, 0x10]);
+        assert!(unhex("abc").is_err());
+        assert!(unhex("zz").is_err());
+        assert!(equal(b"abc", b"abc"));
+        assert!(!equal(b"abc", b"abd"));
+        assert!(!equal(b"abc", b"ab"));
+        assert_ne!(key().unwrap(), key().unwrap());
+    }
+}
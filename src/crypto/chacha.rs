@@ -0,0 +1,287 @@
+// Mã hoá xác thực ChaCha20-Poly1305 theo RFC 8439 và HChaCha20 để dẫn xuất khoá
+
+// Khai báo các thư viện cần thiết
+use std::io;
+
+use crate::crypto::equal;
+
+// Khai báo độ dài của mã xác thực (tag) Poly1305
+pub const TAG: usize = 16;
+
+// Hàm quarter thực hiện một vòng quarter round trên trạng thái
+fn quarter(s: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    s[a] = s[a].wrapping_add(s[b]);
+    s[d] = (s[d] ^ s[a]).rotate_left(16);
+    s[c] = s[c].wrapping_add(s[d]);
+    s[b] = (s[b] ^ s[c]).rotate_left(12);
+    s[a] = s[a].wrapping_add(s[b]);
+    s[d] = (s[d] ^ s[a]).rotate_left(8);
+    s[c] = s[c].wrapping_add(s[d]);
+    s[b] = (s[b] ^ s[c]).rotate_left(7);
+}
+
+// Hàm rounds thực hiện 20 vòng (10 cặp vòng cột và vòng chéo)
+fn rounds(s: &mut [u32; 16]) {
+    for _ in 0..10 {
+        quarter(s, 0, 4, 8, 12);
+        quarter(s, 1, 5, 9, 13);
+        quarter(s, 2, 6, 10, 14);
+        quarter(s, 3, 7, 11, 15);
+        quarter(s, 0, 5, 10, 15);
+        quarter(s, 1, 6, 11, 12);
+        quarter(s, 2, 7, 8, 13);
+        quarter(s, 3, 4, 9, 14);
+    }
+}
+
+// Hàm setup tạo trạng thái ban đầu từ hằng số, khoá và 16 byte cuối (bộ đếm và nonce)
+fn setup(key: &[u8; 32], tail: &[u8; 16]) -> [u32; 16] {
+    let mut s = [0u32; 16];
+    s[..4].copy_from_slice(&[0x61707865, 0x3320646e, 0x79622d32, 0x6b206574]);
+    for i in 0..8 {
+        s[4 + i] = le32(&key[i * 4..]);
+    }
+    for i in 0..4 {
+        s[12 + i] = le32(&tail[i * 4..]);
+    }
+    s
+}
+
+// Hàm block tạo một khối 64 byte của dòng khoá ChaCha20
+pub fn block(key: &[u8; 32], counter: u32, nonce: &[u8; 12]) -> [u8; 64] {
+    let mut tail = [0; 16];
+    tail[..4].copy_from_slice(&counter.to_le_bytes());
+    tail[4..].copy_from_slice(nonce);
+    let input = setup(key, &tail);
+    let mut s = input;
+    rounds(&mut s);
+    let mut out = [0; 64];
+    for i in 0..16 {
+        out[i * 4..i * 4 + 4].copy_from_slice(&s[i].wrapping_add(input[i]).to_le_bytes());
+    }
+    out
+}
+
+// Hàm xor mã hoá (hoặc giải mã) dữ liệu tại chỗ bằng dòng khoá bắt đầu từ bộ đếm cho trước
+pub fn xor(key: &[u8; 32], counter: u32, nonce: &[u8; 12], data: &mut [u8]) {
+    for (i, chunk) in data.chunks_mut(64).enumerate() {
+        let stream = block(key, counter.wrapping_add(i as u32), nonce);
+        for (byte, k) in chunk.iter_mut().zip(stream) {
+            *byte ^= k;
+        }
+    }
+}
+
+// Hàm hchacha dẫn xuất một khoá con 32 byte từ khoá và nonce 16 byte
+pub fn hchacha(key: &[u8; 32], nonce: &[u8; 16]) -> [u8; 32] {
+    let mut s = setup(key, nonce);
+    rounds(&mut s);
+    let mut out = [0; 32];
+    for (i, word) in s[..4].iter().chain(&s[12..]).enumerate() {
+        out[i * 4..i * 4 + 4].copy_from_slice(&word.to_le_bytes());
+    }
+    out
+}
+
+// Hàm poly1305 tính mã xác thực của thông điệp bằng khoá dùng một lần
+pub fn poly1305(key: &[u8; 32], message: &[u8]) -> [u8; TAG] {
+    const MASK: u32 = 0x3ffffff;
+
+    // Tách r (đã kẹp bit) thành 5 phần 26 bit
+    let r = [
+        le32(&key[0..]) & 0x3ffffff,
+        (le32(&key[3..]) >> 2) & 0x3ffff03,
+        (le32(&key[6..]) >> 4) & 0x3ffc0ff,
+        (le32(&key[9..]) >> 6) & 0x3f03fff,
+        (le32(&key[12..]) >> 8) & 0x00fffff,
+    ];
+    let s = [r[1] * 5, r[2] * 5, r[3] * 5, r[4] * 5];
+    let mut h = [0u32; 5];
+
+    // Cộng từng khối 16 byte (thêm bit 1 ở cuối) rồi nhân với r modulo 2^130 - 5
+    for chunk in message.chunks(16) {
+        let mut m = [0u8; 17];
+        m[..chunk.len()].copy_from_slice(chunk);
+        m[chunk.len()] = 1;
+        let high = u32::from(m[16]) << 24;
+        h[0] += le32(&m[0..]) & MASK;
+        h[1] += (le32(&m[3..]) >> 2) & MASK;
+        h[2] += (le32(&m[6..]) >> 4) & MASK;
+        h[3] += (le32(&m[9..]) >> 6) & MASK;
+        h[4] += (le32(&m[12..]) >> 8) | high;
+
+        let [h0, h1, h2, h3, h4] = h.map(u64::from);
+        let [r0, r1, r2, r3, r4] = r.map(u64::from);
+        let [s1, s2, s3, s4] = s.map(u64::from);
+        let mut d = [
+            h0 * r0 + h1 * s4 + h2 * s3 + h3 * s2 + h4 * s1,
+            h0 * r1 + h1 * r0 + h2 * s4 + h3 * s3 + h4 * s2,
+            h0 * r2 + h1 * r1 + h2 * r0 + h3 * s4 + h4 * s3,
+            h0 * r3 + h1 * r2 + h2 * r1 + h3 * r0 + h4 * s4,
+            h0 * r4 + h1 * r3 + h2 * r2 + h3 * r1 + h4 * r0,
+        ];
+        for i in 0..4 {
+            d[i + 1] += d[i] >> 26;
+            h[i] = d[i] as u32 & MASK;
+        }
+        h[4] = d[4] as u32 & MASK;
+        let c = u64::from(h[0]) + (d[4] >> 26) * 5;
+        h[0] = c as u32 & MASK;
+        h[1] += (c >> 26) as u32;
+    }
+
+    // Lan truyền nhớ hoàn toàn
+    for i in 1..5 {
+        h[i] += h[i - 1] >> 26;
+        h[i - 1] &= MASK;
+    }
+    h[0] += (h[4] >> 26) * 5;
+    h[4] &= MASK;
+    h[1] += h[0] >> 26;
+    h[0] &= MASK;
+
+    // Tính g = h + 5 - 2^130 và chọn g nếu h >= 2^130 - 5 (không rẽ nhánh)
+    let mut g = [0u32; 5];
+    let mut carry = 5;
+    for i in 0..5 {
+        g[i] = h[i] + carry;
+        carry = g[i] >> 26;
+        g[i] &= MASK;
+    }
+    let select = (carry ^ 1).wrapping_sub(1);
+    for i in 0..5 {
+        h[i] = (h[i] & !select) | (g[i] & select);
+    }
+
+    // Ghép thành 128 bit rồi cộng s (16 byte cuối của khoá)
+    let words = [
+        h[0] | (h[1] << 26),
+        (h[1] >> 6) | (h[2] << 20),
+        (h[2] >> 12) | (h[3] << 14),
+        (h[3] >> 18) | (h[4] << 8),
+    ];
+    let mut tag = [0; TAG];
+    let mut f = 0u64;
+    for i in 0..4 {
+        f = u64::from(words[i]) + u64::from(le32(&key[16 + i * 4..])) + (f >> 32);
+        tag[i * 4..i * 4 + 4].copy_from_slice(&(f as u32).to_le_bytes());
+    }
+    tag
+}
+
+// Hàm mac tính mã xác thực AEAD trên dữ liệu phụ và bản mã
+fn mac(key: &[u8; 32], nonce: &[u8; 12], aad: &[u8], ciphertext: &[u8]) -> [u8; TAG] {
+    let mut otk = [0; 32];
+    otk.copy_from_slice(&block(key, 0, nonce)[..32]);
+    let pad = |len: usize| vec![0; (16 - len % 16) % 16];
+    let mut data = Vec::with_capacity(aad.len() + ciphertext.len() + 48);
+    data.extend_from_slice(aad);
+    data.extend(pad(aad.len()));
+    data.extend_from_slice(ciphertext);
+    data.extend(pad(ciphertext.len()));
+    data.extend_from_slice(&(aad.len() as u64).to_le_bytes());
+    data.extend_from_slice(&(ciphertext.len() as u64).to_le_bytes());
+    poly1305(&otk, &data)
+}
+
+// Hàm seal mã hoá và xác thực bản rõ, trả về bản mã kèm tag ở cuối
+pub fn seal(key: &[u8; 32], nonce: &[u8; 12], aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+    let mut out = plaintext.to_vec();
+    xor(key, 1, nonce, &mut out);
+    let tag = mac(key, nonce, aad, &out);
+    out.extend_from_slice(&tag);
+    out
+}
+
+// Hàm open kiểm tra tag rồi giải mã; sai khoá, nonce, dữ liệu phụ hoặc bản mã bị sửa đều trả về lỗi
+pub fn open(key: &[u8; 32], nonce: &[u8; 12], aad: &[u8], sealed: &[u8]) -> io::Result<Vec<u8>> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Authentication failed");
+    let split = sealed.len().checked_sub(TAG).ok_or_else(invalid)?;
+    let (ciphertext, tag) = sealed.split_at(split);
+    if !equal(&mac(key, nonce, aad, ciphertext), tag) {
+        return Err(invalid());
+    }
+    let mut out = ciphertext.to_vec();
+    xor(key, 1, nonce, &mut out);
+    Ok(out)
+}
+
+// Hàm le32 đọc một số 32 bit little-endian
+fn le32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+// Khai báo một module để chứa các unit test (các vectơ kiểm thử của RFC 8439 và draft XChaCha)
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::crypto::{hex, unhex};
+
+    // Khai báo một hàm để đọc một mảng có độ dài cố định từ chuỗi thập lục phân
+    fn array<const N: usize>(text: &str) -> [u8; N] {
+        unhex(text).unwrap().try_into().unwrap()
+    }
+
+    // Khai báo khoá 00 01 02 ... 1f dùng trong nhiều vectơ
+    fn sequential() -> [u8; 32] {
+        std::array::from_fn(|i| i as u8)
+    }
+
+    // Khai báo một hàm để kiểm tra khối ChaCha20 (RFC 8439 mục 2.3.2)
+    #[test]
+    fn test_block() {
+        let nonce = array("000000090000004a00000000");
+        assert_eq!(
+            hex(&block(&sequential(), 1, &nonce)),
+            "10f1e7e4d13b5915500fdd1fa32071c4c7d1f4c733c068030422aa9ac3d46c4e\
+             d2826446079faa0914c2d705d98b02a2b5129cd1de164eb9cbd083e8a2503c4e"
+        );
+    }
+
+    // Khai báo một hàm để kiểm tra Poly1305 (RFC 8439 mục 2.5.2)
+    #[test]
+    fn test_poly1305() {
+        let key = array("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b");
+        assert_eq!(
+            hex(&poly1305(&key, b"Cryptographic Forum Research Group")),
+            "a8061dc1305136c6c22b8baf0c0127a9"
+        );
+    }
+
+    // Khai báo một hàm để kiểm tra HChaCha20 (draft-irtf-cfrg-xchacha mục 2.2.1)
+    #[test]
+    fn test_hchacha() {
+        let nonce = array("000000090000004a0000000031415927");
+        assert_eq!(
+            hex(&hchacha(&sequential(), &nonce)),
+            "82413b4227b27bfed30e42508a877d73a0f9e4d58a74a853c12ec41326d3ecdc"
+        );
+    }
+
+    // Khai báo một hàm để kiểm tra AEAD ChaCha20-Poly1305 (RFC 8439 mục 2.8.2)
+    #[test]
+    fn test_aead() {
+        let key: [u8; 32] = std::array::from_fn(|i| 0x80 + i as u8);
+        let nonce = array("070000004041424344454647");
+        let aad = unhex("50515253c0c1c2c3c4c5c6c7").unwrap();
+        let plaintext = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+        let sealed = seal(&key, &nonce, &aad, plaintext);
+        assert_eq!(
+            hex(&sealed),
+            "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d6\
+             3dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b36\
+             92ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc\
+             3ff4def08e4b7a9de576d26586cec64b6116\
+             1ae10b594f09e26a7e902ecbd0600691"
+        );
+        assert_eq!(open(&key, &nonce, &aad, &sealed).unwrap(), plaintext);
+
+        // Sửa bất kỳ phần nào đều bị phát hiện
+        let mut tampered = sealed.clone();
+        tampered[0] ^= 1;
+        assert!(open(&key, &nonce, &aad, &tampered).is_err());
+        assert!(open(&key, &nonce, b"other", &sealed).is_err());
+        assert!(open(&key, &nonce, &aad, &sealed[..10]).is_err());
+    }
+}
@@ -0,0 +1,306 @@
+// Hàm băm SHA-256 (FIPS 180-4), HMAC-SHA256 (RFC 2104) và PBKDF2-HMAC-SHA256 (RFC 8018)
+
+// Khai báo độ dài của giá trị băm
+pub const LEN: usize = 32;
+
+// Khai báo độ dài khối của SHA-256
+const BLOCK: usize = 64;
+
+// Khai báo các hằng số vòng
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+// Khai báo một struct để băm dữ liệu theo từng phần
+#[derive(Debug, Clone)]
+pub struct Sha256 {
+    state: [u32; 8],     // Trạng thái băm
+    buffer: [u8; BLOCK], // Phần dữ liệu chưa đủ một khối
+    filled: usize,       // Số byte đang có trong buffer
+    length: u64,         // Tổng số byte đã nhận
+}
+
+// Triển khai trait Default cho Sha256
+impl Default for Sha256 {
+    fn default() -> Self {
+        Sha256::new()
+    }
+}
+
+// Khai báo các phương thức cho struct Sha256
+impl Sha256 {
+    // Phương thức new để bắt đầu một phép băm mới
+    pub fn new() -> Sha256 {
+        Sha256 {
+            state: [
+                0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+                0x5be0cd19,
+            ],
+            buffer: [0; BLOCK],
+            filled: 0,
+            length: 0,
+        }
+    }
+
+    // Phương thức update để thêm dữ liệu
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.length += data.len() as u64;
+        while !data.is_empty() {
+            let take = (BLOCK - self.filled).min(data.len());
+            self.buffer[self.filled..self.filled + take].copy_from_slice(&data[..take]);
+            self.filled += take;
+            data = &data[take..];
+            if self.filled == BLOCK {
+                let block = self.buffer;
+                self.compress(&block);
+                self.filled = 0;
+            }
+        }
+    }
+
+    // Phương thức finish để thêm phần đệm và trả về giá trị băm
+    pub fn finish(mut self) -> [u8; LEN] {
+        let bits = self.length * 8;
+        self.update(&[0x80]);
+        while self.filled != BLOCK - 8 {
+            self.update(&[0]);
+        }
+        self.update(&bits.to_be_bytes());
+        let mut out = [0; LEN];
+        for (i, word) in self.state.iter().enumerate() {
+            out[i * 4..i * 4 + 4].copy_from_slice(&word.to_be_bytes());
+        }
+        out
+    }
+
+    // Phương thức export trả về trạng thái đang băm (trạng thái, tổng số byte và phần chưa đủ một khối) để
+    // tiếp tục băm sau này bằng `resume`; phần chưa đủ khối là dữ liệu gốc nên cần được giữ kín như dữ liệu
+    pub fn export(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(LEN + 8 + self.filled);
+        for word in self.state {
+            out.extend_from_slice(&word.to_be_bytes());
+        }
+        out.extend_from_slice(&self.length.to_be_bytes());
+        out.extend_from_slice(&self.buffer[..self.filled]);
+        out
+    }
+
+    // Phương thức resume để tiếp tục một phép băm từ trạng thái do `export` trả về (None nếu không hợp lệ)
+    pub fn resume(bytes: &[u8]) -> Option<Sha256> {
+        let length = u64::from_be_bytes(bytes.get(LEN..LEN + 8)?.try_into().ok()?);
+        let tail = &bytes[LEN + 8..];
+        if tail.len() as u64 != length % BLOCK as u64 {
+            return None;
+        }
+        let mut hasher = Sha256::new();
+        for (word, chunk) in hasher.state.iter_mut().zip(bytes[..LEN].chunks(4)) {
+            *word = u32::from_be_bytes(chunk.try_into().unwrap());
+        }
+        hasher.buffer[..tail.len()].copy_from_slice(tail);
+        hasher.filled = tail.len();
+        hasher.length = length;
+        Some(hasher)
+    }
+
+    // Phương thức length trả về tổng số byte đã nhận
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+
+    // Phương thức compress để xử lý một khối 64 byte
+    fn compress(&mut self, block: &[u8; BLOCK]) {
+        let mut w = [0u32; 64];
+        for i in 0..16 {
+            w[i] = u32::from_be_bytes([
+                block[i * 4],
+                block[i * 4 + 1],
+                block[i * 4 + 2],
+                block[i * 4 + 3],
+            ]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (state, value) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+}
+
+// Hàm digest băm toàn bộ dữ liệu trong một lần
+pub fn digest(data: &[u8]) -> [u8; LEN] {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hasher.finish()
+}
+
+// Hàm hmac tính mã xác thực HMAC-SHA256
+pub fn hmac(key: &[u8], data: &[u8]) -> [u8; LEN] {
+    let (inner, outer) = pads(key);
+    let mut hasher = inner;
+    hasher.update(data);
+    let inside = hasher.finish();
+    let mut hasher = outer;
+    hasher.update(&inside);
+    hasher.finish()
+}
+
+// Hàm pbkdf2 dẫn xuất khoá dài `out.len()` byte từ mật khẩu, muối và số vòng lặp
+pub fn pbkdf2(password: &[u8], salt: &[u8], iterations: u32, out: &mut [u8]) {
+    // Tính trước trạng thái sau khối đệm trong và ngoài để mỗi vòng chỉ băm phần còn lại
+    let (inner, outer) = pads(password);
+    let prf = |data: &[u8]| {
+        let mut hasher = inner.clone();
+        hasher.update(data);
+        let inside = hasher.finish();
+        let mut hasher = outer.clone();
+        hasher.update(&inside);
+        hasher.finish()
+    };
+
+    for (index, chunk) in out.chunks_mut(LEN).enumerate() {
+        let mut first = salt.to_vec();
+        first.extend_from_slice(&(index as u32 + 1).to_be_bytes());
+        let mut u = prf(&first);
+        let mut t = u;
+        for _ in 1..iterations {
+            u = prf(&u);
+            for (t, u) in t.iter_mut().zip(u) {
+                *t ^= u;
+            }
+        }
+        chunk.copy_from_slice(&t[..chunk.len()]);
+    }
+}
+
+// Hàm pads tạo hai bộ băm đã nhận khối đệm trong (0x36) và ngoài (0x5c) của HMAC
+fn pads(key: &[u8]) -> (Sha256, Sha256) {
+    let mut block = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        block[..LEN].copy_from_slice(&digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let mut inner = Sha256::new();
+    inner.update(&block.map(|b| b ^ 0x36));
+    let mut outer = Sha256::new();
+    outer.update(&block.map(|b| b ^ 0x5c));
+    (inner, outer)
+}
+
+// Khai báo một module để chứa các unit test (vectơ của FIPS 180-2, RFC 4231 và RFC 7914)
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::crypto::hex;
+
+    // Khai báo một hàm để kiểm tra SHA-256
+    #[test]
+    fn test_digest() {
+        assert_eq!(
+            hex(&digest(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex(&digest(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hex(&digest(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+
+        // Băm theo từng phần cho cùng kết quả
+        let data: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+        let mut hasher = Sha256::new();
+        for chunk in data.chunks(37) {
+            hasher.update(chunk);
+        }
+        assert_eq!(hasher.finish(), digest(&data));
+
+        // Xuất trạng thái giữa chừng rồi tiếp tục cho cùng kết quả
+        let mut hasher = Sha256::new();
+        hasher.update(&data[..333]);
+        let mut resumed = Sha256::resume(&hasher.export()).unwrap();
+        assert_eq!(resumed.length(), 333);
+        resumed.update(&data[333..]);
+        assert_eq!(resumed.finish(), digest(&data));
+        assert!(Sha256::resume(&hasher.export()[..40]).is_none());
+        assert!(Sha256::resume(&[0; 12]).is_none());
+    }
+
+    // Khai báo một hàm để kiểm tra HMAC-SHA256 (RFC 4231 trường hợp 1, 2 và 6)
+    #[test]
+    fn test_hmac() {
+        assert_eq!(
+            hex(&hmac(&[0x0b; 20], b"Hi There")),
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"
+        );
+        assert_eq!(
+            hex(&hmac(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            hex(&hmac(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    // Khai báo một hàm để kiểm tra PBKDF2-HMAC-SHA256 (RFC 7914 mục 11)
+    #[test]
+    fn test_pbkdf2() {
+        let mut out = [0; 64];
+        pbkdf2(b"passwd", b"salt", 1, &mut out);
+        assert_eq!(
+            hex(&out),
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc\
+             49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783"
+        );
+        let mut out = [0; 32];
+        pbkdf2(b"password", b"salt", 4096, &mut out);
+        assert_eq!(
+            hex(&out),
+            "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a"
+        );
+    }
+}
@@ -0,0 +1,231 @@
+// Trao đổi khoá Diffie-Hellman X25519 theo RFC 7748
+
+// Khai báo kiểu phần tử của trường modulo 2^255 - 19 (5 phần 51 bit)
+type Fe = [u64; 5];
+
+// Khai báo mặt nạ 51 bit
+const MASK: u64 = (1 << 51) - 1;
+
+// Khai báo điểm cơ sở u = 9
+pub const BASE: [u8; 32] = {
+    let mut base = [0; 32];
+    base[0] = 9;
+    base
+};
+
+// Hàm public tính khoá công khai từ khoá bí mật
+pub fn public(secret: &[u8; 32]) -> [u8; 32] {
+    x25519(secret, &BASE)
+}
+
+// Hàm x25519 nhân vô hướng `scalar` (đã kẹp bit) với điểm có toạ độ `u`
+pub fn x25519(scalar: &[u8; 32], u: &[u8; 32]) -> [u8; 32] {
+    let mut k = *scalar;
+    k[0] &= 248;
+    k[31] &= 127;
+    k[31] |= 64;
+
+    // Thang Montgomery với hoán đổi không rẽ nhánh
+    let x1 = unpack(u);
+    let (mut x2, mut z2) = ([1, 0, 0, 0, 0], [0; 5]);
+    let (mut x3, mut z3) = (x1, [1, 0, 0, 0, 0]);
+    let mut swap = 0;
+    for t in (0..255).rev() {
+        let bit = u64::from((k[t / 8] >> (t % 8)) & 1);
+        swap ^= bit;
+        cswap(swap, &mut x2, &mut x3);
+        cswap(swap, &mut z2, &mut z3);
+        swap = bit;
+
+        let a = add(&x2, &z2);
+        let aa = square(&a);
+        let b = sub(&x2, &z2);
+        let bb = square(&b);
+        let e = sub(&aa, &bb);
+        let c = add(&x3, &z3);
+        let d = sub(&x3, &z3);
+        let da = mul(&d, &a);
+        let cb = mul(&c, &b);
+        x3 = square(&add(&da, &cb));
+        z3 = mul(&x1, &square(&sub(&da, &cb)));
+        x2 = mul(&aa, &bb);
+        z2 = mul(&e, &add(&aa, &small(&e, 121665)));
+    }
+    cswap(swap, &mut x2, &mut x3);
+    cswap(swap, &mut z2, &mut z3);
+    pack(&mul(&x2, &invert(&z2)))
+}
+
+// Hàm unpack đọc phần tử trường từ 32 byte little-endian (bỏ bit cao nhất)
+fn unpack(bytes: &[u8; 32]) -> Fe {
+    let load = |i: usize| u64::from_le_bytes(bytes[i..i + 8].try_into().unwrap());
+    [
+        load(0) & MASK,
+        (load(6) >> 3) & MASK,
+        (load(12) >> 6) & MASK,
+        (load(19) >> 1) & MASK,
+        (load(24) >> 12) & MASK,
+    ]
+}
+
+// Hàm pack rút gọn hoàn toàn rồi ghi phần tử trường thành 32 byte
+fn pack(h: &Fe) -> [u8; 32] {
+    let mut h = carry(carry(*h));
+
+    // q = 1 khi h >= p; khi đó cộng 19 và bỏ bit 255 tương đương trừ p
+    let mut q = (h[0] + 19) >> 51;
+    for limb in &h[1..] {
+        q = (limb + q) >> 51;
+    }
+    h[0] += 19 * q;
+    for i in 0..4 {
+        h[i + 1] += h[i] >> 51;
+        h[i] &= MASK;
+    }
+    h[4] &= MASK;
+
+    let words = [
+        h[0] | (h[1] << 51),
+        (h[1] >> 13) | (h[2] << 38),
+        (h[2] >> 26) | (h[3] << 25),
+        (h[3] >> 39) | (h[4] << 12),
+    ];
+    let mut out = [0; 32];
+    for (i, word) in words.iter().enumerate() {
+        out[i * 8..i * 8 + 8].copy_from_slice(&word.to_le_bytes());
+    }
+    out
+}
+
+// Hàm carry lan truyền nhớ để mỗi phần nhỏ hơn 2^51 (cộng thêm phần dư nhỏ ở phần đầu)
+fn carry(mut h: Fe) -> Fe {
+    for i in 0..4 {
+        h[i + 1] += h[i] >> 51;
+        h[i] &= MASK;
+    }
+    h[0] += 19 * (h[4] >> 51);
+    h[4] &= MASK;
+    h
+}
+
+// Hàm add cộng hai phần tử trường
+fn add(a: &Fe, b: &Fe) -> Fe {
+    carry(std::array::from_fn(|i| a[i] + b[i]))
+}
+
+// Hàm sub trừ hai phần tử trường (cộng 2p trước để không bị âm)
+fn sub(a: &Fe, b: &Fe) -> Fe {
+    const TWO_P: Fe = [
+        0xfffffffffffda,
+        0xffffffffffffe,
+        0xffffffffffffe,
+        0xffffffffffffe,
+        0xffffffffffffe,
+    ];
+    carry(std::array::from_fn(|i| a[i] + TWO_P[i] - b[i]))
+}
+
+// Hàm mul nhân hai phần tử trường
+fn mul(a: &Fe, b: &Fe) -> Fe {
+    let [a0, a1, a2, a3, a4] = a.map(u128::from);
+    let [b0, b1, b2, b3, b4] = b.map(u128::from);
+    let r = [
+        a0 * b0 + 19 * (a1 * b4 + a2 * b3 + a3 * b2 + a4 * b1),
+        a0 * b1 + a1 * b0 + 19 * (a2 * b4 + a3 * b3 + a4 * b2),
+        a0 * b2 + a1 * b1 + a2 * b0 + 19 * (a3 * b4 + a4 * b3),
+        a0 * b3 + a1 * b2 + a2 * b1 + a3 * b0 + 19 * (a4 * b4),
+        a0 * b4 + a1 * b3 + a2 * b2 + a3 * b1 + a4 * b0,
+    ];
+    let mut h = [0u64; 5];
+    let mut c = 0u128;
+    for i in 0..5 {
+        let v = r[i] + c;
+        h[i] = v as u64 & MASK;
+        c = v >> 51;
+    }
+    h[0] += 19 * c as u64;
+    carry(h)
+}
+
+// Hàm square bình phương một phần tử trường
+fn square(a: &Fe) -> Fe {
+    mul(a, a)
+}
+
+// Hàm small nhân phần tử trường với một số nhỏ
+fn small(a: &Fe, n: u64) -> Fe {
+    let mut h = [0u64; 5];
+    let mut c = 0u128;
+    for i in 0..5 {
+        let v = u128::from(a[i]) * u128::from(n) + c;
+        h[i] = v as u64 & MASK;
+        c = v >> 51;
+    }
+    h[0] += 19 * c as u64;
+    carry(h)
+}
+
+// Hàm invert tính nghịch đảo bằng luỹ thừa p - 2 = 2^255 - 21
+fn invert(z: &Fe) -> Fe {
+    let mut result = [1, 0, 0, 0, 0];
+    for bit in (0..255).rev() {
+        result = square(&result);
+        // Các bit của p - 2: mọi bit từ 0 tới 254 đều là 1 trừ bit 2 và bit 4
+        if bit != 2 && bit != 4 {
+            result = mul(&result, z);
+        }
+    }
+    result
+}
+
+// Hàm cswap hoán đổi hai phần tử khi `swap` bằng 1 mà không rẽ nhánh
+fn cswap(swap: u64, a: &mut Fe, b: &mut Fe) {
+    let mask = swap.wrapping_neg();
+    for i in 0..5 {
+        let t = mask & (a[i] ^ b[i]);
+        a[i] ^= t;
+        b[i] ^= t;
+    }
+}
+
+// Khai báo một module để chứa các unit test (các vectơ kiểm thử của RFC 7748)
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::crypto::{hex, unhex};
+
+    // Khai báo một hàm để đọc 32 byte từ chuỗi thập lục phân
+    fn array(text: &str) -> [u8; 32] {
+        unhex(text).unwrap().try_into().unwrap()
+    }
+
+    // Khai báo một hàm để kiểm tra phép nhân vô hướng (RFC 7748 mục 5.2)
+    #[test]
+    fn test_scalar() {
+        let scalar = array("a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4");
+        let u = array("e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c");
+        assert_eq!(
+            hex(&x25519(&scalar, &u)),
+            "c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552"
+        );
+    }
+
+    // Khai báo một hàm để kiểm tra trao đổi khoá Diffie-Hellman (RFC 7748 mục 6.1)
+    #[test]
+    fn test_exchange() {
+        let alice = array("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a");
+        let bob = array("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb");
+        assert_eq!(
+            hex(&public(&alice)),
+            "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a"
+        );
+        assert_eq!(
+            hex(&public(&bob)),
+            "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f"
+        );
+        let shared = "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742";
+        assert_eq!(hex(&x25519(&alice, &public(&bob))), shared);
+        assert_eq!(hex(&x25519(&bob, &public(&alice))), shared);
+    }
+}
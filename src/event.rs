@@ -0,0 +1,249 @@
+// Khai báo các thư viện cần thiết
+use std::sync::{mpsc, Mutex};
+
+use crate::auth::role::Role;
+use crate::bank::Money;
+use crate::peer::connection;
+use crate::shop::order::Status;
+use crate::shop::returns;
+use crate::social::Reaction;
+
+// Khai báo một enum để đại diện cho các sự kiện tích hợp giữa các module
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    // Một doanh nghiệp yêu cầu doanh nghiệp khác xuất hóa đơn
+    InvoiceRequested {
+        customer: String,
+        issuer: String,
+        memo: String,
+    },
+    // Hóa đơn đã được xuất và gửi cho khách hàng
+    InvoiceIssued {
+        invoice: u64,
+        issuer: String,
+        customer: String,
+        total: Money,
+    },
+    // Hóa đơn nhận được một khoản thanh toán (remaining là số tiền còn nợ)
+    InvoicePaid {
+        invoice: u64,
+        amount: Money,
+        remaining: Money,
+    },
+    // Hóa đơn bị huỷ
+    InvoiceVoided {
+        invoice: u64,
+    },
+    // Đơn hàng mới được đặt (đang chờ thanh toán)
+    OrderPlaced {
+        order: u64,
+        buyer: String,
+        shop: u64,
+        total: Money,
+    },
+    // Đơn hàng chuyển trạng thái
+    OrderChanged {
+        order: u64,
+        from: Status,
+        to: Status,
+    },
+    // Số lượng có thể bán của một mã SKU trong một kho vừa xuống dưới ngưỡng cảnh báo
+    LowStock {
+        warehouse: String,
+        sku: String,
+        available: u32,
+    },
+    // Người mua gửi yêu cầu trả hàng
+    ReturnRequested {
+        rma: u64,
+        order: u64,
+        buyer: String,
+    },
+    // Yêu cầu trả hàng chuyển trạng thái
+    ReturnChanged {
+        rma: u64,
+        from: returns::State,
+        to: returns::State,
+    },
+    // Một khoản hoàn tiền đã được ghi vào sổ cái
+    RefundPosted {
+        rma: u64,
+        order: u64,
+        amount: Money,
+        entry: u64,
+    },
+    // Một doanh nghiệp gửi lời mời kết nối
+    ConnectionRequested {
+        request: u64,
+        from: String,
+        to: String,
+    },
+    // Lời mời kết nối chuyển trạng thái
+    ConnectionChanged {
+        request: u64,
+        state: connection::State,
+    },
+    // Một doanh nghiệp đăng bài viết mới
+    PostCreated {
+        post: u64,
+        author: String,
+    },
+    // Bài viết được sửa nội dung, tập tin đính kèm hoặc quyền xem
+    PostEdited {
+        post: u64,
+    },
+    // Bài viết bị xoá
+    PostDeleted {
+        post: u64,
+    },
+    // Một doanh nghiệp bình luận bài viết
+    CommentAdded {
+        comment: u64,
+        post: u64,
+        author: String,
+    },
+    // Bình luận được sửa
+    CommentEdited {
+        comment: u64,
+    },
+    // Một doanh nghiệp bày tỏ (hoặc bỏ) cảm xúc với bài viết
+    Reacted {
+        post: u64,
+        by: String,
+        reaction: Option<Reaction>,
+    },
+    // Một cuộc trò chuyện mới được tạo
+    ConversationOpened {
+        conversation: u64,
+        members: Vec<String>,
+    },
+    // Thành viên được mời vào hoặc rời nhóm trò chuyện
+    MembersChanged {
+        conversation: u64,
+    },
+    // Một thành viên gửi tin nhắn
+    MessageSent {
+        conversation: u64,
+        message: u64,
+        from: String,
+    },
+    // Các tin nhắn tới `upto` đã tới một thành viên
+    MessageDelivered {
+        conversation: u64,
+        member: String,
+        upto: u64,
+    },
+    // Một thành viên đã đọc các tin nhắn tới `upto`
+    MessageRead {
+        conversation: u64,
+        member: String,
+        upto: u64,
+    },
+    // Một thành viên đang soạn tin nhắn
+    Typing {
+        conversation: u64,
+        member: String,
+    },
+    // Khoá mã hoá đầu cuối của cuộc trò chuyện được xoay sang thế hệ mới
+    KeyRotated {
+        conversation: u64,
+        epoch: u32,
+    },
+    // Doanh nghiệp đã thay khoá công khai dùng cho mã hoá đầu cuối (kèm dấu vân tay mới)
+    KeyChanged {
+        business: String,
+        fingerprint: String,
+    },
+    // Người dùng đăng nhập thành công
+    LoggedIn {
+        business: String,
+        user: String,
+    },
+    // Một lần đăng nhập thất bại
+    LoginFailed {
+        business: String,
+        user: String,
+    },
+    // Tài khoản bị khoá sau nhiều lần đăng nhập sai
+    AccountLocked {
+        business: String,
+        user: String,
+    },
+    // Người dùng được chỉ định vai trò mới trong doanh nghiệp
+    RoleChanged {
+        business: String,
+        user: String,
+        role: Role,
+    },
+    // Một mã làm mới đã dùng bị đưa ra lần nữa nên chuỗi phiên của nó bị kết thúc
+    RefreshReused {
+        business: String,
+        user: String,
+    },
+}
+
+// Khai báo một struct để đại diện cho kênh sự kiện (mỗi người nhận có một mpsc::Sender riêng)
+#[derive(Debug, Default)]
+pub struct Bus {
+    sinks: Mutex<Vec<mpsc::Sender<Event>>>, // Danh sách các người nhận sự kiện
+}
+
+// Khai báo các phương thức cho struct Bus
+impl Bus {
+    // Phương thức new để tạo một kênh sự kiện mới chưa có người nhận
+    pub fn new() -> Bus {
+        Bus::default()
+    }
+
+    // Phương thức subscribe để đăng ký một người nhận mới và trả về đầu nhận của nó
+    pub fn subscribe(&self) -> mpsc::Receiver<Event> {
+        // Tạo một cặp kênh mpsc mới
+        let (sender, receiver) = mpsc::channel();
+
+        // Thêm đầu gửi vào danh sách người nhận
+        self.sinks.lock().unwrap().push(sender);
+
+        // Trả về đầu nhận cho người đăng ký
+        receiver
+    }
+
+    // Phương thức publish để gửi một sự kiện tới tất cả người nhận
+    pub fn publish(&self, event: Event) {
+        // Gửi sự kiện và loại bỏ những người nhận đã đóng kênh
+        self.sinks
+            .lock()
+            .unwrap()
+            .retain(|sink| sink.send(event.clone()).is_ok());
+    }
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+
+    // Khai báo một hàm để kiểm tra việc gửi và nhận sự kiện
+    #[test]
+    fn test_publish() {
+        // Tạo một kênh sự kiện với hai người nhận
+        let bus = Bus::new();
+        let first = bus.subscribe();
+        let second = bus.subscribe();
+
+        // Người nhận thứ hai đóng kênh
+        drop(second);
+
+        // Gửi một sự kiện
+        bus.publish(Event::InvoiceVoided { invoice: 1 });
+
+        // Người nhận thứ nhất nhận được sự kiện
+        assert_eq!(
+            first.try_recv().unwrap(),
+            Event::InvoiceVoided { invoice: 1 }
+        );
+
+        // Người nhận đã đóng kênh bị loại khỏi danh sách
+        assert_eq!(bus.sinks.lock().unwrap().len(), 1);
+    }
+}
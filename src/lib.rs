@@ -1,34 +1,91 @@
 
-mod storage {
+pub mod audit;
+pub mod auth;
+pub mod bank;
+pub mod clock;
+pub mod crypto;
+pub mod event;
+pub mod peer;
+pub mod shop;
+pub mod social;
+pub mod xml;
+
+pub mod storage {
+// Khai báo các module con của lưu trữ
+pub mod backup;
+pub mod deflate;
+pub mod link;
+pub mod pack;
+pub mod scrub;
+pub mod snapshot;
+pub mod tar;
+pub mod vault;
+
 // Khai báo các thư viện cần thiết
+use std::fmt;
 use std::fs::{self, File, OpenOptions};
 use std::io::{self, BufReader, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
-use std::sync::{mpsc, Arc, Mutex, RwLock};
-use std::thread;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+use crate::audit::{self, Audit};
+use crate::auth::role::Action;
+use crate::auth::{self, Principal};
+use crate::clock;
+use backup::Catalog;
+use pack::Reader;
+use scrub::{Finding, Problem, Replica, Report};
+use snapshot::{Change, Entry, Manifest, Retention, Snapshot};
+use vault::Key;
 
 // Khai báo một enum để đại diện cho các loại quyền truy cập
 #[derive(Debug, Clone, Copy, PartialEq)]
-enum Access {
+pub enum Access {
     Private, // Chỉ có chủ sở hữu mới có thể truy cập
     Public,  // Bất kỳ ai cũng có thể truy cập
     Shared,  // Chỉ có những người được chia sẻ mới có thể truy cập
 }
 
+// Khai báo một trait để xác định ai được chủ sở hữu cấp quyền với tài nguyên Shared (ví dụ: các kết nối của doanh nghiệp)
+pub trait Grants {
+    // Phương thức granted kiểm tra người xem có được chủ sở hữu cấp quyền hay không
+    fn granted(&self, owner: &str, viewer: &str) -> bool;
+}
+
+// Cho phép dùng một closure làm nguồn cấp quyền
+impl<F: Fn(&str, &str) -> bool> Grants for F {
+    fn granted(&self, owner: &str, viewer: &str) -> bool {
+        self(owner, viewer)
+    }
+}
+
 // Khai báo một struct để đại diện cho một đối tượng lưu trữ
 #[derive(Debug)]
-struct Storage {
+pub struct Storage {
     name: String,             // Tên của đối tượng lưu trữ
     path: PathBuf,            // Đường dẫn của đối tượng lưu trữ trên hệ thống tập tin
     size: u64,                // Kích thước của đối tượng lưu trữ (tính bằng byte)
     files: Vec<PathBuf>,      // Danh sách các tập tin và dữ liệu trong đối tượng lưu trữ
     access: RwLock<Access>,   // Quyền truy cập của đối tượng lưu trữ (có khóa đọc ghi)
+    owner: String,            // Doanh nghiệp sở hữu đối tượng lưu trữ (chỉ người dùng của doanh nghiệp này được đăng nhập)
+    principal: Option<Principal>, // Người dùng đã đăng nhập vào đối tượng lưu trữ
+    audit: Option<Arc<Audit>>, // Nhật ký kiểm toán ghi lại các thay đổi quyền, chia sẻ và xoá (nếu có)
+    key: Option<Key>,         // Khoá của đối tượng lưu trữ khi đã mở khoá (nếu được mã hoá)
 }
 
 // Khai báo các phương thức cho struct Storage
 impl Storage {
-    // Phương thức new để tạo một đối tượng lưu trữ mới với tên và quyền truy cập cho trước
-    fn new(name: &str, access: Access) -> io::Result<Storage> {
+    // Phương thức new để tạo một đối tượng lưu trữ mới với tên, doanh nghiệp sở hữu và quyền truy cập cho trước
+    pub fn new(name: &str, owner: &str, access: Access) -> io::Result<Storage> {
+        // Tên doanh nghiệp sở hữu được ghi thành một dòng trong tập tin
+        if owner.is_empty() || owner.contains(['\n', '\r']) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid storage owner: {:?}", owner),
+            ));
+        }
+
         // Tạo một đường dẫn mới từ tên của đối tượng lưu trữ
         let path = Path::new(name);
 
@@ -51,14 +108,23 @@ impl Storage {
             size: 0,                    // Kích thước của đối tượng lưu trữ là 0 (vì chưa có gì trong thư mục)
             files: Vec::new(),          // Danh sách các tập tin và dữ liệu là một Vec rỗng (vì chưa có gì trong thư mục)
             access: RwLock::new(access),// Quyền truy cập của đối tượng lưu trữ là một RwLock được khởi tạo từ tham số access
+            owner: owner.to_string(),   // Doanh nghiệp sở hữu là chuỗi được sao chép từ tham số owner
+            principal: None,            // Chưa có ai đăng nhập
+            audit: None,                // Chưa gắn nhật ký kiểm toán
+            key: None,                  // Chưa mở khoá
         };
 
+        // Ghi quyền truy cập ban đầu vào tập tin .access và doanh nghiệp sở hữu vào tập tin .owner để open có thể đọc lại
+        storage.write_access(access)?;
+        fs::create_dir(path.join(".owner"))?;
+        fs::write(path.join(".owner").join(".owner"), owner)?;
+
         // Trả về đối tượng lưu trữ mới với kết quả Ok
         Ok(storage)
     }
 
     // Phương thức open để mở một đối tượng lưu trữ đã tồn tại với tên cho trước
-    fn open(name: &str) -> io::Result<Storage> {
+    pub fn open(name: &str) -> io::Result<Storage> {
         // Tạo một đường dẫn mới từ tên của đối tượng lưu trữ
         let path = Path::new(name);
 
@@ -80,6 +146,10 @@ impl Storage {
             ));
         }
 
+        // Hoàn tất hoặc huỷ lần thay khoá hay ảnh chụp bị dừng giữa chừng (nếu có)
+        vault::recover(path)?;
+        snapshot::recover(path)?;
+
         // Nếu là một thư mục, tạo một biến để lưu trữ kích thước của đối tượng lưu trữ
         let mut size = 0;
 
@@ -89,6 +159,13 @@ impl Storage {
         // Tạo một biến để lưu trữ quyền truy cập của đối tượng lưu trữ (mặc định là Private)
         let mut access = Access::Private;
 
+        // Đọc doanh nghiệp sở hữu (đối tượng lưu trữ cũ chưa có tập tin .owner thì không ai đăng nhập được)
+        let owner = match fs::read_to_string(path.join(".owner").join(".owner")) {
+            Ok(owner) => owner.trim().to_string(),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(error) => return Err(error),
+        };
+
         // Duyệt qua các mục con trong thư mục với đường dẫn cho trước
         for entry in fs::read_dir(path)? {
             // Lấy ra đường dẫn của mục con
@@ -122,19 +199,8 @@ impl Storage {
                     // Loại bỏ các khoảng trắng ở đầu và cuối chuỗi
                     content = content.trim().to_string();
 
-                    // Kiểm tra xem nội dung của chuỗi có phải là Private, Public hay Shared không
-                    match content.as_str() {
-                        "Private" => access = Access::Private, // Nếu là Private, gán quyền truy cập là Private
-                        "Public" => access = Access::Public,   // Nếu là Public, gán quyền truy cập là Public
-                        "Shared" => access = Access::Shared,   // Nếu là Shared, gán quyền truy cập là Shared
-                        _ => {
-                            // Nếu không phải là ba giá trị trên, trả về một lỗi io với thông báo
-                            return Err(io::Error::new(
-                                io::ErrorKind::InvalidData,
-                                format!("Invalid access value: {}", content),
-                            ));
-                        }
-                    }
+                    // Chuyển nội dung của chuỗi thành quyền truy cập (Private, Public hoặc Shared)
+                    access = content.parse()?;
                 }
             }
         }
@@ -146,14 +212,133 @@ impl Storage {
             size,                       // Kích thước của đối tượng lưu trữ là biến size đã tính toán ở trên
             files,                      // Danh sách các tập tin và dữ liệu là biến files đã thu thập ở trên
             access: RwLock::new(access),// Quyền truy cập của đối tượng lưu trữ là một RwLock được khởi tạo từ biến access đã xác định ở trên
+            owner,                      // Doanh nghiệp sở hữu là biến owner đã đọc ở trên
+            principal: None,            // Chưa có ai đăng nhập
+            audit: None,                // Chưa gắn nhật ký kiểm toán
+            key: None,                  // Chưa mở khoá
         };
 
         // Trả về đối tượng lưu trữ mới với kết quả Ok
         Ok(storage)
     }
 
+    // Phương thức name trả về tên của đối tượng lưu trữ
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Phương thức path trả về đường dẫn của đối tượng lưu trữ trên hệ thống tập tin
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Phương thức owner trả về doanh nghiệp sở hữu đối tượng lưu trữ
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    // Phương thức login để đăng nhập vào đối tượng lưu trữ bằng người dùng đã xác thực của doanh nghiệp sở hữu
+    pub fn login(&mut self, principal: &Principal) -> io::Result<()> {
+        // Phiên đăng nhập đã hết hạn thì không được dùng
+        if !principal.active(clock::now()) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("Session of {} has expired", principal.name()),
+            ));
+        }
+
+        // Người dùng của doanh nghiệp khác không được đăng nhập
+        self.member(principal)?;
+
+        // Ghi nhớ người dùng cho các thao tác tiếp theo
+        self.principal = Some(principal.clone());
+        Ok(())
+    }
+
+    // Phương thức serve để dịch vụ sở hữu đối tượng lưu trữ đăng nhập bằng tài khoản dịch vụ của doanh nghiệp
+    // sở hữu, để dịch vụ không phụ thuộc vào phiên của người gọi (phiên đó có thể hết hạn)
+    pub(crate) fn serve(&mut self) -> io::Result<()> {
+        let service = Principal::service(&self.owner);
+        self.login(&service)
+    }
+
+    // Phương thức logout để đăng xuất khỏi đối tượng lưu trữ
+    pub fn logout(&mut self) {
+        self.principal = None;
+    }
+
+    // Phương thức principal trả về người dùng đang đăng nhập (nếu có)
+    pub fn principal(&self) -> Option<&Principal> {
+        self.principal.as_ref()
+    }
+
+    // Phương thức set_audit để gắn nhật ký kiểm toán cho các thao tác liên quan đến bảo mật
+    pub fn set_audit(&mut self, audit: Arc<Audit>) {
+        self.audit = Some(audit);
+    }
+
+    // Phương thức record ghi một thao tác của người dùng đang đăng nhập vào nhật ký kiểm toán (nếu đã gắn)
+    fn record(&self, action: &str, resource: &str, before: &str, after: &str) -> io::Result<()> {
+        if let (Some(audit), Some(principal)) = (&self.audit, &self.principal) {
+            audit.record(&audit::actor(principal), action, resource, before, after)?;
+        }
+        Ok(())
+    }
+
+    // Phương thức require trả về người dùng đang đăng nhập nếu vai trò của họ cho phép hành động,
+    // hoặc lỗi nếu chưa đăng nhập, phiên đã hết hạn hoặc không đủ quyền
+    fn require(&self, action: Action) -> io::Result<&Principal> {
+        match &self.principal {
+            Some(principal) if principal.active(clock::now()) => {
+                self.member(principal)?;
+                auth::authorize(principal, action, &format!("storage {}", self.name))?;
+                Ok(principal)
+            }
+            Some(principal) => Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("Session of {} has expired", principal.name()),
+            )),
+            None => Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("Login required for storage {}", self.name),
+            )),
+        }
+    }
+
+    // Phương thức member kiểm tra người dùng thuộc doanh nghiệp sở hữu đối tượng lưu trữ
+    fn member(&self, principal: &Principal) -> io::Result<()> {
+        if self.owner.is_empty() || principal.business() != self.owner {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("Storage {} does not belong to {}", self.name, principal.business()),
+            ));
+        }
+        Ok(())
+    }
+
+    // Phương thức list trả về tên các tập tin trong đối tượng lưu trữ (theo thứ tự bảng chữ cái)
+    pub fn list(&self) -> io::Result<Vec<String>> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền đọc
+        self.require(Action::StorageRead)?;
+
+        // Lấy ra tên của từng tập tin trong danh sách
+        let mut names: Vec<String> = self
+            .files
+            .iter()
+            .filter_map(|file| file.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .collect();
+
+        // Sắp xếp để kết quả không phụ thuộc vào thứ tự của hệ thống tập tin
+        names.sort();
+        Ok(names)
+    }
+
     // Phương thức info để lấy thông tin của đối tượng lưu trữ
-    fn info(&self) -> String {
+    pub fn info(&self) -> io::Result<String> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền đọc
+        self.require(Action::StorageRead)?;
+
         // Tạo một chuỗi để lưu trữ thông tin của đối tượng lưu trữ
         let mut info = String::new();
 
@@ -163,7 +348,7 @@ impl Storage {
         // Thêm đường dẫn của đối tượng lưu trữ vào chuỗi
         info.push_str(&format!("Path: {:?}\n", self.path));
 
-        // Thêm kích thước của đối tượng lưu trữ vào chuỗi
+        // Thêm kích thước trên đĩa của đối tượng lưu trữ vào chuỗi
         info.push_str(&format!("Size: {} bytes\n", self.size));
 
         // Thêm số lượng các tập tin và dữ liệu trong đối tượng lưu trữ vào chuỗi
@@ -172,12 +357,26 @@ impl Storage {
         // Thêm quyền truy cập của đối tượng lưu trữ vào chuỗi (cần khóa đọc để truy cập)
         info.push_str(&format!("Access: {:?}\n", *self.access.read().unwrap()));
 
+        // Thêm kích thước gốc của các tập tin (trước khi nén và mã hoá) vào chuỗi
+        info.push_str(&format!("Logical size: {} bytes\n", self.logical()?));
+
         // Trả về chuỗi thông tin
-        info
+        Ok(info)
     }
 
     // Phương thức set_access để thiết lập quyền truy cập cho đối tượng lưu trữ
-    fn set_access(&self, access: Access) -> io::Result<()> {
+    pub fn set_access(&self, access: Access) -> io::Result<()> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền ghi
+        self.require(Action::StorageWrite)?;
+
+        // Ghi quyền truy cập mới và ghi lại giá trị trước và sau vào nhật ký kiểm toán
+        let before = *self.access.read().unwrap();
+        self.write_access(access)?;
+        self.record("set_access", &format!("storage {}", self.name), &before.to_string(), &access.to_string())
+    }
+
+    // Phương thức write_access để ghi quyền truy cập vào tập tin .access và cập nhật trong bộ nhớ
+    fn write_access(&self, access: Access) -> io::Result<()> {
         // Tạo một đường dẫn mới cho thư mục .access trong đối tượng lưu trữ
         let access_dir = self.path.join(".access");
 
@@ -213,8 +412,614 @@ impl Storage {
         Ok(())
     }
 
+    // Phương thức set_file_access để thiết lập quyền truy cập riêng cho một tập tin trong đối tượng lưu trữ
+    pub fn set_file_access(&self, name: &str, access: Access) -> io::Result<()> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền ghi
+        self.require(Action::StorageWrite)?;
+
+        // Tên .access được dành cho quyền truy cập của toàn bộ đối tượng lưu trữ
+        if name == ".access" {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid file name: {}", name),
+            ));
+        }
+
+        // Kiểm tra xem tập tin có tồn tại trong đối tượng lưu trữ hay không
+        let source = self.path.join(name);
+        if !source.is_file() {
+            // Nếu không tồn tại, trả về một lỗi io với thông báo
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Source {} not found", source.display()),
+            ));
+        }
+
+        // Tạo thư mục .access nếu chưa tồn tại
+        let access_dir = self.path.join(".access");
+        if !access_dir.exists() {
+            fs::create_dir(&access_dir)?;
+        }
+
+        // Ghi quyền truy cập của tập tin vào .access/<tên tập tin> và ghi lại giá trị trước và sau vào nhật ký kiểm toán
+        let before = self.file_access(name)?;
+        fs::write(access_dir.join(name), access.to_string())?;
+        self.record("set_file_access", &format!("storage {}/{}", self.name, name), &before.to_string(), &access.to_string())
+    }
+
+    // Phương thức file_access trả về quyền truy cập của một tập tin (mặc định là quyền của đối tượng lưu trữ)
+    pub fn file_access(&self, name: &str) -> io::Result<Access> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền đọc
+        self.require(Action::StorageRead)?;
+
+        // Tạo đường dẫn tới tập tin ghi quyền truy cập riêng của tập tin
+        let marker = self.path.join(".access").join(name);
+
+        // Nếu tập tin có quyền truy cập riêng, đọc và chuyển đổi nó
+        if name != ".access" && marker.is_file() {
+            return fs::read_to_string(marker)?.trim().parse();
+        }
+
+        // Nếu không, trả về quyền truy cập của đối tượng lưu trữ
+        Ok(*self.access.read().unwrap())
+    }
+
+    // Phương thức encrypted kiểm tra đối tượng lưu trữ có được mã hoá hay không
+    pub fn encrypted(&self) -> bool {
+        vault::encrypted(&self.path)
+    }
+
+    // Phương thức encrypt để bật mã hoá cho đối tượng lưu trữ: tạo khoá của đối tượng lưu trữ, bọc nó
+    // bằng khoá chủ (do người gọi giữ, không được lưu lại) và mã hoá các tập tin hiện có
+    //
+    // Khoá đã bọc được ghi lại như một khoá đang chờ trước khi mã hoá các tập tin, nên nếu bị dừng giữa
+    // chừng thì gọi lại encrypt (hoặc unlock) với cùng khoá chủ sẽ mã hoá nốt các tập tin còn lại.
+    pub fn encrypt(&mut self, master: &[u8; 32]) -> io::Result<()> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền ghi
+        self.require(Action::StorageWrite)?;
+
+        // Tiếp tục lần mã hoá bị dừng giữa chừng (nếu có)
+        if vault::pending(&self.path) {
+            let key = vault::unlock(&self.path, &Key::raw(master))?;
+            return self.resume(key);
+        }
+
+        // Đối tượng lưu trữ đã mã hoá thì không mã hoá lại
+        if self.encrypted() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("Storage {} is already encrypted", self.name),
+            ));
+        }
+
+        // Ảnh chụp giữ nội dung chưa mã hoá nên phải được xoá trước
+        if !snapshot::dirs(&self.path)?.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Storage {} has snapshots; delete them before encrypting",
+                    self.name
+                ),
+            ));
+        }
+
+        // Ghi khoá của đối tượng lưu trữ (đã bọc) như khoá đang chờ rồi mã hoá các tập tin hiện có
+        let key = Key::new()?;
+        vault::begin(&self.path, &Key::raw(master), &key)?;
+        self.resume(key)
+    }
+
+    // Phương thức resume để mã hoá các tập tin chưa có khoá dữ liệu bằng khoá dữ liệu riêng, tính lại kích
+    // thước trên đĩa và đánh dấu việc mã hoá đã hoàn tất
+    fn resume(&mut self, key: Key) -> io::Result<()> {
+        let mut size = 0;
+        for file in &self.files {
+            let name = file.file_name().unwrap().to_string_lossy();
+            if !vault::sealed(&self.path, &name) {
+                vault::write(&self.path, Some(&key), &name, &fs::read(file)?)?;
+            }
+            scrub::reseal(&self.path, &key, &name)?;
+            size += fs::metadata(file)?.len();
+        }
+
+        // Khoá đang chờ chỉ được đổi thành khoá của đối tượng lưu trữ khi mọi tập tin đã được mã hoá
+        vault::commit(&self.path)?;
+        self.size = size;
+        self.key = Some(key);
+        self.record("encrypt", &format!("storage {}", self.name), "plain", "encrypted")
+    }
+
+    // Phương thức unlock để mở khoá đối tượng lưu trữ đã mã hoá bằng khoá chủ
+    pub fn unlock(&mut self, master: &[u8; 32]) -> io::Result<()> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền đọc
+        self.require(Action::StorageRead)?;
+
+        // Đối tượng lưu trữ không mã hoá thì không cần mở khoá
+        if !self.encrypted() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Storage {} is not encrypted", self.name),
+            ));
+        }
+
+        // Mở khoá của đối tượng lưu trữ (sai khoá chủ thì bị từ chối)
+        let key = vault::unlock(&self.path, &Key::raw(master))?;
+
+        // Lần mã hoá bị dừng giữa chừng phải được hoàn tất (cần quyền ghi) trước khi dùng được
+        if vault::pending(&self.path) {
+            self.require(Action::StorageWrite)?;
+            return self.resume(key);
+        }
+        self.key = Some(key);
+        Ok(())
+    }
+
+    // Phương thức lock để quên khoá của đối tượng lưu trữ (các thao tác đọc ghi bị từ chối tới khi mở khoá lại)
+    pub fn lock(&mut self) {
+        self.key = None;
+    }
+
+    // Phương thức rotate_master để bọc lại khoá của đối tượng lưu trữ bằng khoá chủ mới
+    pub fn rotate_master(&self, master: &[u8; 32], new: &[u8; 32]) -> io::Result<()> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền ghi
+        self.require(Action::StorageWrite)?;
+
+        // Mở khoá bằng khoá chủ cũ rồi bọc lại bằng khoá chủ mới (các tập tin không bị ghi lại)
+        let key = vault::unlock(&self.path, &Key::raw(master))?;
+        vault::enclose(&self.path, &Key::raw(new), &key)?;
+        self.record("rotate_master", &format!("storage {}", self.name), "", "")
+    }
+
+    // Phương thức rotate_key để thay khoá của đối tượng lưu trữ: các khoá dữ liệu (cả trong ảnh chụp) được bọc
+    // lại bằng khoá mới, còn nội dung các tập tin và khoá dữ liệu giữ nguyên
+    pub fn rotate_key(&mut self, master: &[u8; 32]) -> io::Result<()> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền ghi
+        self.require(Action::StorageWrite)?;
+
+        // Mở khoá cũ bằng khoá chủ rồi bọc lại mọi khoá dữ liệu bằng khoá mới
+        let master = Key::raw(master);
+        let old = vault::unlock(&self.path, &master)?;
+        let new = Key::new()?;
+        for dir in snapshot::dirs(&self.path)? {
+            if dir.join(vault::KEYS).is_dir() {
+                vault::rotate(&dir, &master, &old, &new)?;
+            }
+        }
+        vault::rotate(&self.path, &master, &old, &new)?;
+        self.key = Some(new);
+        self.record("rotate_key", &format!("storage {}", self.name), "", "")
+    }
+
+    // Phương thức compressed kiểm tra đối tượng lưu trữ có bật nén hay không
+    pub fn compressed(&self) -> bool {
+        pack::enabled(&self.path)
+    }
+
+    // Phương thức compress để bật nén cho đối tượng lưu trữ: từ đó mỗi tập tin được ghi sẽ được nén nếu đủ lớn,
+    // không phải định dạng đã nén sẵn và nhỏ đi đáng kể; các tập tin hiện có được xét lại theo cùng tiêu chí
+    pub fn compress(&mut self) -> io::Result<()> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền ghi
+        self.require(Action::StorageWrite)?;
+
+        // Đối tượng lưu trữ đã bật nén thì không bật lại
+        if self.compressed() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("Storage {} is already compressed", self.name),
+            ));
+        }
+
+        // Bật nén rồi ghi lại từng tập tin hiện có và tính lại kích thước trên đĩa
+        let key = self.cipher()?;
+        pack::enable(&self.path)?;
+        let mut size = 0;
+        for file in &self.files {
+            let name = file.file_name().unwrap().to_string_lossy();
+            let content = pack::read(&self.path, key, &name)?;
+            pack::write(&self.path, key, &name, &content)?;
+            size += fs::metadata(file)?.len();
+        }
+        self.size = size;
+        self.record("compress", &format!("storage {}", self.name), "plain", "compressed")
+    }
+
+    // Phương thức logical tính tổng kích thước gốc của các tập tin (không cần mở khoá)
+    fn logical(&self) -> io::Result<u64> {
+        self.files
+            .iter()
+            .map(|file| pack::logical(&self.path, &file.file_name().unwrap().to_string_lossy()))
+            .sum()
+    }
+
+    // Phương thức verify để kiểm tra mọi tập tin với mã kiểm tra ghi lúc ghi tập tin: báo các tập tin bị thiếu,
+    // thừa (không có mã kiểm tra), sai kích thước hoặc sai mã kiểm tra; nếu có bản sao thì khôi phục các tập tin
+    // bị thiếu hoặc hỏng từ bản sao khi nội dung của bản sao khớp mã kiểm tra đã ghi
+    pub fn verify(&mut self, replica: Option<&dyn Replica>) -> io::Result<Report> {
+        // Yêu cầu người dùng đã đăng nhập, có quyền đọc (và quyền ghi nếu khôi phục)
+        self.require(if replica.is_some() { Action::StorageWrite } else { Action::StorageRead })?;
+        let key = self.cipher()?.cloned();
+
+        // Gom tên các tập tin đang có trên đĩa và các tập tin có mã kiểm tra
+        let mut names = scrub::names(&self.path)?;
+        for entry in fs::read_dir(&self.path)? {
+            let entry = entry?;
+            if entry.path().is_file() {
+                names.push(entry.file_name().to_string_lossy().into_owned());
+            }
+        }
+        names.sort();
+        names.dedup();
+
+        // Kiểm tra từng tập tin và khôi phục nếu được
+        let mut report = Report {
+            storage: self.name.clone(),
+            time: clock::now(),
+            checked: names.len(),
+            findings: Vec::new(),
+        };
+        for name in &names {
+            let Some(problem) = scrub::check(&self.path, key.as_ref(), name)? else {
+                continue;
+            };
+            let repaired = match replica {
+                Some(replica) if problem != Problem::Extra => self.repair(name, &problem, replica)?,
+                _ => false,
+            };
+            report.findings.push(Finding { name: name.clone(), problem, repaired });
+        }
+
+        // Đồng bộ lại danh sách và kích thước các tập tin với những gì đang có trên đĩa
+        self.files = names.iter().map(|name| self.path.join(name)).filter(|file| file.is_file()).collect();
+        self.size = self.files.iter().map(|file| Ok(fs::metadata(file)?.len())).sum::<io::Result<u64>>()?;
+        Ok(report)
+    }
+
+    // Phương thức repair để khôi phục một tập tin từ bản sao nếu nội dung của bản sao khớp mã kiểm tra đã ghi
+    fn repair(&mut self, name: &str, problem: &Problem, replica: &dyn Replica) -> io::Result<bool> {
+        let (Some(expected), Some(content)) = (scrub::sum(&self.path, name)?, replica.fetch(name)?) else {
+            return Ok(false);
+        };
+        if scrub::digest(&content[..])? != expected {
+            return Ok(false);
+        }
+        pack::write(&self.path, self.cipher()?, name, &content)?;
+        self.record("repair", &format!("storage {}/{}", self.name, name), &problem.to_string(), "restored from replica")?;
+        Ok(true)
+    }
+
+    // Phương thức baseline để ghi mã kiểm tra cho các tập tin chưa có (ví dụ: tập tin ghi trước khi có mã kiểm
+    // tra) và trả về số tập tin đã ghi; nội dung hiện tại được coi là đúng
+    pub fn baseline(&mut self) -> io::Result<usize> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền ghi
+        self.require(Action::StorageWrite)?;
+        let key = self.cipher()?;
+
+        // Ghi mã kiểm tra cho từng tập tin chưa có
+        let mut count = 0;
+        for file in &self.files {
+            let name = file.file_name().unwrap().to_string_lossy();
+            if scrub::sum(&self.path, &name)?.is_none() {
+                scrub::rehash(&self.path, key, &name)?;
+                count += 1;
+            }
+        }
+        self.record("baseline", &format!("storage {}", self.name), "", &format!("{} files", count))?;
+        Ok(count)
+    }
+
+    // Phương thức manifest trả về bản kê hiện tại của đối tượng lưu trữ: kích thước, mã kiểm tra và quyền truy cập
+    // riêng của từng tập tin cùng quyền truy cập của đối tượng lưu trữ
+    pub fn manifest(&self) -> io::Result<Manifest> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền đọc
+        let principal = self.require(Action::StorageRead)?;
+        let key = self.cipher()?;
+
+        // Lấy mã kiểm tra đã ghi của từng tập tin (tính lại nếu tập tin chưa có)
+        let mut names: Vec<String> = self
+            .files
+            .iter()
+            .map(|file| file.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        let mut entries = Vec::new();
+        for name in names {
+            let sum = match scrub::sum(&self.path, &name)? {
+                Some(sum) => sum,
+                None => scrub::digest(pack::reader(&self.path, key, &name)?)?,
+            };
+            let marker = self.path.join(".access").join(&name);
+            let access = if marker.is_file() {
+                Some(fs::read_to_string(marker)?.trim().parse()?)
+            } else {
+                None
+            };
+            entries.push(Entry {
+                name,
+                size: sum.size,
+                hash: sum.hash,
+                access,
+            });
+        }
+        Ok(Manifest {
+            name: String::new(),
+            time: clock::now(),
+            actor: audit::actor(principal),
+            access: *self.access.read().unwrap(),
+            encrypted: self.encrypted(),
+            entries,
+        })
+    }
+
+    // Phương thức snapshot để chụp toàn bộ đối tượng lưu trữ với tên cho trước (tập tin được liên kết cứng nên
+    // không chép dữ liệu), rồi xoá các ảnh chụp hết hạn theo chính sách giữ ảnh chụp
+    pub fn snapshot(&self, name: &str) -> io::Result<Manifest> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền ghi
+        self.require(Action::StorageWrite)?;
+
+        // Lập bản kê rồi chụp các tập tin trong bản kê
+        snapshot::check(&self.path, name)?;
+        let mut manifest = self.manifest()?;
+        manifest.name = name.to_string();
+        snapshot::take(&self.path, &manifest)?;
+        self.record(
+            "snapshot",
+            &format!("storage {}@{}", self.name, name),
+            "",
+            &format!("{} files", manifest.entries.len()),
+        )?;
+        self.prune()?;
+        Ok(manifest)
+    }
+
+    // Phương thức snapshots trả về bản kê của các ảnh chụp, cũ nhất trước
+    pub fn snapshots(&self) -> io::Result<Vec<Manifest>> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền đọc
+        self.require(Action::StorageRead)?;
+        snapshot::list(&self.path)
+    }
+
+    // Phương thức open_snapshot để mở một ảnh chụp để đọc (ví dụ: làm nguồn khôi phục cho verify)
+    pub fn open_snapshot(&self, name: &str) -> io::Result<Snapshot> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền đọc
+        self.require(Action::StorageRead)?;
+        snapshot::open(&self.path, name, self.cipher()?)
+    }
+
+    // Phương thức diff so sánh một ảnh chụp với một ảnh chụp khác hoặc với trạng thái hiện tại (nếu `to` là None)
+    pub fn diff(&self, from: &str, to: Option<&str>) -> io::Result<Vec<Change>> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền đọc
+        self.require(Action::StorageRead)?;
+        let from = snapshot::load(&self.path, from)?;
+        let to = match to {
+            Some(to) => snapshot::load(&self.path, to)?,
+            None => self.manifest()?,
+        };
+        Ok(snapshot::diff(&from, &to))
+    }
+
+    // Phương thức restore để đưa toàn bộ đối tượng lưu trữ về trạng thái của một ảnh chụp, kể cả quyền truy cập
+    pub fn restore(&mut self, name: &str) -> io::Result<()> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền ghi
+        self.require(Action::StorageWrite)?;
+        self.cipher()?;
+
+        // Ảnh chụp phải được chụp cùng trạng thái mã hoá với hiện tại
+        let manifest = snapshot::load(&self.path, name)?;
+        if manifest.encrypted != self.encrypted() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Snapshot {} does not match the encryption of storage {}",
+                    name, self.name
+                ),
+            ));
+        }
+
+        // Thay các tập tin, quyền truy cập, rồi cập nhật danh sách và kích thước các tập tin
+        let before = format!("{} files", self.files.len());
+        snapshot::restore(&self.path, &manifest)?;
+        self.write_access(manifest.access)?;
+        self.files = manifest
+            .entries
+            .iter()
+            .map(|entry| self.path.join(&entry.name))
+            .collect();
+        self.size = self
+            .files
+            .iter()
+            .map(|file| Ok(fs::metadata(file)?.len()))
+            .sum::<io::Result<u64>>()?;
+        self.record(
+            "restore_snapshot",
+            &format!("storage {}@{}", self.name, name),
+            &before,
+            &format!("{} files", manifest.entries.len()),
+        )
+    }
+
+    // Phương thức delete_snapshot để xoá một ảnh chụp
+    pub fn delete_snapshot(&self, name: &str) -> io::Result<()> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền ghi
+        self.require(Action::StorageWrite)?;
+        snapshot::remove(&self.path, name)?;
+        self.record(
+            "delete_snapshot",
+            &format!("storage {}@{}", self.name, name),
+            "",
+            "",
+        )
+    }
+
+    // Phương thức retention trả về chính sách giữ ảnh chụp
+    pub fn retention(&self) -> io::Result<Retention> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền đọc
+        self.require(Action::StorageRead)?;
+        snapshot::retention(&self.path)
+    }
+
+    // Phương thức set_retention để đặt chính sách giữ ảnh chụp và áp dụng ngay
+    pub fn set_retention(&self, retention: Retention) -> io::Result<Vec<String>> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền ghi
+        self.require(Action::StorageWrite)?;
+        let before = snapshot::retention(&self.path)?;
+        snapshot::set_retention(&self.path, &retention)?;
+        self.record(
+            "set_retention",
+            &format!("storage {}", self.name),
+            &format!("{:?}", before),
+            &format!("{:?}", retention),
+        )?;
+        self.prune()
+    }
+
+    // Phương thức prune để xoá các ảnh chụp hết hạn theo chính sách giữ ảnh chụp và trả về tên của chúng
+    pub fn prune(&self) -> io::Result<Vec<String>> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền ghi
+        self.require(Action::StorageWrite)?;
+        let expired =
+            snapshot::retention(&self.path)?.expired(&snapshot::list(&self.path)?, clock::now());
+        for name in &expired {
+            snapshot::remove(&self.path, name)?;
+            self.record(
+                "delete_snapshot",
+                &format!("storage {}@{}", self.name, name),
+                "",
+                "retention",
+            )?;
+        }
+        Ok(expired)
+    }
+
+    // Phương thức export để sao lưu đối tượng lưu trữ (tập tin, quyền truy cập, thiết lập và các ảnh chụp) vào một
+    // luồng tar; nếu có danh mục của bản sao lưu trước thì chỉ ghi nội dung chưa có trong đó (sao lưu tăng dần).
+    // Nội dung được ghi ở dạng gốc (đã giải nén) để nhập được ở nơi khác, nên đối tượng lưu trữ đã mã hoá bị từ
+    // chối; dùng export_decrypted để chủ động xuất nội dung đã giải mã
+    pub fn export(&self, out: impl Write, base: Option<&Catalog>) -> io::Result<Catalog> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền đọc
+        self.require(Action::StorageRead)?;
+        if self.encrypted() {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!(
+                    "Storage {} is encrypted; use export_decrypted to back it up in plaintext",
+                    self.name
+                ),
+            ));
+        }
+        self.backup(out, base, "")
+    }
+
+    // Phương thức export_decrypted để sao lưu đối tượng lưu trữ đã mã hoá với nội dung đã giải mã; vì bản sao lưu
+    // không còn được bảo vệ bởi khoá chủ, thao tác này cần quyền ghi và được ghi rõ vào nhật ký kiểm toán
+    pub fn export_decrypted(&self, out: impl Write, base: Option<&Catalog>) -> io::Result<Catalog> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền ghi
+        self.require(Action::StorageWrite)?;
+        self.backup(out, base, ", decrypted")
+    }
+
+    // Phương thức backup để ghi bản sao lưu và ghi lại vào nhật ký kiểm toán (kèm ghi chú cho biết nội dung đã
+    // được giải mã, nếu có)
+    fn backup(&self, out: impl Write, base: Option<&Catalog>, note: &str) -> io::Result<Catalog> {
+        let key = self.cipher()?;
+
+        // Bản sao lưu gốc phải là của chính đối tượng lưu trữ này
+        if let Some(base) = base.filter(|base| base.storage != self.name) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Backup {} is not of storage {}", base.id, self.name),
+            ));
+        }
+
+        // Lập danh mục gồm mọi nội dung trong chuỗi sao lưu rồi ghi bản sao lưu
+        let manifest = self.manifest()?;
+        let mut objects = backup::objects(&self.path, &manifest)?;
+        objects.extend(base.iter().flat_map(|base| base.objects.iter().cloned()));
+        let catalog = Catalog {
+            id: backup::id()?,
+            storage: self.name.clone(),
+            time: manifest.time,
+            base: base.map(|base| base.id.clone()),
+            compressed: self.compressed(),
+            retention: snapshot::retention(&self.path)?,
+            objects,
+        };
+        let count = backup::export(&self.path, key, out, &manifest, &catalog, base)?;
+        let before = base.map(|base| format!("backup {}", base.id)).unwrap_or_default();
+        self.record("export", &format!("storage {}", self.name), &before, &format!("backup {} ({} objects{})", catalog.id, count, note))?;
+        Ok(catalog)
+    }
+
+    // Phương thức import để tạo một đối tượng lưu trữ mới với tên cho trước từ một bản sao lưu đầy đủ và các bản
+    // sao lưu tăng dần tiếp theo (theo thứ tự); nội dung được kiểm tra mã kiểm tra khi nhập, đối tượng lưu trữ mới
+    // chưa được mã hoá và người dùng cho trước được đăng nhập sẵn. Nếu nhập thất bại, thư mục mới bị xoá
+    pub fn import(name: &str, principal: &Principal, archives: &mut [&mut dyn Read]) -> io::Result<Storage> {
+        let mut storage = Storage::new(name, principal.business(), Access::Private)?;
+        if let Err(error) = storage.login(principal).and_then(|_| storage.load_backup(archives)) {
+            fs::remove_dir_all(&storage.path)?;
+            return Err(error);
+        }
+        Ok(storage)
+    }
+
+    // Phương thức load_backup để nạp các bản sao lưu vào đối tượng lưu trữ vừa tạo
+    fn load_backup(&mut self, archives: &mut [&mut dyn Read]) -> io::Result<()> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền ghi
+        self.require(Action::StorageWrite)?;
+
+        // Nạp tập tin và ảnh chụp, rồi cập nhật quyền truy cập, danh sách và kích thước các tập tin
+        let (_, manifest) = backup::import(&self.path, archives)?;
+        self.write_access(manifest.access)?;
+        self.files = manifest.entries.iter().map(|entry| self.path.join(&entry.name)).collect();
+        self.size = self.files.iter().map(|file| Ok(fs::metadata(file)?.len())).sum::<io::Result<u64>>()?;
+        Ok(())
+    }
+
+    // Phương thức reader để mở một tập tin như một luồng chỉ đọc (giải nén và giải mã nếu cần)
+    pub fn reader(&self, name: &str) -> io::Result<Reader> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền đọc
+        self.require(Action::StorageRead)?;
+
+        // Kiểm tra xem nguồn có phải là một tập tin có tồn tại hay không
+        let source = self.path.join(name);
+        if !source.is_file() {
+            // Nếu không phải, trả về một lỗi io với thông báo
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Source {} not found", source.display()),
+            ));
+        }
+
+        // Mở tập tin để đọc
+        pack::reader(&self.path, self.cipher()?, name)
+    }
+
+    // Phương thức range để đọc một đoạn nội dung của tập tin (chỉ giải mã các khối chứa đoạn đó; tập tin đã
+    // nén được giải nén toàn bộ)
+    pub fn range(&self, name: &str, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+        let mut reader = self.reader(name)?;
+        reader.seek(offset)?;
+        let mut content = Vec::new();
+        reader.take(len).read_to_end(&mut content)?;
+        Ok(content)
+    }
+
+    // Phương thức cipher trả về khoá của đối tượng lưu trữ nếu được mã hoá, hoặc lỗi nếu chưa mở khoá
+    fn cipher(&self) -> io::Result<Option<&Key>> {
+        match &self.key {
+            Some(key) => Ok(Some(key)),
+            None if self.encrypted() => Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("Storage {} is encrypted; unlock it first", self.name),
+            )),
+            None => Ok(None),
+        }
+    }
+
     // Phương thức upload để tải lên một tập tin hoặc dữ liệu vào đối tượng lưu trữ
-    fn upload(&mut self, source: &Path) -> io::Result<()> {
+    pub fn upload(&mut self, source: &Path) -> io::Result<()> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền ghi
+        self.require(Action::StorageWrite)?;
+
         // Kiểm tra xem nguồn có tồn tại hay không
         if !source.exists() {
             // Nếu không tồn tại, trả về một lỗi io với thông báo
@@ -245,13 +1050,14 @@ impl Storage {
             ));
         }
 
-        // Nếu chưa tồn tại, sao chép nội dung từ nguồn sang đích
-        fs::copy(source, &dest)?;
+        // Nếu chưa tồn tại, sao chép nội dung từ nguồn sang đích (mã hoá nếu đối tượng lưu trữ được mã hoá)
+        let name = source.file_name().unwrap().to_string_lossy();
+        pack::write(&self.path, self.cipher()?, &name, &fs::read(source)?)?;
 
-        // Lấy ra kích thước của tập tin nguồn
-        let file_size = fs::metadata(source)?.len();
+        // Lấy ra kích thước của tập tin đích trên đĩa
+        let file_size = fs::metadata(&dest)?.len();
 
-        // Cộng kích thước của tập tin nguồn vào kích thước của đối tượng lưu trữ
+        // Cộng kích thước của tập tin đích vào kích thước của đối tượng lưu trữ
         self.size += file_size;
 
         // Thêm đường dẫn của đích vào danh sách các tập tin và dữ liệu của đối tượng lưu trữ
@@ -261,8 +1067,119 @@ impl Storage {
         Ok(())
     }
 
+    // Phương thức put để tạo một tập tin mới trong đối tượng lưu trữ từ nội dung cho trước
+    pub fn put(&mut self, name: &str, content: &[u8]) -> io::Result<()> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền ghi
+        self.require(Action::StorageWrite)?;
+
+        // Tên tập tin phải nằm ngay trong đối tượng lưu trữ và không trùng với các tập tin ẩn
+        check_name(name)?;
+
+        // Tạo một đường dẫn mới cho đích bằng cách nối tên vào đường dẫn của đối tượng lưu trữ
+        let dest = self.path.join(name);
+
+        // Kiểm tra xem đích đã tồn tại hay chưa
+        if dest.exists() {
+            // Nếu đã tồn tại, trả về một lỗi io với thông báo
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("Destination {} already exists", dest.display()),
+            ));
+        }
+
+        // Tạo tập tin mới và ghi nội dung vào đó (mã hoá nếu đối tượng lưu trữ được mã hoá)
+        pack::write(&self.path, self.cipher()?, name, content)?;
+
+        // Cộng kích thước trên đĩa của tập tin vào kích thước của đối tượng lưu trữ
+        self.size += fs::metadata(&dest)?.len();
+
+        // Thêm đường dẫn của đích vào danh sách các tập tin và dữ liệu của đối tượng lưu trữ
+        self.files.push(dest);
+
+        // Trả về kết quả Ok
+        Ok(())
+    }
+
+    // Phương thức put_with_access để tạo một tập tin mới với quyền truy cập riêng cho trước; quyền được ghi trước nội dung
+    // nên tập tin không lúc nào mang quyền mặc định của đối tượng lưu trữ (kể cả khi tiến trình dừng giữa chừng)
+    pub fn put_with_access(&mut self, name: &str, content: &[u8], access: Access) -> io::Result<()> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền ghi
+        self.require(Action::StorageWrite)?;
+
+        // Kiểm tra tên trước khi ghi quyền vào .access/<tên tập tin>
+        check_name(name)?;
+
+        // Kiểm tra đích trước khi ghi quyền để không thay đổi quyền của một tập tin đã có
+        let dest = self.path.join(name);
+        if dest.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("Destination {} already exists", dest.display()),
+            ));
+        }
+
+        // Ghi quyền truy cập của tập tin vào .access/<tên tập tin> trước
+        let access_dir = self.path.join(".access");
+        if !access_dir.exists() {
+            fs::create_dir(&access_dir)?;
+        }
+        let marker = access_dir.join(name);
+        fs::write(&marker, access.to_string())?;
+
+        // Ghi nội dung; nếu lỗi thì xoá quyền vừa ghi
+        if let Err(error) = self.put(name, content) {
+            let _ = fs::remove_file(&marker);
+            return Err(error);
+        }
+
+        // Ghi lại giá trị trước và sau vào nhật ký kiểm toán như set_file_access
+        let before = *self.access.read().unwrap();
+        self.record("set_file_access", &format!("storage {}/{}", self.name, name), &before.to_string(), &access.to_string())
+    }
+
+    // Phương thức append để ghi thêm dữ liệu vào cuối một tập tin (tạo mới nếu chưa có), dùng cho các nhật ký chỉ ghi thêm
+    pub fn append(&mut self, name: &str, content: &[u8]) -> io::Result<()> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền ghi
+        self.require(Action::StorageWrite)?;
+
+        // Tên tập tin phải nằm ngay trong đối tượng lưu trữ và không trùng với các tập tin ẩn như put
+        check_name(name)?;
+
+        // Tạo một đường dẫn mới cho đích bằng cách nối tên vào đường dẫn của đối tượng lưu trữ
+        let dest = self.path.join(name);
+
+        // Kiểm tra xem đích có phải là một thư mục hay không
+        if dest.is_dir() {
+            // Nếu là thư mục, trả về một lỗi io với thông báo
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Destination {} is not a file", dest.display()),
+            ));
+        }
+
+        // Ghi nhớ tập tin mới vào danh sách các tập tin và dữ liệu của đối tượng lưu trữ
+        let old_size = if dest.exists() {
+            fs::metadata(&dest)?.len()
+        } else {
+            self.files.push(dest.clone());
+            0
+        };
+
+        // Mở hoặc tạo tập tin và ghi nội dung vào cuối (khi mã hoá, chỉ khối cuối được mã hoá lại)
+        pack::append(&self.path, self.cipher()?, name, content)?;
+
+        // Cập nhật kích thước trên đĩa của tập tin trong kích thước của đối tượng lưu trữ
+        self.size = self.size - old_size + fs::metadata(&dest)?.len();
+
+        // Trả về kết quả Ok
+        Ok(())
+    }
+
     // Phương thức download để tải xuống một tập tin hoặc dữ liệu từ đối tượng lưu trữ
-    fn download(&self, name: &str, dest: &Path) -> io::Result<()> {
+    pub fn download(&self, name: &str, dest: &Path) -> io::Result<()> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền đọc
+        self.require(Action::StorageRead)?;
+
         // Tạo một đường dẫn mới cho nguồn bằng cách nối tên của tập tin hoặc dữ liệu vào đường dẫn của đối tượng lưu trữ
         let source = self.path.join(name);
 
@@ -293,15 +1210,18 @@ impl Storage {
             ));
         }
 
-        // Nếu chưa tồn tại, sao chép nội dung từ nguồn sang đích
-        fs::copy(&source, dest)?;
+        // Nếu chưa tồn tại, sao chép nội dung (đã giải mã) từ nguồn sang đích
+        fs::write(dest, pack::read(&self.path, self.cipher()?, name)?)?;
 
         // Trả về kết quả Ok
         Ok(())
     }
 
     // Phương thức view để xem nội dung của một tập tin hoặc dữ liệu trong đối tượng lưu trữ
-    fn view(&self, name: &str) -> io::Result<String> {
+    pub fn view(&self, name: &str) -> io::Result<String> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền đọc
+        self.require(Action::StorageRead)?;
+
         // Tạo một đường dẫn mới cho nguồn bằng cách nối tên của tập tin hoặc dữ liệu vào đường dẫn của đối tượng lưu trữ
         let source = self.path.join(name);
 
@@ -323,24 +1243,24 @@ impl Storage {
             ));
         }
 
-        // Mở tập tin nguồn để đọc nội dung
-        let file = File::open(&source)?;
-
-        // Tạo một bộ đọc để đọc nội dung của tập tin nguồn
-        let mut reader = BufReader::new(file);
+        // Mở tập tin nguồn để đọc nội dung (giải mã nếu đối tượng lưu trữ được mã hoá)
+        let reader = pack::reader(&self.path, self.cipher()?, name)?;
 
         // Tạo một chuỗi để lưu trữ nội dung của tập tin nguồn
         let mut content = String::new();
 
         // Đọc nội dung của tập tin nguồn vào chuỗi
-        reader.read_to_string(&mut content)?;
+        BufReader::new(reader).read_to_string(&mut content)?;
 
         // Trả về chuỗi nội dung với kết quả Ok
         Ok(content)
     }
 
     // Phương thức edit để sửa nội dung của một tập tin hoặc dữ liệu trong đối tượng lưu trữ
-    fn edit(&mut self, name: &str, content: &str) -> io::Result<()> {
+    pub fn edit(&mut self, name: &str, content: &str) -> io::Result<()> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền ghi
+        self.require(Action::StorageWrite)?;
+
         // Tạo một đường dẫn mới cho nguồn bằng cách nối tên của tập tin hoặc dữ liệu vào đường dẫn của đối tượng lưu trữ
         let source = self.path.join(name);
 
@@ -362,30 +1282,17 @@ impl Storage {
             ));
         }
 
-        // Mở hoặc tạo một tập tin mới với đường dẫn cho trước, cho phép ghi và xoá nội dung cũ
-        let file = OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .create(true)
-            .open(&source)?;
+        // Lấy ra kích thước cũ của tập tin nguồn trước khi ghi đè
+        let old_size = fs::metadata(&source)?.len();
 
-        // Tạo một bộ ghi để ghi nội dung vào tập tin nguồn
-        let mut writer = BufWriter::new(file);
-
-        // Ghi nội dung từ tham số content vào tập tin nguồn
-        writer.write_all(content.as_bytes())?;
-
-        // Đồng bộ nội dung với hệ thống tập tin
-        writer.flush()?;
+        // Ghi đè nội dung của tập tin nguồn (mã hoá bằng khoá dữ liệu mới nếu đối tượng lưu trữ được mã hoá)
+        pack::write(&self.path, self.cipher()?, name, content.as_bytes())?;
 
         // Lấy ra kích thước mới của tập tin nguồn
         let new_size = fs::metadata(&source)?.len();
 
-        // Tìm vị trí của đường dẫn nguồn trong danh sách các tập tin và dữ liệu của đối tượng lưu trữ
-        if let Some(index) = self.files.iter().position(|p| p == &source) {
-            // Nếu tìm thấy, lấy ra kích thước cũ của tập tin nguồn
-            let old_size = fs::metadata(&self.files[index])?.len();
-
+        // Kiểm tra xem đường dẫn nguồn có nằm trong danh sách các tập tin và dữ liệu của đối tượng lưu trữ hay không
+        if self.files.iter().any(|p| p == &source) {
             // Trừ kích thước cũ khỏi kích thước của đối tượng lưu trữ
             self.size -= old_size;
 
@@ -398,7 +1305,10 @@ impl Storage {
     }
 
     // Phương thức delete để xoá một tập tin hoặc dữ liệu khỏi đối tượng lưu trữ
-    fn delete(&mut self, name: &str) -> io::Result<()> {
+    pub fn delete(&mut self, name: &str) -> io::Result<()> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền ghi
+        self.require(Action::StorageWrite)?;
+
         // Tạo một đường dẫn mới cho nguồn bằng cách nối tên của tập tin hoặc dữ liệu vào đường dẫn của đối tượng lưu trữ
         let source = self.path.join(name);
 
@@ -420,12 +1330,12 @@ impl Storage {
             ));
         }
 
+        // Lấy ra kích thước của tập tin nguồn (trước khi xoá)
+        let file_size = fs::metadata(&source)?.len();
+
         // Xoá tập tin nguồn khỏi hệ thống tập tin
         fs::remove_file(&source)?;
 
-        // Lấy ra kích thước của tập tin nguồn
-        let file_size = fs::metadata(&source)?.len();
-
         // Trừ kích thước của tập tin nguồn khỏi kích thước của đối tượng lưu trữ
         self.size -= file_size;
 
@@ -435,12 +1345,29 @@ impl Storage {
             self.files.remove(index);
         }
 
-        // Trả về kết quả Ok
-        Ok(())
+        // Xoá quyền truy cập riêng và khoá dữ liệu của tập tin (nếu có)
+        let marker = self.path.join(".access").join(name);
+        if marker.is_file() {
+            fs::remove_file(marker)?;
+        }
+        pack::remove(&self.path, name)?;
+
+        // Ghi lại kích thước của tập tin đã xoá vào nhật ký kiểm toán
+        self.record("delete", &format!("storage {}/{}", self.name, name), &format!("{} bytes", file_size), "")
     }
 
     // Phương thức share để chia sẻ một tập tin hoặc dữ liệu trong đối tượng lưu trữ với một đối tượng lưu trữ khác
-    fn share(&self, name: &str, dest: &mut Storage) -> io::Result<()> {
+    pub fn share(&self, name: &str, dest: &mut Storage) -> io::Result<()> {
+        let to = format!("storage {}", dest.name);
+        self.copy(name, dest, &to)
+    }
+
+    // Phương thức copy để sao chép một tập tin sang đối tượng lưu trữ đích và ghi lại người nhận vào nhật ký kiểm toán
+    fn copy(&self, name: &str, dest: &mut Storage, to: &str) -> io::Result<()> {
+        // Yêu cầu người dùng đã đăng nhập vào cả nguồn và đích, với quyền ghi
+        self.require(Action::StorageWrite)?;
+        dest.require(Action::StorageWrite)?;
+
         // Tạo một đường dẫn mới cho nguồn bằng cách nối tên của tập tin hoặc dữ liệu vào đường dẫn của đối tượng lưu trữ
         let source = self.path.join(name);
 
@@ -474,65 +1401,167 @@ impl Storage {
             ));
         }
 
-        // Nếu chưa tồn tại, sao chép nội dung từ nguồn sang đích
-        fs::copy(&source, &dest_path)?;
+        // Nếu chưa tồn tại, giải mã nội dung của nguồn (nếu cần) rồi ghi sang đích theo cách của đích
+        let content = pack::read(&self.path, self.cipher()?, name)?;
+        let dest_name = source.file_name().unwrap().to_string_lossy();
+        pack::write(&dest.path, dest.cipher()?, &dest_name, &content)?;
 
-        // Lấy ra kích thước của tập tin nguồn
-        let file_size = fs::metadata(&source)?.len();
+        // Lấy ra kích thước trên đĩa của tập tin đích
+        let file_size = fs::metadata(&dest_path)?.len();
 
-        // Cộng kích thước của tập tin nguồn vào kích thước của đối tượng lưu trữ đích
+        // Cộng kích thước của tập tin đích vào kích thước của đối tượng lưu trữ đích
         dest.size += file_size;
 
         // Thêm đường dẫn của đích vào danh sách các tập tin và dữ liệu của đối tượng lưu trữ đích
         dest.files.push(dest_path);
 
-        // Trả về kết quả Ok
-        Ok(())
+        // Ghi lại việc chia sẻ vào nhật ký kiểm toán
+        self.record("share", &format!("storage {}/{}", self.name, name), "", to)
+    }
+
+    // Phương thức readable kiểm tra người xem có được đọc một tập tin của doanh nghiệp sở hữu đối tượng lưu trữ hay không
+    // (Shared nghĩa là chủ sở hữu và những người được cấp quyền, ví dụ các kết nối của chủ sở hữu)
+    pub fn readable(&self, name: &str, viewer: &str, grants: &dyn Grants) -> io::Result<bool> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền đọc
+        self.require(Action::StorageRead)?;
+
+        // Chủ sở hữu luôn được đọc
+        let owner = self.owner.as_str();
+        if owner == viewer {
+            return Ok(true);
+        }
+
+        // Những người khác phụ thuộc vào quyền truy cập của tập tin
+        Ok(match self.file_access(name)? {
+            Access::Public => true,
+            Access::Private => false,
+            Access::Shared => grants.granted(owner, viewer),
+        })
+    }
+
+    // Phương thức share_with để chia sẻ một tập tin với đối tượng lưu trữ của người nhận nếu người nhận được phép đọc
+    pub fn share_with(&self, name: &str, dest: &mut Storage, recipient: &str, grants: &dyn Grants) -> io::Result<()> {
+        // Yêu cầu người dùng đã đăng nhập và có quyền ghi
+        self.require(Action::StorageWrite)?;
+
+        // Kiểm tra xem người nhận có được đọc tập tin hay không
+        if !self.readable(name, recipient, grants)? {
+            // Nếu không, trả về một lỗi io với thông báo
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{} may not read {} of {}", recipient, name, self.owner),
+            ));
+        }
+
+        // Nếu được phép, chia sẻ tập tin như bình thường (ghi lại cả người nhận)
+        let to = format!("{} (storage {})", recipient, dest.name);
+        self.copy(name, dest, &to)
     }
 }
 
-// Khai báo một hàm để chuyển enum Access sang chuỗi
-impl ToString for Access {
-    fn to_string(&self) -> String {
+// Hàm check_name kiểm tra tên của một tập tin được tạo hoặc ghi thêm: không rỗng, không bắt đầu bằng dấu chấm (tập tin ẩn như .access,
+// .owner hoặc ..), không chứa dấu gạch chéo (đường dẫn tuyệt đối hoặc thư mục con) và không chứa ký tự xuống dòng
+fn check_name(name: &str) -> io::Result<()> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\', '\n']) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid file name: {:?}", name),
+        ));
+    }
+    Ok(())
+}
+
+// Khai báo cách chuyển chuỗi thành enum Access (ngược lại với Display)
+impl FromStr for Access {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Access> {
+        match s {
+            "Private" => Ok(Access::Private),
+            "Public" => Ok(Access::Public),
+            "Shared" => Ok(Access::Shared),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid access value: {}", s),
+            )),
+        }
+    }
+}
+
+// Khai báo cách hiển thị enum Access thành chuỗi (cũng cung cấp to_string)
+impl fmt::Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Access::Private => "Private".to_string(),
-            Access::Public => "Public".to_string(),
-            Access::Shared => "Shared".to_string(),
+            Access::Private => write!(f, "Private"),
+            Access::Public => write!(f, "Public"),
+            Access::Shared => write!(f, "Shared"),
         }
     }
 }
 
 // Khai báo một module để chứa các unit test
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     // Nhập các thư viện cần thiết
     use super::*;
+    use crate::auth::role::Role;
+    use std::env;
     use std::fs;
-    use tempfile::tempdir;
+    use std::process;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
-        // Khai báo một hàm để tạo một đối tượng lưu trữ tạm thời với tên và quyền truy cập cho trước
-    fn create_temp_storage(name: &str, access: Access) -> io::Result<Storage> {
-        // Lấy ra đường dẫn của thư mục tạm thời của hệ thống
-        let temp_dir = env::temp_dir();
+    // Bộ đếm để mỗi bài kiểm tra có một đường dẫn tạm thời riêng (các bài kiểm tra chạy song song)
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
 
-        // Tạo một đường dẫn mới bằng cách nối tên của đối tượng lưu trữ vào đường dẫn của thư mục tạm thời
-        let path = temp_dir.join(name);
+    // Khai báo một hàm để tạo một đường dẫn tạm thời duy nhất (chưa tồn tại) với tên cho trước
+    pub(crate) fn temp_path(name: &str) -> PathBuf {
+        // Lấy ra số thứ tự tiếp theo của bộ đếm
+        let id = COUNTER.fetch_add(1, Ordering::SeqCst);
 
-        // Tạo một thư mục mới với đường dẫn mới
+        // Nối mã tiến trình, số thứ tự và tên vào đường dẫn của thư mục tạm thời của hệ thống
+        env::temp_dir().join(format!("rim-{}-{}-{}", process::id(), id, name))
+    }
+
+    // Khai báo một hàm để tạo một thư mục tạm thời trống
+    pub(crate) fn tempdir() -> io::Result<PathBuf> {
+        // Tạo một đường dẫn tạm thời duy nhất
+        let path = temp_path("dir");
+
+        // Tạo một thư mục mới với đường dẫn đó
         fs::create_dir_all(&path)?;
 
+        // Trả về đường dẫn của thư mục với kết quả Ok
+        Ok(path)
+    }
+
+    // Khai báo một hàm để tạo một đối tượng lưu trữ tạm thời với tên và quyền truy cập cho trước
+    pub(crate) fn create_temp_storage(name: &str, access: Access) -> io::Result<Storage> {
+        // Tạo một đường dẫn tạm thời duy nhất cho đối tượng lưu trữ
+        let path = temp_path(name);
+
         // Tạo một đối tượng lưu trữ mới với đường dẫn mới và quyền truy cập cho trước
-        let storage = Storage::new(path.to_str().unwrap(), access)?;
+        let mut storage = Storage::new(path.to_str().unwrap(), name, access)?;
+
+        // Đăng nhập bằng một người dùng thử nghiệm của doanh nghiệp cùng tên
+        storage.login(&crate::auth::tests::principal(name))?;
 
         // Trả về đối tượng lưu trữ mới với kết quả Ok
         Ok(storage)
     }
 
+    // Khai báo một hàm để mở lại một đối tượng lưu trữ đã có và đăng nhập bằng người dùng thử nghiệm
+    pub(crate) fn reopen(path: &Path) -> io::Result<Storage> {
+        let mut storage = Storage::open(path.to_str().unwrap())?;
+        let owner = storage.owner().to_string();
+        storage.login(&crate::auth::tests::principal(&owner))?;
+        Ok(storage)
+    }
+
     // Khai báo một hàm để kiểm tra phương thức new của struct Storage
     #[test]
     fn test_new() {
         // Gọi phương thức new với tên và quyền truy cập cho trước
-        let storage = Storage::new("test", Access::Private);
+        let storage = Storage::new("test", "alpha", Access::Private);
 
         // Kiểm tra xem kết quả có phải là Ok hay không
         assert!(storage.is_ok());
@@ -546,6 +1575,8 @@ mod tests {
         assert_eq!(storage.size, 0);
         assert_eq!(storage.files.len(), 0);
         assert_eq!(*storage.access.read().unwrap(), Access::Private);
+        assert_eq!(storage.owner(), "alpha");
+        assert!(Storage::new("test-owner", "", Access::Private).is_err());
 
         // Xoá đối tượng lưu trữ khỏi hệ thống tập tin
         fs::remove_dir_all("test").unwrap();
@@ -561,7 +1592,7 @@ mod tests {
         let path = storage.path.clone();
 
         // Gọi phương thức open với tên của đối tượng lưu trữ tạm thời
-        let storage = Storage::open("test");
+        let storage = Storage::open(path.to_str().unwrap());
 
         // Kiểm tra xem kết quả có phải là Ok hay không
         assert!(storage.is_ok());
@@ -570,7 +1601,7 @@ mod tests {
         let storage = storage.unwrap();
 
         // Kiểm tra xem các trường của đối tượng lưu trữ có khớp với các giá trị mong muốn hay không
-        assert_eq!(storage.name, "test");
+        assert_eq!(storage.name, path.to_str().unwrap());
         assert_eq!(storage.path, path);
         assert_eq!(storage.size, 0);
         assert_eq!(storage.files.len(), 0);
@@ -580,6 +1611,533 @@ mod tests {
         fs::remove_dir_all(path).unwrap();
     }
 
+    // Khai báo một hàm để kiểm tra việc yêu cầu đăng nhập trước khi thao tác với đối tượng lưu trữ
+    #[test]
+    fn test_login() {
+        // Tạo một đối tượng lưu trữ tạm thời rồi mở lại (chưa đăng nhập)
+        let storage = create_temp_storage("test", Access::Private).unwrap();
+        let mut storage = Storage::open(storage.path().to_str().unwrap()).unwrap();
+        assert!(storage.principal().is_none());
+
+        // Mọi thao tác đều bị từ chối khi chưa đăng nhập
+        let error = storage.put("source.txt", b"Hello").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        assert!(storage.list().is_err());
+        assert!(storage.info().is_err());
+        assert!(storage.set_access(Access::Public).is_err());
+
+        // Phiên đã hết hạn thì không đăng nhập được
+        let expired = Principal::new(0, "tester", "test", Role::Owner, 0);
+        assert!(storage.login(&expired).is_err());
+
+        // Chủ sở hữu của doanh nghiệp khác không đăng nhập được
+        let error = storage.login(&crate::auth::tests::principal("other")).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        assert!(storage.principal().is_none());
+
+        // Đăng nhập thì thao tác được
+        storage.login(&crate::auth::tests::principal("test")).unwrap();
+        storage.put("source.txt", b"Hello").unwrap();
+        assert_eq!(storage.list().unwrap(), ["source.txt"]);
+
+        // Đăng xuất thì lại bị từ chối
+        storage.logout();
+        assert!(storage.view("source.txt").is_err());
+
+        // Nhân viên chỉ được đọc, không được ghi
+        let staff = Principal::new(1, "staff", "test", Role::Staff, u64::MAX);
+        storage.login(&staff).unwrap();
+        assert_eq!(storage.view("source.txt").unwrap(), "Hello");
+        let error = storage.edit("source.txt", "Bye").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        assert!(storage.delete("source.txt").is_err());
+
+        // Xoá đối tượng lưu trữ khỏi hệ thống tập tin
+        fs::remove_dir_all(storage.path()).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra việc tạo tập tin cùng với quyền truy cập riêng
+    #[test]
+    fn test_put_with_access() {
+        let mut storage = create_temp_storage("test", Access::Public).unwrap();
+
+        // Tập tin mới mang quyền được chỉ định thay vì quyền của đối tượng lưu trữ
+        storage.put_with_access("secret.txt", b"Hidden", Access::Private).unwrap();
+        assert_eq!(storage.file_access("secret.txt").unwrap(), Access::Private);
+        assert_eq!(storage.view("secret.txt").unwrap(), "Hidden");
+
+        // Tập tin đã tồn tại thì bị từ chối và giữ nguyên quyền cũ
+        let error = storage.put_with_access("secret.txt", b"Shown", Access::Public).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(storage.file_access("secret.txt").unwrap(), Access::Private);
+        assert!(storage.put_with_access(".access", b"Public", Access::Public).is_err());
+
+        // Xoá đối tượng lưu trữ khỏi hệ thống tập tin
+        fs::remove_dir_all(storage.path()).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra tên tập tin không hợp lệ bị từ chối
+    #[test]
+    fn test_names() {
+        let mut storage = create_temp_storage("test", Access::Private).unwrap();
+
+        // Tên rỗng, tập tin ẩn, đường dẫn ra ngoài hoặc tuyệt đối đều bị từ chối
+        for name in ["", ".access", ".owner", "../escape.txt", "/tmp/escape.txt", "sub/file.txt", "a\\b", "a\nb"] {
+            let error = storage.put(name, b"Hello").unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+            assert!(storage.put_with_access(name, b"Hello", Access::Private).is_err());
+            assert_eq!(storage.append(name, b"Hello").unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        }
+        assert!(storage.list().unwrap().is_empty());
+        assert!(!storage.path().parent().unwrap().join("escape.txt").exists());
+
+        // Xoá đối tượng lưu trữ khỏi hệ thống tập tin
+        fs::remove_dir_all(storage.path()).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra việc ghi các thay đổi quyền, chia sẻ và xoá vào nhật ký kiểm toán
+    #[test]
+    fn test_audit() {
+        // Tạo hai đối tượng lưu trữ tạm thời và gắn nhật ký kiểm toán cho đối tượng nguồn
+        let (audit, audit_path) = crate::audit::tests::audit();
+        let mut storage = create_temp_storage("test", Access::Private).unwrap();
+        let mut dest = create_temp_storage("dest", Access::Private).unwrap();
+        storage.set_audit(audit.clone());
+
+        // Thực hiện các thao tác liên quan đến bảo mật
+        storage.put("source.txt", b"Hello").unwrap();
+        storage.set_access(Access::Public).unwrap();
+        storage.set_file_access("source.txt", Access::Shared).unwrap();
+        storage.share("source.txt", &mut dest).unwrap();
+        storage.delete("source.txt").unwrap();
+
+        // Thao tác bị từ chối thì không được ghi
+        let staff = Principal::new(1, "staff", "test", Role::Staff, u64::MAX);
+        storage.login(&staff).unwrap();
+        assert!(storage.set_access(Access::Private).is_err());
+
+        // Kiểm tra các mục đã ghi (put không phải thao tác liên quan đến bảo mật)
+        let entries = audit.entries().unwrap();
+        let actions: Vec<&str> = entries.iter().map(|entry| entry.action.as_str()).collect();
+        assert_eq!(actions, ["set_access", "set_file_access", "share", "delete"]);
+        assert_eq!(entries[0].actor, "tester@test");
+        assert_eq!(entries[0].resource, format!("storage {}", storage.name()));
+        assert_eq!((entries[0].before.as_str(), entries[0].after.as_str()), ("Private", "Public"));
+        assert_eq!((entries[1].before.as_str(), entries[1].after.as_str()), ("Public", "Shared"));
+        assert_eq!(entries[2].after, format!("storage {}", dest.name()));
+        assert_eq!(entries[3].before, "5 bytes");
+        assert_eq!(audit.verify().unwrap(), 4);
+
+        // Xoá các đối tượng lưu trữ khỏi hệ thống tập tin
+        fs::remove_dir_all(storage.path()).unwrap();
+        fs::remove_dir_all(dest.path()).unwrap();
+        fs::remove_dir_all(audit_path).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra việc phát hiện và khôi phục tập tin hỏng
+    #[test]
+    fn test_verify() {
+        // Ghi các tập tin vào đối tượng lưu trữ và một bản sao của chúng
+        let mut storage = create_temp_storage("test", Access::Private).unwrap();
+        let mut replica = create_temp_storage("replica", Access::Private).unwrap();
+        for (name, content) in [("a.txt", "Alpha"), ("b.txt", "Bravo"), ("c.txt", "Charlie")] {
+            storage.put(name, content.as_bytes()).unwrap();
+            replica.put(name, content.as_bytes()).unwrap();
+        }
+        storage.append("log.txt", b"first\n").unwrap();
+        storage.append("log.txt", b"second\n").unwrap();
+        let report = storage.verify(None).unwrap();
+        assert_eq!(report.checked, 4);
+        assert!(report.findings.is_empty() && report.clean());
+
+        // Sửa, cắt bớt, xoá và thêm tập tin ngoài API
+        let path = storage.path().to_path_buf();
+        fs::write(path.join("a.txt"), "Alpho").unwrap();
+        fs::write(path.join("b.txt"), "Bra").unwrap();
+        fs::remove_file(path.join("c.txt")).unwrap();
+        fs::write(path.join("extra.txt"), "Dropped in").unwrap();
+        let report = storage.verify(None).unwrap();
+        let problems: Vec<(&str, &str)> = report.findings.iter().map(|f| (f.name.as_str(), f.problem.kind())).collect();
+        assert_eq!(problems, [("a.txt", "checksum"), ("b.txt", "size"), ("c.txt", "missing"), ("extra.txt", "extra")]);
+        assert_eq!(report.findings[1].problem, Problem::Size { expected: 5, actual: 3 });
+        assert!(!report.clean());
+        let xml = report.xml();
+        assert!(xml.contains("<ScrubReport storage=\"") && xml.contains("checked=\"5\" clean=\"false\">"));
+        assert!(xml.contains("<Finding name=\"b.txt\" problem=\"size\" repaired=\"false\">"));
+        assert!(xml.contains("<Expected>5</Expected>"));
+
+        // Bản sao có nội dung không khớp mã kiểm tra thì không được dùng
+        let wrong = |_: &str| -> io::Result<Option<Vec<u8>>> { Ok(Some(b"Wrong".to_vec())) };
+        let report = storage.verify(Some(&wrong)).unwrap();
+        assert!(report.findings.iter().all(|f| !f.repaired));
+
+        // Khôi phục từ bản sao: tập tin thừa vẫn được báo nhưng không bị động tới
+        let report = storage.verify(Some(&replica)).unwrap();
+        let repaired: Vec<&str> = report.findings.iter().filter(|f| f.repaired).map(|f| f.name.as_str()).collect();
+        assert_eq!(repaired, ["a.txt", "b.txt", "c.txt"]);
+        assert!(!report.clean());
+        assert_eq!(storage.view("a.txt").unwrap(), "Alpha");
+        assert_eq!(storage.view("c.txt").unwrap(), "Charlie");
+        assert_eq!(storage.baseline().unwrap(), 1);
+        assert!(storage.verify(None).unwrap().clean());
+        assert!(storage.info().unwrap().contains("Files: 5 items"));
+
+        // Tập tin mã hoá bị sửa không giải mã được nên bị báo sai mã kiểm tra
+        storage.encrypt(&[7; 32]).unwrap();
+        let mut sealed = fs::read(path.join("log.txt")).unwrap();
+        sealed[20] ^= 1;
+        fs::write(path.join("log.txt"), sealed).unwrap();
+        let report = storage.verify(None).unwrap();
+        assert_eq!(report.findings.len(), 1);
+        assert!(matches!(&report.findings[0].problem, Problem::Checksum { actual: None, .. }));
+        let history = |name: &str| -> io::Result<Option<Vec<u8>>> { Ok((name == "log.txt").then(|| b"first\nsecond\n".to_vec())) };
+        assert!(storage.verify(Some(&history)).unwrap().clean());
+        assert_eq!(storage.view("log.txt").unwrap(), "first\nsecond\n");
+
+        // Ghi thêm băm tiếp từ trạng thái đã lưu, được mã hoá cùng đối tượng lưu trữ
+        storage.append("log.txt", b"third\n").unwrap();
+        assert!(storage.verify(None).unwrap().clean());
+        let record = fs::read_to_string(path.join(".sums").join("log.txt")).unwrap();
+        assert!(!record.contains(&crate::crypto::hex(b"third")));
+
+        // Nội dung bị sửa ngoài API trước khi ghi thêm vẫn bị phát hiện vì không bị băm lại từ đầu
+        fs::write(replica.path().join("a.txt"), "Alphx").unwrap();
+        replica.append("a.txt", b"!").unwrap();
+        let report = replica.verify(None).unwrap();
+        assert!(matches!(&report.findings[0].problem, Problem::Checksum { actual: Some(_), .. }));
+
+        // Xoá các đối tượng lưu trữ khỏi hệ thống tập tin
+        fs::remove_dir_all(path).unwrap();
+        fs::remove_dir_all(replica.path()).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra ảnh chụp, so sánh, khôi phục và chính sách giữ ảnh chụp
+    #[test]
+    fn test_snapshot() {
+        // Chụp đối tượng lưu trữ với một tập tin lớn được nén và một tập tin có quyền truy cập riêng
+        let mut storage = create_temp_storage("test", Access::Private).unwrap();
+        storage.compress().unwrap();
+        let big = "snapshot ".repeat(500);
+        storage.put("big.txt", big.as_bytes()).unwrap();
+        storage.put("a.txt", b"Alpha").unwrap();
+        storage.append("log.txt", b"first\n").unwrap();
+        storage.set_file_access("a.txt", Access::Public).unwrap();
+        let manifest = storage.snapshot("monday").unwrap();
+        assert_eq!(manifest.entries.len(), 3);
+        assert_eq!(manifest.entry("a.txt").unwrap().access, Some(Access::Public));
+        assert_eq!(storage.snapshot("monday").unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(storage.snapshot(".hidden").unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert!(storage.diff("monday", None).unwrap().is_empty());
+
+        // Các thay đổi sau đó không làm hỏng ảnh chụp dù tập tin được liên kết cứng
+        let path = storage.path().to_path_buf();
+        storage.append("log.txt", b"second\n").unwrap();
+        storage.edit("big.txt", "Replaced").unwrap();
+        storage.delete("a.txt").unwrap();
+        storage.put("b.txt", b"Bravo").unwrap();
+        storage.set_access(Access::Shared).unwrap();
+        let changes = storage.diff("monday", None).unwrap();
+        assert_eq!(changes, [
+            Change::Access(Access::Private, Access::Shared),
+            Change::Removed("a.txt".to_string()),
+            Change::Added("b.txt".to_string()),
+            Change::Modified("big.txt".to_string()),
+            Change::Modified("log.txt".to_string()),
+        ]);
+        let snapshot = storage.open_snapshot("monday").unwrap();
+        assert_eq!(snapshot.read("big.txt").unwrap(), big.as_bytes());
+        assert_eq!(snapshot.read("log.txt").unwrap(), b"first\n");
+        assert_eq!(snapshot.read("b.txt").unwrap_err().kind(), io::ErrorKind::NotFound);
+
+        // Ảnh chụp dùng được làm nguồn khôi phục cho verify (tập tin bị thay thế hoặc xoá)
+        storage.snapshot("tuesday").unwrap();
+        fs::remove_file(path.join("b.txt")).unwrap();
+        fs::write(path.join("b.txt"), "Brovo").unwrap();
+        let snapshot = storage.open_snapshot("tuesday").unwrap();
+        let report = storage.verify(Some(&snapshot)).unwrap();
+        assert!(report.clean() && report.findings.len() == 1);
+        assert_eq!(storage.view("b.txt").unwrap(), "Bravo");
+        assert_eq!(storage.diff("monday", Some("tuesday")).unwrap(), storage.diff("monday", None).unwrap());
+
+        // Khôi phục đưa tập tin, quyền truy cập riêng và quyền truy cập của đối tượng lưu trữ về lúc chụp
+        storage.restore("monday").unwrap();
+        assert!(storage.diff("monday", None).unwrap().is_empty());
+        assert_eq!(storage.file_access("log.txt").unwrap(), Access::Private);
+        assert_eq!(storage.file_access("a.txt").unwrap(), Access::Public);
+        assert_eq!(storage.view("log.txt").unwrap(), "first\n");
+        assert!(storage.verify(None).unwrap().findings.is_empty());
+        let reopened = reopen(&path).unwrap();
+        assert_eq!(reopened.file_access("log.txt").unwrap(), Access::Private);
+        assert!(reopened.info().unwrap().contains("Files: 3 items"));
+        assert_eq!(storage.restore("friday").unwrap_err().kind(), io::ErrorKind::NotFound);
+
+        // Chính sách giữ ảnh chụp xoá các ảnh chụp cũ ngay khi đặt và sau mỗi lần chụp
+        assert_eq!(storage.retention().unwrap(), Retention::default());
+        assert_eq!(storage.set_retention(Retention { last: Some(1), within: None }).unwrap(), ["monday"]);
+        storage.snapshot("wednesday").unwrap();
+        let names: Vec<String> = storage.snapshots().unwrap().into_iter().map(|m| m.name).collect();
+        assert_eq!(names, ["wednesday"]);
+
+        // Không mã hoá được khi còn ảnh chụp chưa mã hoá
+        assert_eq!(storage.encrypt(&[7; 32]).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        storage.delete_snapshot("wednesday").unwrap();
+        storage.encrypt(&[7; 32]).unwrap();
+
+        // Ảnh chụp của đối tượng lưu trữ đã mã hoá vẫn đọc được sau khi thay khoá
+        storage.snapshot("sealed").unwrap();
+        storage.rotate_key(&[7; 32]).unwrap();
+        let mut reopened = reopen(&path).unwrap();
+        reopened.unlock(&[7; 32]).unwrap();
+        assert_eq!(reopened.open_snapshot("sealed").unwrap().read("log.txt").unwrap(), b"first\n");
+        reopened.restore("sealed").unwrap();
+        assert_eq!(reopened.view("a.txt").unwrap(), "Alpha");
+
+        // Xoá đối tượng lưu trữ khỏi hệ thống tập tin
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra sao lưu đầy đủ, sao lưu tăng dần và nhập lại dưới tên mới
+    #[test]
+    fn test_backup() {
+        // Sao lưu đầy đủ một đối tượng lưu trữ có nén, quyền truy cập riêng và một ảnh chụp
+        let mut storage = create_temp_storage("test", Access::Shared).unwrap();
+        storage.compress().unwrap();
+        let big = "backup ".repeat(500);
+        storage.put("big.txt", big.as_bytes()).unwrap();
+        storage.put("a.txt", b"Alpha").unwrap();
+        storage.set_file_access("a.txt", Access::Public).unwrap();
+        storage.snapshot("monday").unwrap();
+        storage.edit("a.txt", "Alpha 2").unwrap();
+        storage.set_retention(Retention { last: Some(5), within: None }).unwrap();
+        let mut full = Vec::new();
+        let catalog = storage.export(&mut full, None).unwrap();
+        assert!(!catalog.incremental() && catalog.objects.len() == 3);
+        assert_eq!(backup::catalog(&full[..]).unwrap(), catalog);
+
+        // Sao lưu tăng dần chỉ chứa nội dung mới
+        storage.put("b.txt", b"Bravo").unwrap();
+        storage.delete("big.txt").unwrap();
+        let mut incremental = Vec::new();
+        let next = storage.export(&mut incremental, Some(&backup::catalog(&full[..]).unwrap())).unwrap();
+        assert_eq!(next.base.as_deref(), Some(catalog.id.as_str()));
+        assert_eq!(next.objects.len(), 4);
+        let mut reader = tar::Reader::new(&incremental[..]);
+        let mut objects = Vec::new();
+        while let Some((name, _)) = reader.entry().unwrap() {
+            objects.extend(name.strip_prefix("rim/objects/").map(str::to_string));
+        }
+        assert_eq!(objects, [scrub::digest(&b"Bravo"[..]).unwrap().hash]);
+
+        // Nhập chuỗi sao lưu dưới tên mới: tập tin, quyền truy cập, thiết lập và ảnh chụp đều được dựng lại
+        let principal = crate::auth::tests::principal("test");
+        let name = temp_path("imported");
+        let mut imported = Storage::import(name.to_str().unwrap(), &principal, &mut [&mut &full[..], &mut &incremental[..]]).unwrap();
+        assert_eq!(imported.list().unwrap(), ["a.txt", "b.txt"]);
+        assert_eq!(imported.view("a.txt").unwrap(), "Alpha 2");
+        assert_eq!(imported.file_access("a.txt").unwrap(), Access::Public);
+        assert_eq!(imported.file_access("b.txt").unwrap(), Access::Shared);
+        assert!(imported.compressed());
+        assert_eq!(imported.retention().unwrap(), Retention { last: Some(5), within: None });
+        assert_eq!(imported.open_snapshot("monday").unwrap().read("big.txt").unwrap(), big.as_bytes());
+        assert!(imported.diff("monday", None).unwrap().contains(&Change::Removed("big.txt".to_string())));
+        assert!(imported.verify(None).unwrap().findings.is_empty());
+        imported.restore("monday").unwrap();
+        assert_eq!(imported.view("a.txt").unwrap(), "Alpha");
+        let reopened = reopen(&name).unwrap();
+        assert!(reopened.info().unwrap().contains("Files: 2 items\nAccess: Shared"));
+
+        // Tên đã tồn tại, chuỗi sao lưu thiếu bản đầy đủ và nội dung bị sửa đều bị từ chối
+        let again = Storage::import(name.to_str().unwrap(), &principal, &mut [&mut &full[..]]);
+        assert_eq!(again.unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+        let other = temp_path("other");
+        let error = Storage::import(other.to_str().unwrap(), &principal, &mut [&mut &incremental[..]]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(!other.exists());
+        let offset = incremental.windows(5).position(|window| window == b"Bravo").unwrap();
+        incremental[offset] = b'C';
+        let error = Storage::import(other.to_str().unwrap(), &principal, &mut [&mut &full[..], &mut &incremental[..]]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(!other.exists());
+
+        // Đối tượng lưu trữ đã mã hoá chỉ được xuất ở dạng giải mã khi chủ động yêu cầu, với quyền ghi,
+        // và việc đó được ghi vào nhật ký kiểm toán
+        let (audit, audit_path) = crate::audit::tests::audit();
+        let mut sealed = create_temp_storage("sealed", Access::Private).unwrap();
+        sealed.set_audit(audit.clone());
+        sealed.put("secret.txt", b"Top secret").unwrap();
+        sealed.encrypt(&[7; 32]).unwrap();
+        let error = sealed.export(&mut Vec::new(), None).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        sealed.login(&Principal::new(1, "staff", "sealed", Role::Staff, u64::MAX)).unwrap();
+        assert!(sealed.export_decrypted(&mut Vec::new(), None).is_err());
+        sealed.login(&crate::auth::tests::principal("sealed")).unwrap();
+        let mut archive = Vec::new();
+        let catalog = sealed.export_decrypted(&mut archive, None).unwrap();
+        let entry = audit.entries().unwrap().pop().unwrap();
+        assert_eq!(entry.action, "export");
+        assert_eq!(entry.after, format!("backup {} (1 objects, decrypted)", catalog.id));
+        assert!(reopen(sealed.path()).unwrap().export_decrypted(&mut Vec::new(), None).is_err());
+        let plain = Storage::import(other.to_str().unwrap(), &principal, &mut [&mut &archive[..]]).unwrap();
+        assert!(!plain.encrypted());
+        assert_eq!(plain.view("secret.txt").unwrap(), "Top secret");
+
+        // Xoá các đối tượng lưu trữ khỏi hệ thống tập tin
+        for path in [storage.path(), imported.path(), sealed.path(), plain.path(), &audit_path] {
+            fs::remove_dir_all(path).unwrap();
+        }
+    }
+
+    // Khai báo một hàm để kiểm tra việc nén nội dung của đối tượng lưu trữ
+    #[test]
+    fn test_compress() {
+        // Tạo các tập tin trước khi bật nén: một nhật ký lớn, một tập tin nhỏ và một ảnh đã nén sẵn
+        let log: String = (0..2000).map(|i| format!("2026-10-19 INFO request {} served\n", i)).collect();
+        let image = vec![7; 4096];
+        let mut storage = create_temp_storage("test", Access::Private).unwrap();
+        storage.put("export.log", log.as_bytes()).unwrap();
+        storage.put("note.txt", b"Short note").unwrap();
+        storage.put("photo.png", &image).unwrap();
+        assert!(!storage.compressed());
+        storage.compress().unwrap();
+        assert!(storage.compressed());
+        assert!(storage.compress().is_err());
+
+        // Chỉ nhật ký được nén (ở dạng gzip chuẩn), nội dung đọc ra vẫn như cũ
+        let path = storage.path().to_path_buf();
+        let on_disk = fs::read(path.join("export.log")).unwrap();
+        assert!(on_disk.len() < log.len() / 4);
+        assert_eq!(super::deflate::gunzip(&on_disk).unwrap(), log.as_bytes());
+        assert_eq!(fs::read(path.join("note.txt")).unwrap(), b"Short note");
+        assert_eq!(fs::read(path.join("photo.png")).unwrap(), image);
+        assert_eq!(storage.view("export.log").unwrap(), log);
+        assert_eq!(storage.range("export.log", 11, 4).unwrap(), b"INFO");
+
+        // info báo cả kích thước trên đĩa lẫn kích thước gốc
+        let logical = log.len() + 10 + image.len();
+        let physical = on_disk.len() + 10 + image.len();
+        let info = storage.info().unwrap();
+        assert!(info.contains(&format!("Size: {} bytes\n", physical)));
+        assert!(info.contains(&format!("Logical size: {} bytes\n", logical)));
+
+        // Ghi thêm vào tập tin đã nén tạo thành phần gzip mới; sửa thành nội dung nhỏ thì lưu nguyên
+        storage.append("export.log", b"tail\n").unwrap();
+        assert_eq!(storage.view("export.log").unwrap(), format!("{}tail\n", log));
+        storage.edit("note.txt", &log).unwrap();
+        assert!(fs::metadata(path.join("note.txt")).unwrap().len() < log.len() as u64 / 4);
+        storage.edit("note.txt", "Short again").unwrap();
+        assert_eq!(fs::read(path.join("note.txt")).unwrap(), b"Short again");
+
+        // Chia sẻ sang đối tượng lưu trữ không nén thì tập tin được giải nén
+        let mut dest = create_temp_storage("dest", Access::Private).unwrap();
+        storage.share("export.log", &mut dest).unwrap();
+        assert_eq!(fs::read_to_string(dest.path().join("export.log")).unwrap(), format!("{}tail\n", log));
+
+        // Nén rồi mã hoá: mở lại vẫn biết kích thước gốc khi chưa mở khoá và đọc được sau khi mở khoá
+        storage.encrypt(&[7; 32]).unwrap();
+        let info = storage.info().unwrap();
+        drop(storage);
+        let mut storage = reopen(&path).unwrap();
+        assert_eq!(storage.info().unwrap(), info);
+        storage.unlock(&[7; 32]).unwrap();
+        assert_eq!(storage.view("export.log").unwrap(), format!("{}tail\n", log));
+
+        // Xoá tập tin thì xoá cả dấu nén
+        storage.delete("export.log").unwrap();
+        assert!(!path.join(".packed").join("export.log").exists());
+
+        // Xoá các đối tượng lưu trữ khỏi hệ thống tập tin
+        fs::remove_dir_all(path).unwrap();
+        fs::remove_dir_all(dest.path()).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra việc mã hoá nội dung của đối tượng lưu trữ
+    #[test]
+    fn test_encrypt() {
+        // Bật mã hoá cho một đối tượng lưu trữ đã có tập tin
+        let master = [7; 32];
+        let mut storage = create_temp_storage("test", Access::Private).unwrap();
+        storage.put("plain.txt", b"Before encryption").unwrap();
+        assert!(!storage.encrypted());
+        storage.encrypt(&master).unwrap();
+        assert!(storage.encrypted());
+        assert!(storage.encrypt(&master).is_err());
+
+        // Nội dung trên đĩa đã được mã hoá nhưng vẫn đọc ghi được như bình thường
+        let on_disk = fs::read(storage.path().join("plain.txt")).unwrap();
+        assert!(!String::from_utf8_lossy(&on_disk).contains("Before"));
+        assert_eq!(storage.view("plain.txt").unwrap(), "Before encryption");
+        storage.put("secret.txt", b"Top secret").unwrap();
+        storage.edit("secret.txt", "Still secret").unwrap();
+        storage.append("secret.txt", b", more").unwrap();
+        assert_eq!(storage.view("secret.txt").unwrap(), "Still secret, more");
+        assert_eq!(storage.range("secret.txt", 6, 6).unwrap(), b"secret");
+        let download = temp_path("download.txt");
+        storage.download("secret.txt", &download).unwrap();
+        assert_eq!(fs::read_to_string(&download).unwrap(), "Still secret, more");
+
+        // Chia sẻ sang đối tượng lưu trữ không mã hoá thì tập tin được giải mã
+        let mut dest = create_temp_storage("dest", Access::Private).unwrap();
+        storage.share("secret.txt", &mut dest).unwrap();
+        assert_eq!(dest.view("secret.txt").unwrap(), "Still secret, more");
+
+        // Mở lại: chưa mở khoá hoặc sai khoá chủ thì bị từ chối, kích thước vẫn khớp với trên đĩa
+        let path = storage.path().to_path_buf();
+        let info = storage.info().unwrap();
+        drop(storage);
+        let mut storage = reopen(&path).unwrap();
+        let error = storage.view("secret.txt").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        assert!(storage.put("new.txt", b"x").is_err());
+        assert!(storage.unlock(&[8; 32]).is_err());
+        storage.unlock(&master).unwrap();
+        assert_eq!(storage.view("secret.txt").unwrap(), "Still secret, more");
+        assert_eq!(storage.info().unwrap(), info);
+
+        // Thay khoá chủ và khoá của đối tượng lưu trữ mà không ghi lại nội dung các tập tin
+        let before = fs::read(path.join("secret.txt")).unwrap();
+        let new = [9; 32];
+        storage.rotate_master(&master, &new).unwrap();
+        assert!(storage.rotate_key(&master).is_err());
+        storage.rotate_key(&new).unwrap();
+        assert_eq!(fs::read(path.join("secret.txt")).unwrap(), before);
+        assert_eq!(storage.view("secret.txt").unwrap(), "Still secret, more");
+        storage.lock();
+        assert!(storage.view("secret.txt").is_err());
+        assert!(storage.unlock(&master).is_err());
+        storage.unlock(&new).unwrap();
+        assert_eq!(storage.view("plain.txt").unwrap(), "Before encryption");
+
+        // Xoá tập tin thì xoá cả khoá dữ liệu của nó
+        storage.delete("secret.txt").unwrap();
+        assert!(!path.join(".keys").join("secret.txt").exists());
+
+        // Lần mã hoá bị dừng sau khi đã mã hoá một phần tập tin được hoàn tất khi mở khoá
+        let mut partial = create_temp_storage("partial", Access::Private).unwrap();
+        partial.put("one.txt", b"First").unwrap();
+        partial.put("two.txt", b"Second").unwrap();
+        let key = vault::Key::new().unwrap();
+        vault::begin(partial.path(), &vault::Key::raw(&master), &key).unwrap();
+        vault::write(partial.path(), Some(&key), "one.txt", b"First").unwrap();
+        let partial_path = partial.path().to_path_buf();
+        drop(partial);
+        let mut partial = reopen(&partial_path).unwrap();
+        assert!(partial.encrypted());
+        assert!(partial.view("two.txt").is_err());
+        assert!(partial.unlock(&new).is_err());
+        partial.unlock(&master).unwrap();
+        assert!(!vault::pending(&partial_path));
+        let on_disk = fs::read(partial_path.join("two.txt")).unwrap();
+        assert!(!String::from_utf8_lossy(&on_disk).contains("Second"));
+        assert_eq!(partial.view("one.txt").unwrap(), "First");
+        assert_eq!(partial.view("two.txt").unwrap(), "Second");
+        fs::remove_dir_all(partial_path).unwrap();
+
+        // Xoá các đối tượng lưu trữ khỏi hệ thống tập tin
+        fs::remove_dir_all(path).unwrap();
+        fs::remove_dir_all(dest.path()).unwrap();
+        fs::remove_file(download).unwrap();
+    }
+
     // Khai báo một hàm để kiểm tra phương thức info của struct Storage
     #[test]
     fn test_info() {
@@ -587,12 +2145,12 @@ mod tests {
         let storage = create_temp_storage("test", Access::Shared).unwrap();
 
         // Gọi phương thức info để lấy thông tin của đối tượng lưu trữ
-        let info = storage.info();
+        let info = storage.info().unwrap();
 
         // Tạo một chuỗi để lưu trữ thông tin mong muốn của đối tượng lưu trữ
         let expected_info = format!(
-            "Name: test\nPath: {:?}\nSize: 0 bytes\nFiles: 0 items\nAccess: Shared\n",
-            storage.path
+            "Name: {}\nPath: {:?}\nSize: 0 bytes\nFiles: 0 items\nAccess: Shared\nLogical size: 0 bytes\n",
+            storage.name, storage.path
         );
 
         // Kiểm tra xem thông tin có khớp với thông tin mong muốn hay không
@@ -659,7 +2217,7 @@ mod tests {
         let dir = tempdir().unwrap();
 
         // Lấy ra đường dẫn của thư mục tạm thời
-        let temp_path = dir.path();
+        let temp_path = dir.as_path();
 
         // Tạo một đường dẫn mới cho nguồn bằng cách nối tên của tập tin vào đường dẫn của thư mục tạm thời
         let source = temp_path.join("source.txt");
@@ -699,6 +2257,7 @@ mod tests {
 
         // Xoá đối tượng lưu trữ khỏi hệ thống tập tin
         fs::remove_dir_all(path).unwrap();
+        fs::remove_dir_all(dir).unwrap();
     }
 
     // Khai báo một hàm để kiểm tra phương thức download của struct Storage
@@ -714,7 +2273,7 @@ mod tests {
         let dir = tempdir().unwrap();
 
         // Lấy ra đường dẫn của thư mục tạm thời
-        let temp_path = dir.path();
+        let temp_path = dir.as_path();
 
         // Tạo một đường dẫn mới cho nguồn bằng cách nối tên của tập tin vào đường dẫn của đối tượng lưu trữ
         let source = path.join("source.txt");
@@ -779,6 +2338,7 @@ mod tests {
 
         // Xoá đối tượng lưu trữ khỏi hệ thống tập tin
         fs::remove_dir_all(path).unwrap();
+        fs::remove_dir_all(dir).unwrap();
     }
 
     // Khai báo một hàm để kiểm tra phương thức view của struct Storage
@@ -1019,5 +2579,89 @@ mod tests {
         fs::remove_dir_all(path).unwrap();
         fs::remove_dir_all(dest_path).unwrap();
     }
+
+    // Khai báo một hàm để kiểm tra các phương thức set_file_access và file_access của struct Storage
+    #[test]
+    fn test_file_access() {
+        // Tạo một đối tượng lưu trữ tạm thời với quyền truy cập Public
+        let mut storage = create_temp_storage("test", Access::Public).unwrap();
+
+        // Lấy ra đường dẫn của đối tượng lưu trữ tạm thời
+        let path = storage.path.clone();
+
+        // Tạo một tập tin mới trong đối tượng lưu trữ
+        storage.put("source.txt", b"Hello, world!").unwrap();
+
+        // Tập tin chưa có quyền truy cập riêng nên dùng quyền của đối tượng lưu trữ
+        assert_eq!(storage.file_access("source.txt").unwrap(), Access::Public);
+
+        // Thiết lập quyền truy cập riêng cho tập tin
+        storage.set_file_access("source.txt", Access::Private).unwrap();
+        assert_eq!(storage.file_access("source.txt").unwrap(), Access::Private);
+
+        // Quyền truy cập riêng vẫn còn sau khi mở lại đối tượng lưu trữ
+        let mut reopened = Storage::open(path.to_str().unwrap()).unwrap();
+        reopened.login(storage.principal().unwrap()).unwrap();
+        assert_eq!(reopened.file_access("source.txt").unwrap(), Access::Private);
+        assert_eq!(*reopened.access.read().unwrap(), Access::Public);
+
+        // Không thể thiết lập quyền truy cập cho tập tin không tồn tại
+        assert!(storage.set_file_access("missing.txt", Access::Public).is_err());
+
+        // Xoá tập tin thì quyền truy cập riêng cũng bị xoá
+        storage.delete("source.txt").unwrap();
+        assert!(!path.join(".access/source.txt").exists());
+
+        // Xoá đối tượng lưu trữ khỏi hệ thống tập tin
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra việc chia sẻ theo quyền Shared với những người được cấp quyền
+    #[test]
+    fn test_share_with() {
+        // Tạo hai đối tượng lưu trữ tạm thời của chủ sở hữu và người nhận
+        let mut storage = create_temp_storage("alpha", Access::Private).unwrap();
+        let mut dest = create_temp_storage("beta", Access::Private).unwrap();
+
+        // Tạo một tập tin với quyền Shared
+        storage.put("source.txt", b"Hello, world!").unwrap();
+        storage.set_file_access("source.txt", Access::Shared).unwrap();
+
+        // Chỉ alpha cấp quyền cho beta (chủ sở hữu lấy từ đối tượng lưu trữ, không do người gọi khai báo)
+        let grants = |owner: &str, viewer: &str| owner == "alpha" && viewer == "beta";
+        assert!(storage.readable("source.txt", "alpha", &grants).unwrap());
+        assert!(storage.readable("source.txt", "beta", &grants).unwrap());
+        assert!(!storage.readable("source.txt", "gamma", &grants).unwrap());
+
+        // Người không được cấp quyền không thể nhận tập tin
+        assert!(storage.share_with("source.txt", &mut dest, "gamma", &grants).is_err());
+        storage.share_with("source.txt", &mut dest, "beta", &grants).unwrap();
+        assert_eq!(dest.view("source.txt").unwrap(), "Hello, world!");
+
+        // Tập tin Private không chia sẻ được với ai khác
+        storage.set_file_access("source.txt", Access::Private).unwrap();
+        assert!(!storage.readable("source.txt", "beta", &grants).unwrap());
+
+        // Xoá các đối tượng lưu trữ khỏi hệ thống tập tin
+        fs::remove_dir_all(storage.path()).unwrap();
+        fs::remove_dir_all(dest.path()).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra việc ghi thêm vào cuối tập tin
+    #[test]
+    fn test_append() {
+        // Tạo một đối tượng lưu trữ tạm thời
+        let mut storage = create_temp_storage("test", Access::Private).unwrap();
+
+        // Ghi thêm hai lần: lần đầu tạo tập tin, lần sau nối vào cuối
+        storage.append("log.txt", b"first\n").unwrap();
+        storage.append("log.txt", b"second\n").unwrap();
+        assert_eq!(storage.view("log.txt").unwrap(), "first\nsecond\n");
+        assert_eq!(storage.list().unwrap(), ["log.txt"]);
+        assert!(storage.info().unwrap().contains("Size: 13 bytes\nFiles: 1 items"));
+
+        // Xoá đối tượng lưu trữ khỏi hệ thống tập tin
+        fs::remove_dir_all(storage.path()).unwrap();
+    }
 }
 }
\ No newline at end of file
@@ -0,0 +1,493 @@
+// Khai báo các module con của mạng lưới đối tác
+pub mod connection;
+pub mod graph;
+
+// Khai báo các thư viện cần thiết
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::sync::{Arc, Mutex, RwLock};
+
+use crate::auth::role::Action;
+use crate::auth::{self, Principal};
+use crate::shop::{paginate, Page};
+use crate::storage::{Access, Grants, Storage};
+use connection::Connections;
+
+// Phần mở rộng của tập tin hồ sơ trong đối tượng lưu trữ
+const EXTENSION: &str = ".profile";
+
+// Khai báo một struct để đại diện cho hồ sơ tìm kiếm đối tác của một doanh nghiệp
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub business: String,          // Tên doanh nghiệp (duy nhất trong mạng lưới)
+    pub industry: String,          // Ngành nghề
+    pub location: String,          // Địa điểm
+    pub capabilities: Vec<String>, // Năng lực (sản phẩm, dịch vụ có thể cung cấp)
+    pub keywords: Vec<String>,     // Từ khóa tìm kiếm
+    pub about: String,             // Giới thiệu ngắn
+    pub access: Access,            // Ai được thấy hồ sơ
+}
+
+// Khai báo các phương thức cho struct Profile
+impl Profile {
+    // Phương thức new để tạo một hồ sơ công khai chỉ có tên, ngành nghề và địa điểm
+    pub fn new(business: &str, industry: &str, location: &str) -> Profile {
+        Profile {
+            business: business.to_string(),
+            industry: industry.to_string(),
+            location: location.to_string(),
+            capabilities: Vec::new(),
+            keywords: Vec::new(),
+            about: String::new(),
+            access: Access::Public,
+        }
+    }
+
+    // Phương thức file trả về tên tập tin lưu hồ sơ
+    pub fn file(&self) -> String {
+        format!("{}{}", self.business, EXTENSION)
+    }
+
+    // Phương thức render để chuyển hồ sơ thành văn bản dạng "khóa: giá trị" (quyền truy cập lưu riêng)
+    pub fn render(&self) -> String {
+        format!(
+            "Business: {}\nIndustry: {}\nLocation: {}\nCapabilities: {}\nKeywords: {}\nAbout: {}\n",
+            line(&self.business),
+            line(&self.industry),
+            line(&self.location),
+            line(&self.capabilities.join(", ")),
+            line(&self.keywords.join(", ")),
+            line(&self.about),
+        )
+    }
+
+    // Phương thức parse để đọc hồ sơ từ văn bản do render tạo ra
+    pub fn parse(text: &str, access: Access) -> io::Result<Profile> {
+        let mut profile = Profile::new("", "", "");
+        profile.access = access;
+        for row in text.lines() {
+            let (key, value) = row.split_once(':').unwrap_or((row, ""));
+            let value = value.trim().to_string();
+            match key {
+                "Business" => profile.business = value,
+                "Industry" => profile.industry = value,
+                "Location" => profile.location = value,
+                "Capabilities" => profile.capabilities = list(&value),
+                "Keywords" => profile.keywords = list(&value),
+                "About" => profile.about = value,
+                _ => {}
+            }
+        }
+        if profile.business.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Profile has no business name".to_string(),
+            ));
+        }
+        Ok(profile)
+    }
+
+    // Phương thức fields trả về các trường được lập chỉ mục cùng trọng số của chúng
+    fn fields(&self) -> Vec<(String, u32)> {
+        let mut fields = vec![
+            (self.business.clone(), 3),
+            (self.industry.clone(), 2),
+            (self.location.clone(), 1),
+            (self.about.clone(), 1),
+        ];
+        fields.extend(self.capabilities.iter().map(|c| (c.clone(), 2)));
+        fields.extend(self.keywords.iter().map(|k| (k.clone(), 3)));
+        fields
+    }
+}
+
+// Khai báo một struct để đại diện cho một truy vấn tìm kiếm đối tác
+#[derive(Debug, Clone, Default)]
+pub struct Search {
+    pub text: Option<String>, // Các từ khóa (tìm theo từ hoặc tiền tố của từ)
+    pub industry: Option<String>, // Chỉ tìm trong một ngành nghề
+    pub location: Option<String>, // Chỉ tìm ở một địa điểm
+    pub page: usize,          // Số trang (bắt đầu từ 0)
+    pub size: usize,          // Số hồ sơ mỗi trang (0 nghĩa là mặc định)
+}
+
+// Khai báo một struct để đại diện cho một kết quả tìm kiếm
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit {
+    pub profile: Profile, // Hồ sơ phù hợp
+    pub matched: usize,   // Số từ khóa khớp
+    pub score: u32,       // Điểm xếp hạng (theo trọng số của trường)
+}
+
+// Khai báo một struct để chứa hồ sơ và chỉ mục đảo (được bảo vệ bởi khóa)
+#[derive(Debug, Default)]
+struct Data {
+    profiles: BTreeMap<String, Profile>, // Các hồ sơ theo tên doanh nghiệp
+    index: BTreeMap<String, BTreeMap<String, u32>>, // Từ -> doanh nghiệp -> trọng số
+}
+
+// Khai báo một struct để đại diện cho danh bạ đối tác, lưu hồ sơ trong một đối tượng lưu trữ
+#[derive(Debug)]
+pub struct Directory {
+    connections: Arc<Connections>, // Các kết nối (được xem hồ sơ Shared)
+    storage: Mutex<Storage>,       // Đối tượng lưu trữ chứa các tập tin hồ sơ
+    data: RwLock<Data>,            // Hồ sơ và chỉ mục trong bộ nhớ
+}
+
+// Khai báo các phương thức cho struct Directory
+impl Directory {
+    // Phương thức open để nạp tất cả hồ sơ từ đối tượng lưu trữ và xây dựng chỉ mục
+    pub fn open(mut storage: Storage, connections: Arc<Connections>) -> io::Result<Directory> {
+        storage.serve()?;
+        let mut data = Data::default();
+        for name in storage.list()? {
+            if name.ends_with(EXTENSION) {
+                let profile = Profile::parse(&storage.view(&name)?, storage.file_access(&name)?)?;
+                insert(&mut data, profile);
+            }
+        }
+        Ok(Directory {
+            connections,
+            storage: Mutex::new(storage),
+            data: RwLock::new(data),
+        })
+    }
+
+    // Phương thức save để người dùng của doanh nghiệp tạo hoặc cập nhật hồ sơ của mình: ghi vào đối tượng lưu
+    // trữ rồi lập lại chỉ mục
+    pub fn save(&self, principal: &Principal, profile: Profile) -> io::Result<()> {
+        // Tên doanh nghiệp được dùng làm tên tập tin
+        if profile.business.is_empty()
+            || profile.business.starts_with('.')
+            || profile.business.contains(['/', '\\', '\n'])
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid business name: {:?}", profile.business),
+            ));
+        }
+        owner(principal, &profile.business)?;
+
+        // Ghi hồ sơ và quyền truy cập của nó
+        let mut storage = self.storage.lock().unwrap();
+        let name = profile.file();
+        let text = profile.render();
+        if storage.list()?.contains(&name) {
+            storage.edit(&name, &text)?;
+        } else {
+            storage.put(&name, text.as_bytes())?;
+        }
+        storage.set_file_access(&name, profile.access)?;
+
+        // Cập nhật chỉ mục trong bộ nhớ
+        let mut data = self.data.write().unwrap();
+        remove(&mut data, &profile.business);
+        insert(&mut data, profile);
+        Ok(())
+    }
+
+    // Phương thức delete để người dùng của doanh nghiệp xoá hồ sơ của mình
+    pub fn delete(&self, principal: &Principal, business: &str) -> io::Result<Profile> {
+        owner(principal, business)?;
+
+        // Khóa đối tượng lưu trữ trước chỉ mục (cùng thứ tự với save)
+        let mut storage = self.storage.lock().unwrap();
+        let mut data = self.data.write().unwrap();
+        let profile = data
+            .profiles
+            .get(business)
+            .cloned()
+            .ok_or_else(|| missing(business))?;
+        storage.delete(&profile.file())?;
+        remove(&mut data, business);
+        Ok(profile)
+    }
+
+    // Phương thức get để xem hồ sơ của một doanh nghiệp (nếu người xem được phép)
+    pub fn get(&self, viewer: &str, business: &str) -> io::Result<Profile> {
+        let data = self.data.read().unwrap();
+        data.profiles
+            .get(business)
+            .filter(|profile| self.visible(profile, viewer))
+            .cloned()
+            .ok_or_else(|| missing(business))
+    }
+
+    // Phương thức search để tìm các hồ sơ mà người xem được phép thấy, xếp hạng theo độ phù hợp
+    pub fn search(&self, viewer: &str, search: &Search) -> Page<Hit> {
+        let data = self.data.read().unwrap();
+
+        // Tính số từ khóa khớp và điểm của từng doanh nghiệp
+        let words: BTreeSet<String> = search.text.as_deref().map(tokens).unwrap_or_default();
+        let mut scores: BTreeMap<&str, (usize, u32)> = BTreeMap::new();
+        if words.is_empty() {
+            for business in data.profiles.keys() {
+                scores.insert(business, (0, 0));
+            }
+        }
+        for word in &words {
+            // Khớp nguyên từ được gấp đôi điểm so với khớp tiền tố
+            let mut best: BTreeMap<&str, u32> = BTreeMap::new();
+            for (token, postings) in data.index.range(word.clone()..) {
+                if !token.starts_with(word.as_str()) {
+                    break;
+                }
+                let factor = if token == word { 2 } else { 1 };
+                for (business, weight) in postings {
+                    let score = best.entry(business).or_default();
+                    *score = (*score).max(weight * factor);
+                }
+            }
+            for (business, score) in best {
+                let entry = scores.entry(business).or_default();
+                entry.0 += 1;
+                entry.1 += score;
+            }
+        }
+
+        // Lọc theo quyền xem, ngành nghề, địa điểm rồi sắp xếp
+        let same =
+            |a: &str, b: &Option<String>| b.as_ref().is_none_or(|b| a.eq_ignore_ascii_case(b));
+        let mut hits: Vec<Hit> = scores
+            .into_iter()
+            .filter_map(|(business, (matched, score))| {
+                let profile = &data.profiles[business];
+                let keep = self.visible(profile, viewer)
+                    && same(&profile.industry, &search.industry)
+                    && same(&profile.location, &search.location);
+                keep.then(|| Hit {
+                    profile: profile.clone(),
+                    matched,
+                    score,
+                })
+            })
+            .collect();
+        hits.sort_by_key(|hit| (Reverse(hit.matched), Reverse(hit.score)));
+        paginate(hits, search.page, search.size)
+    }
+
+    // Phương thức visible kiểm tra người xem có được thấy hồ sơ hay không (Shared nghĩa là các kết nối)
+    fn visible(&self, profile: &Profile, viewer: &str) -> bool {
+        match profile.access {
+            Access::Public => true,
+            Access::Private => profile.business == viewer,
+            Access::Shared => {
+                profile.business == viewer || self.connections.granted(&profile.business, viewer)
+            }
+        }
+    }
+}
+
+// Hàm insert thêm hồ sơ vào danh sách và chỉ mục đảo
+fn insert(data: &mut Data, profile: Profile) {
+    for (text, weight) in profile.fields() {
+        for token in tokens(&text) {
+            let postings = data.index.entry(token).or_default();
+            let entry = postings.entry(profile.business.clone()).or_default();
+            *entry = (*entry).max(weight);
+        }
+    }
+    data.profiles.insert(profile.business.clone(), profile);
+}
+
+// Hàm remove xoá hồ sơ khỏi danh sách và chỉ mục đảo
+fn remove(data: &mut Data, business: &str) {
+    if data.profiles.remove(business).is_some() {
+        data.index.retain(|_, postings| {
+            postings.remove(business);
+            !postings.is_empty()
+        });
+    }
+}
+
+// Hàm owner kiểm tra người dùng được ghi và thuộc doanh nghiệp sở hữu hồ sơ
+fn owner(principal: &Principal, business: &str) -> io::Result<()> {
+    auth::authorize(
+        principal,
+        Action::StorageWrite,
+        &format!("profile of {}", business),
+    )?;
+    if principal.business() != business {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!(
+                "{} may not change the profile of {}",
+                principal.business(),
+                business
+            ),
+        ));
+    }
+    Ok(())
+}
+
+// Hàm tokens tách văn bản thành các từ viết thường (bỏ dấu câu)
+fn tokens(text: &str) -> BTreeSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+// Hàm line thay ký tự xuống dòng để mỗi giá trị nằm trên một dòng
+fn line(text: &str) -> String {
+    text.replace(['\r', '\n'], " ")
+}
+
+// Hàm list tách danh sách phân cách bằng dấu phẩy
+fn list(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+// Hàm missing tạo lỗi io cho hồ sơ không tồn tại (hoặc không được phép xem)
+fn missing(business: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("Profile of {} not found", business),
+    )
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::auth::tests::principal;
+    use crate::event::Bus;
+    use crate::storage::tests::{create_temp_storage, reopen};
+    use std::fs;
+
+    // Khai báo một hàm để tạo hồ sơ mẫu
+    fn profile(business: &str, industry: &str, location: &str, keywords: &[&str]) -> Profile {
+        let mut profile = Profile::new(business, industry, location);
+        profile.keywords = keywords.iter().map(|k| k.to_string()).collect();
+        profile
+    }
+
+    // Khai báo một hàm để tạo mạng lưới kết nối rỗng
+    fn connections() -> Arc<Connections> {
+        Arc::new(Connections::new(Arc::new(Bus::new())))
+    }
+
+    // Khai báo một hàm để kiểm tra việc lưu và nạp lại hồ sơ
+    #[test]
+    fn test_profile() {
+        let storage = create_temp_storage("peer", Access::Private).unwrap();
+        let path = storage.path().to_path_buf();
+        let directory = Directory::open(storage, connections()).unwrap();
+
+        // Lưu hai hồ sơ, sửa một hồ sơ
+        let mut alpha = profile("alpha", "Textiles", "Hanoi", &["cotton", "dyeing"]);
+        alpha.capabilities = vec!["Weaving".to_string(), "Printing".to_string()];
+        alpha.about = "Family mill\nsince 1990".to_string();
+        directory.save(&principal("alpha"), alpha.clone()).unwrap();
+        let mut beta = profile("beta", "Logistics", "Da Nang", &["freight"]);
+        beta.access = Access::Private;
+        directory.save(&principal("beta"), beta.clone()).unwrap();
+        alpha.location = "Hai Phong".to_string();
+        directory.save(&principal("alpha"), alpha.clone()).unwrap();
+        assert!(directory
+            .save(&principal("../x"), profile("../x", "", "", &[]))
+            .is_err());
+
+        // Doanh nghiệp khác không được sửa hay xoá hồ sơ
+        for result in [
+            directory.save(&principal("gamma"), alpha.clone()),
+            directory.delete(&principal("gamma"), "alpha").map(|_| ()),
+        ] {
+            assert_eq!(result.unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+        }
+
+        // Mở lại từ đối tượng lưu trữ cho kết quả như cũ (trừ ký tự xuống dòng)
+        drop(directory);
+        let directory = Directory::open(reopen(&path).unwrap(), connections()).unwrap();
+        alpha.about = "Family mill since 1990".to_string();
+        assert_eq!(directory.get("gamma", "alpha").unwrap(), alpha);
+        assert_eq!(directory.get("beta", "beta").unwrap(), beta);
+
+        // Xoá hồ sơ
+        directory.delete(&principal("alpha"), "alpha").unwrap();
+        assert!(directory.get("alpha", "alpha").is_err());
+        assert!(directory.delete(&principal("alpha"), "alpha").is_err());
+
+        // Xoá đối tượng lưu trữ tạm thời
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra tìm kiếm, xếp hạng và quyền xem
+    #[test]
+    fn test_search() {
+        let storage = create_temp_storage("peer", Access::Private).unwrap();
+        let path = storage.path().to_path_buf();
+        let connections = connections();
+        let directory = Directory::open(storage, connections.clone()).unwrap();
+        directory
+            .save(
+                &principal("alpha"),
+                profile("alpha", "Textiles", "Hanoi", &["organic cotton"]),
+            )
+            .unwrap();
+        directory
+            .save(
+                &principal("beta"),
+                profile("beta", "Textiles", "Hue", &["silk"]),
+            )
+            .unwrap();
+        let mut gamma = profile("gamma", "Farming", "Hanoi", &["cotton", "silk"]);
+        gamma.about = "Organic cotton farm".to_string();
+        directory.save(&principal("gamma"), gamma).unwrap();
+        let mut delta = profile("delta", "Textiles", "Hanoi", &["organic cotton"]);
+        delta.access = Access::Shared;
+        directory.save(&principal("delta"), delta).unwrap();
+
+        // Nhiều từ khóa khớp hơn thì xếp trước, cùng số từ thì theo điểm
+        let search = |viewer: &str, text: &str| -> Vec<String> {
+            let search = Search {
+                text: Some(text.to_string()),
+                ..Search::default()
+            };
+            directory
+                .search(viewer, &search)
+                .items
+                .into_iter()
+                .map(|hit| hit.profile.business)
+                .collect()
+        };
+        assert_eq!(search("x", "organic cotton"), ["alpha", "gamma"]);
+        assert_eq!(search("x", "silk"), ["beta", "gamma"]);
+        assert_eq!(search("x", "Cott"), ["alpha", "gamma"]);
+        assert_eq!(search("x", "textile"), ["alpha", "beta"]);
+
+        // Hồ sơ không công khai chỉ chủ sở hữu thấy
+        assert_eq!(search("delta", "organic"), ["alpha", "delta", "gamma"]);
+        assert!(directory.get("x", "delta").is_err());
+
+        // Hồ sơ Shared được các kết nối của chủ sở hữu thấy
+        let id = connections.send(&principal("x"), "delta", "").unwrap();
+        connections.accept(&principal("delta"), id).unwrap();
+        assert_eq!(search("x", "organic"), ["alpha", "delta", "gamma"]);
+        assert!(directory.get("x", "delta").is_ok());
+
+        // Lọc theo ngành nghề và địa điểm
+        let page = directory.search(
+            "x",
+            &Search {
+                industry: Some("textiles".to_string()),
+                location: Some("Hanoi".to_string()),
+                ..Search::default()
+            },
+        );
+        let found: Vec<&str> = page
+            .items
+            .iter()
+            .map(|hit| hit.profile.business.as_str())
+            .collect();
+        assert_eq!(found, ["alpha", "delta"]);
+
+        // Xoá đối tượng lưu trữ tạm thời
+        fs::remove_dir_all(path).unwrap();
+    }
+}
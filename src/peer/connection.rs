@@ -0,0 +1,478 @@
+// Khai báo các thư viện cần thiết
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::sync::{Arc, RwLock};
+
+use crate::auth::role::Action;
+use crate::auth::{self, Principal};
+use crate::clock::{self, DAY};
+use crate::event::{Bus, Event};
+use crate::storage::Grants;
+
+// Thời gian mặc định trước khi lời mời kết nối hết hạn (tính bằng giây)
+pub const EXPIRY: u64 = 14 * DAY;
+
+// Khai báo một enum để đại diện cho các trạng thái của lời mời kết nối
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Pending,   // Đang chờ phản hồi
+    Accepted,  // Đã chấp nhận
+    Rejected,  // Đã từ chối
+    Withdrawn, // Người gửi đã rút lại
+    Expired,   // Đã hết hạn
+}
+
+// Khai báo một struct để đại diện cho một lời mời kết nối giữa hai doanh nghiệp
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    pub id: u64,         // Mã của lời mời
+    pub from: String,    // Doanh nghiệp gửi
+    pub to: String,      // Doanh nghiệp nhận
+    pub message: String, // Lời nhắn kèm theo
+    pub state: State,    // Trạng thái hiện tại
+    pub sent: u64,       // Thời điểm gửi
+    pub expires: u64,    // Thời điểm hết hạn
+}
+
+// Khai báo một struct để chứa lời mời, đồ thị kết nối và danh sách chặn (được bảo vệ bởi khóa)
+#[derive(Debug, Default)]
+struct Data {
+    requests: BTreeMap<u64, Request>,           // Các lời mời theo mã
+    graph: BTreeMap<String, BTreeSet<String>>,  // Đồ thị kết nối hai chiều
+    blocks: BTreeMap<String, BTreeSet<String>>, // Doanh nghiệp -> các doanh nghiệp bị chặn
+    next: u64,                                  // Mã tiếp theo cho lời mời
+}
+
+// Khai báo một struct để quản lý kết nối giữa các doanh nghiệp (an toàn khi dùng chung giữa các luồng)
+#[derive(Debug)]
+pub struct Connections {
+    bus: Arc<Bus>,      // Kênh sự kiện
+    data: RwLock<Data>, // Dữ liệu kết nối
+}
+
+// Khai báo các phương thức cho struct Connections
+impl Connections {
+    // Phương thức new để tạo một mạng lưới kết nối rỗng
+    pub fn new(bus: Arc<Bus>) -> Connections {
+        Connections {
+            bus,
+            data: RwLock::new(Data::default()),
+        }
+    }
+
+    // Phương thức send để doanh nghiệp của người dùng gửi lời mời kết nối với thời hạn mặc định
+    pub fn send(&self, principal: &Principal, to: &str, message: &str) -> io::Result<u64> {
+        self.request(principal, to, message, clock::now(), EXPIRY)
+    }
+
+    // Phương thức request để doanh nghiệp của người dùng gửi lời mời kết nối tại thời điểm now, hết hạn sau
+    // ttl giây
+    pub fn request(
+        &self,
+        principal: &Principal,
+        to: &str,
+        message: &str,
+        now: u64,
+        ttl: u64,
+    ) -> io::Result<u64> {
+        let from = actor(principal)?;
+        let mut data = self.data.write().unwrap();
+        sweep(&mut data, now);
+
+        // Không thể tự kết nối hoặc kết nối lại
+        if from == to || linked(&data, from, to) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} is already connected to {}", from, to),
+            ));
+        }
+
+        // Không thể gửi khi một trong hai bên đã chặn bên kia
+        if blocked(&data, from, to) || blocked(&data, to, from) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{} cannot send a request to {}", from, to),
+            ));
+        }
+
+        // Chỉ có một lời mời đang chờ giữa hai doanh nghiệp
+        if data.requests.values().any(|request| {
+            request.state == State::Pending
+                && ((request.from == from && request.to == to)
+                    || (request.from == to && request.to == from))
+        }) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("A request between {} and {} is pending", from, to),
+            ));
+        }
+
+        data.next += 1;
+        let id = data.next;
+        data.requests.insert(
+            id,
+            Request {
+                id,
+                from: from.to_string(),
+                to: to.to_string(),
+                message: message.to_string(),
+                state: State::Pending,
+                sent: now,
+                expires: now + ttl,
+            },
+        );
+
+        // Công bố sự kiện lời mời mới cho doanh nghiệp nhận
+        self.bus.publish(Event::ConnectionRequested {
+            request: id,
+            from: from.to_string(),
+            to: to.to_string(),
+        });
+        Ok(id)
+    }
+
+    // Phương thức get để lấy một bản sao của lời mời
+    pub fn get(&self, id: u64) -> io::Result<Request> {
+        self.data
+            .read()
+            .unwrap()
+            .requests
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| missing(id))
+    }
+
+    // Phương thức accept để doanh nghiệp nhận chấp nhận lời mời (tạo kết nối hai chiều)
+    pub fn accept(&self, principal: &Principal, id: u64) -> io::Result<()> {
+        self.respond(actor(principal)?, id, State::Accepted)
+    }
+
+    // Phương thức reject để doanh nghiệp nhận từ chối lời mời
+    pub fn reject(&self, principal: &Principal, id: u64) -> io::Result<()> {
+        self.respond(actor(principal)?, id, State::Rejected)
+    }
+
+    // Phương thức withdraw để doanh nghiệp gửi rút lại lời mời
+    pub fn withdraw(&self, principal: &Principal, id: u64) -> io::Result<()> {
+        self.respond(actor(principal)?, id, State::Withdrawn)
+    }
+
+    // Phương thức expire để đánh dấu hết hạn các lời mời đang chờ tại thời điểm now
+    pub fn expire(&self, now: u64) -> Vec<u64> {
+        let mut data = self.data.write().unwrap();
+        let expired = sweep(&mut data, now);
+        for id in &expired {
+            self.bus.publish(Event::ConnectionChanged {
+                request: *id,
+                state: State::Expired,
+            });
+        }
+        expired
+    }
+
+    // Phương thức disconnect để doanh nghiệp của người dùng huỷ kết nối với một doanh nghiệp khác
+    pub fn disconnect(&self, principal: &Principal, other: &str) -> io::Result<()> {
+        let (a, b) = (actor(principal)?, other);
+        let mut data = self.data.write().unwrap();
+        if !linked(&data, a, b) {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not connected to {}", a, b),
+            ));
+        }
+        unlink(&mut data, a, b);
+        Ok(())
+    }
+
+    // Phương thức block để doanh nghiệp của người dùng chặn một doanh nghiệp: huỷ kết nối và các lời mời đang
+    // chờ giữa hai bên
+    pub fn block(&self, principal: &Principal, whom: &str) -> io::Result<()> {
+        let by = actor(principal)?;
+        let mut data = self.data.write().unwrap();
+        unlink(&mut data, by, whom);
+        for request in data.requests.values_mut() {
+            let between = (request.from == by && request.to == whom)
+                || (request.from == whom && request.to == by);
+            if between && request.state == State::Pending {
+                request.state = State::Rejected;
+            }
+        }
+        data.blocks
+            .entry(by.to_string())
+            .or_default()
+            .insert(whom.to_string());
+        Ok(())
+    }
+
+    // Phương thức unblock để doanh nghiệp của người dùng bỏ chặn một doanh nghiệp
+    pub fn unblock(&self, principal: &Principal, whom: &str) -> io::Result<()> {
+        let by = actor(principal)?;
+        let mut data = self.data.write().unwrap();
+        if let Some(blocks) = data.blocks.get_mut(by) {
+            blocks.remove(whom);
+        }
+        Ok(())
+    }
+
+    // Phương thức connected kiểm tra hai doanh nghiệp có kết nối với nhau hay không
+    pub fn connected(&self, a: &str, b: &str) -> bool {
+        linked(&self.data.read().unwrap(), a, b)
+    }
+
+    // Phương thức connections trả về các doanh nghiệp đã kết nối (theo thứ tự bảng chữ cái)
+    pub fn connections(&self, business: &str) -> Vec<String> {
+        self.data
+            .read()
+            .unwrap()
+            .graph
+            .get(business)
+            .map(|peers| peers.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    // Phương thức edges trả về tất cả các kết nối, mỗi kết nối một lần (tên nhỏ hơn đứng trước)
+    pub fn edges(&self) -> Vec<(String, String)> {
+        let data = self.data.read().unwrap();
+        data.graph
+            .iter()
+            .flat_map(|(a, peers)| {
+                peers
+                    .iter()
+                    .filter(move |b| a < *b)
+                    .map(move |b| (a.clone(), b.clone()))
+            })
+            .collect()
+    }
+
+    // Phương thức incoming trả về các lời mời đang chờ doanh nghiệp phản hồi
+    pub fn incoming(&self, business: &str) -> Vec<Request> {
+        self.pending(|request| request.to == business)
+    }
+
+    // Phương thức outgoing trả về các lời mời doanh nghiệp đã gửi và đang chờ
+    pub fn outgoing(&self, business: &str) -> Vec<Request> {
+        self.pending(|request| request.from == business)
+    }
+
+    // Phương thức pending trả về các lời mời đang chờ thỏa mãn điều kiện
+    fn pending(&self, filter: impl Fn(&Request) -> bool) -> Vec<Request> {
+        let now = clock::now();
+        self.data
+            .read()
+            .unwrap()
+            .requests
+            .values()
+            .filter(|request| request.state == State::Pending && request.expires > now)
+            .filter(|request| filter(request))
+            .cloned()
+            .collect()
+    }
+
+    // Phương thức respond để chuyển lời mời đang chờ sang trạng thái mới (người nhận trả lời, người gửi rút lại)
+    fn respond(&self, by: &str, id: u64, to: State) -> io::Result<()> {
+        let mut data = self.data.write().unwrap();
+        sweep(&mut data, clock::now());
+        let request = data.requests.get_mut(&id).ok_or_else(|| missing(id))?;
+
+        // Kiểm tra người thực hiện và trạng thái hiện tại
+        let actor = match to {
+            State::Withdrawn => &request.from,
+            _ => &request.to,
+        };
+        if actor != by {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{} cannot answer request {}", by, id),
+            ));
+        }
+        if request.state != State::Pending {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Request {} is {:?}", id, request.state),
+            ));
+        }
+        request.state = to;
+
+        // Chấp nhận thì thêm cạnh hai chiều vào đồ thị
+        if to == State::Accepted {
+            let (from, to) = (request.from.clone(), request.to.clone());
+            data.graph
+                .entry(from.clone())
+                .or_default()
+                .insert(to.clone());
+            data.graph.entry(to).or_default().insert(from);
+        }
+
+        // Công bố sự kiện thay đổi trạng thái
+        self.bus.publish(Event::ConnectionChanged {
+            request: id,
+            state: to,
+        });
+        Ok(())
+    }
+}
+
+// Các kết nối được dùng làm quyền Shared trong đối tượng lưu trữ
+impl Grants for Connections {
+    fn granted(&self, owner: &str, viewer: &str) -> bool {
+        self.connected(owner, viewer)
+    }
+}
+
+// Hàm actor trả về doanh nghiệp của người dùng nếu họ được thay doanh nghiệp quản lý kết nối (kết nối cho
+// phép xem nội dung Shared nên cần cùng quyền với việc chia sẻ tập tin)
+fn actor(principal: &Principal) -> io::Result<&str> {
+    auth::authorize(
+        principal,
+        Action::StorageWrite,
+        &format!("connections of {}", principal.business()),
+    )?;
+    Ok(principal.business())
+}
+
+// Hàm linked kiểm tra hai doanh nghiệp có cạnh trong đồ thị hay không
+fn linked(data: &Data, a: &str, b: &str) -> bool {
+    data.graph.get(a).is_some_and(|peers| peers.contains(b))
+}
+
+// Hàm blocked kiểm tra doanh nghiệp by có chặn whom hay không
+fn blocked(data: &Data, by: &str, whom: &str) -> bool {
+    data.blocks
+        .get(by)
+        .is_some_and(|blocks| blocks.contains(whom))
+}
+
+// Hàm unlink xoá cạnh hai chiều giữa hai doanh nghiệp
+fn unlink(data: &mut Data, a: &str, b: &str) {
+    if let Some(peers) = data.graph.get_mut(a) {
+        peers.remove(b);
+    }
+    if let Some(peers) = data.graph.get_mut(b) {
+        peers.remove(a);
+    }
+}
+
+// Hàm sweep đánh dấu hết hạn các lời mời đang chờ và trả về mã của chúng
+fn sweep(data: &mut Data, now: u64) -> Vec<u64> {
+    let mut expired = Vec::new();
+    for request in data.requests.values_mut() {
+        if request.state == State::Pending && request.expires <= now {
+            request.state = State::Expired;
+            expired.push(request.id);
+        }
+    }
+    expired
+}
+
+// Hàm missing tạo lỗi io cho lời mời không tồn tại
+fn missing(id: u64) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("Request {} not found", id))
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::auth::role::Role;
+    use crate::auth::tests::principal;
+
+    // Khai báo một hàm để kiểm tra việc gửi, chấp nhận và từ chối lời mời
+    #[test]
+    fn test_handshake() {
+        let bus = Arc::new(Bus::new());
+        let events = bus.subscribe();
+        let connections = Connections::new(bus);
+
+        // Gửi lời mời; không thể gửi trùng theo cả hai chiều
+        let id = connections
+            .send(&principal("alpha"), "beta", "Hello")
+            .unwrap();
+        assert!(connections
+            .send(&principal("alpha"), "beta", "Again")
+            .is_err());
+        assert!(connections.send(&principal("beta"), "alpha", "Hi").is_err());
+        assert!(connections
+            .send(&principal("alpha"), "alpha", "Me")
+            .is_err());
+        let staff = Principal::new(1, "staff", "gamma", Role::Staff, u64::MAX);
+        assert!(connections.send(&staff, "alpha", "Hi").is_err());
+        assert_eq!(connections.incoming("beta")[0].id, id);
+        assert_eq!(connections.outgoing("alpha")[0].id, id);
+
+        // Chỉ người nhận được chấp nhận
+        assert!(connections.accept(&principal("alpha"), id).is_err());
+        connections.accept(&principal("beta"), id).unwrap();
+        assert!(connections.connected("beta", "alpha"));
+        assert_eq!(connections.connections("alpha"), ["beta"]);
+        assert!(connections.reject(&principal("beta"), id).is_err());
+        assert!(connections.send(&principal("beta"), "alpha", "Hi").is_err());
+
+        // Từ chối và rút lại
+        let second = connections
+            .send(&principal("gamma"), "alpha", "Hi")
+            .unwrap();
+        connections.reject(&principal("alpha"), second).unwrap();
+        let third = connections
+            .send(&principal("gamma"), "alpha", "Please")
+            .unwrap();
+        assert!(connections.withdraw(&principal("alpha"), third).is_err());
+        connections.withdraw(&principal("gamma"), third).unwrap();
+        assert!(!connections.connected("gamma", "alpha"));
+
+        // Các sự kiện được công bố theo thứ tự
+        let events: Vec<Event> = events.try_iter().collect();
+        assert_eq!(events.len(), 6);
+        assert_eq!(
+            events[1],
+            Event::ConnectionChanged {
+                request: id,
+                state: State::Accepted
+            }
+        );
+
+        // Huỷ kết nối
+        connections.disconnect(&principal("alpha"), "beta").unwrap();
+        assert!(connections.connections("beta").is_empty());
+        assert!(connections.disconnect(&principal("alpha"), "beta").is_err());
+    }
+
+    // Khai báo một hàm để kiểm tra việc hết hạn và chặn
+    #[test]
+    fn test_block() {
+        let connections = Connections::new(Arc::new(Bus::new()));
+
+        // Lời mời hết hạn không thể chấp nhận
+        let id = connections
+            .request(&principal("alpha"), "beta", "", 100, 60)
+            .unwrap();
+        assert_eq!(connections.expire(159), Vec::<u64>::new());
+        assert_eq!(connections.expire(160), [id]);
+        assert_eq!(connections.get(id).unwrap().state, State::Expired);
+        assert!(connections.accept(&principal("beta"), id).is_err());
+        let old = connections
+            .request(&principal("alpha"), "beta", "", 0, 1)
+            .unwrap();
+        assert!(connections.accept(&principal("beta"), old).is_err());
+
+        // Chặn huỷ kết nối và lời mời đang chờ, ngăn gửi lời mời mới theo cả hai chiều
+        let id = connections.send(&principal("alpha"), "beta", "").unwrap();
+        connections.accept(&principal("beta"), id).unwrap();
+        let pending = connections.send(&principal("beta"), "gamma", "").unwrap();
+        connections.block(&principal("gamma"), "beta").unwrap();
+        connections.block(&principal("alpha"), "beta").unwrap();
+        assert!(!connections.connected("alpha", "beta"));
+        assert_eq!(connections.get(pending).unwrap().state, State::Rejected);
+        assert!(connections.send(&principal("beta"), "alpha", "").is_err());
+        assert!(connections.send(&principal("alpha"), "beta", "").is_err());
+
+        // Bỏ chặn thì có thể kết nối lại
+        connections.unblock(&principal("alpha"), "beta").unwrap();
+        let id = connections
+            .send(&principal("beta"), "alpha", "Sorry")
+            .unwrap();
+        connections.accept(&principal("alpha"), id).unwrap();
+        assert!(connections.granted("alpha", "beta"));
+        assert!(!connections.granted("alpha", "gamma"));
+    }
+}
@@ -0,0 +1,475 @@
+// Khai báo các thư viện cần thiết
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::io;
+
+use super::connection::Connections;
+use super::Directory;
+use crate::storage::Storage;
+
+// Dòng đầu tiên của tập tin đồ thị (dùng để nhận dạng định dạng)
+const HEADER: &str = "rim-graph 1";
+
+// Khai báo một struct để đại diện cho ảnh chụp đồ thị quan hệ doanh nghiệp, lưu danh sách kề dạng nén (CSR):
+// các láng giềng của đỉnh i nằm trong edges[offsets[i]..offsets[i + 1]] và được sắp xếp tăng dần
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Graph {
+    names: Vec<String>, // Tên doanh nghiệp theo mã đỉnh (theo thứ tự bảng chữ cái)
+    ids: HashMap<String, u32>, // Tên doanh nghiệp tới mã đỉnh
+    offsets: Vec<u32>,  // Vị trí bắt đầu danh sách láng giềng của mỗi đỉnh
+    edges: Vec<u32>,    // Các láng giềng của tất cả các đỉnh nối liền nhau
+}
+
+// Khai báo các phương thức cho struct Graph
+impl Graph {
+    // Phương thức build để tạo đồ thị từ danh sách cạnh (mỗi cạnh là một kết nối hai chiều)
+    pub fn build(edges: &[(String, String)]) -> Graph {
+        let names: BTreeSet<&String> = edges.iter().flat_map(|(a, b)| [a, b]).collect();
+        let names: Vec<String> = names.into_iter().cloned().collect();
+        let ids: HashMap<&str, u32> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.as_str(), i as u32))
+            .collect();
+        let pairs = edges
+            .iter()
+            .map(|(a, b)| (ids[a.as_str()], ids[b.as_str()]))
+            .collect();
+        Graph::assemble(names, pairs)
+    }
+
+    // Phương thức snapshot để chụp đồ thị từ các kết nối hiện tại
+    pub fn snapshot(connections: &Connections) -> Graph {
+        Graph::build(&connections.edges())
+    }
+
+    // Phương thức assemble để xây dựng danh sách kề nén từ tên đỉnh và các cặp mã đỉnh
+    fn assemble(names: Vec<String>, pairs: Vec<(u32, u32)>) -> Graph {
+        // Đếm bậc của mỗi đỉnh (bỏ khuyên)
+        let mut degree = vec![0u32; names.len() + 1];
+        for &(a, b) in &pairs {
+            if a != b {
+                degree[a as usize + 1] += 1;
+                degree[b as usize + 1] += 1;
+            }
+        }
+
+        // Cộng dồn để có vị trí bắt đầu của mỗi đỉnh
+        let mut offsets = degree;
+        for i in 1..offsets.len() {
+            offsets[i] += offsets[i - 1];
+        }
+
+        // Ghi láng giềng vào vị trí của từng đỉnh
+        let mut cursor = offsets.clone();
+        let mut edges = vec![0u32; *offsets.last().unwrap_or(&0) as usize];
+        for &(a, b) in &pairs {
+            if a != b {
+                edges[cursor[a as usize] as usize] = b;
+                cursor[a as usize] += 1;
+                edges[cursor[b as usize] as usize] = a;
+                cursor[b as usize] += 1;
+            }
+        }
+
+        // Sắp xếp và bỏ cạnh trùng trong danh sách láng giềng của từng đỉnh
+        let mut compact = Vec::with_capacity(edges.len());
+        let mut starts = Vec::with_capacity(offsets.len());
+        starts.push(0);
+        for i in 0..names.len() {
+            let slice = &mut edges[offsets[i] as usize..offsets[i + 1] as usize];
+            slice.sort_unstable();
+            let before = compact.len();
+            for &n in slice.iter() {
+                if compact.len() == before || compact.last() != Some(&n) {
+                    compact.push(n);
+                }
+            }
+            starts.push(compact.len() as u32);
+        }
+        compact.shrink_to_fit();
+
+        let ids = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.clone(), i as u32))
+            .collect();
+        Graph {
+            names,
+            ids,
+            offsets: starts,
+            edges: compact,
+        }
+    }
+
+    // Phương thức len trả về số đỉnh (số doanh nghiệp có ít nhất một kết nối)
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    // Phương thức is_empty kiểm tra đồ thị có rỗng hay không
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    // Phương thức size trả về số cạnh (số kết nối)
+    pub fn size(&self) -> usize {
+        self.edges.len() / 2
+    }
+
+    // Phương thức neighbors trả về các doanh nghiệp đã kết nối với một doanh nghiệp
+    pub fn neighbors(&self, business: &str) -> Vec<String> {
+        self.id(business)
+            .map(|id| self.names_of(self.adjacent(id)))
+            .unwrap_or_default()
+    }
+
+    // Phương thức mutual trả về các kết nối chung của hai doanh nghiệp
+    pub fn mutual(&self, a: &str, b: &str) -> Vec<String> {
+        match (self.id(a), self.id(b)) {
+            (Some(a), Some(b)) => {
+                let shared = intersect(self.adjacent(a), self.adjacent(b));
+                self.names_of(&shared)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    // Phương thức path trả về đường đi ngắn nhất giữa hai doanh nghiệp (gồm cả hai đầu), None nếu không có
+    pub fn path(&self, from: &str, to: &str) -> Option<Vec<String>> {
+        let (start, goal) = (self.id(from)?, self.id(to)?);
+
+        // Tìm kiếm theo chiều rộng, ghi lại đỉnh cha để dựng lại đường đi
+        let mut parent = vec![u32::MAX; self.len()];
+        parent[start as usize] = start;
+        let mut queue = VecDeque::from([start]);
+        while let Some(node) = queue.pop_front() {
+            if node == goal {
+                let mut path = vec![goal];
+                let mut node = goal;
+                while node != start {
+                    node = parent[node as usize];
+                    path.push(node);
+                }
+                path.reverse();
+                return Some(self.names_of(&path));
+            }
+            for &next in self.adjacent(node) {
+                if parent[next as usize] == u32::MAX {
+                    parent[next as usize] = node;
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    // Phương thức within trả về các doanh nghiệp cách một doanh nghiệp không quá hops bước cùng khoảng cách
+    // (gần trước, cùng khoảng cách thì theo tên)
+    pub fn within(&self, from: &str, hops: usize) -> Vec<(String, usize)> {
+        let Some(start) = self.id(from) else {
+            return Vec::new();
+        };
+        let mut seen = HashSet::from([start]);
+        let mut frontier = vec![start];
+        let mut found = Vec::new();
+        for hop in 1..=hops {
+            let mut next = Vec::new();
+            for node in frontier {
+                for &n in self.adjacent(node) {
+                    if seen.insert(n) {
+                        next.push(n);
+                    }
+                }
+            }
+            next.sort_unstable();
+            found.extend(next.iter().map(|&n| (self.names[n as usize].clone(), hop)));
+            frontier = next;
+        }
+        found
+    }
+
+    // Phương thức nearby trả về các doanh nghiệp trong phạm vi hops bước thuộc một ngành nghề
+    // (chỉ những hồ sơ mà doanh nghiệp from được phép xem)
+    pub fn nearby(
+        &self,
+        from: &str,
+        hops: usize,
+        directory: &Directory,
+        industry: &str,
+    ) -> Vec<(String, usize)> {
+        self.within(from, hops)
+            .into_iter()
+            .filter(|(business, _)| {
+                directory
+                    .get(from, business)
+                    .is_ok_and(|profile| profile.industry.eq_ignore_ascii_case(industry))
+            })
+            .collect()
+    }
+
+    // Phương thức suggest gợi ý các doanh nghiệp chưa kết nối, xếp hạng theo số kết nối chung
+    pub fn suggest(&self, business: &str, limit: usize) -> Vec<(String, usize)> {
+        let Some(me) = self.id(business) else {
+            return Vec::new();
+        };
+        let mine = self.adjacent(me);
+
+        // Đếm số kết nối chung với các đỉnh cách hai bước
+        let mut counts: HashMap<u32, usize> = HashMap::new();
+        for &friend in mine {
+            for &candidate in self.adjacent(friend) {
+                if candidate != me && mine.binary_search(&candidate).is_err() {
+                    *counts.entry(candidate).or_default() += 1;
+                }
+            }
+        }
+
+        // Nhiều kết nối chung hơn xếp trước, cùng số thì theo tên
+        let mut ranked: Vec<(u32, usize)> = counts.into_iter().collect();
+        ranked.sort_unstable_by_key(|&(id, count)| (Reverse(count), id));
+        ranked
+            .into_iter()
+            .take(limit)
+            .map(|(id, count)| (self.names[id as usize].clone(), count))
+            .collect()
+    }
+
+    // Phương thức save để ghi đồ thị vào một tập tin trong đối tượng lưu trữ (tạo mới hoặc ghi đè)
+    pub fn save(&self, storage: &mut Storage, name: &str) -> io::Result<()> {
+        // Ghi tiêu đề, số đỉnh và số cạnh, tên các đỉnh (mỗi tên một dòng nên không được chứa ký tự xuống dòng),
+        // rồi mỗi cạnh một lần
+        let mut text = format!("{}\n{} {}\n", HEADER, self.len(), self.size());
+        for name in &self.names {
+            if name.contains(['\n', '\r']) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid business name: {:?}", name),
+                ));
+            }
+            text.push_str(name);
+            text.push('\n');
+        }
+        for a in 0..self.len() as u32 {
+            for &b in self.adjacent(a) {
+                if a < b {
+                    text.push_str(&format!("{} {}\n", a, b));
+                }
+            }
+        }
+
+        if storage.list()?.iter().any(|file| file == name) {
+            storage.edit(name, &text)
+        } else {
+            storage.put(name, text.as_bytes())
+        }
+    }
+
+    // Phương thức load để đọc đồ thị đã ghi bằng save
+    pub fn load(storage: &Storage, name: &str) -> io::Result<Graph> {
+        let text = storage.view(name)?;
+        let mut lines = text.lines();
+        let invalid = |what: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid graph {}: {}", name, what),
+            )
+        };
+
+        // Đọc tiêu đề, số đỉnh và số cạnh
+        if lines.next() != Some(HEADER) {
+            return Err(invalid("header"));
+        }
+        let counts: Vec<usize> = lines
+            .next()
+            .unwrap_or_default()
+            .split(' ')
+            .map(|n| n.parse().map_err(|_| invalid("counts")))
+            .collect::<io::Result<_>>()?;
+        let &[nodes, size] = counts.as_slice() else {
+            return Err(invalid("counts"));
+        };
+
+        // Đọc tên các đỉnh và các cạnh
+        let names: Vec<String> = lines.by_ref().take(nodes).map(str::to_string).collect();
+        if names.len() != nodes {
+            return Err(invalid("names"));
+        }
+        // Số cạnh trong tập tin không đáng tin nên không dùng để cấp phát trước; chỉ so sánh sau khi đọc
+        let mut pairs = Vec::new();
+        for line in lines {
+            let (a, b) = line.split_once(' ').ok_or_else(|| invalid("edge"))?;
+            let a: u32 = a.parse().map_err(|_| invalid("edge"))?;
+            let b: u32 = b.parse().map_err(|_| invalid("edge"))?;
+            if a as usize >= nodes || b as usize >= nodes {
+                return Err(invalid("edge"));
+            }
+            pairs.push((a, b));
+        }
+        if pairs.len() != size {
+            return Err(invalid("edges"));
+        }
+        Ok(Graph::assemble(names, pairs))
+    }
+
+    // Phương thức id trả về mã đỉnh của một doanh nghiệp
+    fn id(&self, business: &str) -> Option<u32> {
+        self.ids.get(business).copied()
+    }
+
+    // Phương thức adjacent trả về các láng giềng (đã sắp xếp) của một đỉnh
+    fn adjacent(&self, id: u32) -> &[u32] {
+        let (start, end) = (self.offsets[id as usize], self.offsets[id as usize + 1]);
+        &self.edges[start as usize..end as usize]
+    }
+
+    // Phương thức names_of chuyển danh sách mã đỉnh thành tên doanh nghiệp
+    fn names_of(&self, ids: &[u32]) -> Vec<String> {
+        ids.iter()
+            .map(|&id| self.names[id as usize].clone())
+            .collect()
+    }
+}
+
+// Hàm intersect tìm giao của hai danh sách đã sắp xếp
+fn intersect(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (mut i, mut j) = (0, 0);
+    let mut shared = Vec::new();
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            Ordering::Less => i += 1,
+            Ordering::Greater => j += 1,
+            Ordering::Equal => {
+                shared.push(a[i]);
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    shared
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::auth::tests::principal;
+    use crate::event::Bus;
+    use crate::peer::Profile;
+    use crate::storage::tests::create_temp_storage;
+    use crate::storage::Access;
+    use std::fs;
+    use std::sync::Arc;
+
+    // Khai báo một hàm để tạo đồ thị mẫu: a-b, a-c, b-c, b-d, c-d, d-e, f-g
+    fn sample() -> Graph {
+        let edges: Vec<(String, String)> = [
+            ("a", "b"),
+            ("a", "c"),
+            ("b", "c"),
+            ("b", "d"),
+            ("c", "d"),
+            ("d", "e"),
+            ("f", "g"),
+            ("b", "a"),
+        ]
+        .iter()
+        .map(|(a, b)| (a.to_string(), b.to_string()))
+        .collect();
+        Graph::build(&edges)
+    }
+
+    // Khai báo một hàm để kiểm tra các truy vấn trên đồ thị
+    #[test]
+    fn test_queries() {
+        let graph = sample();
+        assert_eq!((graph.len(), graph.size()), (7, 7));
+        assert_eq!(graph.neighbors("b"), ["a", "c", "d"]);
+
+        // Kết nối chung và đường đi ngắn nhất
+        assert_eq!(graph.mutual("a", "d"), ["b", "c"]);
+        assert_eq!(graph.path("a", "e").unwrap(), ["a", "b", "d", "e"]);
+        assert_eq!(graph.path("a", "a").unwrap(), ["a"]);
+        assert_eq!(graph.path("a", "g"), None);
+        assert_eq!(graph.path("a", "x"), None);
+
+        // Các doanh nghiệp trong phạm vi hai bước
+        let near: Vec<(String, usize)> = graph.within("a", 2);
+        assert_eq!(
+            near,
+            [
+                ("b".to_string(), 1),
+                ("c".to_string(), 1),
+                ("d".to_string(), 2)
+            ]
+        );
+
+        // Gợi ý theo số kết nối chung
+        assert_eq!(graph.suggest("a", 5), [("d".to_string(), 2)]);
+        assert_eq!(
+            graph.suggest("e", 5),
+            [("b".to_string(), 1), ("c".to_string(), 1)]
+        );
+        assert_eq!(graph.suggest("e", 1).len(), 1);
+    }
+
+    // Khai báo một hàm để kiểm tra việc ghi, đọc đồ thị và lọc theo ngành nghề
+    #[test]
+    fn test_storage() {
+        // Chụp đồ thị từ các kết nối thật
+        let connections = Arc::new(Connections::new(Arc::new(Bus::new())));
+        for (a, b) in [("alpha", "beta"), ("beta", "gamma"), ("beta", "delta")] {
+            let id = connections.send(&principal(a), b, "").unwrap();
+            connections.accept(&principal(b), id).unwrap();
+        }
+        let graph = Graph::snapshot(&connections);
+
+        // Ghi hai lần (ghi đè) rồi đọc lại
+        let mut storage = create_temp_storage("graph", Access::Private).unwrap();
+        graph.save(&mut storage, "network.graph").unwrap();
+        graph.save(&mut storage, "network.graph").unwrap();
+        let loaded = Graph::load(&storage, "network.graph").unwrap();
+        assert_eq!(loaded, graph);
+        storage
+            .put("broken.graph", b"rim-graph 1\n2 1\nx\ny\n0 5\n")
+            .unwrap();
+        assert!(Graph::load(&storage, "broken.graph").is_err());
+
+        // Số cạnh khai báo quá lớn không được cấp phát trước, tên chứa ký tự xuống dòng không được ghi
+        storage
+            .put("huge.graph", b"rim-graph 1\n1 18446744073709551615\nx\n")
+            .unwrap();
+        let error = Graph::load(&storage, "huge.graph").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        let forged = Graph::build(&[("a\nb".to_string(), "c".to_string())]);
+        let error = forged.save(&mut storage, "forged.graph").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(!storage
+            .list()
+            .unwrap()
+            .contains(&"forged.graph".to_string()));
+
+        // Lọc các doanh nghiệp trong hai bước theo ngành nghề (chỉ hồ sơ được phép xem)
+        let profiles = create_temp_storage("peer", Access::Private).unwrap();
+        let path = profiles.path().to_path_buf();
+        let directory = Directory::open(profiles, connections.clone()).unwrap();
+        directory
+            .save(&principal("beta"), Profile::new("beta", "Textiles", ""))
+            .unwrap();
+        directory
+            .save(&principal("gamma"), Profile::new("gamma", "Textiles", ""))
+            .unwrap();
+        let mut delta = Profile::new("delta", "Textiles", "");
+        delta.access = Access::Private;
+        directory.save(&principal("delta"), delta).unwrap();
+        assert_eq!(
+            loaded.nearby("alpha", 2, &directory, "textiles"),
+            [("beta".to_string(), 1), ("gamma".to_string(), 2)]
+        );
+
+        // Xoá các đối tượng lưu trữ tạm thời
+        fs::remove_dir_all(storage.path()).unwrap();
+        fs::remove_dir_all(path).unwrap();
+    }
+}
@@ -0,0 +1,610 @@
+// Khai báo các module con của thương mại điện tử
+pub mod order;
+pub mod promo;
+pub mod returns;
+pub mod review;
+pub mod stock;
+
+// Khai báo các thư viện cần thiết
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::Path;
+use std::sync::RwLock;
+
+use crate::auth::role::Action;
+use crate::auth::{self, Principal};
+use crate::bank::Money;
+use crate::storage::{Access, Storage};
+
+// Khai báo một struct để đại diện cho một cửa hàng trực tuyến của doanh nghiệp
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shop {
+    pub id: u64,       // Mã của cửa hàng
+    pub owner: String, // Doanh nghiệp sở hữu cửa hàng
+    pub name: String,  // Tên của cửa hàng
+}
+
+// Khai báo một struct để đại diện cho một biến thể của sản phẩm (kích cỡ, màu sắc)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    pub sku: String,          // Mã quản lý hàng tồn kho (duy nhất trong toàn bộ danh mục)
+    pub size: Option<String>, // Kích cỡ
+    pub color: Option<String>, // Màu sắc
+    pub price: Money,         // Giá bán
+}
+
+// Khai báo một struct để đại diện cho một sản phẩm hoặc dịch vụ
+#[derive(Debug, Clone, PartialEq)]
+pub struct Product {
+    pub id: u64,                // Mã của sản phẩm
+    pub shop: u64,              // Mã của cửa hàng bán sản phẩm
+    pub name: String,           // Tên của sản phẩm
+    pub category: String,       // Danh mục của sản phẩm
+    pub description: String,    // Mô tả sản phẩm
+    pub variants: Vec<Variant>, // Các biến thể của sản phẩm
+    pub images: Vec<String>,    // Tên các tập tin hình ảnh trong đối tượng lưu trữ của cửa hàng
+}
+
+// Khai báo các phương thức cho struct Product
+impl Product {
+    // Phương thức price trả về giá thấp nhất trong các biến thể
+    pub fn price(&self) -> Option<Money> {
+        self.variants
+            .iter()
+            .map(|variant| variant.price)
+            .min_by_key(|price| price.amount)
+    }
+
+    // Phương thức variant tìm một biến thể theo mã SKU
+    pub fn variant(&self, sku: &str) -> Option<&Variant> {
+        self.variants.iter().find(|variant| variant.sku == sku)
+    }
+}
+
+// Khai báo một enum để đại diện cho cách sắp xếp kết quả tìm kiếm
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Sort {
+    #[default]
+    Name, // Theo tên (A-Z)
+    Cheap,  // Theo giá tăng dần
+    Dear,   // Theo giá giảm dần
+    Newest, // Sản phẩm mới nhất trước
+}
+
+// Khai báo một struct để đại diện cho một truy vấn danh mục
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    pub shop: Option<u64>,        // Chỉ tìm trong một cửa hàng
+    pub category: Option<String>, // Chỉ tìm trong một danh mục
+    pub text: Option<String>,     // Từ khóa xuất hiện trong tên hoặc mô tả
+    pub min: Option<Money>,       // Giá thấp nhất
+    pub max: Option<Money>,       // Giá cao nhất
+    pub sort: Sort,               // Cách sắp xếp
+    pub page: usize,              // Số trang (bắt đầu từ 0)
+    pub size: usize,              // Số sản phẩm mỗi trang (0 nghĩa là mặc định)
+}
+
+// Số sản phẩm mặc định mỗi trang
+pub const PAGE: usize = 20;
+
+// Khai báo một struct để đại diện cho một trang kết quả
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    pub items: Vec<T>, // Các phần tử của trang
+    pub page: usize,   // Số trang
+    pub pages: usize,  // Tổng số trang
+    pub total: usize,  // Tổng số phần tử phù hợp
+}
+
+// Khai báo một struct để chứa dữ liệu của danh mục (được bảo vệ bởi khóa)
+#[derive(Debug, Default)]
+struct Data {
+    shops: HashMap<u64, Shop>,        // Các cửa hàng theo mã
+    products: BTreeMap<u64, Product>, // Các sản phẩm theo mã
+    skus: HashMap<String, u64>,       // Mã SKU tới mã sản phẩm
+    next: u64,                        // Mã tiếp theo cho cửa hàng và sản phẩm
+}
+
+// Khai báo một struct để đại diện cho danh mục sản phẩm của mạng lưới (an toàn khi dùng chung giữa các luồng)
+#[derive(Debug, Default)]
+pub struct Catalog {
+    data: RwLock<Data>, // Dữ liệu của danh mục
+}
+
+// Khai báo các phương thức cho struct Catalog
+impl Catalog {
+    // Phương thức new để tạo một danh mục rỗng
+    pub fn new() -> Catalog {
+        Catalog::default()
+    }
+
+    // Phương thức open để tạo một cửa hàng mới cho doanh nghiệp của người dùng và trả về mã cửa hàng
+    pub fn open(&self, principal: &Principal, name: &str) -> io::Result<u64> {
+        auth::authorize(principal, Action::ShopManage, &format!("shop {}", name))?;
+        let mut data = self.data.write().unwrap();
+        data.next += 1;
+        let id = data.next;
+        data.shops.insert(
+            id,
+            Shop {
+                id,
+                owner: principal.business().to_string(),
+                name: name.to_string(),
+            },
+        );
+        Ok(id)
+    }
+
+    // Phương thức shop để lấy thông tin của một cửa hàng
+    pub fn shop(&self, id: u64) -> io::Result<Shop> {
+        self.data
+            .read()
+            .unwrap()
+            .shops
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| missing("Shop", id))
+    }
+
+    // Phương thức manage để kiểm tra người dùng được quản lý cửa hàng (có quyền ShopManage và thuộc doanh
+    // nghiệp sở hữu cửa hàng), dùng cho các module khác của cửa hàng
+    pub(crate) fn manage(&self, principal: &Principal, shop: u64) -> io::Result<()> {
+        manage(&self.data.read().unwrap(), principal, shop)
+    }
+
+    // Phương thức add để thêm một sản phẩm mới (chưa có biến thể) vào cửa hàng
+    pub fn add(
+        &self,
+        principal: &Principal,
+        shop: u64,
+        name: &str,
+        category: &str,
+        description: &str,
+    ) -> io::Result<u64> {
+        let mut data = self.data.write().unwrap();
+
+        // Kiểm tra xem người dùng có được quản lý cửa hàng hay không
+        manage(&data, principal, shop)?;
+
+        // Cấp mã mới và thêm sản phẩm
+        data.next += 1;
+        let id = data.next;
+        data.products.insert(
+            id,
+            Product {
+                id,
+                shop,
+                name: name.to_string(),
+                category: category.to_string(),
+                description: description.to_string(),
+                variants: Vec::new(),
+                images: Vec::new(),
+            },
+        );
+        Ok(id)
+    }
+
+    // Phương thức variant để thêm một biến thể vào sản phẩm (mã SKU phải duy nhất)
+    pub fn variant(&self, principal: &Principal, product: u64, variant: Variant) -> io::Result<()> {
+        let mut data = self.data.write().unwrap();
+
+        // Kiểm tra xem người dùng có được quản lý cửa hàng của sản phẩm hay không
+        let shop = data
+            .products
+            .get(&product)
+            .ok_or_else(|| missing("Product", product))?
+            .shop;
+        manage(&data, principal, shop)?;
+
+        // Kiểm tra xem mã SKU đã được dùng hay chưa
+        if data.skus.contains_key(&variant.sku) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("SKU {} already exists", variant.sku),
+            ));
+        }
+
+        // Giá bán phải dương
+        if variant.price.amount <= 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid price {}", variant.price),
+            ));
+        }
+
+        // Thêm biến thể vào sản phẩm
+        let item = data
+            .products
+            .get_mut(&product)
+            .ok_or_else(|| missing("Product", product))?;
+        let sku = variant.sku.clone();
+        item.variants.push(variant);
+        data.skus.insert(sku, product);
+        Ok(())
+    }
+
+    // Phương thức reprice để thay đổi giá bán của một biến thể
+    pub fn reprice(&self, principal: &Principal, sku: &str, price: Money) -> io::Result<()> {
+        let mut data = self.data.write().unwrap();
+        let product = *data.skus.get(sku).ok_or_else(|| unknown(sku))?;
+        manage(&data, principal, data.products[&product].shop)?;
+        let item = data
+            .products
+            .get_mut(&product)
+            .ok_or_else(|| unknown(sku))?;
+        if let Some(variant) = item.variants.iter_mut().find(|v| v.sku == sku) {
+            // Giá bán mới phải cùng loại tiền tệ và dương như khi thêm biến thể
+            variant.price.check(price)?;
+            if price.amount <= 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid price {}", price),
+                ));
+            }
+            variant.price = price;
+        }
+        Ok(())
+    }
+
+    // Phương thức product để lấy một bản sao của sản phẩm
+    pub fn product(&self, id: u64) -> io::Result<Product> {
+        self.data
+            .read()
+            .unwrap()
+            .products
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| missing("Product", id))
+    }
+
+    // Phương thức sku để tìm sản phẩm và biến thể theo mã SKU
+    pub fn sku(&self, sku: &str) -> io::Result<(Product, Variant)> {
+        let data = self.data.read().unwrap();
+        let product = data
+            .skus
+            .get(sku)
+            .and_then(|id| data.products.get(id))
+            .ok_or_else(|| unknown(sku))?;
+        let variant = product.variant(sku).cloned().ok_or_else(|| unknown(sku))?;
+        Ok((product.clone(), variant))
+    }
+
+    // Phương thức remove để xoá một sản phẩm cùng các mã SKU của nó
+    pub fn remove(&self, principal: &Principal, id: u64) -> io::Result<Product> {
+        let mut data = self.data.write().unwrap();
+        let shop = data
+            .products
+            .get(&id)
+            .ok_or_else(|| missing("Product", id))?
+            .shop;
+        manage(&data, principal, shop)?;
+        let product = data
+            .products
+            .remove(&id)
+            .ok_or_else(|| missing("Product", id))?;
+        for variant in &product.variants {
+            data.skus.remove(&variant.sku);
+        }
+        Ok(product)
+    }
+
+    // Phương thức image để tải hình ảnh sản phẩm lên đối tượng lưu trữ của cửa hàng với quyền Public
+    pub fn image(
+        &self,
+        principal: &Principal,
+        product: u64,
+        storage: &mut Storage,
+        source: &Path,
+    ) -> io::Result<String> {
+        // Kiểm tra xem sản phẩm có tồn tại và người dùng có được quản lý cửa hàng hay không trước khi tải lên
+        let shop = self.product(product)?.shop;
+        manage(&self.data.read().unwrap(), principal, shop)?;
+
+        // Tải hình ảnh lên và cho phép mọi người xem
+        storage.upload(source)?;
+        let name = source
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        storage.set_file_access(&name, Access::Public)?;
+
+        // Ghi tên hình ảnh vào sản phẩm
+        let mut data = self.data.write().unwrap();
+        if let Some(item) = data.products.get_mut(&product) {
+            item.images.push(name.clone());
+        }
+        Ok(name)
+    }
+
+    // Phương thức query để tìm kiếm sản phẩm theo bộ lọc, sắp xếp và phân trang
+    pub fn query(&self, query: &Query) -> Page<Product> {
+        let data = self.data.read().unwrap();
+
+        // Chuẩn hóa từ khóa về chữ thường
+        let text = query.text.as_ref().map(|text| text.to_lowercase());
+
+        // Lọc các sản phẩm phù hợp
+        let mut items: Vec<&Product> = data
+            .products
+            .values()
+            .filter(|product| query.shop.is_none_or(|shop| product.shop == shop))
+            .filter(|product| {
+                query
+                    .category
+                    .as_ref()
+                    .is_none_or(|category| product.category.eq_ignore_ascii_case(category))
+            })
+            .filter(|product| {
+                text.as_ref().is_none_or(|text| {
+                    product.name.to_lowercase().contains(text)
+                        || product.description.to_lowercase().contains(text)
+                })
+            })
+            .filter(|product| priced(product, query.min, query.max))
+            .collect();
+
+        // Sắp xếp theo yêu cầu (mã sản phẩm giúp thứ tự luôn xác định)
+        let cheapest = |product: &Product| product.price().map_or(i64::MAX, |p| p.amount);
+        match query.sort {
+            Sort::Name => items.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id))),
+            Sort::Cheap => items.sort_by_key(|p| (cheapest(p), p.id)),
+            Sort::Dear => items.sort_by_key(|p| (Reverse(cheapest(p)), p.id)),
+            Sort::Newest => items.sort_by_key(|p| Reverse(p.id)),
+        }
+
+        // Phân trang
+        paginate(items.into_iter().cloned().collect(), query.page, query.size)
+    }
+}
+
+// Hàm priced kiểm tra sản phẩm có biến thể nằm trong khoảng giá hay không
+fn priced(product: &Product, min: Option<Money>, max: Option<Money>) -> bool {
+    if min.is_none() && max.is_none() {
+        return true;
+    }
+    product.variants.iter().any(|variant| {
+        let price = variant.price;
+        min.is_none_or(|min| min.currency == price.currency && price.amount >= min.amount)
+            && max.is_none_or(|max| max.currency == price.currency && price.amount <= max.amount)
+    })
+}
+
+// Hàm paginate cắt một trang từ danh sách kết quả
+pub fn paginate<T>(items: Vec<T>, page: usize, size: usize) -> Page<T> {
+    let size = if size == 0 { PAGE } else { size };
+    let total = items.len();
+    let pages = total.div_ceil(size);
+    let items = items
+        .into_iter()
+        .skip(page.saturating_mul(size))
+        .take(size)
+        .collect();
+    Page {
+        items,
+        page,
+        pages,
+        total,
+    }
+}
+
+// Hàm missing tạo lỗi io cho đối tượng không tồn tại
+fn missing(kind: &str, id: u64) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{} {} not found", kind, id),
+    )
+}
+
+// Hàm unknown tạo lỗi io cho mã SKU không tồn tại
+fn unknown(sku: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("SKU {} not found", sku))
+}
+
+// Hàm manage kiểm tra người dùng được quản lý cửa hàng và cửa hàng thuộc doanh nghiệp của họ
+fn manage(data: &Data, principal: &Principal, shop: u64) -> io::Result<()> {
+    auth::authorize(principal, Action::ShopManage, &format!("shop {}", shop))?;
+    let owner = &data
+        .shops
+        .get(&shop)
+        .ok_or_else(|| missing("Shop", shop))?
+        .owner;
+    if owner != principal.business() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("Shop {} does not belong to {}", shop, principal.business()),
+        ));
+    }
+    Ok(())
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+pub(crate) mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::auth::role::Role;
+    use crate::auth::tests::principal;
+    use crate::bank::Currency;
+    use crate::storage::tests::{create_temp_storage, tempdir};
+    use std::fs;
+
+    // Khai báo một hàm để tạo biến thể với giá USD cho trước
+    pub(crate) fn variant(sku: &str, size: &str, price: i64) -> Variant {
+        Variant {
+            sku: sku.to_string(),
+            size: Some(size.to_string()),
+            color: None,
+            price: Money::new(price, Currency::Usd),
+        }
+    }
+
+    // Khai báo một hàm để tạo danh mục mẫu với ba sản phẩm
+    pub(crate) fn catalog() -> (Catalog, u64) {
+        let catalog = Catalog::new();
+        let owner = principal("alpha");
+        let shop = catalog.open(&owner, "Alpha Store").unwrap();
+        let shirt = catalog
+            .add(&owner, shop, "Shirt", "Clothes", "Cotton shirt")
+            .unwrap();
+        catalog
+            .variant(&owner, shirt, variant("SHIRT-S", "S", 1500))
+            .unwrap();
+        catalog
+            .variant(&owner, shirt, variant("SHIRT-L", "L", 1800))
+            .unwrap();
+        let hat = catalog
+            .add(&owner, shop, "Hat", "Clothes", "Wool hat")
+            .unwrap();
+        catalog
+            .variant(&owner, hat, variant("HAT", "M", 900))
+            .unwrap();
+        let mug = catalog
+            .add(&owner, shop, "Mug", "Kitchen", "Cotton pattern mug")
+            .unwrap();
+        catalog
+            .variant(&owner, mug, variant("MUG", "M", 1200))
+            .unwrap();
+        (catalog, shop)
+    }
+
+    // Khai báo một hàm để kiểm tra việc thêm sản phẩm và biến thể
+    #[test]
+    fn test_variant() {
+        let (catalog, shop) = catalog();
+        let owner = principal("alpha");
+
+        // Mã SKU trùng bị từ chối
+        let id = catalog.add(&owner, shop, "Cap", "Clothes", "").unwrap();
+        assert!(catalog
+            .variant(&owner, id, variant("HAT", "S", 100))
+            .is_err());
+
+        // Tìm theo mã SKU
+        let (product, found) = catalog.sku("SHIRT-L").unwrap();
+        assert_eq!(product.name, "Shirt");
+        assert_eq!(found.price.amount, 1800);
+        assert_eq!(product.price().unwrap().amount, 1500);
+
+        // Cửa hàng không tồn tại bị từ chối
+        assert!(catalog.add(&owner, 999, "Ghost", "None", "").is_err());
+
+        // Xoá sản phẩm thì mã SKU được giải phóng
+        catalog.remove(&owner, product.id).unwrap();
+        assert!(catalog.sku("SHIRT-L").is_err());
+        catalog
+            .variant(&owner, id, variant("SHIRT-L", "L", 100))
+            .unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra quyền quản lý cửa hàng
+    #[test]
+    fn test_manage() {
+        let (catalog, shop) = catalog();
+        let price = Money::new(1000, Currency::Usd);
+
+        // Nhân viên được quản lý cửa hàng, kế toán thì không
+        let staff = Principal::new(1, "staff", "alpha", Role::Staff, u64::MAX);
+        catalog.reprice(&staff, "HAT", price).unwrap();
+        for invalid in [0, -1] {
+            let error = catalog
+                .reprice(&staff, "HAT", Money::new(invalid, Currency::Usd))
+                .unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        }
+        let accountant = Principal::new(2, "accountant", "alpha", Role::Accountant, u64::MAX);
+        let error = catalog.reprice(&accountant, "HAT", price).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        assert!(catalog.open(&accountant, "Books").is_err());
+
+        // Doanh nghiệp khác không được sửa cửa hàng của alpha
+        let other = principal("beta");
+        assert!(catalog.add(&other, shop, "Fake", "None", "").is_err());
+        assert!(catalog.reprice(&other, "HAT", price).is_err());
+        let (product, _) = catalog.sku("HAT").unwrap();
+        assert!(catalog.remove(&other, product.id).is_err());
+        assert_eq!(catalog.sku("HAT").unwrap().1.price, price);
+
+        // Cửa hàng mới thuộc doanh nghiệp của người mở
+        let id = catalog.open(&other, "Beta Store").unwrap();
+        assert_eq!(catalog.shop(id).unwrap().owner, "beta");
+    }
+
+    // Khai báo một hàm để kiểm tra truy vấn danh mục
+    #[test]
+    fn test_query() {
+        let (catalog, _) = catalog();
+
+        // Lọc theo danh mục và sắp xếp theo giá tăng dần
+        let page = catalog.query(&Query {
+            category: Some("clothes".to_string()),
+            sort: Sort::Cheap,
+            ..Query::default()
+        });
+        let names: Vec<&str> = page.items.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Hat", "Shirt"]);
+
+        // Lọc theo khoảng giá (một biến thể nằm trong khoảng là đủ)
+        let page = catalog.query(&Query {
+            min: Some(Money::new(1600, Currency::Usd)),
+            max: Some(Money::new(2000, Currency::Usd)),
+            ..Query::default()
+        });
+        assert_eq!(page.total, 1);
+        assert_eq!(page.items[0].name, "Shirt");
+
+        // Lọc theo từ khóa và sắp xếp theo giá giảm dần
+        let page = catalog.query(&Query {
+            text: Some("COTTON".to_string()),
+            sort: Sort::Dear,
+            ..Query::default()
+        });
+        let names: Vec<&str> = page.items.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Shirt", "Mug"]);
+
+        // Phân trang theo tên
+        let page = catalog.query(&Query {
+            page: 1,
+            size: 2,
+            ..Query::default()
+        });
+        assert_eq!((page.total, page.pages, page.items.len()), (3, 2, 1));
+        assert_eq!(page.items[0].name, "Shirt");
+
+        // Số trang quá lớn cho trang rỗng thay vì tràn số
+        let page = catalog.query(&Query {
+            page: usize::MAX,
+            size: 2,
+            ..Query::default()
+        });
+        assert!(page.items.is_empty());
+    }
+
+    // Khai báo một hàm để kiểm tra việc tải hình ảnh sản phẩm
+    #[test]
+    fn test_image() {
+        let (catalog, shop) = catalog();
+        let owner = principal("alpha");
+        let product = catalog.add(&owner, shop, "Lamp", "Home", "").unwrap();
+
+        // Tạo hình ảnh nguồn và đối tượng lưu trữ riêng tư của cửa hàng
+        let dir = tempdir().unwrap();
+        let source = dir.join("lamp.png");
+        fs::write(&source, b"\x89PNG").unwrap();
+        let mut storage = create_temp_storage("shop", Access::Private).unwrap();
+
+        // Hình ảnh được tải lên với quyền Public và gắn vào sản phẩm
+        let name = catalog
+            .image(&owner, product, &mut storage, &source)
+            .unwrap();
+        assert_eq!(storage.file_access(&name).unwrap(), Access::Public);
+        assert_eq!(catalog.product(product).unwrap().images, ["lamp.png"]);
+
+        // Sản phẩm không tồn tại bị từ chối
+        assert!(catalog.image(&owner, 999, &mut storage, &source).is_err());
+
+        // Xoá các thư mục tạm thời
+        fs::remove_dir_all(storage.path()).unwrap();
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,796 @@
+// Khai báo các thư viện cần thiết
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use super::promo::{Discount, Promotions};
+use super::stock::Stock;
+use super::Catalog;
+use crate::auth::role::Action;
+use crate::auth::{self, Principal};
+use crate::bank::{Ledger, Money};
+use crate::clock;
+use crate::event::{Bus, Event};
+
+// Khai báo một enum để đại diện cho các trạng thái của đơn hàng
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Pending,   // Đã đặt, chờ thanh toán
+    Paid,      // Đã thanh toán
+    Packed,    // Đã đóng gói
+    Shipped,   // Đã giao cho đơn vị vận chuyển
+    Delivered, // Đã giao tới người mua
+    Cancelled, // Đã huỷ trước khi thanh toán
+    Refunded,  // Đã hoàn tiền
+}
+
+// Khai báo các phương thức cho enum Status
+impl Status {
+    // Phương thức allows kiểm tra có thể chuyển từ trạng thái hiện tại sang trạng thái cho trước hay không
+    pub fn allows(&self, to: Status) -> bool {
+        use Status::*;
+        matches!(
+            (self, to),
+            (Pending, Paid)
+                | (Pending, Cancelled)
+                | (Paid, Packed)
+                | (Paid, Refunded)
+                | (Packed, Shipped)
+                | (Packed, Refunded)
+                | (Shipped, Delivered)
+                | (Delivered, Refunded)
+        )
+    }
+}
+
+// Khai báo một struct để đại diện cho giỏ hàng của một doanh nghiệp mua hàng
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cart {
+    pub buyer: String,             // Doanh nghiệp mua hàng
+    pub lines: Vec<(String, u32)>, // Các mã SKU và số lượng
+    pub codes: Vec<String>,        // Các mã giảm giá đã nhập
+}
+
+// Khai báo các phương thức cho struct Cart
+impl Cart {
+    // Phương thức new để tạo một giỏ hàng rỗng
+    pub fn new(buyer: &str) -> Cart {
+        Cart {
+            buyer: buyer.to_string(),
+            lines: Vec::new(),
+            codes: Vec::new(),
+        }
+    }
+
+    // Phương thức add để thêm hàng vào giỏ (cộng dồn nếu mã SKU đã có)
+    pub fn add(&mut self, sku: &str, quantity: u32) {
+        match self.lines.iter_mut().find(|(s, _)| s == sku) {
+            Some((_, q)) => *q += quantity,
+            None => self.lines.push((sku.to_string(), quantity)),
+        }
+    }
+
+    // Phương thức remove để bỏ một mã SKU khỏi giỏ
+    pub fn remove(&mut self, sku: &str) {
+        self.lines.retain(|(s, _)| s != sku);
+    }
+
+    // Phương thức coupon để nhập một mã giảm giá (bỏ qua nếu đã nhập)
+    pub fn coupon(&mut self, code: &str) {
+        if !self.codes.iter().any(|c| c == code) {
+            self.codes.push(code.to_string());
+        }
+    }
+}
+
+// Khai báo một struct để đại diện cho một dòng hàng của đơn hàng
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    pub sku: String,   // Mã SKU
+    pub name: String,  // Tên sản phẩm tại thời điểm đặt hàng
+    pub quantity: u32, // Số lượng
+    pub price: Money,  // Đơn giá tại thời điểm đặt hàng
+    pub hold: u64,     // Mã lượt giữ hàng trong kho
+}
+
+// Khai báo các phương thức cho struct Line
+impl Line {
+    // Phương thức amount trả về thành tiền của dòng hàng (đơn giá nhân số lượng, có kiểm tra tràn số)
+    pub fn amount(&self) -> io::Result<i64> {
+        self.price
+            .amount
+            .checked_mul(self.quantity as i64)
+            .ok_or_else(|| overflow(&self.sku))
+    }
+}
+
+// Khai báo một struct để đại diện cho khoản thanh toán của đơn hàng
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Payment {
+    pub entry: u64, // Mã bút toán trong sổ cái
+    pub from: u64,  // Tài khoản của người mua
+    pub to: u64,    // Tài khoản của cửa hàng
+}
+
+// Khai báo một struct để đại diện cho một đơn hàng
+#[derive(Debug, Clone, PartialEq)]
+pub struct Order {
+    pub id: u64,                     // Mã của đơn hàng
+    pub buyer: String,               // Doanh nghiệp mua hàng
+    pub shop: u64,                   // Mã của cửa hàng bán
+    pub lines: Vec<Line>,            // Các dòng hàng
+    pub discounts: Vec<Discount>,    // Các khoản giảm giá theo thứ tự áp dụng
+    pub total: Money,                // Tổng tiền sau giảm giá
+    pub status: Status,              // Trạng thái hiện tại
+    pub payment: Option<Payment>,    // Khoản thanh toán (nếu đã thanh toán)
+    pub refunded: Money,             // Tổng số tiền đã hoàn (kể cả hoàn tiền trả hàng)
+    pub history: Vec<(Status, u64)>, // Lịch sử trạng thái và thời điểm
+}
+
+// Khai báo các phương thức cho struct Order
+impl Order {
+    // Phương thức subtotal trả về tổng tiền trước giảm giá
+    pub fn subtotal(&self) -> io::Result<Money> {
+        Ok(Money::new(sum(&self.lines)?, self.total.currency))
+    }
+
+    // Phương thức explain trả về phần giải thích cách tính tổng tiền (mỗi khoản giảm giá một dòng)
+    pub fn explain(&self) -> io::Result<String> {
+        let mut text = format!("Subtotal: {}\n", self.subtotal()?);
+        for discount in &self.discounts {
+            text.push_str(&format!(
+                "{}: {} (-{})\n",
+                discount.name, discount.note, discount.amount
+            ));
+        }
+        text.push_str(&format!("Total: {}\n", self.total));
+        Ok(text)
+    }
+}
+
+// Khai báo một struct để quản lý đặt hàng, kết nối danh mục, kho hàng, sổ cái và kênh sự kiện
+#[derive(Debug)]
+pub struct Orders {
+    catalog: Arc<Catalog>,               // Danh mục sản phẩm
+    stock: Arc<Stock>,                   // Kho hàng
+    promotions: Arc<Promotions>,         // Các quy tắc khuyến mãi
+    ledger: Arc<Ledger>,                 // Sổ cái dùng để thanh toán
+    bus: Arc<Bus>,                       // Kênh sự kiện
+    orders: Mutex<BTreeMap<u64, Order>>, // Các đơn hàng theo mã
+}
+
+// Khai báo các phương thức cho struct Orders
+impl Orders {
+    // Phương thức new để tạo một bộ quản lý đặt hàng mới
+    pub fn new(
+        catalog: Arc<Catalog>,
+        stock: Arc<Stock>,
+        promotions: Arc<Promotions>,
+        ledger: Arc<Ledger>,
+        bus: Arc<Bus>,
+    ) -> Orders {
+        Orders {
+            catalog,
+            stock,
+            promotions,
+            ledger,
+            bus,
+            orders: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    // Phương thức checkout để người dùng của người mua đặt hàng từ giỏ: giữ hàng trong kho, áp dụng khuyến mãi
+    // và tạo một đơn hàng cho mỗi cửa hàng
+    pub fn checkout(&self, principal: &Principal, cart: &Cart) -> io::Result<Vec<u64>> {
+        // Người dùng phải được chi tiền và thuộc doanh nghiệp mua hàng
+        buyer(principal, &cart.buyer)?;
+
+        // Giỏ hàng không được rỗng
+        if cart.lines.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Cart of {} is empty", cart.buyer),
+            ));
+        }
+
+        // Các mã giảm giá phải tồn tại
+        if let Some(code) = cart.codes.iter().find(|code| !self.promotions.known(code)) {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Coupon {} not found", code),
+            ));
+        }
+
+        // Tra cứu giá và cửa hàng của từng mã SKU, nhóm theo cửa hàng
+        let mut groups: BTreeMap<u64, Vec<Line>> = BTreeMap::new();
+        for (sku, quantity) in &cart.lines {
+            let (product, variant) = self.catalog.sku(sku)?;
+            let lines = groups.entry(product.shop).or_default();
+
+            // Các dòng hàng của một đơn hàng phải cùng loại tiền tệ
+            if let Some(first) = lines.first() {
+                first.price.check(variant.price)?;
+            }
+            lines.push(Line {
+                sku: sku.clone(),
+                name: product.name,
+                quantity: *quantity,
+                price: variant.price,
+                hold: 0,
+            });
+        }
+
+        // Tổng tiền của mỗi đơn hàng phải biểu diễn được (không tràn số)
+        for lines in groups.values() {
+            sum(lines)?;
+        }
+
+        // Giữ hàng cho tất cả các dòng; nếu một dòng thất bại thì huỷ các lượt giữ trước đó
+        let mut holds = Vec::new();
+        for line in groups.values_mut().flatten() {
+            match self.stock.reserve(&line.sku, line.quantity) {
+                Ok(hold) => {
+                    line.hold = hold;
+                    holds.push(hold);
+                }
+                Err(error) => {
+                    for hold in holds {
+                        self.stock.release(hold)?;
+                    }
+                    return Err(error);
+                }
+            }
+        }
+
+        // Tạo đơn hàng cho mỗi cửa hàng
+        let mut orders = self.orders.lock().unwrap();
+        let mut ids = Vec::new();
+        for (shop, lines) in groups {
+            let id = orders.keys().next_back().copied().unwrap_or(0) + 1;
+            let currency = lines[0].price.currency;
+            let subtotal = sum(&lines)?;
+
+            // Áp dụng khuyến mãi của cửa hàng (tổng giảm giá không vượt quá tổng tiền)
+            let discounts =
+                self.promotions
+                    .apply(shop, &cart.buyer, &lines, &cart.codes, clock::now())?;
+            let discount: i64 = discounts.iter().map(|d| d.amount.amount).sum();
+            let order = Order {
+                id,
+                buyer: cart.buyer.clone(),
+                shop,
+                lines,
+                discounts,
+                total: Money::new(subtotal - discount, currency),
+                status: Status::Pending,
+                payment: None,
+                refunded: Money::zero(currency),
+                history: vec![(Status::Pending, clock::now())],
+            };
+
+            // Công bố sự kiện đơn hàng mới
+            self.bus.publish(Event::OrderPlaced {
+                order: id,
+                buyer: order.buyer.clone(),
+                shop,
+                total: order.total,
+            });
+            orders.insert(id, order);
+            ids.push(id);
+        }
+
+        Ok(ids)
+    }
+
+    // Phương thức get để lấy một bản sao của đơn hàng
+    pub fn get(&self, id: u64) -> io::Result<Order> {
+        self.orders
+            .lock()
+            .unwrap()
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| missing(id))
+    }
+
+    // Phương thức pay để người dùng của người mua thanh toán đơn hàng bằng chuyển khoản trên sổ cái tới một
+    // tài khoản của chủ cửa hàng (đơn hàng 0 đồng được đánh dấu đã thanh toán mà không chuyển tiền, trả về 0)
+    pub fn pay(&self, principal: &Principal, id: u64, from: u64, to: u64) -> io::Result<u64> {
+        self.change(id, Status::Paid, |order| {
+            // Người dùng phải thuộc doanh nghiệp mua hàng và tiền phải về tài khoản của chủ cửa hàng
+            buyer(principal, &order.buyer)?;
+            let owner = self.catalog.shop(order.shop)?.owner;
+            if self.ledger.account(to)?.owner != owner {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!("Account {} does not belong to {}", to, owner),
+                ));
+            }
+
+            // Xác nhận các lượt giữ hàng (thất bại nếu lượt giữ đã hết hạn) rồi chuyển tiền; nếu một bước
+            // thất bại thì hoàn tác các lượt xác nhận để hàng vẫn được trả lại kho khi hết hạn
+            let now = clock::now();
+            let mut confirmed = Vec::new();
+            let mut result = Ok(0);
+            for line in &order.lines {
+                match self.stock.confirm(line.hold, now) {
+                    Ok(expires) => confirmed.push((line.hold, expires)),
+                    Err(error) => {
+                        result = Err(error);
+                        break;
+                    }
+                }
+            }
+            if result.is_ok() && order.total.amount > 0 {
+                let memo = format!("Order {}", order.id);
+                result = self
+                    .ledger
+                    .transfer(principal, from, to, order.total, &memo);
+            }
+            match result {
+                Ok(0) => Ok(0),
+                Ok(entry) => {
+                    order.payment = Some(Payment { entry, from, to });
+                    Ok(entry)
+                }
+                Err(error) => {
+                    for (hold, expires) in confirmed {
+                        self.stock.unconfirm(hold, expires)?;
+                    }
+                    Err(error)
+                }
+            }
+        })
+    }
+
+    // Phương thức pack để người dùng của cửa hàng đánh dấu đơn hàng đã đóng gói
+    pub fn pack(&self, principal: &Principal, id: u64) -> io::Result<()> {
+        self.change(id, Status::Packed, |order| {
+            self.catalog.manage(principal, order.shop)
+        })
+    }
+
+    // Phương thức ship để người dùng của cửa hàng giao đơn hàng cho vận chuyển (xuất kho số hàng đã giữ)
+    pub fn ship(&self, principal: &Principal, id: u64) -> io::Result<()> {
+        self.change(id, Status::Shipped, |order| {
+            self.catalog.manage(principal, order.shop)?;
+            for line in &order.lines {
+                self.stock.commit(line.hold)?;
+            }
+            Ok(())
+        })
+    }
+
+    // Phương thức deliver để người dùng của cửa hàng đánh dấu đơn hàng đã giao tới người mua
+    pub fn deliver(&self, principal: &Principal, id: u64) -> io::Result<()> {
+        self.change(id, Status::Delivered, |order| {
+            self.catalog.manage(principal, order.shop)
+        })
+    }
+
+    // Phương thức cancel để người dùng của người mua hoặc của cửa hàng huỷ đơn hàng chưa thanh toán (trả lại
+    // hàng đã giữ)
+    pub fn cancel(&self, principal: &Principal, id: u64) -> io::Result<()> {
+        self.change(id, Status::Cancelled, |order| {
+            if buyer(principal, &order.buyer).is_err() {
+                self.catalog.manage(principal, order.shop)?;
+            }
+            for line in &order.lines {
+                // Lượt giữ đã hết hạn thì hàng đã được trả lại kho
+                match self.stock.release(line.hold) {
+                    Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+                    _ => {}
+                }
+            }
+
+            // Trả lại lượt sử dụng của các khuyến mãi
+            self.promotions.restore(&order.buyer, &order.discounts);
+            Ok(())
+        })
+    }
+
+    // Phương thức refund để người dùng của cửa hàng hoàn phần tiền chưa hoàn của đơn hàng đã thanh toán bằng bút
+    // toán đảo ngược (không còn gì để hoàn thì chỉ cần quyền quản lý cửa hàng, không chuyển tiền và trả về 0)
+    pub fn refund(&self, principal: &Principal, id: u64) -> io::Result<u64> {
+        self.change(id, Status::Refunded, |order| {
+            let remaining = order.total.minus(order.refunded)?;
+            let entry = match order.payment {
+                // Hoàn tiền từ tài khoản của cửa hàng về tài khoản của người mua
+                Some(payment) if remaining.amount > 0 => self.ledger.transfer(
+                    principal,
+                    payment.to,
+                    payment.from,
+                    remaining,
+                    &format!("Refund order {}", order.id),
+                )?,
+                _ if remaining.amount == 0 => {
+                    self.catalog.manage(principal, order.shop)?;
+                    0
+                }
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("Order {} has no payment", order.id),
+                    ))
+                }
+            };
+            order.refunded = order.total;
+
+            // Hàng chưa xuất kho thì trả lại kho
+            if order.status != Status::Delivered {
+                for line in &order.lines {
+                    self.stock.release(line.hold)?;
+                }
+            }
+            Ok(entry)
+        })
+    }
+
+    // Phương thức credit để hoàn một phần đơn hàng đã thanh toán (ví dụ khi trả hàng): số tiền không được vượt
+    // quá phần chưa hoàn, hàm transfer thực hiện chuyển tiền theo khoản thanh toán (trong cùng một khóa)
+    pub(crate) fn credit(
+        &self,
+        id: u64,
+        amount: Money,
+        transfer: impl FnOnce(&Payment) -> io::Result<u64>,
+    ) -> io::Result<u64> {
+        let mut orders = self.orders.lock().unwrap();
+        let order = orders.get_mut(&id).ok_or_else(|| missing(id))?;
+        let payment = order.payment.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Order {} has no payment", order.id),
+            )
+        })?;
+        let refunded = order.refunded.plus(amount)?;
+        if order.status == Status::Refunded || refunded.amount > order.total.amount {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Refund {} exceeds the unrefunded amount of order {}",
+                    amount, order.id
+                ),
+            ));
+        }
+        let entry = transfer(&payment)?;
+        order.refunded = refunded;
+        Ok(entry)
+    }
+
+    // Phương thức change để chuyển trạng thái: kiểm tra hợp lệ, thực hiện tác động rồi công bố sự kiện
+    fn change<T>(
+        &self,
+        id: u64,
+        to: Status,
+        effect: impl FnOnce(&mut Order) -> io::Result<T>,
+    ) -> io::Result<T> {
+        // Lấy khóa của danh sách đơn hàng (giữ trong suốt quá trình chuyển trạng thái)
+        let mut orders = self.orders.lock().unwrap();
+        let order = orders.get_mut(&id).ok_or_else(|| missing(id))?;
+
+        // Kiểm tra xem có thể chuyển sang trạng thái mới hay không
+        let from = order.status;
+        if !from.allows(to) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Order {} cannot go from {:?} to {:?}", id, from, to),
+            ));
+        }
+
+        // Thực hiện tác động của việc chuyển trạng thái (thanh toán, xuất kho...)
+        let result = effect(order)?;
+
+        // Cập nhật trạng thái và lịch sử
+        order.status = to;
+        order.history.push((to, clock::now()));
+
+        // Công bố sự kiện thay đổi trạng thái
+        self.bus.publish(Event::OrderChanged {
+            order: id,
+            from,
+            to,
+        });
+
+        Ok(result)
+    }
+}
+
+// Hàm buyer kiểm tra người dùng được chi tiền và thuộc doanh nghiệp mua hàng
+pub(super) fn buyer(principal: &Principal, buyer: &str) -> io::Result<()> {
+    auth::authorize(
+        principal,
+        Action::BankTransfer,
+        &format!("orders of {}", buyer),
+    )?;
+    if principal.business() != buyer {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} may not buy for {}", principal.business(), buyer),
+        ));
+    }
+    Ok(())
+}
+
+// Hàm sum cộng thành tiền của các dòng hàng có kiểm tra tràn số
+fn sum(lines: &[Line]) -> io::Result<i64> {
+    lines.iter().try_fold(0i64, |total, line| {
+        total
+            .checked_add(line.amount()?)
+            .ok_or_else(|| overflow(&line.sku))
+    })
+}
+
+// Hàm overflow tạo lỗi io cho thành tiền vượt quá giới hạn
+pub(super) fn overflow(sku: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("Amount of {} is too large", sku),
+    )
+}
+
+// Hàm missing tạo lỗi io cho đơn hàng không tồn tại
+fn missing(id: u64) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("Order {} not found", id))
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+pub(crate) mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::auth::tests::principal;
+    use crate::bank::Currency;
+    use crate::shop::promo::{Kind, Rule};
+    use crate::shop::tests::catalog;
+
+    // Khai báo một struct để chứa các thành phần dùng trong bài kiểm tra
+    pub(crate) struct Fixture {
+        pub(crate) orders: Arc<Orders>,
+        pub(crate) catalog: Arc<Catalog>,
+        pub(crate) stock: Arc<Stock>,
+        pub(crate) promotions: Arc<Promotions>,
+        pub(crate) ledger: Arc<Ledger>,
+        pub(crate) bus: Arc<Bus>,
+        pub(crate) buyer: u64,
+        pub(crate) seller: u64,
+        pub(crate) shop: u64,
+    }
+
+    // Khai báo một hàm để tạo các thành phần mẫu: danh mục, kho có hàng và hai tài khoản
+    pub(crate) fn fixture() -> Fixture {
+        let (catalog, shop) = catalog();
+        let catalog = Arc::new(catalog);
+        let bus = Arc::new(Bus::new());
+        let stock = Arc::new(Stock::new(catalog.clone(), bus.clone()));
+        let owner = principal("alpha");
+        stock.receive(&owner, "main", "SHIRT-S", 10).unwrap();
+        stock.receive(&owner, "main", "HAT", 2).unwrap();
+        stock.receive(&owner, "main", "MUG", 5).unwrap();
+        let ledger = Arc::new(Ledger::new());
+        let buyer = ledger.open("beta", Currency::Usd);
+        let seller = ledger.open("alpha", Currency::Usd);
+        ledger
+            .deposit(
+                &principal("beta"),
+                buyer,
+                Money::new(100_000, Currency::Usd),
+                "seed",
+            )
+            .unwrap();
+        let promotions = Arc::new(Promotions::new(catalog.clone()));
+        let orders = Arc::new(Orders::new(
+            catalog.clone(),
+            stock.clone(),
+            promotions.clone(),
+            ledger.clone(),
+            bus.clone(),
+        ));
+        Fixture {
+            orders,
+            catalog,
+            stock,
+            promotions,
+            ledger,
+            bus,
+            buyer,
+            seller,
+            shop,
+        }
+    }
+
+    // Khai báo một hàm để kiểm tra việc đặt hàng giữ hàng trong kho
+    #[test]
+    fn test_checkout() {
+        let fixture = fixture();
+
+        // Giỏ hàng có hai mã SKU
+        let mut cart = Cart::new("beta");
+        cart.add("SHIRT-S", 2);
+        cart.add("HAT", 1);
+        cart.add("HAT", 1);
+        let ids = fixture.orders.checkout(&principal("beta"), &cart).unwrap();
+        let order = fixture.orders.get(ids[0]).unwrap();
+        assert_eq!(order.total.amount, 2 * 1500 + 2 * 900);
+        assert_eq!(fixture.stock.available("HAT"), 0);
+
+        // Không đủ hàng thì không giữ dòng nào
+        let mut cart = Cart::new("beta");
+        cart.add("SHIRT-S", 1);
+        cart.add("HAT", 1);
+        assert!(fixture.orders.checkout(&principal("beta"), &cart).is_err());
+        assert_eq!(fixture.stock.available("SHIRT-S"), 8);
+
+        // Thành tiền tràn số thì bị từ chối mà không giữ hàng
+        let price = Money::new(i64::MAX / 2, Currency::Usd);
+        fixture
+            .catalog
+            .reprice(&principal("alpha"), "MUG", price)
+            .unwrap();
+        let mut cart = Cart::new("beta");
+        cart.add("MUG", 3);
+        let error = fixture
+            .orders
+            .checkout(&principal("beta"), &cart)
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(fixture.stock.available("MUG"), 5);
+
+        // Chỉ người mua hoặc cửa hàng được huỷ; huỷ đơn hàng trả lại hàng đã giữ
+        assert!(fixture
+            .orders
+            .cancel(&principal("gamma"), order.id)
+            .is_err());
+        fixture.orders.cancel(&principal("beta"), order.id).unwrap();
+        assert_eq!(fixture.stock.available("HAT"), 2);
+        assert!(fixture
+            .orders
+            .pay(&principal("beta"), order.id, fixture.buyer, fixture.seller)
+            .is_err());
+    }
+
+    // Khai báo một hàm để kiểm tra quyền đặt hàng, thanh toán và việc hoàn tác khi chuyển tiền thất bại
+    #[test]
+    fn test_payment() {
+        let fixture = fixture();
+        let mut cart = Cart::new("beta");
+        cart.add("MUG", 2);
+
+        // Doanh nghiệp khác không được đặt hàng thay người mua
+        assert_eq!(
+            fixture
+                .orders
+                .checkout(&principal("alpha"), &cart)
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::PermissionDenied
+        );
+        let id = fixture.orders.checkout(&principal("beta"), &cart).unwrap()[0];
+
+        // Doanh nghiệp khác không được thanh toán, và tiền phải về tài khoản của chủ cửa hàng
+        let other = fixture.ledger.open("beta", Currency::Usd);
+        for (by, to) in [("alpha", fixture.seller), ("beta", other)] {
+            assert_eq!(
+                fixture
+                    .orders
+                    .pay(&principal(by), id, fixture.buyer, to)
+                    .unwrap_err()
+                    .kind(),
+                io::ErrorKind::PermissionDenied
+            );
+        }
+
+        // Chuyển tiền thất bại (tài khoản không đủ tiền) thì lượt giữ hàng vẫn hết hạn như bình thường
+        assert!(fixture
+            .orders
+            .pay(&principal("beta"), id, other, fixture.seller)
+            .is_err());
+        assert_eq!(fixture.orders.get(id).unwrap().status, Status::Pending);
+        assert_eq!(fixture.stock.expire(u64::MAX).len(), 1);
+        assert_eq!(fixture.stock.available("MUG"), 5);
+    }
+
+    // Khai báo một hàm để kiểm tra vòng đời của đơn hàng
+    #[test]
+    fn test_lifecycle() {
+        let fixture = fixture();
+        let events = fixture.bus.subscribe();
+        let mut cart = Cart::new("beta");
+        cart.add("MUG", 2);
+        let id = fixture.orders.checkout(&principal("beta"), &cart).unwrap()[0];
+
+        // Không thể giao hàng trước khi thanh toán
+        assert!(fixture.orders.ship(&principal("alpha"), id).is_err());
+
+        // Thanh toán, đóng gói, vận chuyển, giao hàng
+        fixture
+            .orders
+            .pay(&principal("beta"), id, fixture.buyer, fixture.seller)
+            .unwrap();
+        assert!(fixture.orders.pack(&principal("beta"), id).is_err());
+        fixture.orders.pack(&principal("alpha"), id).unwrap();
+        fixture.orders.ship(&principal("alpha"), id).unwrap();
+        assert!(fixture.orders.deliver(&principal("beta"), id).is_err());
+        fixture.orders.deliver(&principal("alpha"), id).unwrap();
+        assert_eq!(fixture.ledger.balance(fixture.seller).unwrap().amount, 2400);
+        assert_eq!(fixture.stock.available("MUG"), 3);
+
+        // Không thể huỷ đơn hàng đã giao, nhưng có thể hoàn tiền
+        assert!(fixture.orders.cancel(&principal("beta"), id).is_err());
+        fixture.orders.refund(&principal("alpha"), id).unwrap();
+        assert_eq!(fixture.ledger.balance(fixture.seller).unwrap().amount, 0);
+
+        // Mỗi lần chuyển trạng thái đều công bố một sự kiện
+        let events: Vec<Event> = events.try_iter().collect();
+        assert_eq!(events.len(), 6);
+        assert_eq!(
+            events[5],
+            Event::OrderChanged {
+                order: id,
+                from: Status::Delivered,
+                to: Status::Refunded
+            }
+        );
+        assert_eq!(fixture.orders.get(id).unwrap().history.len(), 6);
+    }
+
+    // Khai báo một hàm để kiểm tra việc áp dụng khuyến mãi khi đặt hàng
+    #[test]
+    fn test_promotions() {
+        let fixture = fixture();
+        let mut rule = Rule::new(fixture.shop, "Launch", Kind::Percent(1000));
+        rule.code = Some("LAUNCH".to_string());
+        rule.limit = Some(1);
+        rule.priority = 1;
+        let launch = fixture.promotions.add(&principal("alpha"), rule).unwrap();
+        let mut rule = Rule::new(fixture.shop, "Mugs", Kind::Bundle { buy: 1, get: 1 });
+        rule.sku = Some("MUG".to_string());
+        fixture.promotions.add(&principal("alpha"), rule).unwrap();
+
+        // Mã giảm giá không tồn tại thì không đặt hàng
+        let mut cart = Cart::new("beta");
+        cart.add("MUG", 2);
+        cart.coupon("NOPE");
+        assert!(fixture.orders.checkout(&principal("beta"), &cart).is_err());
+        assert_eq!(fixture.stock.available("MUG"), 5);
+
+        // Mua 1 tặng 1, sau đó giảm 10% trên phần còn lại
+        cart.remove("MUG");
+        cart.add("MUG", 2);
+        cart.codes = vec!["LAUNCH".to_string()];
+        let id = fixture.orders.checkout(&principal("beta"), &cart).unwrap()[0];
+        let order = fixture.orders.get(id).unwrap();
+        assert_eq!(order.subtotal().unwrap().amount, 2400);
+        assert_eq!(order.total.amount, 1080);
+        assert_eq!(
+            order.explain().unwrap(),
+            "Subtotal: 24.00 USD\n\
+             Mugs: Buy 1 get 1 on MUG: 1 free (-12.00 USD)\n\
+             Launch: 10% off order (-1.20 USD)\n\
+             Total: 10.80 USD\n"
+        );
+
+        // Huỷ đơn hàng trả lại lượt sử dụng của mã giảm giá
+        assert_eq!(fixture.promotions.uses(launch), 1);
+        fixture.orders.cancel(&principal("alpha"), id).unwrap();
+        assert_eq!(fixture.promotions.uses(launch), 0);
+
+        // Đơn hàng 0 đồng được thanh toán và hoàn tiền mà không chuyển tiền
+        let mut rule = Rule::new(fixture.shop, "Free", Kind::Percent(10_000));
+        rule.code = Some("FREE".to_string());
+        fixture.promotions.add(&principal("alpha"), rule).unwrap();
+        cart.codes = vec!["FREE".to_string()];
+        let id = fixture.orders.checkout(&principal("beta"), &cart).unwrap()[0];
+        assert_eq!(fixture.orders.get(id).unwrap().total.amount, 0);
+        let orders = &fixture.orders;
+        assert_eq!(
+            orders
+                .pay(&principal("beta"), id, fixture.buyer, fixture.seller)
+                .unwrap(),
+            0
+        );
+        assert_eq!(orders.get(id).unwrap().status, Status::Paid);
+        assert!(orders.refund(&principal("beta"), id).is_err());
+        orders.refund(&principal("alpha"), id).unwrap();
+        assert_eq!(fixture.ledger.balance(fixture.seller).unwrap().amount, 0);
+        assert_eq!(fixture.stock.available("MUG"), 5);
+    }
+}
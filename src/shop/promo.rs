@@ -0,0 +1,568 @@
+// Khai báo các thư viện cần thiết
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::{Arc, RwLock};
+
+use super::order::{overflow, Line};
+use super::Catalog;
+use crate::auth::Principal;
+use crate::bank::Money;
+
+// Khai báo một enum để đại diện cho loại ưu đãi của một quy tắc khuyến mãi
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    Percent(u32),                  // Giảm theo phần trăm (basis points, 1000 = 10%)
+    Fixed(Money),                  // Giảm một số tiền cố định
+    Bundle { buy: u32, get: u32 }, // Mua X tặng Y (trên cùng một mã SKU)
+}
+
+// Khai báo một struct để đại diện cho một quy tắc khuyến mãi của cửa hàng
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub id: u64,              // Mã của quy tắc
+    pub shop: u64,            // Mã của cửa hàng áp dụng
+    pub name: String,         // Tên chương trình khuyến mãi
+    pub kind: Kind,           // Loại ưu đãi
+    pub sku: Option<String>,  // Chỉ áp dụng cho một mã SKU (None nghĩa là cả đơn hàng)
+    pub code: Option<String>, // Mã giảm giá cần nhập (None nghĩa là tự động áp dụng)
+    pub start: Option<u64>,   // Thời điểm bắt đầu (tính bằng giây)
+    pub end: Option<u64>,     // Thời điểm kết thúc (không bao gồm)
+    pub limit: Option<u32>,   // Tổng số lượt sử dụng tối đa
+    pub cap: Option<u32>,     // Số lượt sử dụng tối đa của mỗi khách hàng
+    pub priority: u32,        // Thứ tự áp dụng (nhỏ hơn áp dụng trước)
+}
+
+// Khai báo các phương thức cho struct Rule
+impl Rule {
+    // Phương thức new để tạo một quy tắc tự động áp dụng, không giới hạn thời gian và số lượt
+    pub fn new(shop: u64, name: &str, kind: Kind) -> Rule {
+        Rule {
+            id: 0,
+            shop,
+            name: name.to_string(),
+            kind,
+            sku: None,
+            code: None,
+            start: None,
+            end: None,
+            limit: None,
+            cap: None,
+            priority: 0,
+        }
+    }
+
+    // Phương thức active kiểm tra quy tắc có hiệu lực tại thời điểm now hay không
+    pub fn active(&self, now: u64) -> bool {
+        self.start.is_none_or(|start| start <= now) && self.end.is_none_or(|end| now < end)
+    }
+
+    // Phương thức scope trả về phạm vi áp dụng dùng trong phần giải thích
+    fn scope(&self) -> String {
+        match &self.sku {
+            Some(sku) => sku.clone(),
+            None => "order".to_string(),
+        }
+    }
+}
+
+// Khai báo một struct để đại diện cho một khoản giảm giá đã áp dụng vào đơn hàng
+#[derive(Debug, Clone, PartialEq)]
+pub struct Discount {
+    pub rule: u64,     // Mã của quy tắc
+    pub name: String,  // Tên chương trình khuyến mãi
+    pub amount: Money, // Số tiền được giảm
+    pub note: String,  // Giải thích cách tính
+}
+
+// Khai báo một struct để chứa dữ liệu khuyến mãi (được bảo vệ bởi khóa)
+#[derive(Debug, Default)]
+struct Data {
+    rules: BTreeMap<u64, Rule>,              // Các quy tắc theo mã
+    uses: BTreeMap<u64, u32>,                // Số lượt đã dùng của mỗi quy tắc
+    customers: BTreeMap<(u64, String), u32>, // Số lượt đã dùng của mỗi khách hàng
+    next: u64,                               // Mã tiếp theo cho quy tắc
+}
+
+// Khai báo một struct để quản lý các quy tắc khuyến mãi (an toàn khi dùng chung giữa các luồng)
+#[derive(Debug)]
+pub struct Promotions {
+    catalog: Arc<Catalog>, // Danh mục (để kiểm tra quyền quản lý cửa hàng)
+    data: RwLock<Data>,    // Dữ liệu khuyến mãi
+}
+
+// Khai báo các phương thức cho struct Promotions
+impl Promotions {
+    // Phương thức new để tạo một bộ quy tắc rỗng cho các cửa hàng trong danh mục
+    pub fn new(catalog: Arc<Catalog>) -> Promotions {
+        Promotions {
+            catalog,
+            data: RwLock::new(Data::default()),
+        }
+    }
+
+    // Phương thức add để người dùng của cửa hàng thêm một quy tắc và trả về mã của quy tắc
+    pub fn add(&self, principal: &Principal, mut rule: Rule) -> io::Result<u64> {
+        self.catalog.manage(principal, rule.shop)?;
+
+        // Kiểm tra ưu đãi hợp lệ (phần trăm trong khoảng 0.01% đến 100%)
+        let valid = match rule.kind {
+            Kind::Percent(points) => points > 0 && points <= 10_000,
+            Kind::Fixed(money) => money.amount > 0,
+            Kind::Bundle { buy, get } => buy > 0 && get > 0 && buy.checked_add(get).is_some(),
+        };
+        if !valid {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid discount {:?} for {}", rule.kind, rule.name),
+            ));
+        }
+
+        // Kiểm tra khoảng thời gian hợp lệ
+        if let (Some(start), Some(end)) = (rule.start, rule.end) {
+            if end <= start {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Promotion {} ends before it starts", rule.name),
+                ));
+            }
+        }
+
+        let mut data = self.data.write().unwrap();
+        data.next += 1;
+        rule.id = data.next;
+        data.rules.insert(rule.id, rule);
+        Ok(data.next)
+    }
+
+    // Phương thức rule để lấy một bản sao của quy tắc
+    pub fn rule(&self, id: u64) -> io::Result<Rule> {
+        self.data
+            .read()
+            .unwrap()
+            .rules
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| missing(id))
+    }
+
+    // Phương thức remove để người dùng của cửa hàng xoá một quy tắc
+    pub fn remove(&self, principal: &Principal, id: u64) -> io::Result<Rule> {
+        let mut data = self.data.write().unwrap();
+        let shop = data.rules.get(&id).ok_or_else(|| missing(id))?.shop;
+        self.catalog.manage(principal, shop)?;
+        data.rules.remove(&id).ok_or_else(|| missing(id))
+    }
+
+    // Phương thức uses trả về số lượt đã dùng của một quy tắc
+    pub fn uses(&self, id: u64) -> u32 {
+        self.data
+            .read()
+            .unwrap()
+            .uses
+            .get(&id)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    // Phương thức known kiểm tra một mã giảm giá có thuộc quy tắc nào hay không
+    pub fn known(&self, code: &str) -> bool {
+        self.data
+            .read()
+            .unwrap()
+            .rules
+            .values()
+            .any(|rule| rule.code.as_deref() == Some(code))
+    }
+
+    // Phương thức quote để tính trước các khoản giảm giá mà không ghi nhận lượt sử dụng (lỗi nếu tràn số)
+    pub fn quote(
+        &self,
+        shop: u64,
+        buyer: &str,
+        lines: &[Line],
+        codes: &[String],
+        now: u64,
+    ) -> io::Result<Vec<Discount>> {
+        let data = self.data.read().unwrap();
+        evaluate(&data, shop, buyer, lines, codes, now)
+    }
+
+    // Phương thức apply để tính các khoản giảm giá và ghi nhận lượt sử dụng (trong cùng một khóa, không ghi
+    // nhận gì nếu tràn số)
+    pub fn apply(
+        &self,
+        shop: u64,
+        buyer: &str,
+        lines: &[Line],
+        codes: &[String],
+        now: u64,
+    ) -> io::Result<Vec<Discount>> {
+        let mut data = self.data.write().unwrap();
+        let discounts = evaluate(&data, shop, buyer, lines, codes, now)?;
+        for discount in &discounts {
+            *data.uses.entry(discount.rule).or_default() += 1;
+            *data
+                .customers
+                .entry((discount.rule, buyer.to_string()))
+                .or_default() += 1;
+        }
+        Ok(discounts)
+    }
+
+    // Phương thức restore để trả lại lượt sử dụng khi đơn hàng bị huỷ
+    pub fn restore(&self, buyer: &str, discounts: &[Discount]) {
+        let mut data = self.data.write().unwrap();
+        for discount in discounts {
+            if let Some(uses) = data.uses.get_mut(&discount.rule) {
+                *uses = uses.saturating_sub(1);
+            }
+            if let Some(uses) = data.customers.get_mut(&(discount.rule, buyer.to_string())) {
+                *uses = uses.saturating_sub(1);
+            }
+        }
+    }
+}
+
+// Hàm evaluate áp dụng lần lượt các quy tắc hợp lệ theo (priority, id); mỗi quy tắc tính trên số tiền
+// còn lại sau các quy tắc trước nên tổng tiền không bao giờ âm (lỗi nếu số tiền tràn số)
+fn evaluate(
+    data: &Data,
+    shop: u64,
+    buyer: &str,
+    lines: &[Line],
+    codes: &[String],
+    now: u64,
+) -> io::Result<Vec<Discount>> {
+    let currency = match lines.first() {
+        Some(line) => line.price.currency,
+        None => return Ok(Vec::new()),
+    };
+
+    // Chọn các quy tắc của cửa hàng đang có hiệu lực, đã nhập mã và chưa hết lượt
+    let mut rules: Vec<&Rule> = data
+        .rules
+        .values()
+        .filter(|rule| rule.shop == shop && rule.active(now))
+        .filter(|rule| rule.code.as_ref().is_none_or(|code| codes.contains(code)))
+        .filter(|rule| {
+            let uses = data.uses.get(&rule.id).copied().unwrap_or(0);
+            rule.limit.is_none_or(|limit| uses < limit)
+        })
+        .filter(|rule| {
+            let key = (rule.id, buyer.to_string());
+            let uses = data.customers.get(&key).copied().unwrap_or(0);
+            rule.cap.is_none_or(|cap| uses < cap)
+        })
+        .collect();
+    rules.sort_by_key(|rule| (rule.priority, rule.id));
+
+    // Số tiền còn lại của từng dòng hàng
+    let mut remaining: Vec<i64> = lines
+        .iter()
+        .map(|line| Ok(line.amount()?.max(0)))
+        .collect::<io::Result<_>>()?;
+
+    let mut discounts = Vec::new();
+    for rule in rules {
+        // Các dòng hàng thuộc phạm vi của quy tắc
+        let targets: Vec<usize> = (0..lines.len())
+            .filter(|&i| rule.sku.as_ref().is_none_or(|sku| *sku == lines[i].sku))
+            .collect();
+
+        let mut amount: i64 = 0;
+        let note = match rule.kind {
+            Kind::Percent(points) => {
+                for &i in &targets {
+                    let cut = remaining[i]
+                        .checked_mul(points as i64)
+                        .ok_or_else(|| overflow(&lines[i].sku))?
+                        / 10_000;
+                    remaining[i] -= cut;
+                    amount = amount
+                        .checked_add(cut)
+                        .ok_or_else(|| overflow(&lines[i].sku))?;
+                }
+                format!("{} off {}", percent(points), rule.scope())
+            }
+            Kind::Fixed(money) => {
+                // Ưu đãi khác loại tiền tệ của đơn hàng thì bỏ qua
+                if money.currency != currency {
+                    continue;
+                }
+                for &i in &targets {
+                    let cut = (money.amount - amount).min(remaining[i]);
+                    remaining[i] -= cut;
+                    amount += cut;
+                }
+                format!("{} off {}", money, rule.scope())
+            }
+            Kind::Bundle { buy, get } => {
+                let mut free = 0;
+                for &i in &targets {
+                    let units = lines[i].quantity / (buy + get) * get;
+                    let cut = (units as i64)
+                        .checked_mul(lines[i].price.amount)
+                        .ok_or_else(|| overflow(&lines[i].sku))?
+                        .min(remaining[i]);
+                    remaining[i] -= cut;
+                    amount = amount
+                        .checked_add(cut)
+                        .ok_or_else(|| overflow(&lines[i].sku))?;
+                    free += units;
+                }
+                format!("Buy {} get {} on {}: {} free", buy, get, rule.scope(), free)
+            }
+        };
+
+        // Chỉ ghi lại các quy tắc thực sự giảm giá
+        if amount > 0 {
+            discounts.push(Discount {
+                rule: rule.id,
+                name: rule.name.clone(),
+                amount: Money::new(amount, currency),
+                note,
+            });
+        }
+    }
+    Ok(discounts)
+}
+
+// Hàm percent định dạng basis points thành phần trăm (ví dụ: 1250 -> "12.5%")
+fn percent(points: u32) -> String {
+    let text = format!("{}.{:02}", points / 100, points % 100);
+    format!("{}%", text.trim_end_matches('0').trim_end_matches('.'))
+}
+
+// Hàm missing tạo lỗi io cho quy tắc không tồn tại
+fn missing(id: u64) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("Promotion {} not found", id),
+    )
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::auth::tests::principal;
+    use crate::bank::Currency;
+
+    // Khai báo một hàm để tạo bộ quy tắc rỗng cho cửa hàng 1 của alpha và cửa hàng 2 của gamma
+    fn promotions() -> Promotions {
+        let catalog = Catalog::new();
+        catalog.open(&principal("alpha"), "Alpha Store").unwrap();
+        catalog.open(&principal("gamma"), "Gamma Store").unwrap();
+        Promotions::new(Arc::new(catalog))
+    }
+
+    // Khai báo một hàm để tạo một dòng hàng mẫu
+    fn line(sku: &str, quantity: u32, price: i64) -> Line {
+        Line {
+            sku: sku.to_string(),
+            name: sku.to_string(),
+            quantity,
+            price: Money::new(price, Currency::Usd),
+            hold: 0,
+        }
+    }
+
+    // Khai báo một hàm để kiểm tra thứ tự áp dụng và phần giải thích
+    #[test]
+    fn test_evaluate() {
+        let promotions = promotions();
+        let mut bundle = Rule::new(1, "Hat deal", Kind::Bundle { buy: 2, get: 1 });
+        bundle.sku = Some("HAT".to_string());
+        let mut fixed = Rule::new(1, "Welcome", Kind::Fixed(Money::new(500, Currency::Usd)));
+        fixed.priority = 2;
+        let mut percent = Rule::new(1, "Spring", Kind::Percent(1000));
+        percent.priority = 1;
+        promotions.add(&principal("alpha"), fixed).unwrap();
+        promotions.add(&principal("alpha"), percent).unwrap();
+        promotions.add(&principal("alpha"), bundle).unwrap();
+        promotions
+            .add(
+                &principal("gamma"),
+                Rule::new(2, "Other shop", Kind::Percent(5000)),
+            )
+            .unwrap();
+
+        // Mua 3 mũ (tặng 1), sau đó giảm 10%, cuối cùng giảm 5.00 USD
+        let lines = [line("HAT", 3, 900), line("MUG", 1, 1200)];
+        let discounts = promotions.quote(1, "beta", &lines, &[], 0).unwrap();
+        let notes: Vec<&str> = discounts.iter().map(|d| d.note.as_str()).collect();
+        assert_eq!(
+            notes,
+            [
+                "Buy 2 get 1 on HAT: 1 free",
+                "10% off order",
+                "5.00 USD off order"
+            ]
+        );
+        let amounts: Vec<i64> = discounts.iter().map(|d| d.amount.amount).collect();
+        assert_eq!(amounts, [900, 300, 500]);
+
+        // Định dạng phần trăm lẻ
+        assert_eq!(super::percent(1250), "12.5%");
+        assert!(promotions
+            .add(
+                &principal("alpha"),
+                Rule::new(1, "Broken", Kind::Percent(10_001))
+            )
+            .is_err());
+        assert!(promotions
+            .add(
+                &principal("alpha"),
+                Rule::new(1, "Free", Kind::Percent(10_000))
+            )
+            .is_ok());
+
+        // Ưu đãi hoặc số tiền gây tràn số bị từ chối thay vì tính sai
+        let huge = Kind::Bundle {
+            buy: u32::MAX,
+            get: 1,
+        };
+        assert!(promotions
+            .add(&principal("alpha"), Rule::new(1, "Huge", huge))
+            .is_err());
+        let lines = [line("MUG", 1, i64::MAX / 2)];
+        let error = promotions.quote(1, "beta", &lines, &[], 0).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(promotions.apply(1, "beta", &lines, &[], 0).is_err());
+    }
+
+    // Khai báo một hàm để kiểm tra chỉ người dùng của cửa hàng mới được thêm và xoá quy tắc
+    #[test]
+    fn test_manage() {
+        let promotions = promotions();
+        let rule = Rule::new(1, "Spring", Kind::Percent(1000));
+        assert_eq!(
+            promotions
+                .add(&principal("gamma"), rule.clone())
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::PermissionDenied
+        );
+        let id = promotions.add(&principal("alpha"), rule).unwrap();
+        assert_eq!(
+            promotions
+                .remove(&principal("gamma"), id)
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::PermissionDenied
+        );
+        promotions.remove(&principal("alpha"), id).unwrap();
+        assert!(promotions.rule(id).is_err());
+    }
+
+    // Khai báo một hàm để kiểm tra mã giảm giá, giới hạn lượt dùng và khoảng thời gian
+    #[test]
+    fn test_coupon() {
+        let promotions = promotions();
+        let mut rule = Rule::new(1, "Launch", Kind::Percent(2000));
+        rule.code = Some("LAUNCH".to_string());
+        rule.start = Some(100);
+        rule.end = Some(200);
+        rule.limit = Some(2);
+        rule.cap = Some(1);
+        let id = promotions.add(&principal("alpha"), rule).unwrap();
+        let lines = [line("MUG", 1, 1000)];
+        let codes = ["LAUNCH".to_string()];
+
+        // Không nhập mã hoặc ngoài khoảng thời gian thì không được giảm
+        assert!(promotions
+            .quote(1, "beta", &lines, &[], 150)
+            .unwrap()
+            .is_empty());
+        assert!(promotions
+            .quote(1, "beta", &lines, &codes, 200)
+            .unwrap()
+            .is_empty());
+        assert!(promotions.known("LAUNCH"));
+
+        // Mỗi khách hàng chỉ dùng một lần, tổng cộng hai lần
+        assert_eq!(
+            promotions
+                .apply(1, "beta", &lines, &codes, 150)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(promotions
+            .apply(1, "beta", &lines, &codes, 150)
+            .unwrap()
+            .is_empty());
+        let used = promotions.apply(1, "gamma", &lines, &codes, 150).unwrap();
+        assert_eq!(used[0].amount.amount, 200);
+        assert!(promotions
+            .apply(1, "delta", &lines, &codes, 150)
+            .unwrap()
+            .is_empty());
+        assert_eq!(promotions.uses(id), 2);
+
+        // Huỷ đơn hàng trả lại lượt sử dụng
+        promotions.restore("gamma", &used);
+        assert_eq!(
+            promotions
+                .apply(1, "delta", &lines, &codes, 150)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    // Khai báo một hàm để kiểm tra thuộc tính: tổng tiền sau giảm giá không bao giờ âm
+    #[test]
+    fn test_never_negative() {
+        // Bộ sinh số giả ngẫu nhiên xorshift để kết quả có thể lặp lại
+        let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = move |bound: u64| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed % bound
+        };
+        let skus = ["HAT", "MUG", "SHIRT-S"];
+
+        for _ in 0..500 {
+            // Sinh ngẫu nhiên các quy tắc
+            let promotions = promotions();
+            for _ in 0..next(6) {
+                let kind = match next(3) {
+                    0 => Kind::Percent(next(10_000) as u32 + 1),
+                    1 => Kind::Fixed(Money::new(next(10_000) as i64 + 1, Currency::Usd)),
+                    _ => Kind::Bundle {
+                        buy: next(4) as u32 + 1,
+                        get: next(3) as u32 + 1,
+                    },
+                };
+                let mut rule = Rule::new(1, "Random", kind);
+                rule.priority = next(3) as u32;
+                if next(2) == 0 {
+                    rule.sku = Some(skus[next(3) as usize].to_string());
+                }
+                promotions.add(&principal("alpha"), rule).unwrap();
+            }
+
+            // Sinh ngẫu nhiên các dòng hàng
+            let lines: Vec<Line> = (0..next(4) + 1)
+                .map(|i| line(skus[i as usize % 3], next(10) as u32, next(5_000) as i64))
+                .collect();
+            let subtotal: i64 = lines
+                .iter()
+                .map(|line| line.price.amount * line.quantity as i64)
+                .sum();
+
+            // Tổng giảm giá không vượt quá tổng tiền và kết quả luôn như nhau
+            let discounts = promotions.quote(1, "beta", &lines, &[], 0).unwrap();
+            let total: i64 = discounts.iter().map(|d| d.amount.amount).sum();
+            assert!(discounts.iter().all(|d| d.amount.amount > 0));
+            assert!(subtotal - total >= 0);
+            assert_eq!(
+                discounts,
+                promotions.quote(1, "beta", &lines, &[], 0).unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,541 @@
+// Khai báo các thư viện cần thiết
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use super::order::{Order, Orders, Status};
+use super::stock::Stock;
+use super::Catalog;
+use crate::auth::Principal;
+use crate::bank::{Ledger, Money};
+use crate::clock;
+use crate::event::{Bus, Event};
+
+// Khai báo một enum để đại diện cho lý do trả hàng
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reason {
+    Damaged,       // Hàng bị hư hỏng
+    Wrong,         // Giao sai hàng
+    Unwanted,      // Không còn nhu cầu
+    Other(String), // Lý do khác
+}
+
+// Khai báo một enum để đại diện cho các trạng thái của yêu cầu trả hàng
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Requested, // Người mua đã gửi yêu cầu
+    Approved,  // Cửa hàng đã chấp nhận
+    Rejected,  // Cửa hàng đã từ chối
+    Received,  // Cửa hàng đã nhận lại hàng
+    Refunded,  // Đã hoàn tiền
+}
+
+// Khai báo các phương thức cho enum State
+impl State {
+    // Phương thức allows kiểm tra có thể chuyển từ trạng thái hiện tại sang trạng thái cho trước hay không
+    pub fn allows(&self, to: State) -> bool {
+        use State::*;
+        matches!(
+            (self, to),
+            (Requested, Approved)
+                | (Requested, Rejected)
+                | (Approved, Received)
+                | (Approved, Refunded)
+                | (Received, Refunded)
+        )
+    }
+}
+
+// Khai báo một struct để đại diện cho một yêu cầu trả hàng (RMA)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Return {
+    pub id: u64,                      // Mã của yêu cầu trả hàng
+    pub order: u64,                   // Mã của đơn hàng
+    pub buyer: String,                // Doanh nghiệp trả hàng
+    pub lines: Vec<(String, u32)>,    // Các mã SKU và số lượng trả
+    pub reason: Reason,               // Lý do trả hàng
+    pub value: Money,                 // Giá trị hàng trả (đã tính giảm giá của đơn hàng)
+    pub state: State,                 // Trạng thái hiện tại
+    pub note: Option<String>,         // Ghi chú của cửa hàng (ví dụ: lý do từ chối)
+    pub refund: Option<(u64, Money)>, // Bút toán và số tiền đã hoàn
+    pub history: Vec<(State, u64)>,   // Lịch sử trạng thái và thời điểm
+}
+
+// Khai báo một struct để quản lý trả hàng và hoàn tiền
+#[derive(Debug)]
+pub struct Returns {
+    catalog: Arc<Catalog>, // Danh mục (để kiểm tra quyền quản lý cửa hàng)
+    orders: Arc<Orders>,   // Các đơn hàng
+    stock: Arc<Stock>,     // Kho hàng để nhập lại hàng trả
+    ledger: Arc<Ledger>,   // Sổ cái dùng để hoàn tiền
+    bus: Arc<Bus>,         // Kênh sự kiện
+    returns: Mutex<BTreeMap<u64, Return>>, // Các yêu cầu trả hàng theo mã
+}
+
+// Khai báo các phương thức cho struct Returns
+impl Returns {
+    // Phương thức new để tạo một bộ quản lý trả hàng mới
+    pub fn new(
+        catalog: Arc<Catalog>,
+        orders: Arc<Orders>,
+        stock: Arc<Stock>,
+        ledger: Arc<Ledger>,
+        bus: Arc<Bus>,
+    ) -> Returns {
+        Returns {
+            catalog,
+            orders,
+            stock,
+            ledger,
+            bus,
+            returns: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    // Phương thức request để người dùng của người mua gửi yêu cầu trả một phần hoặc toàn bộ đơn hàng đã giao
+    pub fn request(
+        &self,
+        principal: &Principal,
+        order: u64,
+        lines: &[(&str, u32)],
+        reason: Reason,
+    ) -> io::Result<u64> {
+        let placed = self.orders.get(order)?;
+        super::order::buyer(principal, &placed.buyer)?;
+        if placed.status != Status::Delivered {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("Order {} cannot be returned by {}", order, placed.buyer),
+            ));
+        }
+
+        // Gộp số lượng của cùng một mã SKU trong yêu cầu
+        let mut merged: BTreeMap<&str, u32> = BTreeMap::new();
+        for (sku, quantity) in lines {
+            if *quantity == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Cannot return {} x0 of order {}", sku, order),
+                ));
+            }
+            let total = merged.entry(sku).or_default();
+            *total = total.checked_add(*quantity).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Return quantity of {} overflows", sku),
+                )
+            })?;
+        }
+
+        let mut returns = self.returns.lock().unwrap();
+
+        // Số lượng trả không được vượt quá số lượng đã mua trừ đi các yêu cầu trước đó
+        let mut value = 0;
+        for (sku, quantity) in &merged {
+            let line = placed
+                .lines
+                .iter()
+                .find(|line| line.sku == *sku)
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("Order {} has no {}", order, sku),
+                    )
+                })?;
+            let returned: u32 = returns
+                .values()
+                .filter(|rma| rma.order == order && rma.state != State::Rejected)
+                .flat_map(|rma| rma.lines.iter())
+                .filter(|(s, _)| s == sku)
+                .map(|(_, q)| q)
+                .sum();
+            if returned + quantity > line.quantity {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Cannot return {} x{} of order {} ({} of {} already returned)",
+                        sku, quantity, order, returned, line.quantity
+                    ),
+                ));
+            }
+            value += line.price.amount * *quantity as i64;
+        }
+        if lines.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Return of order {} has no lines", order),
+            ));
+        }
+
+        let id = returns.keys().next_back().copied().unwrap_or(0) + 1;
+        let rma = Return {
+            id,
+            order,
+            buyer: placed.buyer.clone(),
+            lines: merged
+                .iter()
+                .map(|(sku, quantity)| (sku.to_string(), *quantity))
+                .collect(),
+            reason,
+            value: Money::new(prorate(&placed, value)?, placed.total.currency),
+            state: State::Requested,
+            note: None,
+            refund: None,
+            history: vec![(State::Requested, clock::now())],
+        };
+        returns.insert(id, rma);
+
+        // Công bố sự kiện yêu cầu trả hàng mới
+        self.bus.publish(Event::ReturnRequested {
+            rma: id,
+            order,
+            buyer: placed.buyer.clone(),
+        });
+        Ok(id)
+    }
+
+    // Phương thức get để lấy một bản sao của yêu cầu trả hàng
+    pub fn get(&self, id: u64) -> io::Result<Return> {
+        self.returns
+            .lock()
+            .unwrap()
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| missing(id))
+    }
+
+    // Phương thức approve để người dùng của cửa hàng chấp nhận yêu cầu trả hàng
+    pub fn approve(&self, principal: &Principal, id: u64) -> io::Result<()> {
+        self.manage(principal, id)?;
+        self.change(id, State::Approved, |_| Ok(()))
+    }
+
+    // Phương thức reject để người dùng của cửa hàng từ chối yêu cầu trả hàng kèm lý do
+    pub fn reject(&self, principal: &Principal, id: u64, note: &str) -> io::Result<()> {
+        self.manage(principal, id)?;
+        self.change(id, State::Rejected, |rma| {
+            rma.note = Some(note.to_string());
+            Ok(())
+        })
+    }
+
+    // Phương thức receive để người dùng của cửa hàng nhận lại hàng; nếu có kho thì nhập lại hàng, nếu không thì
+    // hàng bị loại bỏ
+    pub fn receive(
+        &self,
+        principal: &Principal,
+        id: u64,
+        warehouse: Option<&str>,
+    ) -> io::Result<()> {
+        self.manage(principal, id)?;
+        self.change(id, State::Received, |rma| {
+            if let Some(warehouse) = warehouse {
+                for (sku, quantity) in &rma.lines {
+                    self.stock
+                        .restock(principal, warehouse, sku, *quantity, rma.id)?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    // Phương thức refund để người dùng của cửa hàng hoàn tiền bằng bút toán đảo ngược; None nghĩa là hoàn
+    // toàn bộ giá trị hàng trả, số tiền nhỏ hơn là hoàn một phần (ví dụ: trừ phí nhập lại hàng); tổng số tiền
+    // hoàn của đơn hàng không vượt quá số tiền đã thanh toán
+    pub fn refund(&self, principal: &Principal, id: u64, amount: Option<Money>) -> io::Result<u64> {
+        self.manage(principal, id)?;
+        self.change(id, State::Refunded, |rma| {
+            // Số tiền hoàn phải dương, cùng loại tiền tệ và không vượt quá giá trị hàng trả
+            let amount = amount.unwrap_or(rma.value);
+            rma.value.check(amount)?;
+            if amount.amount <= 0 || amount.amount > rma.value.amount {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Refund {} exceeds return value {}", amount, rma.value),
+                ));
+            }
+
+            // Hoàn tiền từ tài khoản của cửa hàng về tài khoản của người mua
+            let entry = self.orders.credit(rma.order, amount, |payment| {
+                self.ledger.transfer(
+                    principal,
+                    payment.to,
+                    payment.from,
+                    amount,
+                    &format!("Refund return {} of order {}", rma.id, rma.order),
+                )
+            })?;
+            rma.refund = Some((entry, amount));
+
+            // Công bố sự kiện hoàn tiền cho module kế toán
+            self.bus.publish(Event::RefundPosted {
+                rma: rma.id,
+                order: rma.order,
+                amount,
+                entry,
+            });
+            Ok(entry)
+        })
+    }
+
+    // Phương thức manage kiểm tra người dùng được quản lý cửa hàng của đơn hàng bị trả
+    fn manage(&self, principal: &Principal, id: u64) -> io::Result<()> {
+        let order = self.orders.get(self.get(id)?.order)?;
+        self.catalog.manage(principal, order.shop)
+    }
+
+    // Phương thức change để chuyển trạng thái: kiểm tra hợp lệ, thực hiện tác động rồi công bố sự kiện
+    fn change<T>(
+        &self,
+        id: u64,
+        to: State,
+        effect: impl FnOnce(&mut Return) -> io::Result<T>,
+    ) -> io::Result<T> {
+        let mut returns = self.returns.lock().unwrap();
+        let rma = returns.get_mut(&id).ok_or_else(|| missing(id))?;
+
+        // Kiểm tra xem có thể chuyển sang trạng thái mới hay không
+        let from = rma.state;
+        if !from.allows(to) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Return {} cannot go from {:?} to {:?}", id, from, to),
+            ));
+        }
+
+        // Thực hiện tác động, cập nhật trạng thái và lịch sử
+        let result = effect(rma)?;
+        rma.state = to;
+        rma.history.push((to, clock::now()));
+
+        // Công bố sự kiện thay đổi trạng thái
+        self.bus.publish(Event::ReturnChanged { rma: id, from, to });
+        Ok(result)
+    }
+}
+
+// Hàm prorate phân bổ giảm giá của đơn hàng vào giá trị hàng trả theo tỷ lệ
+fn prorate(order: &Order, value: i64) -> io::Result<i64> {
+    let subtotal = order.subtotal()?.amount;
+    if subtotal <= 0 {
+        return Ok(0);
+    }
+    Ok((value as i128 * order.total.amount as i128 / subtotal as i128) as i64)
+}
+
+// Hàm missing tạo lỗi io cho yêu cầu trả hàng không tồn tại
+fn missing(id: u64) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("Return {} not found", id))
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::auth::role::Role;
+    use crate::auth::tests::principal;
+    use crate::bank::Currency;
+    use crate::shop::order::tests::{fixture, Fixture};
+    use crate::shop::order::Cart;
+    use crate::shop::promo::{Kind, Rule};
+    use crate::shop::stock;
+
+    // Khai báo một hàm để tạo một đơn hàng đã giao gồm 4 áo và 1 cốc
+    fn delivered(fixture: &Fixture) -> u64 {
+        let mut cart = Cart::new("beta");
+        cart.add("SHIRT-S", 4);
+        cart.add("MUG", 1);
+        let id = fixture.orders.checkout(&principal("beta"), &cart).unwrap()[0];
+        let orders = &fixture.orders;
+        orders
+            .pay(&principal("beta"), id, fixture.buyer, fixture.seller)
+            .unwrap();
+        orders.pack(&principal("alpha"), id).unwrap();
+        orders.ship(&principal("alpha"), id).unwrap();
+        orders.deliver(&principal("alpha"), id).unwrap();
+        id
+    }
+
+    // Khai báo một hàm để tạo bộ quản lý trả hàng từ các thành phần mẫu
+    fn returns(fixture: &Fixture) -> Returns {
+        Returns::new(
+            fixture.catalog.clone(),
+            fixture.orders.clone(),
+            fixture.stock.clone(),
+            fixture.ledger.clone(),
+            fixture.bus.clone(),
+        )
+    }
+
+    // Khai báo một hàm để kiểm tra việc gửi, từ chối yêu cầu và giới hạn số lượng trả
+    #[test]
+    fn test_request() {
+        let fixture = fixture();
+        let returns = returns(&fixture);
+        let order = delivered(&fixture);
+
+        // Chỉ người mua được trả hàng có trong đơn hàng
+        assert!(returns
+            .request(&principal("gamma"), order, &[("MUG", 1)], Reason::Unwanted)
+            .is_err());
+        assert!(returns
+            .request(&principal("beta"), order, &[("HAT", 1)], Reason::Unwanted)
+            .is_err());
+        assert!(returns
+            .request(
+                &principal("beta"),
+                order,
+                &[("SHIRT-S", 5)],
+                Reason::Unwanted
+            )
+            .is_err());
+        assert!(returns
+            .request(
+                &principal("beta"),
+                order,
+                &[("SHIRT-S", 3), ("SHIRT-S", 2)],
+                Reason::Unwanted
+            )
+            .is_err());
+
+        // Yêu cầu bị từ chối không tính vào số lượng đã trả
+        let first = returns
+            .request(&principal("beta"), order, &[("SHIRT-S", 3)], Reason::Wrong)
+            .unwrap();
+        assert!(returns
+            .request(&principal("beta"), order, &[("SHIRT-S", 2)], Reason::Wrong)
+            .is_err());
+        assert_eq!(
+            returns
+                .reject(&principal("beta"), first, "Worn")
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::PermissionDenied
+        );
+        returns.reject(&principal("alpha"), first, "Worn").unwrap();
+        assert_eq!(returns.get(first).unwrap().note.as_deref(), Some("Worn"));
+        assert!(returns.approve(&principal("alpha"), first).is_err());
+        let second = returns
+            .request(&principal("beta"), order, &[("SHIRT-S", 4)], Reason::Wrong)
+            .unwrap();
+        assert_eq!(returns.get(second).unwrap().value.amount, 6000);
+    }
+
+    // Khai báo một hàm để kiểm tra việc nhập lại hàng và hoàn tiền toàn bộ
+    #[test]
+    fn test_refund() {
+        let fixture = fixture();
+        let events = fixture.bus.subscribe();
+        let returns = returns(&fixture);
+        let order = delivered(&fixture);
+        let id = returns
+            .request(
+                &principal("beta"),
+                order,
+                &[("SHIRT-S", 2), ("MUG", 1)],
+                Reason::Damaged,
+            )
+            .unwrap();
+
+        // Không thể hoàn tiền trước khi chấp nhận
+        assert!(returns.refund(&principal("alpha"), id, None).is_err());
+
+        // Kế toán được chi tiền nhưng không quản lý cửa hàng thì không được hoàn tiền
+        let accountant = Principal::new(1, "accountant", "alpha", Role::Accountant, u64::MAX);
+        returns.approve(&principal("alpha"), id).unwrap();
+        returns
+            .receive(&principal("alpha"), id, Some("main"))
+            .unwrap();
+        assert_eq!(fixture.stock.available("SHIRT-S"), 8);
+        let kinds: Vec<stock::Kind> = fixture
+            .stock
+            .movements("MUG")
+            .into_iter()
+            .map(|m| m.kind)
+            .collect();
+        assert_eq!(kinds.last(), Some(&stock::Kind::Return(id)));
+
+        // Hoàn tiền toàn bộ bằng bút toán đảo ngược
+        let denied = returns.refund(&accountant, id, None).unwrap_err();
+        assert_eq!(denied.kind(), io::ErrorKind::PermissionDenied);
+        let entry = returns.refund(&principal("alpha"), id, None).unwrap();
+        assert_eq!(
+            fixture.ledger.balance(fixture.seller).unwrap().amount,
+            1500 * 2
+        );
+        assert!(returns.refund(&principal("alpha"), id, None).is_err());
+        let events: Vec<Event> = events.try_iter().collect();
+        assert!(events.contains(&Event::RefundPosted {
+            rma: id,
+            order,
+            amount: Money::new(4200, Currency::Usd),
+            entry
+        }));
+        assert_eq!(
+            events.last(),
+            Some(&Event::ReturnChanged {
+                rma: id,
+                from: State::Received,
+                to: State::Refunded
+            })
+        );
+    }
+
+    // Khai báo một hàm để kiểm tra hoàn tiền một phần và phân bổ giảm giá
+    #[test]
+    fn test_partial() {
+        let fixture = fixture();
+        fixture
+            .promotions
+            .add(
+                &principal("alpha"),
+                Rule::new(fixture.shop, "Half", Kind::Percent(5000)),
+            )
+            .unwrap();
+        let returns = returns(&fixture);
+        let order = delivered(&fixture);
+
+        // Giá trị hàng trả được giảm theo tỷ lệ khuyến mãi của đơn hàng
+        let id = returns
+            .request(
+                &principal("beta"),
+                order,
+                &[("SHIRT-S", 2)],
+                Reason::Unwanted,
+            )
+            .unwrap();
+        let value = returns.get(id).unwrap().value;
+        assert_eq!(value.amount, 1500);
+
+        // Hoàn một phần (trừ phí nhập lại hàng) mà không nhận lại hàng vào kho
+        returns.approve(&principal("alpha"), id).unwrap();
+        returns.receive(&principal("alpha"), id, None).unwrap();
+        assert_eq!(fixture.stock.available("SHIRT-S"), 6);
+        assert!(returns
+            .refund(
+                &principal("alpha"),
+                id,
+                Some(Money::new(1600, value.currency))
+            )
+            .is_err());
+        returns
+            .refund(
+                &principal("alpha"),
+                id,
+                Some(Money::new(1200, value.currency)),
+            )
+            .unwrap();
+        assert_eq!(returns.get(id).unwrap().refund.unwrap().1.amount, 1200);
+        assert_eq!(
+            fixture.ledger.balance(fixture.seller).unwrap().amount,
+            (4 * 1500 + 1200) / 2 - 1200
+        );
+
+        // Hoàn tiền cả đơn hàng chỉ hoàn phần chưa hoàn
+        fixture.orders.refund(&principal("alpha"), order).unwrap();
+        assert_eq!(fixture.ledger.balance(fixture.seller).unwrap().amount, 0);
+        let placed = fixture.orders.get(order).unwrap();
+        assert_eq!(placed.refunded, placed.total);
+    }
+}
@@ -0,0 +1,611 @@
+// Khai báo các thư viện cần thiết
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use super::order::{Orders, Status};
+use super::{paginate, Catalog, Page};
+use crate::auth::role::Action;
+use crate::auth::{self, Principal};
+use crate::clock;
+use crate::storage::{Access, Storage};
+
+// Khai báo một enum để đại diện cho trạng thái kiểm duyệt của đánh giá
+#[derive(Debug, Clone, PartialEq)]
+pub enum State {
+    Pending,          // Đang chờ kiểm duyệt
+    Approved,         // Đã duyệt và hiển thị công khai
+    Rejected(String), // Bị từ chối kèm lý do
+}
+
+// Khai báo một struct để đại diện cho một đánh giá sản phẩm hoặc dịch vụ
+#[derive(Debug, Clone, PartialEq)]
+pub struct Review {
+    pub id: u64,             // Mã của đánh giá
+    pub product: u64,        // Mã của sản phẩm được đánh giá
+    pub order: u64,          // Mã của đơn hàng đã mua (xác thực người mua)
+    pub author: String,      // Doanh nghiệp viết đánh giá
+    pub stars: u8,           // Số sao từ 1 đến 5
+    pub text: String,        // Nội dung đánh giá
+    pub photos: Vec<String>, // Tên các tập tin hình ảnh trong đối tượng lưu trữ
+    pub shown: Vec<String>,  // Các hình ảnh đã được duyệt và công khai
+    pub uploads: u32,        // Số hình ảnh đã tải lên (kể cả đã xoá), dùng để đặt tên tập tin
+    pub state: State,        // Trạng thái kiểm duyệt
+    pub time: u64,           // Thời điểm viết
+    pub edited: Option<u64>, // Thời điểm sửa gần nhất
+}
+
+// Khai báo một struct để đại diện cho điểm đánh giá tổng hợp của một sản phẩm (chỉ tính các đánh giá đã duyệt)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rating {
+    pub count: u32,      // Số đánh giá
+    pub total: u32,      // Tổng số sao
+    pub stars: [u32; 5], // Số đánh giá theo từng mức sao (1 đến 5)
+}
+
+// Khai báo các phương thức cho struct Rating
+impl Rating {
+    // Phương thức average trả về số sao trung bình (None nếu chưa có đánh giá)
+    pub fn average(&self) -> Option<f64> {
+        if self.count == 0 {
+            None
+        } else {
+            Some(self.total as f64 / self.count as f64)
+        }
+    }
+
+    // Phương thức apply để cộng (hoặc trừ) một đánh giá vào điểm tổng hợp
+    fn apply(&mut self, stars: u8, add: bool) {
+        let slot = &mut self.stars[stars as usize - 1];
+        if add {
+            self.count += 1;
+            self.total += stars as u32;
+            *slot += 1;
+        } else {
+            self.count -= 1;
+            self.total -= stars as u32;
+            *slot -= 1;
+        }
+    }
+}
+
+// Khai báo một struct để chứa dữ liệu đánh giá (được bảo vệ bởi khóa)
+#[derive(Debug, Default)]
+struct Data {
+    reviews: BTreeMap<u64, Review>, // Các đánh giá theo mã
+    ratings: HashMap<u64, Rating>,  // Điểm tổng hợp theo mã sản phẩm
+    next: u64,                      // Mã tiếp theo cho đánh giá
+}
+
+// Khai báo một struct để quản lý đánh giá, chỉ người đã nhận hàng mới được đánh giá
+#[derive(Debug)]
+pub struct Reviews {
+    catalog: Arc<Catalog>, // Danh mục sản phẩm
+    orders: Arc<Orders>,   // Các đơn hàng dùng để xác thực người mua
+    data: RwLock<Data>,    // Dữ liệu đánh giá
+}
+
+// Khai báo các phương thức cho struct Reviews
+impl Reviews {
+    // Phương thức new để tạo một bộ quản lý đánh giá mới
+    pub fn new(catalog: Arc<Catalog>, orders: Arc<Orders>) -> Reviews {
+        Reviews {
+            catalog,
+            orders,
+            data: RwLock::new(Data::default()),
+        }
+    }
+
+    // Phương thức write để người dùng của người mua viết đánh giá cho một sản phẩm trong đơn hàng đã giao
+    // (chờ kiểm duyệt)
+    pub fn write(
+        &self,
+        principal: &Principal,
+        order: u64,
+        product: u64,
+        stars: u8,
+        text: &str,
+    ) -> io::Result<u64> {
+        let author = author(principal)?;
+        check(stars)?;
+
+        // Đơn hàng phải thuộc về người viết, đã giao và có chứa sản phẩm
+        let placed = self.orders.get(order)?;
+        let bought = placed.lines.iter().any(|line| {
+            self.catalog
+                .sku(&line.sku)
+                .is_ok_and(|(item, _)| item.id == product)
+        });
+        if placed.buyer != author || placed.status != Status::Delivered || !bought {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!(
+                    "Order {} does not verify a purchase of product {} by {}",
+                    order, product, author
+                ),
+            ));
+        }
+
+        // Mỗi đơn hàng chỉ được đánh giá một sản phẩm một lần
+        let mut data = self.data.write().unwrap();
+        if data
+            .reviews
+            .values()
+            .any(|review| review.order == order && review.product == product)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("Product {} of order {} is already reviewed", product, order),
+            ));
+        }
+
+        data.next += 1;
+        let id = data.next;
+        data.reviews.insert(
+            id,
+            Review {
+                id,
+                product,
+                order,
+                author: author.to_string(),
+                stars,
+                text: text.to_string(),
+                photos: Vec::new(),
+                shown: Vec::new(),
+                uploads: 0,
+                state: State::Pending,
+                time: clock::now(),
+                edited: None,
+            },
+        );
+        Ok(id)
+    }
+
+    // Phương thức get để lấy một bản sao của đánh giá
+    pub fn get(&self, id: u64) -> io::Result<Review> {
+        self.data
+            .read()
+            .unwrap()
+            .reviews
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| missing(id))
+    }
+
+    // Phương thức edit để người viết sửa đánh giá (đánh giá quay lại hàng đợi kiểm duyệt)
+    pub fn edit(&self, principal: &Principal, id: u64, stars: u8, text: &str) -> io::Result<()> {
+        let author = author(principal)?;
+        check(stars)?;
+        let mut data = self.data.write().unwrap();
+        let review = own(&mut data, id, author)?.clone();
+
+        // Bỏ đánh giá cũ khỏi điểm tổng hợp nếu đã được duyệt
+        if review.state == State::Approved {
+            rate(&mut data, &review, false);
+        }
+
+        if let Some(review) = data.reviews.get_mut(&id) {
+            review.stars = stars;
+            review.text = text.to_string();
+            review.state = State::Pending;
+            review.edited = Some(clock::now());
+        }
+        Ok(())
+    }
+
+    // Phương thức delete để người viết xoá đánh giá cùng các hình ảnh của nó trong đối tượng lưu trữ
+    pub fn delete(
+        &self,
+        principal: &Principal,
+        id: u64,
+        storage: &mut Storage,
+    ) -> io::Result<Review> {
+        let author = author(principal)?;
+        let mut data = self.data.write().unwrap();
+        for photo in &own(&mut data, id, author)?.photos {
+            match storage.delete(photo) {
+                Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+                _ => {}
+            }
+        }
+        let review = data.reviews.remove(&id).ok_or_else(|| missing(id))?;
+        if review.state == State::Approved {
+            rate(&mut data, &review, false);
+        }
+        Ok(review)
+    }
+
+    // Phương thức photo để người viết đính kèm hình ảnh vào đánh giá (lưu riêng tư cho tới khi hình ảnh được
+    // duyệt)
+    pub fn photo(
+        &self,
+        principal: &Principal,
+        id: u64,
+        storage: &mut Storage,
+        source: &Path,
+    ) -> io::Result<String> {
+        let author = author(principal)?;
+        let mut data = self.data.write().unwrap();
+        let review = own(&mut data, id, author)?;
+
+        // Đặt tên tập tin theo mã đánh giá và số thứ tự tải lên (không dùng lại tên của hình ảnh đã xoá), giữ
+        // nguyên phần mở rộng
+        let extension = source
+            .extension()
+            .map(|ext| format!(".{}", ext.to_string_lossy()))
+            .unwrap_or_default();
+        let name = format!("review-{}-{}{}", id, review.uploads + 1, extension);
+        storage.put_with_access(&name, &fs::read(source)?, Access::Private)?;
+
+        review.uploads += 1;
+        review.photos.push(name.clone());
+        Ok(name)
+    }
+
+    // Phương thức queue trả về các đánh giá đang chờ kiểm duyệt (cũ nhất trước)
+    pub fn queue(&self) -> Vec<Review> {
+        self.data
+            .read()
+            .unwrap()
+            .reviews
+            .values()
+            .filter(|review| review.state == State::Pending)
+            .cloned()
+            .collect()
+    }
+
+    // Phương thức approve để người dùng của cửa hàng duyệt đánh giá và tính vào điểm tổng hợp (hình ảnh được
+    // kiểm duyệt riêng bằng moderate)
+    pub fn approve(&self, principal: &Principal, id: u64) -> io::Result<()> {
+        self.manage(principal, id)?;
+        let mut data = self.data.write().unwrap();
+        let review = data.reviews.get(&id).cloned().ok_or_else(|| missing(id))?;
+        pending(&review)?;
+        rate(&mut data, &review, true);
+        if let Some(review) = data.reviews.get_mut(&id) {
+            review.state = State::Approved;
+        }
+        Ok(())
+    }
+
+    // Phương thức reject để người dùng của cửa hàng từ chối đánh giá kèm lý do
+    pub fn reject(&self, principal: &Principal, id: u64, reason: &str) -> io::Result<()> {
+        self.manage(principal, id)?;
+        let mut data = self.data.write().unwrap();
+        let review = data.reviews.get_mut(&id).ok_or_else(|| missing(id))?;
+        pending(review)?;
+        review.state = State::Rejected(reason.to_string());
+        Ok(())
+    }
+
+    // Phương thức moderate để người dùng của cửa hàng kiểm duyệt một hình ảnh của đánh giá: công khai hình ảnh
+    // (chỉ khi đánh giá đã được duyệt) hoặc xoá hình ảnh khỏi đánh giá và đối tượng lưu trữ
+    pub fn moderate(
+        &self,
+        principal: &Principal,
+        id: u64,
+        photo: &str,
+        publish: bool,
+        storage: &mut Storage,
+    ) -> io::Result<()> {
+        self.manage(principal, id)?;
+        let mut data = self.data.write().unwrap();
+        let review = data.reviews.get_mut(&id).ok_or_else(|| missing(id))?;
+        if !review.photos.iter().any(|name| name == photo) {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Photo {} not found in review {}", photo, id),
+            ));
+        }
+
+        if publish {
+            if review.state != State::Approved {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Review {} is not approved", id),
+                ));
+            }
+            storage.set_file_access(photo, Access::Public)?;
+            if !review.shown.iter().any(|name| name == photo) {
+                review.shown.push(photo.to_string());
+            }
+        } else {
+            storage.delete(photo)?;
+            review.photos.retain(|name| name != photo);
+            review.shown.retain(|name| name != photo);
+        }
+        Ok(())
+    }
+
+    // Phương thức manage kiểm tra người dùng được quản lý cửa hàng bán sản phẩm của đánh giá
+    fn manage(&self, principal: &Principal, id: u64) -> io::Result<()> {
+        let product = self.get(id)?.product;
+        let shop = self.catalog.product(product)?.shop;
+        self.catalog.manage(principal, shop)
+    }
+
+    // Phương thức rating trả về điểm tổng hợp của một sản phẩm
+    pub fn rating(&self, product: u64) -> Rating {
+        self.data
+            .read()
+            .unwrap()
+            .ratings
+            .get(&product)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    // Phương thức list trả về các đánh giá đã duyệt của một sản phẩm (mới nhất trước) theo trang
+    pub fn list(&self, product: u64, page: usize, size: usize) -> Page<Review> {
+        let data = self.data.read().unwrap();
+        let reviews = data
+            .reviews
+            .values()
+            .rev()
+            .filter(|review| review.product == product && review.state == State::Approved)
+            .cloned()
+            .collect();
+        paginate(reviews, page, size)
+    }
+}
+
+// Hàm check kiểm tra số sao nằm trong khoảng 1 đến 5
+fn check(stars: u8) -> io::Result<()> {
+    if (1..=5).contains(&stars) {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Rating must be 1 to 5 stars, got {}", stars),
+        ))
+    }
+}
+
+// Hàm author trả về doanh nghiệp của người dùng nếu họ được viết thay doanh nghiệp (đánh giá được công khai
+// nên cần quyền ghi như hồ sơ doanh nghiệp)
+fn author(principal: &Principal) -> io::Result<&str> {
+    auth::authorize(
+        principal,
+        Action::StorageWrite,
+        &format!("reviews of {}", principal.business()),
+    )?;
+    Ok(principal.business())
+}
+
+// Hàm own trả về đánh giá nếu người gọi là người viết
+fn own<'a>(data: &'a mut Data, id: u64, author: &str) -> io::Result<&'a mut Review> {
+    let review = data.reviews.get_mut(&id).ok_or_else(|| missing(id))?;
+    if review.author != author {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("Review {} belongs to {}", id, review.author),
+        ));
+    }
+    Ok(review)
+}
+
+// Hàm pending kiểm tra đánh giá đang chờ kiểm duyệt
+fn pending(review: &Review) -> io::Result<()> {
+    if review.state != State::Pending {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Review {} is not pending moderation", review.id),
+        ));
+    }
+    Ok(())
+}
+
+// Hàm rate để cộng hoặc trừ một đánh giá vào điểm tổng hợp của sản phẩm
+fn rate(data: &mut Data, review: &Review, add: bool) {
+    data.ratings
+        .entry(review.product)
+        .or_default()
+        .apply(review.stars, add);
+}
+
+// Hàm missing tạo lỗi io cho đánh giá không tồn tại
+fn missing(id: u64) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("Review {} not found", id))
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::auth::role::Role;
+    use crate::auth::tests::principal;
+    use crate::shop::order::tests::{fixture, Fixture};
+    use crate::shop::order::Cart;
+    use crate::storage::tests::{create_temp_storage, tempdir};
+
+    // Khai báo một hàm để tạo một đơn hàng đã giao gồm áo và mũ
+    fn delivered(fixture: &Fixture) -> u64 {
+        let mut cart = Cart::new("beta");
+        cart.add("SHIRT-S", 1);
+        cart.add("HAT", 1);
+        let id = fixture.orders.checkout(&principal("beta"), &cart).unwrap()[0];
+        let orders = &fixture.orders;
+        orders
+            .pay(&principal("beta"), id, fixture.buyer, fixture.seller)
+            .unwrap();
+        orders.pack(&principal("alpha"), id).unwrap();
+        orders.ship(&principal("alpha"), id).unwrap();
+        orders.deliver(&principal("alpha"), id).unwrap();
+        id
+    }
+
+    // Khai báo một hàm để kiểm tra việc xác thực người mua và kiểm duyệt
+    #[test]
+    fn test_write() {
+        let fixture = fixture();
+        let reviews = Reviews::new(fixture.catalog.clone(), fixture.orders.clone());
+        let shirt = fixture.catalog.sku("SHIRT-S").unwrap().0.id;
+        let mug = fixture.catalog.sku("MUG").unwrap().0.id;
+
+        // Đơn hàng chưa giao không được đánh giá
+        let mut cart = Cart::new("beta");
+        cart.add("SHIRT-S", 1);
+        let open = fixture.orders.checkout(&principal("beta"), &cart).unwrap()[0];
+        assert!(reviews
+            .write(&principal("beta"), open, shirt, 5, "Great")
+            .is_err());
+
+        // Chỉ người mua được đánh giá sản phẩm có trong đơn hàng
+        let order = delivered(&fixture);
+        assert!(reviews
+            .write(&principal("gamma"), order, shirt, 5, "Great")
+            .is_err());
+        let staff = Principal::new(1, "staff", "beta", Role::Staff, u64::MAX);
+        assert!(reviews.write(&staff, order, shirt, 5, "Great").is_err());
+        assert!(reviews
+            .write(&principal("beta"), order, mug, 5, "Great")
+            .is_err());
+        assert!(reviews
+            .write(&principal("beta"), order, shirt, 6, "Great")
+            .is_err());
+        let id = reviews
+            .write(&principal("beta"), order, shirt, 5, "Great")
+            .unwrap();
+        assert!(reviews
+            .write(&principal("beta"), order, shirt, 4, "Again")
+            .is_err());
+
+        // Đánh giá chờ kiểm duyệt chưa được tính vào điểm tổng hợp
+        assert_eq!(reviews.queue().len(), 1);
+        assert_eq!(reviews.rating(shirt).average(), None);
+
+        // Chỉ người dùng của cửa hàng bán sản phẩm mới được kiểm duyệt
+        for by in ["beta", "gamma"] {
+            assert_eq!(
+                reviews.approve(&principal(by), id).unwrap_err().kind(),
+                io::ErrorKind::PermissionDenied
+            );
+        }
+        assert!(reviews.reject(&principal("beta"), id, "Spam").is_err());
+        reviews.approve(&principal("alpha"), id).unwrap();
+        assert_eq!(reviews.queue().len(), 0);
+        assert_eq!(reviews.list(shirt, 0, 0).items[0].id, id);
+        assert!(reviews.reject(&principal("alpha"), id, "Spam").is_err());
+    }
+
+    // Khai báo một hàm để kiểm tra điểm tổng hợp khi sửa và xoá đánh giá
+    #[test]
+    fn test_rating() {
+        let fixture = fixture();
+        let reviews = Reviews::new(fixture.catalog.clone(), fixture.orders.clone());
+        let shirt = fixture.catalog.sku("SHIRT-S").unwrap().0.id;
+        let hat = fixture.catalog.sku("HAT").unwrap().0.id;
+        let first = delivered(&fixture);
+        let second = delivered(&fixture);
+
+        // Hai đánh giá được duyệt, một bị từ chối
+        let a = reviews
+            .write(&principal("beta"), first, shirt, 5, "Soft")
+            .unwrap();
+        let b = reviews
+            .write(&principal("beta"), second, shirt, 2, "Shrank")
+            .unwrap();
+        let c = reviews
+            .write(&principal("beta"), first, hat, 1, "Spam")
+            .unwrap();
+        let owner = principal("alpha");
+        reviews.approve(&owner, a).unwrap();
+        reviews.approve(&owner, b).unwrap();
+        reviews.reject(&owner, c, "Off topic").unwrap();
+        assert_eq!(reviews.rating(shirt).average(), Some(3.5));
+        assert_eq!(reviews.rating(hat).count, 0);
+
+        // Sửa đánh giá đưa nó về hàng đợi và bỏ khỏi điểm tổng hợp cho tới khi duyệt lại
+        reviews
+            .edit(&principal("beta"), b, 4, "Fine after all")
+            .unwrap();
+        assert!(reviews.edit(&principal("gamma"), b, 1, "Hijack").is_err());
+        assert_eq!(reviews.rating(shirt).average(), Some(5.0));
+        reviews.approve(&owner, b).unwrap();
+        let rating = reviews.rating(shirt);
+        assert_eq!(rating.average(), Some(4.5));
+        assert_eq!(rating.stars, [0, 0, 0, 1, 1]);
+
+        // Chỉ người viết được xoá; xoá đánh giá cập nhật điểm tổng hợp
+        let mut storage = create_temp_storage("reviews", Access::Private).unwrap();
+        assert!(reviews
+            .delete(&principal("gamma"), a, &mut storage)
+            .is_err());
+        reviews.delete(&principal("beta"), a, &mut storage).unwrap();
+        assert_eq!(reviews.rating(shirt).average(), Some(4.0));
+        assert_eq!(reviews.rating(shirt).count, 1);
+        fs::remove_dir_all(storage.path()).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra việc đính kèm hình ảnh
+    #[test]
+    fn test_photo() {
+        let fixture = fixture();
+        let reviews = Reviews::new(fixture.catalog.clone(), fixture.orders.clone());
+        let shirt = fixture.catalog.sku("SHIRT-S").unwrap().0.id;
+        let order = delivered(&fixture);
+        let id = reviews
+            .write(&principal("beta"), order, shirt, 4, "Nice")
+            .unwrap();
+
+        // Tạo hình ảnh nguồn và đối tượng lưu trữ của cửa hàng
+        let dir = tempdir().unwrap();
+        let source = dir.join("shirt.jpg");
+        fs::write(&source, b"\xff\xd8\xff").unwrap();
+        let mut storage = create_temp_storage("reviews", Access::Private).unwrap();
+
+        // Hình ảnh riêng tư cho tới khi được kiểm duyệt riêng, kể cả khi đánh giá đã được duyệt
+        let name = reviews
+            .photo(&principal("beta"), id, &mut storage, &source)
+            .unwrap();
+        let spam = reviews
+            .photo(&principal("beta"), id, &mut storage, &source)
+            .unwrap();
+        assert_eq!(name, "review-1-1.jpg");
+        assert_eq!(storage.file_access(&name).unwrap(), Access::Private);
+        assert!(reviews
+            .photo(&principal("gamma"), id, &mut storage, &source)
+            .is_err());
+        let owner = principal("alpha");
+        assert!(reviews
+            .moderate(&owner, id, &name, true, &mut storage)
+            .is_err());
+        reviews.approve(&owner, id).unwrap();
+        assert_eq!(storage.file_access(&name).unwrap(), Access::Private);
+
+        // Người dùng của cửa hàng công khai một hình ảnh và xoá hình ảnh còn lại
+        assert!(reviews
+            .moderate(&principal("beta"), id, &name, true, &mut storage)
+            .is_err());
+        reviews
+            .moderate(&owner, id, &name, true, &mut storage)
+            .unwrap();
+        reviews
+            .moderate(&owner, id, &spam, false, &mut storage)
+            .unwrap();
+        assert_eq!(storage.file_access(&name).unwrap(), Access::Public);
+        assert!(storage.view(&spam).is_err());
+        let review = reviews.get(id).unwrap();
+        assert_eq!(review.photos, review.shown);
+        assert_eq!(review.shown, [name]);
+
+        // Tải lên sau khi xoá một hình ảnh không dùng lại tên đã có
+        let next = reviews
+            .photo(&principal("beta"), id, &mut storage, &source)
+            .unwrap();
+        assert_eq!(next, "review-1-3.jpg");
+
+        // Xoá đánh giá thì xoá cả các hình ảnh của nó
+        reviews
+            .delete(&principal("beta"), id, &mut storage)
+            .unwrap();
+        assert!(storage.list().unwrap().is_empty());
+
+        // Xoá các thư mục tạm thời
+        fs::remove_dir_all(storage.path()).unwrap();
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,590 @@
+// Khai báo các thư viện cần thiết
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use super::Catalog;
+use crate::auth::Principal;
+use crate::clock;
+use crate::event::{Bus, Event};
+
+// Thời gian giữ hàng mặc định khi đặt hàng (giây)
+pub const HOLD: u64 = 15 * 60;
+
+// Khai báo một struct để đại diện cho mức tồn kho của một mã SKU trong một kho
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Level {
+    pub hand: u32,      // Số lượng thực có
+    pub held: u32,      // Số lượng đang được giữ
+    pub threshold: u32, // Ngưỡng cảnh báo sắp hết hàng (0 là không cảnh báo)
+}
+
+// Khai báo các phương thức cho struct Level
+impl Level {
+    // Phương thức available trả về số lượng có thể bán (thực có trừ đang giữ)
+    pub fn available(&self) -> u32 {
+        self.hand - self.held
+    }
+}
+
+// Khai báo một struct để đại diện cho một lượt giữ hàng (có thể lấy hàng từ nhiều kho)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hold {
+    pub id: u64,                   // Mã của lượt giữ hàng
+    pub parts: Vec<(String, u32)>, // Các kho giữ hàng và số lượng giữ ở mỗi kho
+    pub sku: String,               // Mã SKU được giữ
+    pub quantity: u32,             // Tổng số lượng được giữ
+    pub expires: Option<u64>,      // Thời điểm hết hạn (None nếu đã xác nhận)
+}
+
+// Khai báo một enum để đại diện cho các loại dịch chuyển hàng hóa
+#[derive(Debug, Clone, PartialEq)]
+pub enum Kind {
+    Receive,          // Nhập hàng vào kho
+    Transfer(String), // Chuyển hàng sang kho khác
+    Adjust(String),   // Điều chỉnh sau kiểm kê (kèm lý do)
+    Ship(u64),        // Xuất kho theo lượt giữ hàng
+    Return(u64),      // Nhập lại hàng trả theo yêu cầu trả hàng
+}
+
+// Khai báo một struct để đại diện cho một dòng trong nhật ký dịch chuyển hàng hóa
+#[derive(Debug, Clone, PartialEq)]
+pub struct Movement {
+    pub id: u64,           // Mã của dịch chuyển
+    pub time: u64,         // Thời điểm dịch chuyển
+    pub warehouse: String, // Kho bị tác động
+    pub sku: String,       // Mã SKU
+    pub delta: i64,        // Thay đổi số lượng thực có
+    pub kind: Kind,        // Loại dịch chuyển
+}
+
+// Khai báo một struct để chứa dữ liệu tồn kho (được bảo vệ bởi khóa)
+#[derive(Debug, Default)]
+struct Levels {
+    levels: BTreeMap<(String, String), Level>, // Mức tồn kho theo (kho, mã SKU)
+    holds: HashMap<u64, Hold>,                 // Các lượt giữ hàng theo mã
+    movements: Vec<Movement>,                  // Nhật ký dịch chuyển hàng hóa
+    next: u64,                                 // Mã tiếp theo cho lượt giữ và dịch chuyển
+}
+
+// Khai báo một struct để đại diện cho tồn kho nhiều kho hàng (an toàn khi nhiều luồng cùng thanh toán)
+#[derive(Debug)]
+pub struct Stock {
+    catalog: Arc<Catalog>, // Danh mục sản phẩm (xác định cửa hàng sở hữu mã SKU)
+    levels: Mutex<Levels>, // Dữ liệu tồn kho
+    bus: Arc<Bus>,         // Kênh sự kiện để cảnh báo sắp hết hàng
+}
+
+// Khai báo các phương thức cho struct Stock
+impl Stock {
+    // Phương thức new để tạo một tồn kho rỗng cho các mã SKU trong danh mục
+    pub fn new(catalog: Arc<Catalog>, bus: Arc<Bus>) -> Stock {
+        Stock {
+            catalog,
+            levels: Mutex::new(Levels::default()),
+            bus,
+        }
+    }
+
+    // Phương thức level trả về mức tồn kho của một mã SKU trong một kho
+    pub fn level(&self, warehouse: &str, sku: &str) -> Level {
+        let levels = self.levels.lock().unwrap();
+        levels
+            .levels
+            .get(&key(warehouse, sku))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    // Phương thức available trả về tổng số lượng có thể bán của một mã SKU trong mọi kho
+    pub fn available(&self, sku: &str) -> u32 {
+        let levels = self.levels.lock().unwrap();
+        levels
+            .levels
+            .iter()
+            .filter(|((_, s), _)| s == sku)
+            .map(|(_, level)| level.available())
+            .sum()
+    }
+
+    // Phương thức threshold để người dùng của cửa hàng đặt ngưỡng cảnh báo sắp hết hàng cho một mã SKU trong
+    // một kho
+    pub fn threshold(
+        &self,
+        principal: &Principal,
+        warehouse: &str,
+        sku: &str,
+        threshold: u32,
+    ) -> io::Result<()> {
+        self.manage(principal, sku)?;
+        let mut levels = self.levels.lock().unwrap();
+        levels
+            .levels
+            .entry(key(warehouse, sku))
+            .or_default()
+            .threshold = threshold;
+        Ok(())
+    }
+
+    // Phương thức receive để người dùng của cửa hàng nhập thêm hàng vào một kho
+    pub fn receive(
+        &self,
+        principal: &Principal,
+        warehouse: &str,
+        sku: &str,
+        quantity: u32,
+    ) -> io::Result<()> {
+        self.manage(principal, sku)?;
+        let mut levels = self.levels.lock().unwrap();
+        self.apply(&mut levels, warehouse, sku, quantity as i64, Kind::Receive)
+    }
+
+    // Phương thức restock để người dùng của cửa hàng nhập lại hàng do khách trả theo yêu cầu trả hàng
+    pub fn restock(
+        &self,
+        principal: &Principal,
+        warehouse: &str,
+        sku: &str,
+        quantity: u32,
+        rma: u64,
+    ) -> io::Result<()> {
+        self.manage(principal, sku)?;
+        let mut levels = self.levels.lock().unwrap();
+        self.apply(
+            &mut levels,
+            warehouse,
+            sku,
+            quantity as i64,
+            Kind::Return(rma),
+        )
+    }
+
+    // Phương thức transfer để người dùng của cửa hàng chuyển hàng có thể bán từ kho này sang kho khác
+    pub fn transfer(
+        &self,
+        principal: &Principal,
+        from: &str,
+        to: &str,
+        sku: &str,
+        quantity: u32,
+    ) -> io::Result<()> {
+        self.manage(principal, sku)?;
+        let mut levels = self.levels.lock().unwrap();
+
+        // Kho nguồn phải có đủ hàng có thể bán
+        let available = levels
+            .levels
+            .get(&key(from, sku))
+            .map_or(0, Level::available);
+        if from == to || available < quantity {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Cannot move {} {} from {} to {}", quantity, sku, from, to),
+            ));
+        }
+
+        // Ghi hai dịch chuyển: xuất khỏi kho nguồn và nhập vào kho đích
+        let kind = Kind::Transfer(to.to_string());
+        self.apply(&mut levels, from, sku, -(quantity as i64), kind)?;
+        let kind = Kind::Transfer(from.to_string());
+        self.apply(&mut levels, to, sku, quantity as i64, kind)
+    }
+
+    // Phương thức adjust để người dùng của cửa hàng điều chỉnh số lượng thực có sau kiểm kê
+    pub fn adjust(
+        &self,
+        principal: &Principal,
+        warehouse: &str,
+        sku: &str,
+        delta: i64,
+        reason: &str,
+    ) -> io::Result<()> {
+        self.manage(principal, sku)?;
+        let mut levels = self.levels.lock().unwrap();
+        let kind = Kind::Adjust(reason.to_string());
+        self.apply(&mut levels, warehouse, sku, delta, kind)
+    }
+
+    // Phương thức reserve để giữ hàng trong thời gian mặc định và trả về mã lượt giữ
+    pub fn reserve(&self, sku: &str, quantity: u32) -> io::Result<u64> {
+        self.hold(sku, quantity, clock::now(), HOLD)
+    }
+
+    // Phương thức hold để giữ hàng tại thời điểm now trong ttl giây: lấy từ kho còn nhiều hàng nhất, và khi không
+    // kho nào đủ thì lấy lần lượt từ các kho còn nhiều hàng nhất cho tới khi đủ (đơn hàng vẫn được giao dù hàng
+    // nằm ở nhiều kho)
+    pub fn hold(&self, sku: &str, quantity: u32, now: u64, ttl: u64) -> io::Result<u64> {
+        let mut levels = self.levels.lock().unwrap();
+
+        // Trả lại các lượt giữ đã hết hạn trước khi giữ hàng mới
+        purge(&mut levels, now);
+
+        // Sắp xếp các kho còn hàng theo số lượng có thể bán giảm dần (cùng số lượng thì theo tên kho)
+        let mut warehouses: Vec<(String, u32)> = levels
+            .levels
+            .iter()
+            .filter(|((_, s), level)| s == sku && level.available() > 0)
+            .map(|((w, _), level)| (w.clone(), level.available()))
+            .collect();
+        warehouses.sort_by(|(a, x), (b, y)| y.cmp(x).then_with(|| a.cmp(b)));
+        let total: u64 = warehouses.iter().map(|(_, n)| *n as u64).sum();
+        if quantity == 0 || total < quantity as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Insufficient stock for {} x{}", sku, quantity),
+            ));
+        }
+
+        // Chia số lượng cần giữ cho các kho
+        let mut parts = Vec::new();
+        let mut left = quantity;
+        for (warehouse, available) in warehouses {
+            let take = available.min(left);
+            parts.push((warehouse, take));
+            left -= take;
+            if left == 0 {
+                break;
+            }
+        }
+
+        // Ghi lượt giữ hàng và cảnh báo nếu số lượng có thể bán của một kho xuống dưới ngưỡng
+        levels.next += 1;
+        let id = levels.next;
+        for (warehouse, take) in &parts {
+            let mut before = 0;
+            if let Some(level) = levels.levels.get_mut(&key(warehouse, sku)) {
+                before = level.available();
+                level.held += take;
+            }
+            self.alert(&levels, warehouse, sku, before);
+        }
+        levels.holds.insert(
+            id,
+            Hold {
+                id,
+                parts,
+                sku: sku.to_string(),
+                quantity,
+                expires: Some(now + ttl),
+            },
+        );
+        Ok(id)
+    }
+
+    // Phương thức confirm để xác nhận lượt giữ hàng tại thời điểm now (không còn hết hạn), ví dụ khi đơn hàng
+    // đã thanh toán, và trả về hạn cũ của lượt giữ (dùng cho unconfirm); lượt giữ đã quá hạn, bị expire hoặc
+    // bị thu hồi thì không thể xác nhận
+    pub fn confirm(&self, id: u64, now: u64) -> io::Result<Option<u64>> {
+        let mut levels = self.levels.lock().unwrap();
+        let hold = levels.holds.get_mut(&id).ok_or_else(|| missing(id))?;
+        if hold.expires.is_some_and(|expires| expires <= now) {
+            return Err(missing(id));
+        }
+        Ok(hold.expires.take())
+    }
+
+    // Phương thức unconfirm để hoàn tác một lần xác nhận, đặt lại hạn cũ của lượt giữ hàng (ví dụ khi thanh
+    // toán thất bại sau khi đã xác nhận)
+    pub fn unconfirm(&self, id: u64, expires: Option<u64>) -> io::Result<()> {
+        let mut levels = self.levels.lock().unwrap();
+        let hold = levels.holds.get_mut(&id).ok_or_else(|| missing(id))?;
+        hold.expires = expires;
+        Ok(())
+    }
+
+    // Phương thức release để huỷ một lượt giữ hàng (hàng trở lại có thể bán)
+    pub fn release(&self, id: u64) -> io::Result<Hold> {
+        let mut levels = self.levels.lock().unwrap();
+        unhold(&mut levels, id)
+    }
+
+    // Phương thức commit để xuất kho số hàng đã giữ (mỗi kho một dịch chuyển)
+    pub fn commit(&self, id: u64) -> io::Result<Hold> {
+        let mut levels = self.levels.lock().unwrap();
+        let hold = unhold(&mut levels, id)?;
+        for (warehouse, quantity) in &hold.parts {
+            let delta = -(*quantity as i64);
+            self.apply(&mut levels, warehouse, &hold.sku, delta, Kind::Ship(id))?;
+        }
+        Ok(hold)
+    }
+
+    // Phương thức expire để trả lại các lượt giữ đã hết hạn tại thời điểm now
+    pub fn expire(&self, now: u64) -> Vec<Hold> {
+        let mut levels = self.levels.lock().unwrap();
+        purge(&mut levels, now)
+    }
+
+    // Phương thức movements trả về nhật ký dịch chuyển của một mã SKU (theo thứ tự thời gian)
+    pub fn movements(&self, sku: &str) -> Vec<Movement> {
+        let levels = self.levels.lock().unwrap();
+        levels
+            .movements
+            .iter()
+            .filter(|movement| movement.sku == sku)
+            .cloned()
+            .collect()
+    }
+
+    // Phương thức manage kiểm tra người dùng được quản lý cửa hàng bán mã SKU
+    fn manage(&self, principal: &Principal, sku: &str) -> io::Result<()> {
+        let shop = self.catalog.sku(sku)?.0.shop;
+        self.catalog.manage(principal, shop)
+    }
+
+    // Phương thức apply để thay đổi số lượng thực có và ghi nhật ký (gọi khi đang giữ khóa)
+    fn apply(
+        &self,
+        levels: &mut Levels,
+        warehouse: &str,
+        sku: &str,
+        delta: i64,
+        kind: Kind,
+    ) -> io::Result<()> {
+        // Số lượng thực có mới không được âm hoặc nhỏ hơn số lượng đang giữ
+        // (riêng xuất kho theo lượt giữ thì lượt giữ đã được trả lại trước đó)
+        let level = levels.levels.entry(key(warehouse, sku)).or_default();
+        let before = level.available();
+        let hand = level.hand as i64 + delta;
+        if hand < level.held as i64 || hand > u32::MAX as i64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid quantity for {} in {}: {}", sku, warehouse, hand),
+            ));
+        }
+        level.hand = hand as u32;
+
+        // Ghi dịch chuyển vào nhật ký
+        levels.next += 1;
+        let id = levels.next;
+        levels.movements.push(Movement {
+            id,
+            time: clock::now(),
+            warehouse: warehouse.to_string(),
+            sku: sku.to_string(),
+            delta,
+            kind,
+        });
+
+        // Cảnh báo nếu số lượng có thể bán xuống dưới ngưỡng
+        self.alert(levels, warehouse, sku, before);
+        Ok(())
+    }
+
+    // Phương thức alert công bố sự kiện khi số lượng có thể bán vừa xuống dưới ngưỡng (before là số lượng trước đó)
+    fn alert(&self, levels: &Levels, warehouse: &str, sku: &str, before: u32) {
+        if let Some(level) = levels.levels.get(&key(warehouse, sku)) {
+            let available = level.available();
+            if available < level.threshold && before >= level.threshold {
+                self.bus.publish(Event::LowStock {
+                    warehouse: warehouse.to_string(),
+                    sku: sku.to_string(),
+                    available,
+                });
+            }
+        }
+    }
+}
+
+// Hàm key tạo khóa (kho, mã SKU)
+fn key(warehouse: &str, sku: &str) -> (String, String) {
+    (warehouse.to_string(), sku.to_string())
+}
+
+// Hàm unhold xoá một lượt giữ hàng và trả lại số lượng đang giữ
+fn unhold(levels: &mut Levels, id: u64) -> io::Result<Hold> {
+    let hold = levels.holds.remove(&id).ok_or_else(|| missing(id))?;
+    for (warehouse, quantity) in &hold.parts {
+        if let Some(level) = levels.levels.get_mut(&key(warehouse, &hold.sku)) {
+            level.held -= quantity;
+        }
+    }
+    Ok(hold)
+}
+
+// Hàm purge trả lại các lượt giữ đã hết hạn tại thời điểm now
+fn purge(levels: &mut Levels, now: u64) -> Vec<Hold> {
+    let mut expired: Vec<u64> = levels
+        .holds
+        .values()
+        .filter(|hold| hold.expires.is_some_and(|expires| expires <= now))
+        .map(|hold| hold.id)
+        .collect();
+    expired.sort_unstable();
+    expired
+        .into_iter()
+        .filter_map(|id| unhold(levels, id).ok())
+        .collect()
+}
+
+// Hàm missing tạo lỗi io cho lượt giữ hàng không tồn tại (hoặc đã hết hạn)
+fn missing(id: u64) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("Hold {} not found or expired", id),
+    )
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::auth::tests::principal;
+    use crate::shop::tests::catalog;
+    use std::thread;
+
+    // Khai báo một hàm để tạo tồn kho rỗng cho danh mục mẫu (cửa hàng của alpha)
+    fn inventory(bus: Arc<Bus>) -> Stock {
+        Stock::new(Arc::new(catalog().0), bus)
+    }
+
+    // Khai báo một hàm để kiểm tra việc giữ, huỷ và xuất kho
+    #[test]
+    fn test_reserve() {
+        let owner = principal("alpha");
+        let stock = inventory(Arc::new(Bus::new()));
+        stock.receive(&owner, "north", "MUG", 5).unwrap();
+        stock.receive(&owner, "south", "MUG", 2).unwrap();
+
+        // Giữ hàng ở kho còn nhiều hàng nhất
+        let hold = stock.reserve("MUG", 3).unwrap();
+        assert_eq!(stock.level("north", "MUG").held, 3);
+        assert_eq!(stock.available("MUG"), 4);
+
+        // Không kho nào đủ thì lượt giữ được chia cho nhiều kho
+        let split = stock.reserve("MUG", 3).unwrap();
+        assert_eq!(stock.level("north", "MUG").held, 5);
+        assert_eq!(stock.level("south", "MUG").held, 1);
+        assert!(stock.reserve("MUG", 2).is_err());
+
+        // Huỷ lượt giữ trả lại hàng, xuất kho làm giảm số lượng thực có ở từng kho
+        stock.release(hold).unwrap();
+        assert_eq!(stock.available("MUG"), 4);
+        let committed = stock.commit(split).unwrap();
+        assert_eq!(
+            committed.parts,
+            [("north".to_string(), 2), ("south".to_string(), 1)]
+        );
+        assert_eq!(stock.level("north", "MUG").hand, 3);
+        assert_eq!(stock.level("south", "MUG").hand, 1);
+        assert!(stock.commit(split).is_err());
+    }
+
+    // Khai báo một hàm để kiểm tra chỉ người quản lý cửa hàng mới được thay đổi tồn kho
+    #[test]
+    fn test_manage() {
+        let owner = principal("alpha");
+        let stock = inventory(Arc::new(Bus::new()));
+        stock.receive(&owner, "north", "MUG", 5).unwrap();
+
+        // Người dùng của doanh nghiệp khác bị từ chối, mã SKU không tồn tại thì báo không tìm thấy
+        let other = principal("beta");
+        assert!(stock.receive(&other, "north", "MUG", 5).is_err());
+        assert!(stock.adjust(&other, "north", "MUG", -5, "theft").is_err());
+        assert!(stock.transfer(&other, "north", "south", "MUG", 1).is_err());
+        assert!(stock.threshold(&other, "north", "MUG", 9).is_err());
+        assert_eq!(
+            stock
+                .receive(&owner, "north", "NOPE", 1)
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::NotFound
+        );
+        assert_eq!(stock.level("north", "MUG").hand, 5);
+    }
+
+    // Khai báo một hàm để kiểm tra việc hết hạn giữ hàng
+    #[test]
+    fn test_expire() {
+        let owner = principal("alpha");
+        let stock = inventory(Arc::new(Bus::new()));
+        stock.receive(&owner, "north", "MUG", 5).unwrap();
+
+        // Hai lượt giữ, một lượt được xác nhận
+        let first = stock.hold("MUG", 2, 100, 60).unwrap();
+        let second = stock.hold("MUG", 2, 100, 60).unwrap();
+        stock.confirm(second, 100).unwrap();
+
+        // Sau khi hết hạn chỉ lượt chưa xác nhận bị trả lại
+        assert!(stock.expire(159).is_empty());
+        let expired = stock.expire(160);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].id, first);
+        assert_eq!(stock.available("MUG"), 3);
+        assert!(stock.release(first).is_err());
+        stock.commit(second).unwrap();
+
+        // Lượt giữ đã quá hạn nhưng chưa bị expire thì cũng không thể xác nhận
+        let third = stock.hold("MUG", 1, 200, 60).unwrap();
+        assert_eq!(
+            stock.confirm(third, 260).unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+        assert_eq!(stock.expire(260).len(), 1);
+    }
+
+    // Khai báo một hàm để kiểm tra chuyển kho, điều chỉnh, cảnh báo và nhật ký
+    #[test]
+    fn test_movements() {
+        let bus = Arc::new(Bus::new());
+        let events = bus.subscribe();
+        let owner = principal("alpha");
+        let stock = inventory(bus);
+        stock.threshold(&owner, "north", "HAT", 3).unwrap();
+        stock.receive(&owner, "north", "HAT", 6).unwrap();
+
+        // Chuyển kho không được vượt quá số lượng có thể bán
+        assert!(stock.transfer(&owner, "north", "south", "HAT", 7).is_err());
+        stock.transfer(&owner, "north", "south", "HAT", 2).unwrap();
+        assert_eq!(stock.level("south", "HAT").hand, 2);
+
+        // Điều chỉnh làm số lượng xuống dưới ngưỡng thì phát cảnh báo đúng một lần
+        stock.adjust(&owner, "north", "HAT", -2, "damaged").unwrap();
+        stock.adjust(&owner, "north", "HAT", -1, "lost").unwrap();
+        assert!(stock.adjust(&owner, "north", "HAT", -2, "count").is_err());
+        let alerts: Vec<Event> = events.try_iter().collect();
+        assert_eq!(
+            alerts,
+            [Event::LowStock {
+                warehouse: "north".to_string(),
+                sku: "HAT".to_string(),
+                available: 2
+            }]
+        );
+
+        // Mọi dịch chuyển đều được ghi nhật ký
+        let kinds: Vec<Kind> = stock.movements("HAT").into_iter().map(|m| m.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                Kind::Receive,
+                Kind::Transfer("south".to_string()),
+                Kind::Transfer("north".to_string()),
+                Kind::Adjust("damaged".to_string()),
+                Kind::Adjust("lost".to_string()),
+            ]
+        );
+    }
+
+    // Khai báo một hàm để kiểm tra nhiều luồng cùng giữ hàng
+    #[test]
+    fn test_concurrent() {
+        let owner = principal("alpha");
+        let stock = Arc::new(inventory(Arc::new(Bus::new())));
+        stock.receive(&owner, "north", "MUG", 25).unwrap();
+
+        // Mười luồng, mỗi luồng giữ năm lần một sản phẩm
+        let handles: Vec<_> = (0..10)
+            .map(|_| {
+                let stock = stock.clone();
+                thread::spawn(move || (0..5).filter(|_| stock.reserve("MUG", 1).is_ok()).count())
+            })
+            .collect();
+        let total: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+
+        // Chỉ đúng số lượng trong kho được giữ
+        assert_eq!(total, 25);
+        assert_eq!(stock.available("MUG"), 0);
+    }
+}
@@ -0,0 +1,519 @@
+// Khai báo các module con của mạng xã hội
+pub mod envelope;
+pub mod feed;
+pub mod message;
+
+// Khai báo các thư viện cần thiết
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use crate::auth::role::Action;
+use crate::auth::{self, Principal};
+use crate::clock;
+use crate::event::{Bus, Event};
+use crate::peer::connection::Connections;
+use crate::storage::{Access, Grants, Storage};
+
+// Khai báo một enum để đại diện cho các loại cảm xúc với bài viết
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Reaction {
+    Like,       // Thích
+    Celebrate,  // Chúc mừng
+    Support,    // Ủng hộ
+    Insightful, // Hữu ích
+    Curious,    // Quan tâm
+}
+
+// Khai báo một struct để đại diện cho một phiên bản cũ của nội dung
+#[derive(Debug, Clone, PartialEq)]
+pub struct Revision {
+    pub text: String, // Nội dung trước khi sửa
+    pub time: u64,    // Thời điểm nội dung được thay thế
+}
+
+// Khai báo một struct để đại diện cho một bài viết của doanh nghiệp
+#[derive(Debug, Clone, PartialEq)]
+pub struct Post {
+    pub id: u64,                               // Mã của bài viết
+    pub author: String,                        // Doanh nghiệp đăng bài
+    pub text: String,                          // Nội dung hiện tại
+    pub media: Vec<String>,                    // Các tập tin đính kèm (trong lưu trữ của tác giả)
+    pub access: Access,                        // Ai được xem bài viết
+    pub time: u64,                             // Thời điểm đăng
+    pub history: Vec<Revision>,                // Các phiên bản trước (cũ nhất trước)
+    pub reactions: BTreeMap<String, Reaction>, // Cảm xúc của từng doanh nghiệp
+}
+
+// Khai báo một struct để đại diện cho một bình luận (có thể trả lời một bình luận khác)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    pub id: u64,                // Mã của bình luận
+    pub post: u64,              // Mã của bài viết
+    pub parent: Option<u64>,    // Bình luận được trả lời (None nếu bình luận trực tiếp bài viết)
+    pub author: String,         // Doanh nghiệp bình luận
+    pub text: String,           // Nội dung hiện tại
+    pub time: u64,              // Thời điểm bình luận
+    pub history: Vec<Revision>, // Các phiên bản trước
+}
+
+// Khai báo một struct để chứa bài viết và bình luận (được bảo vệ bởi khóa)
+#[derive(Debug, Default)]
+struct Data {
+    posts: BTreeMap<u64, Post>,       // Các bài viết theo mã
+    comments: BTreeMap<u64, Comment>, // Các bình luận theo mã
+    next: u64,                        // Mã tiếp theo cho bài viết và bình luận
+}
+
+// Khai báo một struct để quản lý mạng xã hội doanh nghiệp (Shared nghĩa là các kết nối của tác giả)
+#[derive(Debug)]
+pub struct Social {
+    connections: Arc<Connections>, // Các kết nối giữa doanh nghiệp
+    bus: Arc<Bus>,                 // Kênh sự kiện
+    data: RwLock<Data>,            // Bài viết và bình luận
+}
+
+// Khai báo các phương thức cho struct Social
+impl Social {
+    // Phương thức new để tạo một mạng xã hội rỗng
+    pub fn new(connections: Arc<Connections>, bus: Arc<Bus>) -> Social {
+        Social {
+            connections,
+            bus,
+            data: RwLock::new(Data::default()),
+        }
+    }
+
+    // Phương thức post để người dùng đăng bài viết mới cho doanh nghiệp của mình
+    pub fn post(&self, principal: &Principal, text: &str, access: Access) -> io::Result<u64> {
+        let author = author(principal)?;
+        let mut data = self.data.write().unwrap();
+        data.next += 1;
+        let id = data.next;
+        data.posts.insert(
+            id,
+            Post {
+                id,
+                author: author.to_string(),
+                text: text.to_string(),
+                media: Vec::new(),
+                access,
+                time: clock::now(),
+                history: Vec::new(),
+                reactions: BTreeMap::new(),
+            },
+        );
+        self.bus.publish(Event::PostCreated {
+            post: id,
+            author: author.to_string(),
+        });
+        Ok(id)
+    }
+
+    // Phương thức get để xem một bài viết (nếu người xem được phép)
+    pub fn get(&self, viewer: &str, id: u64) -> io::Result<Post> {
+        let data = self.data.read().unwrap();
+        self.visible(&data, viewer, id).cloned()
+    }
+
+    // Phương thức attach để người dùng của tác giả đính kèm tập tin vào bài viết, lưu trong đối tượng lưu trữ của
+    // tác giả với cùng quyền truy cập như bài viết
+    pub fn attach(
+        &self,
+        principal: &Principal,
+        id: u64,
+        storage: &mut Storage,
+        source: &Path,
+    ) -> io::Result<String> {
+        let author = author(principal)?;
+        let mut data = self.data.write().unwrap();
+        let post = own(&mut data, id, author)?;
+
+        // Đặt tên tập tin theo mã bài viết và giữ nguyên phần mở rộng
+        let extension = source
+            .extension()
+            .map(|ext| format!(".{}", ext.to_string_lossy()))
+            .unwrap_or_default();
+        let name = format!("post-{}-{}{}", id, post.media.len() + 1, extension);
+        storage.put_with_access(&name, &fs::read(source)?, post.access)?;
+        post.media.push(name.clone());
+
+        self.bus.publish(Event::PostEdited { post: id });
+        Ok(name)
+    }
+
+    // Phương thức edit để người dùng của tác giả sửa nội dung bài viết (phiên bản cũ được lưu vào lịch sử)
+    pub fn edit(&self, principal: &Principal, id: u64, text: &str) -> io::Result<()> {
+        let author = author(principal)?;
+        let mut data = self.data.write().unwrap();
+        let post = own(&mut data, id, author)?;
+        let old = std::mem::replace(&mut post.text, text.to_string());
+        post.history.push(Revision {
+            text: old,
+            time: clock::now(),
+        });
+        self.bus.publish(Event::PostEdited { post: id });
+        Ok(())
+    }
+
+    // Phương thức restrict để người dùng của tác giả đổi quyền xem bài viết và các tập tin đính kèm
+    pub fn restrict(
+        &self,
+        principal: &Principal,
+        id: u64,
+        access: Access,
+        storage: &Storage,
+    ) -> io::Result<()> {
+        let author = author(principal)?;
+        let mut data = self.data.write().unwrap();
+        let post = own(&mut data, id, author)?;
+        for name in &post.media {
+            storage.set_file_access(name, access)?;
+        }
+        post.access = access;
+        self.bus.publish(Event::PostEdited { post: id });
+        Ok(())
+    }
+
+    // Phương thức delete để người dùng của tác giả xoá bài viết cùng bình luận và tập tin đính kèm
+    pub fn delete(&self, principal: &Principal, id: u64, storage: &mut Storage) -> io::Result<()> {
+        let author = author(principal)?;
+        let mut data = self.data.write().unwrap();
+        let post = own(&mut data, id, author)?.clone();
+        for name in &post.media {
+            storage.delete(name)?;
+        }
+        data.posts.remove(&id);
+        data.comments.retain(|_, comment| comment.post != id);
+        self.bus.publish(Event::PostDeleted { post: id });
+        Ok(())
+    }
+
+    // Phương thức comment để người dùng bình luận bài viết hoặc trả lời một bình luận thay cho doanh nghiệp của mình
+    pub fn comment(
+        &self,
+        principal: &Principal,
+        post: u64,
+        parent: Option<u64>,
+        text: &str,
+    ) -> io::Result<u64> {
+        let viewer = author(principal)?;
+        let mut data = self.data.write().unwrap();
+        self.visible(&data, viewer, post)?;
+
+        // Bình luận được trả lời phải thuộc cùng bài viết
+        if let Some(parent) = parent {
+            if data.comments.get(&parent).map(|c| c.post) != Some(post) {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("Comment {} not found on post {}", parent, post),
+                ));
+            }
+        }
+
+        data.next += 1;
+        let id = data.next;
+        data.comments.insert(
+            id,
+            Comment {
+                id,
+                post,
+                parent,
+                author: viewer.to_string(),
+                text: text.to_string(),
+                time: clock::now(),
+                history: Vec::new(),
+            },
+        );
+        self.bus.publish(Event::CommentAdded {
+            comment: id,
+            post,
+            author: viewer.to_string(),
+        });
+        Ok(id)
+    }
+
+    // Phương thức revise để người dùng của doanh nghiệp bình luận sửa bình luận đó
+    pub fn revise(&self, principal: &Principal, id: u64, text: &str) -> io::Result<()> {
+        let author = author(principal)?;
+        let mut data = self.data.write().unwrap();
+        let comment = data
+            .comments
+            .get_mut(&id)
+            .filter(|comment| comment.author == author)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("Comment {} of {} not found", id, author),
+                )
+            })?;
+        let old = std::mem::replace(&mut comment.text, text.to_string());
+        comment.history.push(Revision {
+            text: old,
+            time: clock::now(),
+        });
+        self.bus.publish(Event::CommentEdited { comment: id });
+        Ok(())
+    }
+
+    // Phương thức thread trả về các bình luận của bài viết theo dạng cây (duyệt theo chiều sâu) kèm độ sâu
+    pub fn thread(&self, viewer: &str, post: u64) -> io::Result<Vec<(Comment, usize)>> {
+        let data = self.data.read().unwrap();
+        self.visible(&data, viewer, post)?;
+
+        // Nhóm bình luận theo bình luận cha (theo thứ tự thời gian vì mã tăng dần)
+        let mut children: BTreeMap<Option<u64>, Vec<&Comment>> = BTreeMap::new();
+        for comment in data.comments.values().filter(|c| c.post == post) {
+            children.entry(comment.parent).or_default().push(comment);
+        }
+
+        // Duyệt cây bằng ngăn xếp
+        let mut thread = Vec::new();
+        let mut stack: Vec<(&Comment, usize)> = children
+            .get(&None)
+            .map(|roots| roots.iter().rev().map(|c| (*c, 0)).collect())
+            .unwrap_or_default();
+        while let Some((comment, depth)) = stack.pop() {
+            thread.push((comment.clone(), depth));
+            if let Some(replies) = children.get(&Some(comment.id)) {
+                stack.extend(replies.iter().rev().map(|c| (*c, depth + 1)));
+            }
+        }
+        Ok(thread)
+    }
+
+    // Phương thức react để người dùng bày tỏ cảm xúc của doanh nghiệp mình với bài viết (None để bỏ cảm xúc)
+    pub fn react(
+        &self,
+        principal: &Principal,
+        post: u64,
+        reaction: Option<Reaction>,
+    ) -> io::Result<()> {
+        let viewer = author(principal)?;
+        let mut data = self.data.write().unwrap();
+        self.visible(&data, viewer, post)?;
+        if let Some(item) = data.posts.get_mut(&post) {
+            match reaction {
+                Some(reaction) => item.reactions.insert(viewer.to_string(), reaction),
+                None => item.reactions.remove(viewer),
+            };
+        }
+        self.bus.publish(Event::Reacted {
+            post,
+            by: viewer.to_string(),
+            reaction,
+        });
+        Ok(())
+    }
+
+    // Phương thức reactions trả về số lượng mỗi loại cảm xúc của bài viết
+    pub fn reactions(&self, viewer: &str, post: u64) -> io::Result<BTreeMap<Reaction, usize>> {
+        let post = self.get(viewer, post)?;
+        let mut counts = BTreeMap::new();
+        for reaction in post.reactions.values() {
+            *counts.entry(*reaction).or_default() += 1;
+        }
+        Ok(counts)
+    }
+
+    // Phương thức timeline trả về các bài viết của một tác giả mà người xem được phép thấy (mới nhất trước)
+    pub fn timeline(&self, viewer: &str, author: &str) -> Vec<Post> {
+        let data = self.data.read().unwrap();
+        data.posts
+            .values()
+            .rev()
+            .filter(|post| post.author == author && self.allowed(post, viewer))
+            .cloned()
+            .collect()
+    }
+
+    // Phương thức allowed kiểm tra người xem có được thấy bài viết hay không
+    pub fn allowed(&self, post: &Post, viewer: &str) -> bool {
+        match post.access {
+            Access::Public => true,
+            Access::Private => post.author == viewer,
+            Access::Shared => {
+                post.author == viewer || self.connections.granted(&post.author, viewer)
+            }
+        }
+    }
+
+    // Phương thức visible trả về bài viết nếu người xem được phép (bài không được phép xem coi như không tồn tại)
+    fn visible<'a>(&self, data: &'a Data, viewer: &str, id: u64) -> io::Result<&'a Post> {
+        data.posts
+            .get(&id)
+            .filter(|post| self.allowed(post, viewer))
+            .ok_or_else(|| missing(id))
+    }
+}
+
+// Hàm author kiểm tra người dùng được đăng nội dung thay cho doanh nghiệp của mình và trả về doanh nghiệp đó
+fn author(principal: &Principal) -> io::Result<&str> {
+    auth::authorize(
+        principal,
+        Action::StorageWrite,
+        &format!("posts of {}", principal.business()),
+    )?;
+    message::business(principal)
+}
+
+// Hàm own trả về bài viết nếu người gọi là tác giả
+fn own<'a>(data: &'a mut Data, id: u64, author: &str) -> io::Result<&'a mut Post> {
+    data.posts
+        .get_mut(&id)
+        .filter(|post| post.author == author)
+        .ok_or_else(|| missing(id))
+}
+
+// Hàm missing tạo lỗi io cho bài viết không tồn tại
+fn missing(id: u64) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("Post {} not found", id))
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+pub(crate) mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::auth::role::Role;
+    use crate::auth::tests::principal;
+    use crate::storage::tests::{create_temp_storage, tempdir};
+
+    // Khai báo một hàm để tạo mạng xã hội mẫu trong đó alpha và beta đã kết nối
+    pub(crate) fn social() -> (Social, Arc<Connections>, Arc<Bus>) {
+        let bus = Arc::new(Bus::new());
+        let connections = Arc::new(Connections::new(bus.clone()));
+        let id = connections.send(&principal("alpha"), "beta", "").unwrap();
+        connections.accept(&principal("beta"), id).unwrap();
+        (
+            Social::new(connections.clone(), bus.clone()),
+            connections,
+            bus,
+        )
+    }
+
+    // Khai báo một hàm để kiểm tra quyền xem, sửa bài viết và sự kiện
+    #[test]
+    fn test_post() {
+        let (social, _, bus) = social();
+        let (alpha, beta, gamma) = (principal("alpha"), principal("beta"), principal("gamma"));
+        let events = bus.subscribe();
+
+        // Bài viết Shared chỉ tác giả và các kết nối thấy
+        let id = social.post(&alpha, "Hiring", Access::Shared).unwrap();
+        assert!(social.get("beta", id).is_ok());
+        assert!(social.get("gamma", id).is_err());
+        assert_eq!(social.timeline("gamma", "alpha").len(), 0);
+
+        // Nhân viên không có quyền ghi thì không được đăng bài thay cho doanh nghiệp
+        let staff = Principal::new(1, "staff", "alpha", Role::Staff, u64::MAX);
+        assert!(social.post(&staff, "Spam", Access::Public).is_err());
+
+        // Chỉ tác giả được sửa; lịch sử giữ phiên bản cũ
+        assert!(social.edit(&beta, id, "Hacked").is_err());
+        social.edit(&alpha, id, "Hiring engineers").unwrap();
+        social.edit(&alpha, id, "Hiring 2 engineers").unwrap();
+        let post = social.get("alpha", id).unwrap();
+        assert_eq!(post.text, "Hiring 2 engineers");
+        let history: Vec<&str> = post.history.iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(history, ["Hiring", "Hiring engineers"]);
+
+        // Cảm xúc: mỗi doanh nghiệp một cảm xúc, có thể đổi hoặc bỏ
+        social.react(&beta, id, Some(Reaction::Like)).unwrap();
+        social.react(&beta, id, Some(Reaction::Celebrate)).unwrap();
+        social.react(&alpha, id, Some(Reaction::Celebrate)).unwrap();
+        assert!(social.react(&gamma, id, Some(Reaction::Like)).is_err());
+        let counts = social.reactions("alpha", id).unwrap();
+        assert_eq!(counts.get(&Reaction::Celebrate), Some(&2));
+        assert_eq!(counts.get(&Reaction::Like), None);
+        social.react(&beta, id, None).unwrap();
+        assert_eq!(social.get("alpha", id).unwrap().reactions.len(), 1);
+
+        // Mỗi hành động công bố một sự kiện
+        let events: Vec<Event> = events.try_iter().collect();
+        assert_eq!(events.len(), 7);
+        assert_eq!(
+            events[0],
+            Event::PostCreated {
+                post: id,
+                author: "alpha".to_string()
+            }
+        );
+    }
+
+    // Khai báo một hàm để kiểm tra bình luận theo dạng cây
+    #[test]
+    fn test_thread() {
+        let (social, _, _) = social();
+        let (alpha, beta, gamma) = (principal("alpha"), principal("beta"), principal("gamma"));
+        let id = social.post(&alpha, "Launch", Access::Public).unwrap();
+        let other = social.post(&alpha, "Other", Access::Public).unwrap();
+
+        // Hai bình luận gốc, một trả lời và một trả lời của trả lời
+        let first = social.comment(&beta, id, None, "Congrats").unwrap();
+        let second = social.comment(&gamma, id, None, "When?").unwrap();
+        let reply = social.comment(&alpha, id, Some(first), "Thanks").unwrap();
+        social
+            .comment(&beta, id, Some(reply), "You're welcome")
+            .unwrap();
+        assert!(social.comment(&beta, other, Some(first), "Wrong").is_err());
+
+        // Sửa bình luận giữ lịch sử
+        assert!(social.revise(&beta, second, "Hijack").is_err());
+        social.revise(&gamma, second, "When is it?").unwrap();
+
+        let thread: Vec<(String, usize)> = social
+            .thread("gamma", id)
+            .unwrap()
+            .into_iter()
+            .map(|(comment, depth)| (comment.text, depth))
+            .collect();
+        assert_eq!(
+            thread,
+            [
+                ("Congrats".to_string(), 0),
+                ("Thanks".to_string(), 1),
+                ("You're welcome".to_string(), 2),
+                ("When is it?".to_string(), 0),
+            ]
+        );
+    }
+
+    // Khai báo một hàm để kiểm tra tập tin đính kèm theo quyền xem của bài viết
+    #[test]
+    fn test_media() {
+        let (social, _, _) = social();
+        let (alpha, beta) = (principal("alpha"), principal("beta"));
+        let id = social
+            .post(&alpha, "Our new plant", Access::Private)
+            .unwrap();
+
+        // Tạo hình ảnh nguồn và đối tượng lưu trữ của tác giả
+        let dir = tempdir().unwrap();
+        let source = dir.join("plant.jpg");
+        fs::write(&source, b"\xff\xd8\xff").unwrap();
+        let mut storage = create_temp_storage("social", Access::Private).unwrap();
+
+        // Tập tin đính kèm theo quyền của bài viết và đổi theo khi bài viết đổi quyền
+        let name = social.attach(&alpha, id, &mut storage, &source).unwrap();
+        assert_eq!(name, "post-1-1.jpg");
+        assert!(social.attach(&beta, id, &mut storage, &source).is_err());
+        assert_eq!(storage.file_access(&name).unwrap(), Access::Private);
+        social
+            .restrict(&alpha, id, Access::Public, &storage)
+            .unwrap();
+        assert_eq!(storage.file_access(&name).unwrap(), Access::Public);
+        assert!(social.get("gamma", id).is_ok());
+
+        // Xoá bài viết xoá luôn tập tin đính kèm
+        social.delete(&alpha, id, &mut storage).unwrap();
+        assert!(storage.list().unwrap().is_empty());
+        assert!(social.get("alpha", id).is_err());
+
+        // Xoá các thư mục tạm thời
+        fs::remove_dir_all(storage.path()).unwrap();
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,714 @@
+// Khai báo các thư viện cần thiết
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::sync::{Arc, Mutex, RwLock};
+
+use crate::auth::role::Action;
+use crate::auth::{self, Principal};
+use crate::crypto::{self, chacha, hex, sha256, unhex, x25519};
+use crate::event::{Bus, Event};
+use crate::social::message::{self, escape, unescape, Messages};
+use crate::storage::Storage;
+
+// Khai báo phiên bản của định dạng phong bì (đứng đầu nội dung tin nhắn đã mã hoá)
+pub const VERSION: &str = "e2e1";
+
+// Khai báo nhãn dùng để dẫn xuất khoá bọc từ bí mật chung X25519
+const LABEL: &[u8; 16] = b"rim e2e key wrap";
+
+// Khai báo tên tập tin nhật ký khoá trong đối tượng lưu trữ của máy chủ
+const LOG: &str = "keys.log";
+
+// Khai báo một struct để đại diện cho cặp khoá X25519 của một doanh nghiệp (chỉ nằm ở phía client)
+#[derive(Clone)]
+pub struct Identity {
+    secret: [u8; 32],     // Khoá bí mật
+    pub public: [u8; 32], // Khoá công khai (đăng ký lên máy chủ)
+}
+
+// Khai báo các phương thức cho struct Identity
+impl Identity {
+    // Phương thức generate để tạo cặp khoá ngẫu nhiên
+    pub fn generate() -> io::Result<Identity> {
+        Ok(Identity::from_secret(crypto::key()?))
+    }
+
+    // Phương thức from_secret để khôi phục cặp khoá từ khoá bí mật đã lưu
+    pub fn from_secret(secret: [u8; 32]) -> Identity {
+        Identity {
+            public: x25519::public(&secret),
+            secret,
+        }
+    }
+}
+
+// Khai báo một struct để đại diện cho khoá của cuộc trò chuyện đã được bọc cho một thành viên
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grant {
+    pub conversation: u64,   // Mã cuộc trò chuyện
+    pub epoch: u32,          // Thế hệ khoá (tăng mỗi lần xoay khoá)
+    pub member: String,      // Thành viên nhận khoá
+    pub ephemeral: [u8; 32], // Khoá công khai tạm thời dùng để bọc
+    pub sealed: Vec<u8>,     // Khoá của cuộc trò chuyện đã mã hoá
+}
+
+// Khai báo một struct để đại diện cho một tin nhắn đã mã hoá đầu cuối
+#[derive(Debug, Clone, PartialEq)]
+pub struct Envelope {
+    pub conversation: u64, // Mã cuộc trò chuyện
+    pub epoch: u32,        // Thế hệ khoá đã dùng
+    pub nonce: [u8; 12],   // Nonce ngẫu nhiên
+    pub sealed: Vec<u8>,   // Bản mã kèm tag
+}
+
+// Khai báo các phương thức cho struct Envelope
+impl Envelope {
+    // Phương thức encode để ghi phong bì thành chuỗi dùng làm nội dung tin nhắn
+    pub fn encode(&self) -> String {
+        format!(
+            "{}:{}:{}:{}:{}",
+            VERSION,
+            self.conversation,
+            self.epoch,
+            hex(&self.nonce),
+            hex(&self.sealed)
+        )
+    }
+
+    // Phương thức decode để đọc lại phong bì từ nội dung tin nhắn
+    pub fn decode(text: &str) -> io::Result<Envelope> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Invalid envelope");
+        let parts: Vec<&str> = text.split(':').collect();
+        let &[VERSION, conversation, epoch, nonce, sealed] = parts.as_slice() else {
+            return Err(invalid());
+        };
+        Ok(Envelope {
+            conversation: conversation.parse().map_err(|_| invalid())?,
+            epoch: epoch.parse().map_err(|_| invalid())?,
+            nonce: unhex(nonce)?.try_into().map_err(|_| invalid())?,
+            sealed: unhex(sealed)?,
+        })
+    }
+}
+
+// Khai báo một struct để giữ khoá các cuộc trò chuyện của một doanh nghiệp (chỉ nằm ở phía client)
+pub struct Keyring {
+    business: String,                     // Doanh nghiệp sở hữu
+    identity: Identity,                   // Cặp khoá của doanh nghiệp
+    keys: BTreeMap<(u64, u32), [u8; 32]>, // Khoá theo cuộc trò chuyện và thế hệ
+}
+
+// Khai báo các phương thức cho struct Keyring
+impl Keyring {
+    // Phương thức new để tạo chùm khoá rỗng
+    pub fn new(business: &str, identity: Identity) -> Keyring {
+        Keyring {
+            business: business.to_string(),
+            identity,
+            keys: BTreeMap::new(),
+        }
+    }
+
+    // Phương thức rotate để tạo khoá mới cho cuộc trò chuyện và bọc nó cho từng thành viên
+    pub fn rotate(
+        &mut self,
+        conversation: u64,
+        epoch: u32,
+        members: &[(String, [u8; 32])],
+    ) -> io::Result<Vec<Grant>> {
+        let key = crypto::key()?;
+        let mut grants = Vec::new();
+        for (member, public) in members {
+            let ephemeral = crypto::key()?;
+            let wrap = wrapping(&ephemeral, public)?;
+            grants.push(Grant {
+                conversation,
+                epoch,
+                member: member.clone(),
+                ephemeral: x25519::public(&ephemeral),
+                sealed: chacha::seal(&wrap, &[0; 12], &bind(conversation, epoch, member), &key),
+            });
+        }
+        self.keys.insert((conversation, epoch), key);
+        Ok(grants)
+    }
+
+    // Phương thức accept để mở khoá được bọc cho doanh nghiệp này
+    pub fn accept(&mut self, grant: &Grant) -> io::Result<()> {
+        if grant.member != self.business {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Grant is for {}", grant.member),
+            ));
+        }
+        let wrap = wrapping(&self.identity.secret, &grant.ephemeral)?;
+        let aad = bind(grant.conversation, grant.epoch, &grant.member);
+        let key = chacha::open(&wrap, &[0; 12], &aad, &grant.sealed)?;
+        let key = key
+            .try_into()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid conversation key"))?;
+        self.keys.insert((grant.conversation, grant.epoch), key);
+        Ok(())
+    }
+
+    // Phương thức seal để mã hoá tin nhắn bằng khoá mới nhất của cuộc trò chuyện
+    pub fn seal(&self, conversation: u64, text: &str) -> io::Result<Envelope> {
+        let (&(_, epoch), key) = self
+            .keys
+            .range((conversation, 0)..=(conversation, u32::MAX))
+            .next_back()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("No key for conversation {}", conversation),
+                )
+            })?;
+        let mut nonce = [0; 12];
+        crypto::random(&mut nonce)?;
+        let aad = header(conversation, epoch, &self.business);
+        Ok(Envelope {
+            conversation,
+            epoch,
+            nonce,
+            sealed: chacha::seal(key, &nonce, &aad, text.as_bytes()),
+        })
+    }
+
+    // Phương thức open để giải mã tin nhắn; người gửi là một phần của dữ liệu được xác thực
+    pub fn open(&self, envelope: &Envelope, from: &str) -> io::Result<String> {
+        let key = self
+            .keys
+            .get(&(envelope.conversation, envelope.epoch))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!(
+                        "No key for conversation {} epoch {}",
+                        envelope.conversation, envelope.epoch
+                    ),
+                )
+            })?;
+        let aad = header(envelope.conversation, envelope.epoch, from);
+        let text = chacha::open(key, &envelope.nonce, &aad, &envelope.sealed)?;
+        String::from_utf8(text)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Message is not UTF-8"))
+    }
+}
+
+// Khai báo một struct để chứa khoá công khai và khoá đã bọc (được bảo vệ bởi khóa)
+#[derive(Debug, Default)]
+struct Data {
+    identities: BTreeMap<String, [u8; 32]>, // Khoá công khai theo doanh nghiệp
+    changed: BTreeSet<String>,              // Doanh nghiệp đã từng thay khoá công khai
+    acknowledged: BTreeMap<(String, String), [u8; 32]>, // Khoá mà người xem đã xác nhận theo doanh nghiệp
+    grants: BTreeMap<(u64, u32), BTreeMap<String, Grant>>, // Khoá đã bọc theo thế hệ
+}
+
+// Khai báo các phương thức cho struct Data
+impl Data {
+    // Phương thức identify để ghi nhận khoá công khai của doanh nghiệp (đánh dấu nếu khoá bị thay)
+    fn identify(&mut self, business: &str, public: [u8; 32]) {
+        if let Some(old) = self.identities.insert(business.to_string(), public) {
+            if old != public {
+                self.changed.insert(business.to_string());
+            }
+        }
+    }
+}
+
+// Khai báo một struct để máy chủ phân phối khoá và chuyển tin nhắn đã mã hoá
+//
+// Máy chủ chỉ thấy khoá công khai, khoá đã bọc và bản mã nên không đọc được nội dung.
+// Khi thành viên của cuộc trò chuyện thay đổi, khoá hiện tại bị coi là cũ và phải được xoay
+// trước khi gửi tin tiếp, để thành viên đã rời không đọc được tin mới và thành viên mới không
+// đọc được tin cũ.
+#[derive(Debug)]
+pub struct Keys {
+    messages: Arc<Messages>, // Dịch vụ tin nhắn
+    bus: Arc<Bus>,           // Kênh sự kiện
+    storage: Mutex<Storage>, // Nơi lưu nhật ký khoá
+    data: RwLock<Data>,      // Khoá công khai và khoá đã bọc
+}
+
+// Khai báo các phương thức cho struct Keys
+impl Keys {
+    // Phương thức open để mở dịch vụ khoá và đọc lại nhật ký khoá đã lưu
+    pub fn open(
+        mut storage: Storage,
+        messages: Arc<Messages>,
+        bus: Arc<Bus>,
+    ) -> io::Result<Keys> {
+        storage.serve()?;
+        let mut data = Data::default();
+        if storage.list()?.iter().any(|name| name == LOG) {
+            for (number, line) in storage.view(LOG)?.lines().enumerate() {
+                let invalid = || {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Invalid record in {} at line {}", LOG, number + 1),
+                    )
+                };
+                let fields: Vec<String> = line.split('\t').map(unescape).collect();
+                match fields.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+                    ["identity", business, public] => {
+                        let public = unhex(public)?.try_into().map_err(|_| invalid())?;
+                        data.identify(business, public);
+                    }
+                    ["acknowledge", viewer, business, public] => {
+                        let public = unhex(public)?.try_into().map_err(|_| invalid())?;
+                        data.acknowledged
+                            .insert((viewer.to_string(), business.to_string()), public);
+                    }
+                    ["grant", conversation, epoch, member, ephemeral, sealed] => {
+                        let grant = Grant {
+                            conversation: conversation.parse().map_err(|_| invalid())?,
+                            epoch: epoch.parse().map_err(|_| invalid())?,
+                            member: member.to_string(),
+                            ephemeral: unhex(ephemeral)?.try_into().map_err(|_| invalid())?,
+                            sealed: unhex(sealed)?,
+                        };
+                        data.grants
+                            .entry((grant.conversation, grant.epoch))
+                            .or_default()
+                            .insert(grant.member.clone(), grant);
+                    }
+                    _ => return Err(invalid()),
+                }
+            }
+        }
+        Ok(Keys {
+            messages,
+            bus,
+            storage: Mutex::new(storage),
+            data: RwLock::new(data),
+        })
+    }
+
+    // Phương thức register để người quản trị đăng ký (hoặc thay) khoá công khai của doanh nghiệp mình
+    //
+    // Khoá đầu tiên được tin ngay; khi khoá bị thay, sự kiện KeyChanged được công bố và các thành viên
+    // khác phải xác nhận dấu vân tay mới (acknowledge) trước khi bọc khoá cho doanh nghiệp đó.
+    pub fn register(&self, principal: &Principal, public: [u8; 32]) -> io::Result<()> {
+        let business = message::business(principal)?;
+        auth::authorize(principal, Action::ManageRoles, business)?;
+        let mut data = self.data.write().unwrap();
+        let old = data.identities.get(business).copied();
+        if old == Some(public) {
+            return Ok(());
+        }
+        let line = format!("identity\t{}\t{}\n", escape(business), hex(&public));
+        self.storage.lock().unwrap().append(LOG, line.as_bytes())?;
+        data.identify(business, public);
+        if old.is_some() {
+            self.bus.publish(Event::KeyChanged {
+                business: business.to_string(),
+                fingerprint: fingerprint(&public),
+            });
+        }
+        Ok(())
+    }
+
+    // Phương thức acknowledge để người dùng xác nhận khoá công khai mới của một doanh nghiệp
+    // bằng dấu vân tay đã so khớp qua kênh khác
+    pub fn acknowledge(
+        &self,
+        principal: &Principal,
+        business: &str,
+        fingerprint: &str,
+    ) -> io::Result<()> {
+        let viewer = message::business(principal)?;
+        let public = self.identity(business)?;
+        if self::fingerprint(&public) != fingerprint {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Fingerprint does not match the public key of {}", business),
+            ));
+        }
+        let mut data = self.data.write().unwrap();
+        let line = format!(
+            "acknowledge\t{}\t{}\t{}\n",
+            escape(viewer),
+            escape(business),
+            hex(&public)
+        );
+        self.storage.lock().unwrap().append(LOG, line.as_bytes())?;
+        data.acknowledged
+            .insert((viewer.to_string(), business.to_string()), public);
+        Ok(())
+    }
+
+    // Phương thức identity trả về khoá công khai của doanh nghiệp
+    pub fn identity(&self, business: &str) -> io::Result<[u8; 32]> {
+        self.data
+            .read()
+            .unwrap()
+            .identities
+            .get(business)
+            .copied()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("No public key for {}", business),
+                )
+            })
+    }
+
+    // Phương thức members trả về các thành viên hiện tại kèm khoá công khai để xoay khoá
+    // (từ chối nếu khoá của một thành viên đã bị thay mà người dùng chưa xác nhận)
+    pub fn members(
+        &self,
+        principal: &Principal,
+        conversation: u64,
+    ) -> io::Result<Vec<(String, [u8; 32])>> {
+        let viewer = principal.business();
+        let members = self.messages.get(principal, conversation)?.members;
+        let data = self.data.read().unwrap();
+        members
+            .into_iter()
+            .map(|member| {
+                let public = *data.identities.get(&member).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("No public key for {}", member),
+                    )
+                })?;
+                let acknowledged = data.acknowledged.get(&(viewer.to_string(), member.clone()));
+                if member != viewer
+                    && data.changed.contains(&member)
+                    && acknowledged != Some(&public)
+                {
+                    return Err(io::Error::new(
+                        io::ErrorKind::PermissionDenied,
+                        format!(
+                            "Public key of {} changed to {}; acknowledge it first",
+                            member,
+                            fingerprint(&public)
+                        ),
+                    ));
+                }
+                Ok((member, public))
+            })
+            .collect()
+    }
+
+    // Phương thức epoch trả về thế hệ khoá hiện tại của cuộc trò chuyện (0 nếu chưa có khoá)
+    pub fn epoch(&self, conversation: u64) -> u32 {
+        latest(&self.data.read().unwrap(), conversation).map_or(0, |(epoch, _)| epoch)
+    }
+
+    // Phương thức stale kiểm tra cuộc trò chuyện có cần xoay khoá hay không
+    // (chưa có khoá, hoặc thành viên đã thay đổi từ lần xoay gần nhất)
+    pub fn stale(&self, principal: &Principal, conversation: u64) -> io::Result<bool> {
+        let members = self.messages.get(principal, conversation)?.members;
+        let data = self.data.read().unwrap();
+        Ok(latest(&data, conversation).is_none_or(|(_, holders)| holders != members))
+    }
+
+    // Phương thức rotate để thành viên gửi lên khoá mới đã bọc cho đúng các thành viên hiện tại
+    pub fn rotate(
+        &self,
+        principal: &Principal,
+        conversation: u64,
+        grants: Vec<Grant>,
+    ) -> io::Result<u32> {
+        let members = self.messages.get(principal, conversation)?.members;
+        let mut data = self.data.write().unwrap();
+        let epoch = latest(&data, conversation).map_or(0, |(epoch, _)| epoch) + 1;
+        let holders: BTreeSet<String> = grants.iter().map(|g| g.member.clone()).collect();
+        if grants
+            .iter()
+            .any(|g| g.conversation != conversation || g.epoch != epoch)
+            || holders.len() != grants.len()
+            || holders != members
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Grants must cover the members of conversation {} for epoch {}",
+                    conversation, epoch
+                ),
+            ));
+        }
+
+        let text: String = grants
+            .iter()
+            .map(|g| {
+                format!(
+                    "grant\t{}\t{}\t{}\t{}\t{}\n",
+                    g.conversation,
+                    g.epoch,
+                    escape(&g.member),
+                    hex(&g.ephemeral),
+                    hex(&g.sealed)
+                )
+            })
+            .collect();
+        self.storage.lock().unwrap().append(LOG, text.as_bytes())?;
+        data.grants.insert(
+            (conversation, epoch),
+            grants.into_iter().map(|g| (g.member.clone(), g)).collect(),
+        );
+        self.bus.publish(Event::KeyRotated {
+            conversation,
+            epoch,
+        });
+        Ok(epoch)
+    }
+
+    // Phương thức grants trả về các khoá đã bọc cho một thành viên (mọi thế hệ họ từng nhận)
+    pub fn grants(&self, conversation: u64, member: &str) -> Vec<Grant> {
+        let data = self.data.read().unwrap();
+        data.grants
+            .range((conversation, 0)..=(conversation, u32::MAX))
+            .filter_map(|(_, grants)| grants.get(member).cloned())
+            .collect()
+    }
+
+    // Phương thức send để gửi tin nhắn đã mã hoá bằng khoá hiện tại của cuộc trò chuyện
+    pub fn send(&self, principal: &Principal, envelope: &Envelope) -> io::Result<u64> {
+        let conversation = envelope.conversation;
+        if self.stale(principal, conversation)? {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("Conversation {} needs a key rotation", conversation),
+            ));
+        }
+        if envelope.epoch != self.epoch(conversation) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Envelope uses an old key of conversation {}", conversation),
+            ));
+        }
+        self.messages
+            .send(principal, conversation, &envelope.encode(), &[])
+    }
+}
+
+// Hàm fingerprint trả về dấu vân tay của khoá công khai để so khớp qua kênh khác
+pub fn fingerprint(public: &[u8; 32]) -> String {
+    hex(&sha256::digest(public)[..16])
+}
+
+// Hàm latest trả về thế hệ khoá mới nhất của cuộc trò chuyện và các thành viên giữ khoá đó
+fn latest(data: &Data, conversation: u64) -> Option<(u32, BTreeSet<String>)> {
+    data.grants
+        .range((conversation, 0)..=(conversation, u32::MAX))
+        .next_back()
+        .map(|(&(_, epoch), grants)| (epoch, grants.keys().cloned().collect()))
+}
+
+// Hàm wrapping dẫn xuất khoá bọc từ bí mật chung X25519
+fn wrapping(secret: &[u8; 32], public: &[u8; 32]) -> io::Result<[u8; 32]> {
+    let shared = x25519::x25519(secret, public);
+    if crypto::equal(&shared, &[0; 32]) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Invalid public key",
+        ));
+    }
+    Ok(chacha::hchacha(&shared, LABEL))
+}
+
+// Hàm bind tạo dữ liệu phụ gắn khoá đã bọc với cuộc trò chuyện, thế hệ và thành viên
+fn bind(conversation: u64, epoch: u32, member: &str) -> Vec<u8> {
+    format!("{}:grant:{}:{}:{}", VERSION, conversation, epoch, member).into_bytes()
+}
+
+// Hàm header tạo dữ liệu phụ gắn tin nhắn với cuộc trò chuyện, thế hệ và người gửi
+fn header(conversation: u64, epoch: u32, from: &str) -> Vec<u8> {
+    format!("{}:message:{}:{}:{}", VERSION, conversation, epoch, from).into_bytes()
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::auth::role::Role;
+    use crate::auth::tests::principal;
+    use crate::storage::tests::{create_temp_storage, reopen};
+    use crate::storage::Access;
+    use std::fs;
+
+    // Khai báo một hàm để tạo chùm khoá cho một doanh nghiệp và đăng ký khoá công khai
+    fn keyring(keys: &Keys, business: &str) -> Keyring {
+        let identity = Identity::generate().unwrap();
+        keys.register(&principal(business), identity.public)
+            .unwrap();
+        Keyring::new(business, identity)
+    }
+
+    // Khai báo một hàm để xoay khoá của cuộc trò chuyện và phát khoá cho các thành viên
+    fn rotate(keys: &Keys, id: u64, by: &mut Keyring, others: &mut [&mut Keyring]) {
+        let members = keys.members(&principal(&by.business), id).unwrap();
+        let epoch = keys.epoch(id) + 1;
+        let grants = by.rotate(id, epoch, &members).unwrap();
+        keys.rotate(&principal(&by.business), id, grants).unwrap();
+        for ring in others {
+            for grant in keys.grants(id, &ring.business) {
+                ring.accept(&grant).unwrap();
+            }
+        }
+    }
+
+    // Khai báo một hàm để kiểm tra mã hoá đầu cuối, xoay khoá khi thành viên thay đổi và đọc lại nhật ký
+    #[test]
+    fn test_envelope() {
+        let bus = Arc::new(Bus::new());
+        let events = bus.subscribe();
+        let storage = create_temp_storage("messages", Access::Private).unwrap();
+        let messages_path = storage.path().to_path_buf();
+        let messages = Arc::new(Messages::open(storage, bus.clone()).unwrap());
+        let storage = create_temp_storage("keys", Access::Private).unwrap();
+        let path = storage.path().to_path_buf();
+        let keys = Keys::open(storage, messages.clone(), bus.clone()).unwrap();
+
+        let mut alpha = keyring(&keys, "alpha");
+        let mut beta = keyring(&keys, "beta");
+        let mut gamma = keyring(&keys, "gamma");
+
+        // Chưa có khoá thì không gửi được
+        let id = messages
+            .group(&principal("alpha"), "Pricing", &["beta"])
+            .unwrap();
+        assert!(keys.stale(&principal("alpha"), id).unwrap());
+        rotate(&keys, id, &mut alpha, &mut [&mut beta]);
+        assert!(!keys.stale(&principal("beta"), id).unwrap());
+
+        // Máy chủ chỉ lưu bản mã; beta giải mã được, người gửi sai bị phát hiện
+        let envelope = alpha.seal(id, "Unit price 42 USD").unwrap();
+        let seq = keys.send(&principal("alpha"), &envelope).unwrap();
+        let stored =
+            messages.get(&principal("beta"), id).unwrap().messages[seq as usize - 1].clone();
+        assert!(!stored.text.contains("Unit price"));
+        let received = Envelope::decode(&stored.text).unwrap();
+        assert_eq!(received, envelope);
+        assert_eq!(
+            beta.open(&received, &stored.from).unwrap(),
+            "Unit price 42 USD"
+        );
+        assert!(beta.open(&received, "beta").is_err());
+        assert!(
+            !fs::read_to_string(messages_path.join(format!("conversation-{}.log", id)))
+                .unwrap()
+                .contains("Unit price")
+        );
+
+        // gamma được mời: khoá cũ thành cũ, phải xoay trước khi gửi tiếp
+        messages.invite(&principal("beta"), id, "gamma").unwrap();
+        let old = beta.seal(id, "stale").unwrap();
+        assert!(keys.send(&principal("beta"), &old).is_err());
+        assert!(keys.rotate(&principal("beta"), id, Vec::new()).is_err());
+        rotate(&keys, id, &mut beta, &mut [&mut alpha, &mut gamma]);
+        assert!(keys.send(&principal("beta"), &old).is_err());
+        let envelope = beta.seal(id, "Welcome gamma").unwrap();
+        assert_eq!(envelope.epoch, 2);
+        keys.send(&principal("beta"), &envelope).unwrap();
+        assert_eq!(gamma.open(&envelope, "beta").unwrap(), "Welcome gamma");
+        assert_eq!(alpha.open(&envelope, "beta").unwrap(), "Welcome gamma");
+
+        // gamma không có khoá thế hệ trước nên không đọc được tin cũ
+        assert!(gamma.open(&received, "alpha").is_err());
+        assert!(gamma.accept(&keys.grants(id, "alpha")[0]).is_err());
+
+        // Mở lại dịch vụ khoá từ nhật ký
+        drop(keys);
+        let keys = Keys::open(reopen(&path).unwrap(), messages, bus).unwrap();
+        assert_eq!(keys.epoch(id), 2);
+        assert_eq!(keys.identity("gamma").unwrap(), gamma.identity.public);
+        assert_eq!(keys.grants(id, "alpha").len(), 2);
+        let events: Vec<Event> = events.try_iter().collect();
+        assert!(events.contains(&Event::KeyRotated {
+            conversation: id,
+            epoch: 2
+        }));
+
+        // Xoá các thư mục tạm thời
+        fs::remove_dir_all(path).unwrap();
+        fs::remove_dir_all(messages_path).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra việc thay khoá công khai và xác nhận dấu vân tay
+    #[test]
+    fn test_register() {
+        let bus = Arc::new(Bus::new());
+        let events = bus.subscribe();
+        let storage = create_temp_storage("messages", Access::Private).unwrap();
+        let messages_path = storage.path().to_path_buf();
+        let messages = Arc::new(Messages::open(storage, bus.clone()).unwrap());
+        let storage = create_temp_storage("keys", Access::Private).unwrap();
+        let path = storage.path().to_path_buf();
+        let keys = Keys::open(storage, messages.clone(), bus.clone()).unwrap();
+
+        let mut alpha = keyring(&keys, "alpha");
+        let beta = keyring(&keys, "beta");
+        let id = messages.direct(&principal("alpha"), "beta").unwrap();
+        rotate(&keys, id, &mut alpha, &mut []);
+
+        // Chỉ người quản trị còn phiên của chính doanh nghiệp mới đăng ký được khoá
+        let staff = Principal::new(0, "sam", "beta", Role::Staff, u64::MAX);
+        assert!(keys.register(&staff, [9; 32]).is_err());
+        let expired = Principal::new(0, "bob", "beta", Role::Owner, 0);
+        assert!(keys.register(&expired, [9; 32]).is_err());
+        assert_eq!(keys.identity("beta").unwrap(), beta.identity.public);
+        keys.register(&principal("beta"), beta.identity.public)
+            .unwrap();
+        assert!(!events
+            .try_iter()
+            .any(|event| matches!(event, Event::KeyChanged { .. })));
+
+        // beta thay khoá: alpha phải xác nhận dấu vân tay mới trước khi bọc khoá cho beta
+        let public = Identity::generate().unwrap().public;
+        keys.register(&principal("beta"), public).unwrap();
+        assert_eq!(
+            events.try_iter().collect::<Vec<_>>(),
+            [Event::KeyChanged {
+                business: "beta".to_string(),
+                fingerprint: fingerprint(&public),
+            }]
+        );
+        assert!(keys.members(&principal("alpha"), id).is_err());
+        assert!(keys.members(&principal("beta"), id).is_ok());
+        assert!(keys
+            .acknowledge(
+                &principal("alpha"),
+                "beta",
+                &fingerprint(&beta.identity.public)
+            )
+            .is_err());
+        keys.acknowledge(&principal("alpha"), "beta", &fingerprint(&public))
+            .unwrap();
+        assert_eq!(
+            keys.members(&principal("alpha"), id).unwrap()[1],
+            ("beta".to_string(), public)
+        );
+
+        // Xác nhận được đọc lại từ nhật ký; thay khoá lần nữa lại cần xác nhận
+        drop(keys);
+        let keys = Keys::open(reopen(&path).unwrap(), messages, bus).unwrap();
+        assert!(keys.members(&principal("alpha"), id).is_ok());
+        keys.register(&principal("beta"), [7; 32]).unwrap();
+        assert!(keys.members(&principal("alpha"), id).is_err());
+
+        // Xoá các thư mục tạm thời
+        fs::remove_dir_all(path).unwrap();
+        fs::remove_dir_all(messages_path).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra định dạng phong bì
+    #[test]
+    fn test_encode() {
+        let envelope = Envelope {
+            conversation: 7,
+            epoch: 3,
+            nonce: [1; 12],
+            sealed: vec![0xde, 0xad],
+        };
+        let text = envelope.encode();
+        assert_eq!(text, "e2e1:7:3:010101010101010101010101:dead");
+        assert_eq!(Envelope::decode(&text).unwrap(), envelope);
+        assert!(Envelope::decode("e2e0:7:3:01:dead").is_err());
+        assert!(Envelope::decode("e2e1:7:3:01:dead").is_err());
+        assert!(Envelope::decode("Hello").is_err());
+    }
+}
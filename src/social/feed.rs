@@ -0,0 +1,279 @@
+// Khai báo các thư viện cần thiết
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+use std::sync::{mpsc, Arc, Mutex, RwLock};
+
+use crate::event::{Bus, Event};
+use crate::peer::connection::Connections;
+use crate::social::{Post, Social};
+
+// Khai báo kiểu hàm xếp hạng: điểm càng cao bài viết càng lên đầu (bằng điểm thì bài mới hơn trước)
+pub type Rank = Box<dyn Fn(&str, &Post) -> i64 + Send + Sync>;
+
+// Hàm recent xếp hạng theo thời điểm đăng
+pub fn recent(_viewer: &str, post: &Post) -> i64 {
+    post.time as i64
+}
+
+// Hàm popular xếp hạng theo số cảm xúc
+pub fn popular(_viewer: &str, post: &Post) -> i64 {
+    post.reactions.len() as i64
+}
+
+// Khai báo một struct để đánh dấu vị trí đã đọc tới trong bảng tin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub score: i64, // Điểm của bài viết cuối cùng đã trả về
+    pub post: u64,  // Mã của bài viết cuối cùng đã trả về
+}
+
+// Triển khai trait Display cho Cursor để trao cho client dưới dạng chuỗi
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.score, self.post)
+    }
+}
+
+// Triển khai trait FromStr cho Cursor để đọc lại chuỗi client gửi lên
+impl FromStr for Cursor {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid cursor: {}", s),
+            )
+        };
+        let (score, post) = s.split_once(':').ok_or_else(invalid)?;
+        Ok(Cursor {
+            score: score.parse().map_err(|_| invalid())?,
+            post: post.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+// Khai báo một struct để đại diện cho một trang của bảng tin
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    pub items: Vec<Post>,     // Các bài viết của trang
+    pub next: Option<Cursor>, // Vị trí để lấy trang tiếp theo (None nếu đã hết)
+}
+
+// Khai báo một struct để chứa hộp tin của từng doanh nghiệp (được bảo vệ bởi khóa)
+#[derive(Debug, Default)]
+struct Data {
+    inboxes: BTreeMap<String, BTreeSet<u64>>, // Bài viết đã được đẩy tới từng doanh nghiệp
+    pulled: BTreeSet<String>,                 // Tác giả có bài viết được kéo khi đọc
+}
+
+// Khai báo một struct để tạo bảng tin cho các doanh nghiệp
+//
+// Bài viết của tác giả có ít kết nối được đẩy vào hộp tin của từng kết nối ngay khi đăng;
+// tác giả có nhiều hơn `threshold` kết nối thì bài viết được kéo về khi đọc bảng tin.
+// Chỉ các kết nối tại thời điểm đăng nhận được bài viết đã đẩy.
+pub struct Feed {
+    social: Arc<Social>,                  // Bài viết
+    connections: Arc<Connections>,        // Các kết nối giữa doanh nghiệp
+    events: Mutex<mpsc::Receiver<Event>>, // Sự kiện bài viết chưa xử lý
+    threshold: usize,                     // Số kết nối tối đa để đẩy bài viết khi đăng
+    rank: Rank,                           // Hàm xếp hạng
+    data: RwLock<Data>,                   // Hộp tin
+}
+
+// Khai báo các phương thức cho struct Feed
+impl Feed {
+    // Phương thức new để tạo bảng tin, đăng ký nhận sự kiện bài viết từ kênh sự kiện
+    pub fn new(
+        social: Arc<Social>,
+        connections: Arc<Connections>,
+        bus: &Bus,
+        threshold: usize,
+        rank: impl Fn(&str, &Post) -> i64 + Send + Sync + 'static,
+    ) -> Feed {
+        Feed {
+            social,
+            connections,
+            events: Mutex::new(bus.subscribe()),
+            threshold,
+            rank: Box::new(rank),
+            data: RwLock::new(Data::default()),
+        }
+    }
+
+    // Phương thức sync để xử lý các sự kiện bài viết đang chờ (đẩy bài mới, gỡ bài đã xoá)
+    pub fn sync(&self) {
+        let events = self.events.lock().unwrap();
+        let mut data = self.data.write().unwrap();
+        for event in events.try_iter() {
+            match event {
+                Event::PostCreated { post, author } => {
+                    let followers = self.connections.connections(&author);
+                    if followers.len() > self.threshold {
+                        data.pulled.insert(author);
+                        continue;
+                    }
+                    for business in followers.into_iter().chain([author]) {
+                        data.inboxes.entry(business).or_default().insert(post);
+                    }
+                }
+                Event::PostDeleted { post } => {
+                    for inbox in data.inboxes.values_mut() {
+                        inbox.remove(&post);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Phương thức page để lấy một trang bảng tin của doanh nghiệp, bắt đầu sau vị trí `after`
+    pub fn page(&self, viewer: &str, after: Option<Cursor>, size: usize) -> Page {
+        self.sync();
+
+        // Gộp bài viết đã đẩy với bài viết kéo từ các kết nối có nhiều người theo dõi
+        let mut posts: BTreeMap<u64, Post> = BTreeMap::new();
+        {
+            let data = self.data.read().unwrap();
+            for id in data.inboxes.get(viewer).into_iter().flatten() {
+                if let Ok(post) = self.social.get(viewer, *id) {
+                    posts.insert(post.id, post);
+                }
+            }
+            let authors = self
+                .connections
+                .connections(viewer)
+                .into_iter()
+                .chain([viewer.to_string()])
+                .filter(|author| data.pulled.contains(author));
+            for author in authors {
+                for post in self.social.timeline(viewer, &author) {
+                    posts.insert(post.id, post);
+                }
+            }
+        }
+
+        // Xếp hạng theo điểm giảm dần, bằng điểm thì mã giảm dần để kết quả luôn xác định
+        let mut ranked: Vec<(Cursor, Post)> = posts
+            .into_values()
+            .map(|post| {
+                let score = (self.rank)(viewer, &post);
+                let cursor = Cursor {
+                    score,
+                    post: post.id,
+                };
+                (cursor, post)
+            })
+            .filter(|(cursor, _)| after.is_none_or(|after| key(cursor) > key(&after)))
+            .collect();
+        ranked.sort_by_key(|(cursor, _)| key(cursor));
+
+        // Cắt trang và trả về vị trí của bài viết cuối cùng nếu còn bài phía sau
+        let more = ranked.len() > size;
+        ranked.truncate(size);
+        Page {
+            next: ranked.last().filter(|_| more).map(|(cursor, _)| *cursor),
+            items: ranked.into_iter().map(|(_, post)| post).collect(),
+        }
+    }
+}
+
+// Hàm key trả về khoá sắp xếp của một vị trí trong bảng tin
+fn key(cursor: &Cursor) -> (Reverse<i64>, Reverse<u64>) {
+    (Reverse(cursor.score), Reverse(cursor.post))
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::auth::tests::principal;
+    use crate::social::tests::social;
+    use crate::social::Reaction;
+    use crate::storage::tests::create_temp_storage;
+    use crate::storage::Access;
+    use std::fs;
+
+    // Khai báo một hàm để lấy nội dung các bài viết của một trang
+    fn texts(page: &Page) -> Vec<&str> {
+        page.items.iter().map(|post| post.text.as_str()).collect()
+    }
+
+    // Khai báo một hàm để kiểm tra đẩy bài khi đăng, kéo bài khi đọc và phân trang
+    #[test]
+    fn test_fanout() {
+        // alpha có một kết nối (beta); gamma kết nối với alpha, beta và delta
+        let (social, connections, bus) = social();
+        for other in ["alpha", "beta", "delta"] {
+            let id = connections.send(&principal("gamma"), other, "").unwrap();
+            connections.accept(&principal(other), id).unwrap();
+        }
+        let social = Arc::new(social);
+        let mut storage = create_temp_storage("feed", Access::Private).unwrap();
+        let feed = Feed::new(
+            social.clone(),
+            connections.clone(),
+            &bus,
+            2,
+            |_: &str, post: &Post| post.id as i64,
+        );
+
+        // Bài của alpha được đẩy; bài của gamma (3 kết nối) được kéo khi đọc
+        let (alpha, gamma, delta) = (principal("alpha"), principal("gamma"), principal("delta"));
+        social.post(&alpha, "a1", Access::Shared).unwrap();
+        social.post(&gamma, "g1", Access::Public).unwrap();
+        social.post(&alpha, "a2", Access::Private).unwrap();
+        social.post(&delta, "d1", Access::Shared).unwrap();
+        feed.sync();
+        {
+            let data = feed.data.read().unwrap();
+            assert_eq!(data.pulled, BTreeSet::from(["gamma".to_string()]));
+            assert_eq!(data.inboxes["beta"], BTreeSet::from([1, 3]));
+        }
+
+        // Bài Private của alpha không hiện với beta
+        let page = feed.page("beta", None, 10);
+        assert_eq!(texts(&page), ["g1", "a1"]);
+        assert_eq!(page.next, None);
+
+        // Phân trang theo vị trí, qua chuỗi như client gửi lên
+        let first = feed.page("alpha", None, 2);
+        assert_eq!(texts(&first), ["a2", "g1"]);
+        let cursor: Cursor = first.next.unwrap().to_string().parse().unwrap();
+        let second = feed.page("alpha", Some(cursor), 2);
+        assert_eq!(texts(&second), ["a1"]);
+        assert_eq!(second.next, None);
+
+        // Bài viết mới không làm lệch trang đang đọc; bài viết đã xoá biến mất
+        social.post(&alpha, "a3", Access::Public).unwrap();
+        assert_eq!(texts(&feed.page("alpha", Some(cursor), 2)), ["a1"]);
+        social.delete(&alpha, 1, &mut storage).unwrap();
+        assert_eq!(texts(&feed.page("beta", None, 10)), ["a3", "g1"]);
+        assert!("1-2".parse::<Cursor>().is_err());
+
+        // Xoá thư mục tạm thời
+        fs::remove_dir_all(storage.path()).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra hàm xếp hạng có thể thay thế và kết quả xác định
+    #[test]
+    fn test_rank() {
+        let (social, connections, bus) = social();
+        let social = Arc::new(social);
+        let feed = Feed::new(social.clone(), connections, &bus, 10, popular);
+
+        // Ba bài cùng điểm xếp theo mã giảm dần; bài có nhiều cảm xúc lên đầu
+        let (alpha, beta) = (principal("alpha"), principal("beta"));
+        let first = social.post(&alpha, "first", Access::Public).unwrap();
+        social.post(&alpha, "second", Access::Public).unwrap();
+        social.post(&beta, "third", Access::Public).unwrap();
+        social.react(&beta, first, Some(Reaction::Like)).unwrap();
+        let page = feed.page("alpha", None, 10);
+        assert_eq!(texts(&page), ["first", "third", "second"]);
+        assert_eq!(page, feed.page("alpha", None, 10));
+    }
+}
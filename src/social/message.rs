@@ -0,0 +1,727 @@
+// Khai báo các thư viện cần thiết
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+
+use crate::auth::Principal;
+use crate::clock;
+use crate::event::{Bus, Event};
+use crate::storage::Storage;
+
+// Khai báo một enum để đại diện cho loại cuộc trò chuyện
+#[derive(Debug, Clone, PartialEq)]
+pub enum Kind {
+    Direct,        // Trò chuyện giữa hai doanh nghiệp
+    Group(String), // Nhóm trò chuyện có tên
+}
+
+// Khai báo một struct để tham chiếu tới một tập tin đã lưu (không sao chép nội dung)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attachment {
+    pub owner: String, // Tên đối tượng lưu trữ chứa tập tin
+    pub file: String,  // Tên tập tin
+}
+
+// Khai báo một struct để đại diện cho một tin nhắn
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub seq: u64,                     // Số thứ tự trong cuộc trò chuyện (bắt đầu từ 1)
+    pub from: String,                 // Doanh nghiệp gửi
+    pub text: String,                 // Nội dung
+    pub time: u64,                    // Thời điểm gửi
+    pub attachments: Vec<Attachment>, // Các tập tin đính kèm
+}
+
+// Khai báo một struct để đại diện cho một cuộc trò chuyện
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conversation {
+    pub id: u64,                          // Mã của cuộc trò chuyện
+    pub kind: Kind,                       // Loại cuộc trò chuyện
+    pub members: BTreeSet<String>,        // Các thành viên hiện tại
+    pub messages: Vec<Message>,           // Các tin nhắn theo thứ tự gửi
+    pub delivered: BTreeMap<String, u64>, // Tin nhắn cuối cùng đã tới từng thành viên
+    pub read: BTreeMap<String, u64>,      // Tin nhắn cuối cùng từng thành viên đã đọc
+    pub time: u64,                        // Thời điểm tạo
+}
+
+// Khai báo một struct để đại diện cho trạng thái nhận của một tin nhắn
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Receipt {
+    pub delivered: Vec<String>, // Các thành viên đã nhận
+    pub read: Vec<String>,      // Các thành viên đã đọc
+}
+
+// Khai báo một enum để đại diện cho một bản ghi trong nhật ký của cuộc trò chuyện
+#[derive(Debug, Clone, PartialEq)]
+enum Record {
+    Open(Kind, u64),        // Tạo cuộc trò chuyện
+    Join(String),           // Thêm thành viên
+    Leave(String),          // Thành viên rời đi
+    Message(Message),       // Tin nhắn mới
+    Delivered(String, u64), // Tin nhắn đã tới thành viên
+    Read(String, u64),      // Thành viên đã đọc tin nhắn
+}
+
+// Khai báo các phương thức cho enum Record
+impl Record {
+    // Phương thức render để ghi bản ghi thành một dòng, các trường cách nhau bằng tab
+    fn render(&self) -> String {
+        let fields: Vec<String> = match self {
+            Record::Open(Kind::Direct, time) => vec!["open".into(), time.to_string()],
+            Record::Open(Kind::Group(name), time) => {
+                vec!["open".into(), time.to_string(), escape(name)]
+            }
+            Record::Join(member) => vec!["join".into(), escape(member)],
+            Record::Leave(member) => vec!["leave".into(), escape(member)],
+            Record::Message(message) => {
+                let mut fields = vec![
+                    "message".into(),
+                    message.seq.to_string(),
+                    escape(&message.from),
+                    message.time.to_string(),
+                    escape(&message.text),
+                ];
+                for attachment in &message.attachments {
+                    fields.push(escape(&attachment.owner));
+                    fields.push(escape(&attachment.file));
+                }
+                fields
+            }
+            Record::Delivered(member, seq) => {
+                vec!["delivered".into(), escape(member), seq.to_string()]
+            }
+            Record::Read(member, seq) => vec!["read".into(), escape(member), seq.to_string()],
+        };
+        format!("{}\n", fields.join("\t"))
+    }
+
+    // Phương thức parse để đọc lại một dòng do render tạo ra
+    fn parse(line: &str) -> Option<Record> {
+        let fields: Vec<String> = line.split('\t').map(unescape).collect();
+        let number = |i: usize| fields.get(i)?.parse::<u64>().ok();
+        let record = match fields[0].as_str() {
+            "open" => match fields.get(2) {
+                Some(name) => Record::Open(Kind::Group(name.clone()), number(1)?),
+                None => Record::Open(Kind::Direct, number(1)?),
+            },
+            "join" => Record::Join(fields.get(1)?.clone()),
+            "leave" => Record::Leave(fields.get(1)?.clone()),
+            "message" if fields.len() >= 5 && fields.len() % 2 == 1 => Record::Message(Message {
+                seq: number(1)?,
+                from: fields[2].clone(),
+                time: number(3)?,
+                text: fields[4].clone(),
+                attachments: fields[5..]
+                    .chunks(2)
+                    .map(|pair| Attachment {
+                        owner: pair[0].clone(),
+                        file: pair[1].clone(),
+                    })
+                    .collect(),
+            }),
+            "delivered" => Record::Delivered(fields.get(1)?.clone(), number(2)?),
+            "read" => Record::Read(fields.get(1)?.clone(), number(2)?),
+            _ => return None,
+        };
+        Some(record)
+    }
+}
+
+// Khai báo một struct để chứa các cuộc trò chuyện (được bảo vệ bởi khóa)
+#[derive(Debug, Default)]
+struct Data {
+    conversations: BTreeMap<u64, Conversation>, // Các cuộc trò chuyện theo mã
+    next: u64,                                  // Mã tiếp theo
+}
+
+// Khai báo một struct để quản lý tin nhắn giữa các doanh nghiệp
+//
+// Mỗi cuộc trò chuyện có một nhật ký chỉ ghi thêm `conversation-<id>.log` trong đối tượng lưu trữ;
+// trạng thái trong bộ nhớ luôn được dựng lại bằng cách áp dụng các bản ghi của nhật ký.
+#[derive(Debug)]
+pub struct Messages {
+    storage: Mutex<Storage>, // Nơi lưu nhật ký các cuộc trò chuyện
+    bus: Arc<Bus>,           // Kênh sự kiện
+    data: RwLock<Data>,      // Các cuộc trò chuyện
+}
+
+// Khai báo các phương thức cho struct Messages
+impl Messages {
+    // Phương thức open để mở dịch vụ tin nhắn và đọc lại nhật ký đã lưu
+    pub fn open(mut storage: Storage, bus: Arc<Bus>) -> io::Result<Messages> {
+        storage.serve()?;
+        let mut data = Data::default();
+        for name in storage.list()? {
+            let Some(id) = name
+                .strip_prefix("conversation-")
+                .and_then(|rest| rest.strip_suffix(".log"))
+                .and_then(|id| id.parse::<u64>().ok())
+            else {
+                continue;
+            };
+            let mut conversation: Option<Conversation> = None;
+            for (number, line) in storage.view(&name)?.lines().enumerate() {
+                let record = Record::parse(line).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Invalid record in {} at line {}", name, number + 1),
+                    )
+                })?;
+                match (&mut conversation, record) {
+                    (None, Record::Open(kind, time)) => conversation = Some(blank(id, kind, time)),
+                    (Some(conversation), record) => apply(conversation, record),
+                    (None, _) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("Log {} does not start with open", name),
+                        ))
+                    }
+                }
+            }
+            if let Some(conversation) = conversation {
+                data.next = data.next.max(id);
+                data.conversations.insert(id, conversation);
+            }
+        }
+        Ok(Messages {
+            storage: Mutex::new(storage),
+            bus,
+            data: RwLock::new(data),
+        })
+    }
+
+    // Phương thức direct để người dùng lấy (hoặc tạo) cuộc trò chuyện riêng giữa doanh nghiệp của mình và một
+    // doanh nghiệp khác (tìm và tạo trong cùng một khóa ghi để không tạo trùng)
+    pub fn direct(&self, principal: &Principal, to: &str) -> io::Result<u64> {
+        let from = business(principal)?;
+        if from == to {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} cannot message itself", from),
+            ));
+        }
+        let mut data = self.data.write().unwrap();
+        let existing = data.conversations.values().find_map(|c| {
+            (c.kind == Kind::Direct && c.members.contains(from) && c.members.contains(to))
+                .then_some(c.id)
+        });
+        match existing {
+            Some(id) => Ok(id),
+            None => self.start(&mut data, Kind::Direct, &[from, to]),
+        }
+    }
+
+    // Phương thức group để người dùng tạo nhóm trò chuyện gồm doanh nghiệp của mình và các thành viên
+    pub fn group(&self, principal: &Principal, name: &str, members: &[&str]) -> io::Result<u64> {
+        let creator = business(principal)?;
+        let mut all = vec![creator];
+        all.extend(members.iter().filter(|member| **member != creator));
+        self.start(
+            &mut self.data.write().unwrap(),
+            Kind::Group(name.to_string()),
+            &all,
+        )
+    }
+
+    // Phương thức get để xem cuộc trò chuyện (chỉ thành viên)
+    pub fn get(&self, principal: &Principal, id: u64) -> io::Result<Conversation> {
+        let data = self.data.read().unwrap();
+        member(&data, id, business(principal)?).cloned()
+    }
+
+    // Phương thức conversations trả về mã các cuộc trò chuyện của doanh nghiệp của người dùng
+    pub fn conversations(&self, principal: &Principal) -> io::Result<Vec<u64>> {
+        let business = business(principal)?;
+        let data = self.data.read().unwrap();
+        Ok(data
+            .conversations
+            .values()
+            .filter(|c| c.members.contains(business))
+            .map(|c| c.id)
+            .collect())
+    }
+
+    // Phương thức invite để thành viên của nhóm mời thêm doanh nghiệp khác
+    pub fn invite(&self, principal: &Principal, id: u64, business: &str) -> io::Result<()> {
+        let by = self::business(principal)?;
+        let mut data = self.data.write().unwrap();
+        let conversation = member(&data, id, by)?;
+        if conversation.kind == Kind::Direct {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Conversation {} is not a group", id),
+            ));
+        }
+        if conversation.members.contains(business) {
+            return Ok(());
+        }
+        self.record(&mut data, id, Record::Join(business.to_string()))?;
+        self.bus.publish(Event::MembersChanged { conversation: id });
+        Ok(())
+    }
+
+    // Phương thức leave để thành viên rời nhóm trò chuyện
+    pub fn leave(&self, principal: &Principal, id: u64) -> io::Result<()> {
+        let business = business(principal)?;
+        let mut data = self.data.write().unwrap();
+        if member(&data, id, business)?.kind == Kind::Direct {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Conversation {} is not a group", id),
+            ));
+        }
+        self.record(&mut data, id, Record::Leave(business.to_string()))?;
+        self.bus.publish(Event::MembersChanged { conversation: id });
+        Ok(())
+    }
+
+    // Phương thức send để gửi tin nhắn, đính kèm các tập tin đã có trong đối tượng lưu trữ bằng tham chiếu
+    pub fn send(
+        &self,
+        principal: &Principal,
+        id: u64,
+        text: &str,
+        files: &[(&Storage, &str)],
+    ) -> io::Result<u64> {
+        let from = business(principal)?;
+        let mut data = self.data.write().unwrap();
+        let seq = member(&data, id, from)?.messages.len() as u64 + 1;
+
+        // Tập tin đính kèm phải tồn tại tại thời điểm gửi
+        let mut attachments = Vec::new();
+        for (storage, file) in files {
+            if !storage.list()?.iter().any(|name| name == file) {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("File {} not found in {}", file, storage.name()),
+                ));
+            }
+            attachments.push(Attachment {
+                owner: storage.name().to_string(),
+                file: file.to_string(),
+            });
+        }
+
+        let message = Message {
+            seq,
+            from: from.to_string(),
+            text: text.to_string(),
+            time: clock::now(),
+            attachments,
+        };
+        self.record(&mut data, id, Record::Message(message))?;
+        self.bus.publish(Event::MessageSent {
+            conversation: id,
+            message: seq,
+            from: from.to_string(),
+        });
+        Ok(seq)
+    }
+
+    // Phương thức deliver để ghi nhận các tin nhắn tới `seq` đã tới thành viên
+    pub fn deliver(&self, principal: &Principal, id: u64, seq: u64) -> io::Result<()> {
+        let business = business(principal)?;
+        let mut data = self.data.write().unwrap();
+        let conversation = member(&data, id, business)?;
+        let seq = seq.min(conversation.messages.len() as u64);
+        if conversation.delivered.get(business).copied().unwrap_or(0) >= seq {
+            return Ok(());
+        }
+        self.record(&mut data, id, Record::Delivered(business.to_string(), seq))?;
+        self.bus.publish(Event::MessageDelivered {
+            conversation: id,
+            member: business.to_string(),
+            upto: seq,
+        });
+        Ok(())
+    }
+
+    // Phương thức read để ghi nhận thành viên đã đọc các tin nhắn tới `seq` (cũng coi là đã tới)
+    pub fn read(&self, principal: &Principal, id: u64, seq: u64) -> io::Result<()> {
+        self.deliver(principal, id, seq)?;
+        let business = business(principal)?;
+        let mut data = self.data.write().unwrap();
+        let conversation = member(&data, id, business)?;
+        let seq = seq.min(conversation.messages.len() as u64);
+        if conversation.read.get(business).copied().unwrap_or(0) >= seq {
+            return Ok(());
+        }
+        self.record(&mut data, id, Record::Read(business.to_string(), seq))?;
+        self.bus.publish(Event::MessageRead {
+            conversation: id,
+            member: business.to_string(),
+            upto: seq,
+        });
+        Ok(())
+    }
+
+    // Phương thức receipt trả về các thành viên (trừ người gửi) đã nhận và đã đọc một tin nhắn
+    pub fn receipt(&self, principal: &Principal, id: u64, seq: u64) -> io::Result<Receipt> {
+        let data = self.data.read().unwrap();
+        let conversation = member(&data, id, business(principal)?)?;
+        let message = conversation
+            .messages
+            .get((seq as usize).wrapping_sub(1))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("Message {} not found in conversation {}", seq, id),
+                )
+            })?;
+        let reached = |marks: &BTreeMap<String, u64>| {
+            conversation
+                .members
+                .iter()
+                .filter(|m| **m != message.from && marks.get(*m).is_some_and(|s| *s >= seq))
+                .cloned()
+                .collect()
+        };
+        Ok(Receipt {
+            delivered: reached(&conversation.delivered),
+            read: reached(&conversation.read),
+        })
+    }
+
+    // Phương thức unread trả về số tin nhắn của người khác mà thành viên chưa đọc
+    pub fn unread(&self, principal: &Principal, id: u64) -> io::Result<usize> {
+        let business = business(principal)?;
+        let data = self.data.read().unwrap();
+        let conversation = member(&data, id, business)?;
+        let read = conversation.read.get(business).copied().unwrap_or(0);
+        Ok(conversation
+            .messages
+            .iter()
+            .filter(|m| m.seq > read && m.from != business)
+            .count())
+    }
+
+    // Phương thức typing để báo cho các thành viên khác rằng một thành viên đang soạn tin (không lưu lại)
+    pub fn typing(&self, principal: &Principal, id: u64) -> io::Result<()> {
+        let business = business(principal)?;
+        member(&self.data.read().unwrap(), id, business)?;
+        self.bus.publish(Event::Typing {
+            conversation: id,
+            member: business.to_string(),
+        });
+        Ok(())
+    }
+
+    // Phương thức fetch để thành viên tải tập tin đính kèm từ đối tượng lưu trữ chứa nó
+    pub fn fetch(
+        &self,
+        principal: &Principal,
+        id: u64,
+        seq: u64,
+        index: usize,
+        storage: &Storage,
+        dest: &Path,
+    ) -> io::Result<()> {
+        let data = self.data.read().unwrap();
+        let attachment = member(&data, id, business(principal)?)?
+            .messages
+            .get((seq as usize).wrapping_sub(1))
+            .and_then(|message| message.attachments.get(index))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("Attachment {} of message {} not found", index, seq),
+                )
+            })?;
+        if attachment.owner != storage.name() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Attachment is stored in {}", attachment.owner),
+            ));
+        }
+        storage.download(&attachment.file, dest)
+    }
+
+    // Phương thức start để tạo cuộc trò chuyện mới và ghi nhật ký của nó (người gọi giữ khóa ghi)
+    fn start(&self, data: &mut Data, kind: Kind, members: &[&str]) -> io::Result<u64> {
+        data.next += 1;
+        let id = data.next;
+        let time = clock::now();
+        let mut records = vec![Record::Open(kind.clone(), time)];
+        records.extend(members.iter().map(|m| Record::Join(m.to_string())));
+        let text: String = records.iter().map(Record::render).collect();
+        self.storage
+            .lock()
+            .unwrap()
+            .append(&log(id), text.as_bytes())?;
+
+        let mut conversation = blank(id, kind, time);
+        for record in records.into_iter().skip(1) {
+            apply(&mut conversation, record);
+        }
+        data.conversations.insert(id, conversation);
+        self.bus.publish(Event::ConversationOpened {
+            conversation: id,
+            members: members.iter().map(|m| m.to_string()).collect(),
+        });
+        Ok(id)
+    }
+
+    // Phương thức record để ghi bản ghi vào nhật ký rồi áp dụng nó vào bộ nhớ
+    fn record(&self, data: &mut Data, id: u64, record: Record) -> io::Result<()> {
+        self.storage
+            .lock()
+            .unwrap()
+            .append(&log(id), record.render().as_bytes())?;
+        if let Some(conversation) = data.conversations.get_mut(&id) {
+            apply(conversation, record);
+        }
+        Ok(())
+    }
+}
+
+// Hàm blank tạo cuộc trò chuyện chưa có thành viên và tin nhắn
+fn blank(id: u64, kind: Kind, time: u64) -> Conversation {
+    Conversation {
+        id,
+        kind,
+        members: BTreeSet::new(),
+        messages: Vec::new(),
+        delivered: BTreeMap::new(),
+        read: BTreeMap::new(),
+        time,
+    }
+}
+
+// Hàm apply áp dụng một bản ghi vào cuộc trò chuyện
+fn apply(conversation: &mut Conversation, record: Record) {
+    match record {
+        Record::Open(..) => {}
+        Record::Join(business) => {
+            conversation.members.insert(business);
+        }
+        Record::Leave(business) => {
+            conversation.members.remove(&business);
+        }
+        Record::Message(message) => conversation.messages.push(message),
+        Record::Delivered(business, seq) => {
+            let mark = conversation.delivered.entry(business).or_default();
+            *mark = (*mark).max(seq);
+        }
+        Record::Read(business, seq) => {
+            let mark = conversation.read.entry(business).or_default();
+            *mark = (*mark).max(seq);
+        }
+    }
+}
+
+// Hàm business trả về doanh nghiệp của người dùng nếu phiên đăng nhập còn hiệu lực
+pub(crate) fn business(principal: &Principal) -> io::Result<&str> {
+    if !principal.active(clock::now()) {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("Session of {} has expired", principal.name()),
+        ));
+    }
+    Ok(principal.business())
+}
+
+// Hàm member trả về cuộc trò chuyện nếu doanh nghiệp là thành viên (không phải thành viên coi như không tồn tại)
+fn member<'a>(data: &'a Data, id: u64, business: &str) -> io::Result<&'a Conversation> {
+    data.conversations
+        .get(&id)
+        .filter(|c| c.members.contains(business))
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Conversation {} not found", id),
+            )
+        })
+}
+
+// Hàm log trả về tên tập tin nhật ký của cuộc trò chuyện
+fn log(id: u64) -> String {
+    format!("conversation-{}.log", id)
+}
+
+// Hàm escape thay các ký tự phân cách để một trường nằm gọn trên một dòng
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+// Hàm unescape khôi phục trường đã được escape
+pub(crate) fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => result.push('\t'),
+            Some('n') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::auth::role::Role;
+    use crate::auth::tests::principal;
+    use crate::storage::tests::{create_temp_storage, reopen, tempdir};
+    use crate::storage::Access;
+    use std::fs;
+
+    // Khai báo một hàm để kiểm tra trò chuyện riêng, xác nhận đã nhận/đã đọc và sự kiện
+    #[test]
+    fn test_direct() {
+        let bus = Arc::new(Bus::new());
+        let events = bus.subscribe();
+        let storage = create_temp_storage("messages", Access::Private).unwrap();
+        let path = storage.path().to_path_buf();
+        let messages = Messages::open(storage, bus.clone()).unwrap();
+
+        // Cùng một cặp doanh nghiệp luôn dùng chung một cuộc trò chuyện
+        let id = messages.direct(&principal("alpha"), "beta").unwrap();
+        assert_eq!(messages.direct(&principal("beta"), "alpha").unwrap(), id);
+        assert!(messages.direct(&principal("alpha"), "alpha").is_err());
+        assert!(messages.send(&principal("gamma"), id, "Hi", &[]).is_err());
+        assert!(messages.invite(&principal("alpha"), id, "gamma").is_err());
+
+        // Phiên đã hết hạn không được gửi tin dù là thành viên
+        let expired = Principal::new(0, "alice", "alpha", Role::Owner, 0);
+        assert!(messages.send(&expired, id, "Hi", &[]).is_err());
+        assert!(messages.direct(&expired, "beta").is_err());
+
+        // Gửi tin, báo đang soạn tin, rồi ghi nhận đã nhận và đã đọc
+        messages
+            .send(&principal("alpha"), id, "Hello\tthere\nfriend", &[])
+            .unwrap();
+        messages.typing(&principal("beta"), id).unwrap();
+        let seq = messages.send(&principal("beta"), id, "Hi!", &[]).unwrap();
+        assert_eq!(seq, 2);
+        assert_eq!(messages.unread(&principal("alpha"), id).unwrap(), 1);
+        messages.deliver(&principal("beta"), id, 1).unwrap();
+        assert_eq!(
+            messages.receipt(&principal("alpha"), id, 1).unwrap(),
+            Receipt {
+                delivered: vec!["beta".to_string()],
+                read: vec![],
+            }
+        );
+        messages.read(&principal("alpha"), id, 5).unwrap();
+        assert_eq!(messages.unread(&principal("alpha"), id).unwrap(), 0);
+        assert_eq!(
+            messages.receipt(&principal("beta"), id, 2).unwrap().read,
+            ["alpha"]
+        );
+        assert!(messages.receipt(&principal("beta"), id, 3).is_err());
+
+        // Mỗi hành động công bố một sự kiện; đánh dấu lùi lại không ghi thêm gì
+        messages.deliver(&principal("beta"), id, 1).unwrap();
+        let events: Vec<Event> = events.try_iter().collect();
+        assert_eq!(events.len(), 7);
+        assert!(events.contains(&Event::Typing {
+            conversation: id,
+            member: "beta".to_string()
+        }));
+        assert!(events.contains(&Event::MessageRead {
+            conversation: id,
+            member: "alpha".to_string(),
+            upto: 2
+        }));
+
+        // Xoá thư mục tạm thời
+        drop(messages);
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra nhóm trò chuyện, tập tin đính kèm và đọc lại nhật ký
+    #[test]
+    fn test_group() {
+        let bus = Arc::new(Bus::new());
+        let storage = create_temp_storage("messages", Access::Private).unwrap();
+        let path = storage.path().to_path_buf();
+        let messages = Messages::open(storage, bus.clone()).unwrap();
+
+        // Tập tin đính kèm nằm trong đối tượng lưu trữ của alpha
+        let mut files = create_temp_storage("alpha", Access::Private).unwrap();
+        files.put("quote.pdf", b"%PDF").unwrap();
+
+        // Nhóm ba thành viên; gamma được mời sau, beta rời nhóm
+        let id = messages
+            .group(&principal("alpha"), "Suppliers", &["beta"])
+            .unwrap();
+        assert!(messages
+            .send(&principal("alpha"), id, "Quote", &[(&files, "missing.pdf")])
+            .is_err());
+        messages
+            .send(&principal("alpha"), id, "Quote", &[(&files, "quote.pdf")])
+            .unwrap();
+        messages.invite(&principal("beta"), id, "gamma").unwrap();
+        messages.read(&principal("gamma"), id, 1).unwrap();
+        messages.leave(&principal("beta"), id).unwrap();
+        assert!(messages.get(&principal("beta"), id).is_err());
+        assert_eq!(messages.conversations(&principal("gamma")).unwrap(), [id]);
+
+        // Thành viên tải tập tin đính kèm từ nơi lưu gốc (không có bản sao)
+        let dir = tempdir().unwrap();
+        let dest = dir.join("quote.pdf");
+        messages
+            .fetch(&principal("gamma"), id, 1, 0, &files, &dest)
+            .unwrap();
+        assert_eq!(fs::read(&dest).unwrap(), b"%PDF");
+        let other = dir.join("other.pdf");
+        assert!(messages
+            .fetch(&principal("beta"), id, 1, 0, &files, &other)
+            .is_err());
+        assert!(messages
+            .fetch(&principal("gamma"), id, 1, 1, &files, &other)
+            .is_err());
+
+        // Mở lại từ nhật ký cho ra đúng trạng thái cũ, và mã mới tiếp tục tăng
+        let before = messages.get(&principal("alpha"), id).unwrap();
+        drop(messages);
+        let messages = Messages::open(reopen(&path).unwrap(), bus).unwrap();
+        assert_eq!(messages.get(&principal("alpha"), id).unwrap(), before);
+        assert_eq!(
+            before.members,
+            BTreeSet::from(["alpha".to_string(), "gamma".to_string()])
+        );
+        assert_eq!(before.messages[0].attachments[0].owner, files.name());
+        assert_eq!(
+            messages.direct(&principal("alpha"), "gamma").unwrap(),
+            id + 1
+        );
+
+        // Xoá các thư mục tạm thời
+        drop(messages);
+        fs::remove_dir_all(path).unwrap();
+        fs::remove_dir_all(files.path()).unwrap();
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra escape và đọc lại bản ghi
+    #[test]
+    fn test_record() {
+        let record = Record::Message(Message {
+            seq: 3,
+            from: "a\\b".to_string(),
+            text: "x\ty\nz\\".to_string(),
+            time: 7,
+            attachments: vec![Attachment {
+                owner: "o".to_string(),
+                file: "f\t1".to_string(),
+            }],
+        });
+        let line = record.render();
+        assert_eq!(line.matches('\n').count(), 1);
+        assert_eq!(Record::parse(line.trim_end_matches('\n')), Some(record));
+        assert_eq!(Record::parse("unknown\t1"), None);
+        assert_eq!(Record::parse("message\t1\ta"), None);
+    }
+}
@@ -0,0 +1,305 @@
+// Khai báo các thư viện cần thiết
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use super::snapshot::{self, Manifest, Retention};
+use super::tar::{self, Writer};
+use super::vault::Key;
+use super::{pack, scrub};
+use crate::crypto::{hex, random};
+use crate::social::message::{escape, unescape};
+
+// Khai báo tên các mục trong một bản sao lưu: danh mục (đầu tiên), bản kê hiện tại, bản kê các ảnh chụp
+// (`rim/snapshots/<số thứ tự>`) và nội dung các tập tin theo SHA-256 (`rim/objects/<mã>`, mỗi nội dung một lần)
+const CATALOG: &str = "rim/catalog";
+const MANIFEST: &str = "rim/manifest";
+const SNAPSHOTS: &str = "rim/snapshots/";
+const OBJECTS: &str = "rim/objects/";
+
+// Khai báo thư mục tạm chứa nội dung đã kiểm tra khi nhập bản sao lưu
+const STAGING: &str = ".tmp/import";
+
+// Khai báo một struct để đại diện cho danh mục của một bản sao lưu: bản sao lưu tăng dần chỉ chứa nội dung chưa
+// có trong bản sao lưu gốc, còn danh mục luôn liệt kê mọi nội dung có trong cả chuỗi sao lưu
+#[derive(Debug, Clone, PartialEq)]
+pub struct Catalog {
+    pub id: String,                // Mã ngẫu nhiên của bản sao lưu
+    pub storage: String,           // Tên đối tượng lưu trữ được sao lưu
+    pub time: u64,                 // Thời điểm sao lưu
+    pub base: Option<String>,      // Mã của bản sao lưu gốc (nếu là bản sao lưu tăng dần)
+    pub compressed: bool,          // Đối tượng lưu trữ có bật nén hay không
+    pub retention: Retention,      // Chính sách giữ ảnh chụp
+    pub objects: BTreeSet<String>, // SHA-256 của mọi nội dung trong chuỗi sao lưu
+}
+
+// Khai báo các phương thức cho struct Catalog
+impl Catalog {
+    // Phương thức incremental kiểm tra đây có phải là bản sao lưu tăng dần hay không
+    pub fn incremental(&self) -> bool {
+        self.base.is_some()
+    }
+
+    // Phương thức render để ghi danh mục thành các dòng phân cách bằng tab
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "catalog\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.id,
+            escape(&self.storage),
+            self.time,
+            self.base.as_deref().unwrap_or("-"),
+            self.compressed,
+            self.retention.render()
+        );
+        for object in &self.objects {
+            out.push_str(&format!("object\t{}\n", object));
+        }
+        out
+    }
+
+    // Phương thức parse để đọc lại danh mục do render tạo ra
+    pub fn parse(text: &str) -> io::Result<Catalog> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Invalid backup catalog");
+        let mut lines = text.lines();
+        let head: Vec<&str> = lines.next().ok_or_else(invalid)?.split('\t').collect();
+        if head.len() != 8 || head[0] != "catalog" {
+            return Err(invalid());
+        }
+        let mut catalog = Catalog {
+            id: head[1].to_string(),
+            storage: unescape(head[2]),
+            time: head[3].parse().map_err(|_| invalid())?,
+            base: Some(head[4].to_string()).filter(|base| base != "-"),
+            compressed: head[5] == "true",
+            retention: Retention::parse(&head[6..].join("\t"))?,
+            objects: BTreeSet::new(),
+        };
+        for line in lines {
+            match line.split_once('\t') {
+                Some(("object", object)) if valid(object) => {
+                    catalog.objects.insert(object.to_string());
+                }
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(catalog)
+    }
+}
+
+// Hàm catalog đọc danh mục của một bản sao lưu (mục đầu tiên của luồng tar)
+pub fn catalog(archive: impl Read) -> io::Result<Catalog> {
+    match tar::Reader::new(archive).entry()? {
+        Some((name, content)) if name == CATALOG => {
+            Catalog::parse(&String::from_utf8_lossy(&content))
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Archive is not a storage backup",
+        )),
+    }
+}
+
+// Hàm export ghi bản kê hiện tại, các ảnh chụp và nội dung của chúng vào một luồng tar; nội dung đã có trong
+// bản sao lưu gốc được bỏ qua, nội dung được đọc ra (đã giải nén và giải mã) phải khớp mã kiểm tra trong bản kê
+pub(crate) fn export(
+    path: &Path,
+    key: Option<&Key>,
+    out: impl Write,
+    manifest: &Manifest,
+    catalog: &Catalog,
+    base: Option<&Catalog>,
+) -> io::Result<usize> {
+    let snapshots = snapshot::list(path)?;
+    let mut archive = Writer::new(out);
+    archive.file(CATALOG, catalog.time, catalog.render().as_bytes())?;
+    archive.file(MANIFEST, catalog.time, manifest.render().as_bytes())?;
+    for (index, snapshot) in snapshots.iter().enumerate() {
+        let name = format!("{}{}", SNAPSHOTS, index);
+        archive.file(&name, snapshot.time, snapshot.render().as_bytes())?;
+    }
+
+    // Ghi mỗi nội dung chưa có một lần, đọc từ đối tượng lưu trữ hoặc từ ảnh chụp chứa nó
+    let mut written = BTreeSet::new();
+    let sources =
+        std::iter::once((path.to_path_buf(), manifest)).chain(snapshots.iter().map(|snapshot| {
+            (
+                path.join(snapshot::SNAPSHOTS).join(&snapshot.name),
+                snapshot,
+            )
+        }));
+    for (dir, manifest) in sources {
+        for entry in &manifest.entries {
+            if base.is_some_and(|base| base.objects.contains(&entry.hash))
+                || written.contains(&entry.hash)
+            {
+                continue;
+            }
+            let content = pack::read(&dir, key, &entry.name)?;
+            if scrub::digest(&content[..])?.hash != entry.hash {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Checksum mismatch for {}; run verify before backing up",
+                        entry.name
+                    ),
+                ));
+            }
+            archive.file(
+                &format!("{}{}", OBJECTS, entry.hash),
+                manifest.time,
+                &content,
+            )?;
+            written.insert(entry.hash.clone());
+        }
+    }
+    archive.finish()?;
+    Ok(written.len())
+}
+
+// Hàm objects trả về SHA-256 của mọi nội dung được tham chiếu bởi bản kê hiện tại và các ảnh chụp
+pub(crate) fn objects(path: &Path, manifest: &Manifest) -> io::Result<BTreeSet<String>> {
+    let mut objects: BTreeSet<String> = manifest
+        .entries
+        .iter()
+        .map(|entry| entry.hash.clone())
+        .collect();
+    for snapshot in snapshot::list(path)? {
+        objects.extend(snapshot.entries.into_iter().map(|entry| entry.hash));
+    }
+    Ok(objects)
+}
+
+// Hàm id tạo mã ngẫu nhiên cho một bản sao lưu
+pub(crate) fn id() -> io::Result<String> {
+    let mut bytes = [0; 8];
+    random(&mut bytes)?;
+    Ok(hex(&bytes))
+}
+
+// Hàm import dựng lại một đối tượng lưu trữ (chưa mã hoá) từ một bản sao lưu đầy đủ và các bản sao lưu tăng dần
+// tiếp theo (theo thứ tự): nội dung được kiểm tra SHA-256 khi đọc, các ảnh chụp được dựng lại từ cũ đến mới rồi
+// tới trạng thái của bản sao lưu cuối cùng; trả về danh mục và bản kê của bản sao lưu cuối cùng
+pub(crate) fn import(
+    path: &Path,
+    archives: &mut [&mut dyn Read],
+) -> io::Result<(Catalog, Manifest)> {
+    let staging = path.join(STAGING);
+    fs::create_dir_all(&staging)?;
+    let mut last: Option<(Catalog, Manifest, Vec<Manifest>)> = None;
+    for archive in archives.iter_mut() {
+        let mut reader = tar::Reader::new(archive);
+        let mut catalog = None;
+        let mut manifest = None;
+        let mut snapshots = BTreeMap::new();
+        while let Some((name, content)) = reader.entry()? {
+            if name == CATALOG {
+                let next = Catalog::parse(&String::from_utf8_lossy(&content))?;
+                chain(last.as_ref().map(|(catalog, _, _)| catalog), &next)?;
+                catalog = Some(next);
+            } else if catalog.is_none() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Archive is not a storage backup",
+                ));
+            } else if name == MANIFEST {
+                manifest = Some(Manifest::parse(&String::from_utf8_lossy(&content))?);
+            } else if let Some(index) = name.strip_prefix(SNAPSHOTS) {
+                let index: usize = index.parse().map_err(|_| corrupt(&name))?;
+                snapshots.insert(index, Manifest::parse(&String::from_utf8_lossy(&content))?);
+            } else if let Some(hash) = name.strip_prefix(OBJECTS) {
+                if !valid(hash) || scrub::digest(&content[..])?.hash != hash {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Checksum mismatch for backup object {}", hash),
+                    ));
+                }
+                fs::write(staging.join(hash), content)?;
+            }
+        }
+        let catalog = catalog.ok_or_else(|| corrupt(CATALOG))?;
+        let manifest = manifest.ok_or_else(|| corrupt(MANIFEST))?;
+        last = Some((catalog, manifest, snapshots.into_values().collect()));
+    }
+    let (catalog, manifest, snapshots) = last
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No backup archives given"))?;
+
+    // Dựng lại các ảnh chụp rồi trạng thái hiện tại
+    if catalog.compressed {
+        pack::enable(path)?;
+    }
+    for mut snapshot in snapshots {
+        snapshot::check(path, &snapshot.name)?;
+        snapshot.encrypted = false;
+        fill(path, &staging, &snapshot)?;
+        snapshot::take(path, &snapshot)?;
+    }
+    fill(path, &staging, &manifest)?;
+    snapshot::set_retention(path, &catalog.retention)?;
+    fs::remove_dir_all(path.join(".tmp"))?;
+    Ok((catalog, manifest))
+}
+
+// Hàm chain kiểm tra một bản sao lưu nối tiếp đúng bản sao lưu trước đó trong chuỗi
+fn chain(previous: Option<&Catalog>, next: &Catalog) -> io::Result<()> {
+    let expected = previous.map(|previous| previous.id.as_str());
+    if next.base.as_deref() != expected
+        || previous.is_some_and(|previous| previous.storage != next.storage)
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            match expected {
+                Some(expected) => format!("Backup {} does not follow backup {}", next.id, expected),
+                None => format!(
+                    "Backup {} is incremental; start from a full backup",
+                    next.id
+                ),
+            },
+        ));
+    }
+    Ok(())
+}
+
+// Hàm fill thay các tập tin của đối tượng lưu trữ bằng các tập tin trong bản kê, lấy nội dung từ thư mục tạm
+fn fill(path: &Path, staging: &Path, manifest: &Manifest) -> io::Result<()> {
+    snapshot::clear(path)?;
+    for entry in &manifest.entries {
+        if entry.name.is_empty() || entry.name.starts_with('.') || entry.name.contains(['/', '\\'])
+        {
+            return Err(corrupt(&entry.name));
+        }
+        let object = staging.join(&entry.hash);
+        if !valid(&entry.hash) || !object.is_file() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Content of {} is missing from the backup", entry.name),
+            ));
+        }
+        let content = fs::read(object)?;
+        if content.len() as u64 != entry.size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Size mismatch for {}", entry.name),
+            ));
+        }
+        pack::write(path, None, &entry.name, &content)?;
+        if let Some(access) = entry.access {
+            fs::create_dir_all(path.join(".access"))?;
+            fs::write(path.join(".access").join(&entry.name), access.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+// Hàm valid kiểm tra một chuỗi là SHA-256 dạng thập lục phân
+fn valid(hash: &str) -> bool {
+    hash.len() == 64 && hash.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+// Hàm corrupt tạo lỗi cho một mục không hợp lệ trong bản sao lưu
+fn corrupt(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Invalid backup entry: {}", name),
+    )
+}
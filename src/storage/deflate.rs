@@ -0,0 +1,834 @@
+// Khai báo các thư viện cần thiết
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::io;
+
+// Khai báo kích thước cửa sổ LZ77 (khoảng cách xa nhất của một đoạn khớp) và độ dài đoạn khớp của DEFLATE
+const WINDOW: usize = 32 * 1024;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+
+// Khai báo kích thước bảng băm ba byte và số bước tối đa khi dò chuỗi băm (đánh đổi tốc độ lấy tỉ lệ nén)
+const HASH: usize = 1 << 15;
+const CHAIN: usize = 128;
+
+// Khai báo số ký hiệu tối đa trong một khối (mỗi khối có bảng mã Huffman riêng)
+const BLOCK: usize = 16 * 1024;
+
+// Khai báo giá trị đánh dấu một ô trống trong bảng băm
+const NONE: usize = usize::MAX;
+
+// Khai báo độ dài cơ sở và số bit thêm của các mã độ dài 257..285 (RFC 1951, mục 3.2.5)
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+// Khai báo khoảng cách cơ sở và số bit thêm của các mã khoảng cách 0..29
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+// Khai báo thứ tự ghi độ dài mã của bảng mã độ dài mã trong khối Huffman động
+const ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+// Khai báo phần đầu của một thành phần gzip: phương thức DEFLATE, không cờ, không thời gian, hệ điều hành
+// không xác định (RFC 1952)
+const GZIP: [u8; 10] = [0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 255];
+
+// Khai báo bảng tra CRC-32 (đa thức 0xedb88320) được tính lúc biên dịch
+const CRC: [u32; 256] = crc_table();
+
+// Hàm crc_table tạo bảng tra CRC-32
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                0xedb88320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+}
+
+// Hàm crc32 tính mã kiểm tra CRC-32 của dữ liệu (dùng trong phần cuối của gzip)
+pub fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0, |c: u32, &b| {
+        CRC[((c ^ b as u32) & 0xff) as usize] ^ (c >> 8)
+    })
+}
+
+// Hàm gzip nén dữ liệu thành một thành phần gzip (đọc được bằng `gzip -d`)
+pub fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut out = GZIP.to_vec();
+    out.extend(compress(data));
+    out.extend_from_slice(&crc32(data).to_le_bytes());
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out
+}
+
+// Hàm gunzip giải nén dữ liệu gzip; nhiều thành phần nối tiếp nhau được giải nén thành một
+pub fn gunzip(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        pos += member(&data[pos..], &mut out)?;
+    }
+    Ok(out)
+}
+
+// Hàm member giải nén một thành phần gzip vào cuối `out` và trả về số byte đã đọc
+fn member(data: &[u8], out: &mut Vec<u8>) -> io::Result<usize> {
+    if data.len() < 18 || data[..3] != GZIP[..3] {
+        return Err(corrupt("not a gzip member"));
+    }
+
+    // Bỏ qua các trường tuỳ chọn của phần đầu theo cờ
+    let flags = data[3];
+    let mut pos = GZIP.len();
+    if flags & 4 != 0 {
+        let extra = u16::from_le_bytes([data[pos], data[pos + 1]]) as usize;
+        pos += 2 + extra;
+    }
+    for flag in [8, 16] {
+        if flags & flag != 0 {
+            let end = data[pos.min(data.len())..]
+                .iter()
+                .position(|&b| b == 0)
+                .ok_or_else(|| corrupt("unterminated header field"))?;
+            pos += end + 1;
+        }
+    }
+    if flags & 2 != 0 {
+        pos += 2;
+    }
+
+    // Giải nén rồi so với mã kiểm tra và độ dài ở phần cuối
+    let (content, used) = inflate_from(data.get(pos..).unwrap_or_default())?;
+    pos += used;
+    let trailer = data
+        .get(pos..pos + 8)
+        .ok_or_else(|| corrupt("missing gzip trailer"))?;
+    if trailer[..4] != crc32(&content).to_le_bytes() {
+        return Err(corrupt("checksum does not match"));
+    }
+    if trailer[4..] != (content.len() as u32).to_le_bytes() {
+        return Err(corrupt("length does not match"));
+    }
+    out.extend(content);
+    Ok(pos + 8)
+}
+
+// Khai báo một enum để đại diện cho một ký hiệu LZ77: một byte hoặc một đoạn khớp (độ dài, khoảng cách)
+#[derive(Debug, Clone, Copy)]
+enum Token {
+    Literal(u8),
+    Match(u16, u16),
+}
+
+// Hàm compress nén dữ liệu thành một luồng DEFLATE (RFC 1951): LZ77 với bảng băm rồi mã Huffman động cho
+// từng khối, hoặc khối lưu nguyên nếu nén không có lợi
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let tokens = tokens(data);
+    let mut bits = Bits::default();
+    if tokens.is_empty() {
+        // Khối cuối dùng mã cố định chỉ chứa ký hiệu kết thúc
+        bits.put(1, 1);
+        bits.put(1, 2);
+        bits.put(0, 7);
+        bits.align();
+        return bits.out;
+    }
+    let count = tokens.len().div_ceil(BLOCK);
+    let mut start = 0;
+    for (i, block) in tokens.chunks(BLOCK).enumerate() {
+        let len: usize = block
+            .iter()
+            .map(|token| match token {
+                Token::Literal(_) => 1,
+                Token::Match(len, _) => *len as usize,
+            })
+            .sum();
+        write_block(&mut bits, block, &data[start..start + len], i + 1 == count);
+        start += len;
+    }
+    bits.align();
+    bits.out
+}
+
+// Hàm tokens tìm các đoạn khớp trong cửa sổ trước đó (tham lam, dò tối đa CHAIN ứng viên mỗi vị trí)
+fn tokens(data: &[u8]) -> Vec<Token> {
+    let mut head = vec![NONE; HASH];
+    let mut prev = vec![NONE; WINDOW];
+    let mut tokens = Vec::new();
+    let insert = |head: &mut Vec<usize>, prev: &mut Vec<usize>, i: usize| {
+        if i + MIN_MATCH <= data.len() {
+            let h = hash(data, i);
+            prev[i % WINDOW] = head[h];
+            head[h] = i;
+        }
+    };
+    let mut i = 0;
+    while i < data.len() {
+        let (mut best, mut distance) = (0, 0);
+        if i + MIN_MATCH <= data.len() {
+            let limit = (data.len() - i).min(MAX_MATCH);
+            let mut candidate = head[hash(data, i)];
+            let mut chain = CHAIN;
+            while candidate != NONE && i - candidate <= WINDOW && chain > 0 {
+                if data[candidate + best] == data[i + best] {
+                    let len = data[candidate..candidate + limit]
+                        .iter()
+                        .zip(&data[i..i + limit])
+                        .take_while(|(a, b)| a == b)
+                        .count();
+                    if len > best {
+                        best = len;
+                        distance = i - candidate;
+                        if len == limit {
+                            break;
+                        }
+                    }
+                }
+
+                // Ô của ứng viên đã bị một vị trí mới hơn ghi đè thì chuỗi kết thúc ở đây
+                let next = prev[candidate % WINDOW];
+                if next == NONE || next >= candidate {
+                    break;
+                }
+                candidate = next;
+                chain -= 1;
+            }
+        }
+        if best >= MIN_MATCH {
+            tokens.push(Token::Match(best as u16, distance as u16));
+            for j in i..i + best {
+                insert(&mut head, &mut prev, j);
+            }
+            i += best;
+        } else {
+            tokens.push(Token::Literal(data[i]));
+            insert(&mut head, &mut prev, i);
+            i += 1;
+        }
+    }
+    tokens
+}
+
+// Hàm hash băm ba byte bắt đầu từ vị trí i
+fn hash(data: &[u8], i: usize) -> usize {
+    ((data[i] as usize) << 10 ^ (data[i + 1] as usize) << 5 ^ data[i + 2] as usize) & (HASH - 1)
+}
+
+// Hàm length_code trả về chỉ số mã độ dài (0..28, tức mã 257..285) của một độ dài
+fn length_code(len: u16) -> usize {
+    (0..LENGTH_BASE.len())
+        .rev()
+        .find(|&i| LENGTH_BASE[i] <= len)
+        .unwrap()
+}
+
+// Hàm distance_code trả về mã khoảng cách (0..29) của một khoảng cách
+fn distance_code(distance: u16) -> usize {
+    (0..DISTANCE_BASE.len())
+        .rev()
+        .find(|&i| DISTANCE_BASE[i] <= distance)
+        .unwrap()
+}
+
+// Hàm write_block ghi một khối bằng mã Huffman động, hoặc lưu nguyên nếu ngắn hơn
+fn write_block(bits: &mut Bits, block: &[Token], raw: &[u8], last: bool) {
+    // Đếm tần suất các ký hiệu rồi dựng bảng mã (ký hiệu 256 là kết thúc khối)
+    let mut literals = [0u32; 286];
+    let mut distances = [0u32; 30];
+    for token in block {
+        match *token {
+            Token::Literal(byte) => literals[byte as usize] += 1,
+            Token::Match(len, distance) => {
+                literals[257 + length_code(len)] += 1;
+                distances[distance_code(distance)] += 1;
+            }
+        }
+    }
+    literals[256] = 1;
+    let literal_lengths = lengths(&literals, 15);
+    let distance_lengths = lengths(&distances, 15);
+    let hlit = used(&literal_lengths).max(257);
+    let hdist = used(&distance_lengths).max(1);
+
+    // Nén độ dài mã của hai bảng bằng các mã lặp 16, 17, 18
+    let mut all = literal_lengths[..hlit].to_vec();
+    all.extend_from_slice(&distance_lengths[..hdist]);
+    let runs = runs(&all);
+    let mut frequencies = [0u32; 19];
+    for &(symbol, _) in &runs {
+        frequencies[symbol as usize] += 1;
+    }
+    let code_lengths = lengths(&frequencies, 7);
+    let hclen = ORDER
+        .iter()
+        .rposition(|&symbol| code_lengths[symbol] != 0)
+        .map_or(4, |i| (i + 1).max(4));
+
+    // So độ dài khối động với khối lưu nguyên (mỗi khối lưu nguyên chứa tối đa 65535 byte)
+    let extra = |symbol: u8| match symbol {
+        16 => 2,
+        17 => 3,
+        18 => 7,
+        _ => 0,
+    };
+    let mut cost = 17 + 3 * hclen as u64;
+    for &(symbol, _) in &runs {
+        cost += code_lengths[symbol as usize] as u64 + extra(symbol);
+    }
+    for (symbol, &frequency) in literals.iter().enumerate() {
+        let extra = if symbol > 256 {
+            LENGTH_EXTRA[symbol - 257] as u64
+        } else {
+            0
+        };
+        cost += frequency as u64 * (literal_lengths[symbol] as u64 + extra);
+    }
+    for (symbol, &frequency) in distances.iter().enumerate() {
+        cost +=
+            frequency as u64 * (distance_lengths[symbol] as u64 + DISTANCE_EXTRA[symbol] as u64);
+    }
+    let pieces = raw.len().div_ceil(65535).max(1);
+    if 8 * raw.len() as u64 + 48 * pieces as u64 <= cost {
+        stored(bits, raw, last);
+        return;
+    }
+
+    // Ghi phần đầu của khối động
+    bits.put(last as u32, 1);
+    bits.put(2, 2);
+    bits.put((hlit - 257) as u32, 5);
+    bits.put((hdist - 1) as u32, 5);
+    bits.put((hclen - 4) as u32, 4);
+    for &symbol in &ORDER[..hclen] {
+        bits.put(code_lengths[symbol] as u32, 3);
+    }
+    let codes_of_lengths = codes(&code_lengths);
+    for &(symbol, value) in &runs {
+        let symbol = symbol as usize;
+        bits.put(codes_of_lengths[symbol] as u32, code_lengths[symbol] as u32);
+        bits.put(value as u32, extra(symbol as u8) as u32);
+    }
+
+    // Ghi các ký hiệu rồi ký hiệu kết thúc khối
+    let literal_codes = codes(&literal_lengths);
+    let distance_codes = codes(&distance_lengths);
+    for token in block {
+        match *token {
+            Token::Literal(byte) => {
+                let byte = byte as usize;
+                bits.put(literal_codes[byte] as u32, literal_lengths[byte] as u32);
+            }
+            Token::Match(len, distance) => {
+                let l = length_code(len);
+                bits.put(
+                    literal_codes[257 + l] as u32,
+                    literal_lengths[257 + l] as u32,
+                );
+                bits.put((len - LENGTH_BASE[l]) as u32, LENGTH_EXTRA[l] as u32);
+                let d = distance_code(distance);
+                bits.put(distance_codes[d] as u32, distance_lengths[d] as u32);
+                bits.put(
+                    (distance - DISTANCE_BASE[d]) as u32,
+                    DISTANCE_EXTRA[d] as u32,
+                );
+            }
+        }
+    }
+    bits.put(literal_codes[256] as u32, literal_lengths[256] as u32);
+}
+
+// Hàm stored ghi dữ liệu thành các khối lưu nguyên
+fn stored(bits: &mut Bits, raw: &[u8], last: bool) {
+    let pieces: Vec<&[u8]> = if raw.is_empty() {
+        vec![raw]
+    } else {
+        raw.chunks(65535).collect()
+    };
+    for (i, piece) in pieces.iter().enumerate() {
+        bits.put((last && i + 1 == pieces.len()) as u32, 1);
+        bits.put(0, 2);
+        bits.align();
+        let len = piece.len() as u16;
+        bits.out.extend_from_slice(&len.to_le_bytes());
+        bits.out.extend_from_slice(&(!len).to_le_bytes());
+        bits.out.extend_from_slice(piece);
+    }
+}
+
+// Hàm used trả về số ký hiệu tính tới ký hiệu cuối cùng có mã
+fn used(lengths: &[u8]) -> usize {
+    lengths
+        .iter()
+        .rposition(|&len| len != 0)
+        .map_or(0, |i| i + 1)
+}
+
+// Hàm runs mã hoá dãy độ dài mã bằng các mã lặp: 16 lặp lại độ dài trước 3..6 lần, 17 và 18 là 3..10 và
+// 11..138 số 0; trả về các cặp (ký hiệu, giá trị bit thêm)
+fn runs(lengths: &[u8]) -> Vec<(u8, u8)> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < lengths.len() {
+        let len = lengths[i];
+        let run = lengths[i..].iter().take_while(|&&l| l == len).count();
+        if len == 0 && run >= 3 {
+            let run = run.min(138);
+            if run >= 11 {
+                out.push((18, (run - 11) as u8));
+            } else {
+                out.push((17, (run - 3) as u8));
+            }
+            i += run;
+        } else if len != 0 && run >= 4 {
+            let repeat = (run - 1).min(6);
+            out.push((len, 0));
+            out.push((16, (repeat - 3) as u8));
+            i += 1 + repeat;
+        } else {
+            out.push((len, 0));
+            i += 1;
+        }
+    }
+    out
+}
+
+// Hàm lengths tính độ dài mã Huffman (không quá `limit` bit) từ tần suất; luôn có ít nhất hai ký hiệu để
+// bảng mã đầy đủ
+fn lengths(frequencies: &[u32], limit: u8) -> Vec<u8> {
+    let mut frequencies: Vec<u64> = frequencies.iter().map(|&f| f as u64).collect();
+    let mut i = 0;
+    while frequencies.iter().filter(|&&f| f > 0).count() < 2 {
+        if frequencies[i] == 0 {
+            frequencies[i] = 1;
+        }
+        i += 1;
+    }
+
+    // Cây quá sâu thì giảm một nửa tần suất (vẫn giữ ít nhất 1) cho tới khi vừa giới hạn
+    loop {
+        let lengths = huffman(&frequencies);
+        if lengths.iter().all(|&len| len <= limit) {
+            return lengths;
+        }
+        for frequency in frequencies.iter_mut().filter(|f| **f > 0) {
+            *frequency = frequency.div_ceil(2);
+        }
+    }
+}
+
+// Hàm huffman dựng cây Huffman và trả về độ sâu của từng ký hiệu (0 nếu không dùng)
+fn huffman(frequencies: &[u64]) -> Vec<u8> {
+    let mut parent = Vec::new();
+    let mut leaves = vec![NONE; frequencies.len()];
+    let mut heap = BinaryHeap::new();
+    for (symbol, &frequency) in frequencies.iter().enumerate() {
+        if frequency > 0 {
+            leaves[symbol] = parent.len();
+            heap.push(Reverse((frequency, parent.len())));
+            parent.push(NONE);
+        }
+    }
+    while heap.len() > 1 {
+        let Reverse((a, left)) = heap.pop().unwrap();
+        let Reverse((b, right)) = heap.pop().unwrap();
+        let node = parent.len();
+        parent.push(NONE);
+        parent[left] = node;
+        parent[right] = node;
+        heap.push(Reverse((a + b, node)));
+    }
+    leaves
+        .iter()
+        .map(|&leaf| {
+            let mut depth = 0;
+            let mut node = leaf;
+            while node != NONE && parent[node] != NONE {
+                node = parent[node];
+                depth += 1;
+            }
+            depth
+        })
+        .collect()
+}
+
+// Hàm codes tạo mã Huffman chuẩn tắc từ độ dài mã, đảo bit để ghi từ bit thấp
+fn codes(lengths: &[u8]) -> Vec<u16> {
+    let mut count = [0u16; 16];
+    for &len in lengths {
+        count[len as usize] += 1;
+    }
+    count[0] = 0;
+    let mut next = [0u16; 16];
+    let mut code = 0;
+    for bits in 1..16 {
+        code = (code + count[bits - 1]) << 1;
+        next[bits] = code;
+    }
+    lengths
+        .iter()
+        .map(|&len| {
+            if len == 0 {
+                return 0;
+            }
+            let code = next[len as usize];
+            next[len as usize] += 1;
+            code.reverse_bits() >> (16 - len)
+        })
+        .collect()
+}
+
+// Khai báo một struct để ghi luồng bit từ bit thấp của mỗi byte
+#[derive(Debug, Default)]
+struct Bits {
+    out: Vec<u8>, // Các byte đã ghi đủ
+    bits: u64,    // Các bit đang chờ
+    count: u32,   // Số bit đang chờ
+}
+
+// Khai báo các phương thức cho struct Bits
+impl Bits {
+    // Phương thức put để ghi n bit thấp của giá trị
+    fn put(&mut self, value: u32, n: u32) {
+        self.bits |= (value as u64) << self.count;
+        self.count += n;
+        while self.count >= 8 {
+            self.out.push(self.bits as u8);
+            self.bits >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    // Phương thức align để ghi nốt các bit đang chờ, đệm 0 cho đủ byte
+    fn align(&mut self) {
+        if self.count > 0 {
+            self.out.push(self.bits as u8);
+        }
+        self.bits = 0;
+        self.count = 0;
+    }
+}
+
+// Hàm inflate giải nén một luồng DEFLATE
+pub fn inflate(data: &[u8]) -> io::Result<Vec<u8>> {
+    Ok(inflate_from(data)?.0)
+}
+
+// Hàm inflate_from giải nén một luồng DEFLATE và trả về nội dung cùng số byte đã đọc
+fn inflate_from(data: &[u8]) -> io::Result<(Vec<u8>, usize)> {
+    let mut input = Input {
+        data,
+        pos: 0,
+        bits: 0,
+        count: 0,
+    };
+    let mut out = Vec::new();
+    loop {
+        let last = input.bits(1)? == 1;
+        match input.bits(2)? {
+            0 => {
+                // Khối lưu nguyên: bỏ các bit còn lại của byte hiện tại
+                input.bits = 0;
+                input.count = 0;
+                let header = input.take(4)?;
+                let len = u16::from_le_bytes([header[0], header[1]]);
+                if !len != u16::from_le_bytes([header[2], header[3]]) {
+                    return Err(corrupt("stored block length does not match"));
+                }
+                out.extend_from_slice(input.take(len as usize)?);
+            }
+            1 => {
+                let mut lengths = [8u8; 288];
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                let literals = Huffman::new(&lengths)?;
+                let distances = Huffman::new(&[5; 30])?;
+                codes_block(&mut input, &mut out, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic(&mut input)?;
+                codes_block(&mut input, &mut out, &literals, &distances)?;
+            }
+            _ => return Err(corrupt("invalid block type")),
+        }
+        if last {
+            return Ok((out, input.pos));
+        }
+    }
+}
+
+// Hàm dynamic đọc hai bảng mã của một khối Huffman động
+fn dynamic(input: &mut Input) -> io::Result<(Huffman, Huffman)> {
+    let hlit = input.bits(5)? as usize + 257;
+    let hdist = input.bits(5)? as usize + 1;
+    let hclen = input.bits(4)? as usize + 4;
+    if hlit > 286 || hdist > 30 {
+        return Err(corrupt("too many codes"));
+    }
+    let mut code_lengths = [0u8; 19];
+    for &symbol in &ORDER[..hclen] {
+        code_lengths[symbol] = input.bits(3)? as u8;
+    }
+    let code = Huffman::new(&code_lengths)?;
+    let mut lengths = Vec::with_capacity(hlit + hdist);
+    while lengths.len() < hlit + hdist {
+        let (len, repeat) = match code.decode(input)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => {
+                let prev = *lengths
+                    .last()
+                    .ok_or_else(|| corrupt("repeat with no previous length"))?;
+                (prev, 3 + input.bits(2)? as usize)
+            }
+            17 => (0, 3 + input.bits(3)? as usize),
+            _ => (0, 11 + input.bits(7)? as usize),
+        };
+        if lengths.len() + repeat > hlit + hdist {
+            return Err(corrupt("too many code lengths"));
+        }
+        lengths.extend(std::iter::repeat_n(len, repeat));
+    }
+    if lengths[256] == 0 {
+        return Err(corrupt("missing end-of-block code"));
+    }
+    Ok((
+        Huffman::new(&lengths[..hlit])?,
+        Huffman::new(&lengths[hlit..])?,
+    ))
+}
+
+// Hàm codes_block giải mã các ký hiệu của một khối tới ký hiệu kết thúc khối
+fn codes_block(
+    input: &mut Input,
+    out: &mut Vec<u8>,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> io::Result<()> {
+    loop {
+        let symbol = literals.decode(input)? as usize;
+        if symbol < 256 {
+            out.push(symbol as u8);
+            continue;
+        }
+        if symbol == 256 {
+            return Ok(());
+        }
+        let l = symbol - 257;
+        if l >= LENGTH_BASE.len() {
+            return Err(corrupt("invalid length code"));
+        }
+        let len = LENGTH_BASE[l] as usize + input.bits(LENGTH_EXTRA[l] as u32)? as usize;
+        let d = distances.decode(input)? as usize;
+        if d >= DISTANCE_BASE.len() {
+            return Err(corrupt("invalid distance code"));
+        }
+        let distance = DISTANCE_BASE[d] as usize + input.bits(DISTANCE_EXTRA[d] as u32)? as usize;
+        if distance > out.len() {
+            return Err(corrupt("distance too far back"));
+        }
+        let start = out.len() - distance;
+        for i in 0..len {
+            out.push(out[start + i]);
+        }
+    }
+}
+
+// Khai báo một struct để đọc luồng bit từ bit thấp của mỗi byte
+#[derive(Debug)]
+struct Input<'a> {
+    data: &'a [u8], // Dữ liệu nén
+    pos: usize,     // Vị trí byte tiếp theo
+    bits: u32,      // Các bit đã nạp nhưng chưa đọc
+    count: u32,     // Số bit đã nạp nhưng chưa đọc
+}
+
+// Khai báo các phương thức cho struct Input
+impl Input<'_> {
+    // Phương thức bits để đọc n bit (tối đa 16)
+    fn bits(&mut self, n: u32) -> io::Result<u32> {
+        while self.count < n {
+            let byte = *self
+                .data
+                .get(self.pos)
+                .ok_or_else(|| corrupt("unexpected end of data"))?;
+            self.pos += 1;
+            self.bits |= (byte as u32) << self.count;
+            self.count += 8;
+        }
+        let value = self.bits & ((1 << n) - 1);
+        self.bits >>= n;
+        self.count -= n;
+        Ok(value)
+    }
+
+    // Phương thức take để đọc n byte nguyên (sau khi đã căn theo byte)
+    fn take(&mut self, n: usize) -> io::Result<&[u8]> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + n)
+            .ok_or_else(|| corrupt("unexpected end of data"))?;
+        self.pos += n;
+        Ok(bytes)
+    }
+}
+
+// Khai báo một struct để giải mã Huffman chuẩn tắc: số mã của mỗi độ dài và các ký hiệu theo thứ tự mã
+#[derive(Debug)]
+struct Huffman {
+    count: [u16; 16],  // Số mã có mỗi độ dài
+    symbols: Vec<u16>, // Các ký hiệu xếp theo độ dài rồi theo giá trị
+}
+
+// Khai báo các phương thức cho struct Huffman
+impl Huffman {
+    // Phương thức new để dựng bảng giải mã từ độ dài mã; bảng mã thừa mã bị từ chối
+    fn new(lengths: &[u8]) -> io::Result<Huffman> {
+        let mut count = [0u16; 16];
+        for &len in lengths {
+            count[len as usize] += 1;
+        }
+        let mut left: i32 = 1;
+        for &n in &count[1..] {
+            left = (left << 1) - n as i32;
+            if left < 0 {
+                return Err(corrupt("over-subscribed code"));
+            }
+        }
+        let mut symbols: Vec<u16> = (0..lengths.len() as u16)
+            .filter(|&s| lengths[s as usize] != 0)
+            .collect();
+        symbols.sort_by_key(|&s| lengths[s as usize]);
+        Ok(Huffman { count, symbols })
+    }
+
+    // Phương thức decode để đọc từng bit cho tới khi được một mã hợp lệ
+    fn decode(&self, input: &mut Input) -> io::Result<u16> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..16 {
+            code |= input.bits(1)? as i32;
+            let count = self.count[len] as i32;
+            if code - count < first {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(corrupt("invalid code"))
+    }
+}
+
+// Hàm corrupt tạo lỗi cho dữ liệu nén hỏng
+fn corrupt(reason: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Corrupt compressed data: {}", reason),
+    )
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+
+    // Khai báo một hàm để tạo dữ liệu giống nhật ký (nén tốt) xen lẫn byte giả ngẫu nhiên (nén kém)
+    fn sample(len: usize) -> Vec<u8> {
+        let mut seed = 7u32;
+        let mut out = Vec::with_capacity(len);
+        let mut line = 0;
+        while out.len() < len {
+            seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+            if line % 5 == 4 {
+                out.extend((0..40).map(|i| (seed >> (i % 24)) as u8));
+            } else {
+                out.extend(
+                    format!(
+                        "2026-10-19 INFO request {} served in {} ms\n",
+                        line,
+                        seed % 97
+                    )
+                    .bytes(),
+                );
+            }
+            line += 1;
+        }
+        out.truncate(len);
+        out
+    }
+
+    // Khai báo một hàm để kiểm tra việc nén rồi giải nén cho lại dữ liệu gốc
+    #[test]
+    fn test_round_trip() {
+        for len in [0, 1, 2, 3, 100, 70_000, 300_000] {
+            let data = sample(len);
+            let packed = gzip(&data);
+            assert_eq!(gunzip(&packed).unwrap(), data);
+            assert_eq!(inflate(&compress(&data)).unwrap(), data);
+        }
+
+        // Dữ liệu lặp lại nén rất tốt, dữ liệu ngẫu nhiên không phình quá vài byte mỗi khối
+        let text = sample(300_000);
+        assert!(compress(&text).len() < text.len() / 3);
+        let zeros = vec![0; 100_000];
+        assert!(compress(&zeros).len() < 1_000);
+        let mut seed = 1u32;
+        let noise: Vec<u8> = (0..100_000)
+            .map(|_| {
+                seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                (seed >> 16) as u8
+            })
+            .collect();
+        assert!(compress(&noise).len() < noise.len() + 100);
+        assert_eq!(inflate(&compress(&noise)).unwrap(), noise);
+    }
+
+    // Khai báo một hàm để kiểm tra khả năng đọc dữ liệu do công cụ khác tạo ra
+    #[test]
+    fn test_compatibility() {
+        // `printf 'hello hello hello hello\n' | gzip -n` (khối mã cố định)
+        let member = [
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0xcb, 0x48, 0xcd, 0xc9,
+            0xc9, 0x57, 0xc8, 0x40, 0x27, 0xb9, 0x00, 0x00, 0x88, 0x59, 0x0b, 0x18, 0x00, 0x00,
+            0x00,
+        ];
+        assert_eq!(gunzip(&member).unwrap(), b"hello hello hello hello\n");
+
+        // Nhiều thành phần nối tiếp nhau (như `cat a.gz b.gz`)
+        let mut joined = member.to_vec();
+        joined.extend(gzip(b"more\n"));
+        assert_eq!(gunzip(&joined).unwrap(), b"hello hello hello hello\nmore\n");
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+
+        // Dữ liệu hỏng hoặc bị cắt bớt bị từ chối
+        let mut broken = member.to_vec();
+        broken[12] ^= 0x40;
+        assert!(gunzip(&broken).is_err());
+        assert!(gunzip(&member[..20]).is_err());
+        assert!(gunzip(b"plain text").is_err());
+    }
+}
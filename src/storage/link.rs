@@ -0,0 +1,915 @@
+// Khai báo các thư viện cần thiết
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use super::pack::{self, Reader};
+use super::vault::{self, Key};
+use super::{Access, Storage};
+use crate::audit::{self, Audit};
+use crate::auth::role::Action;
+use crate::auth::{self, Hash, Principal, ITERATIONS};
+use crate::clock::DAY;
+use crate::crypto::{self, hex, sha256};
+use crate::social::message::{escape, unescape};
+
+// Khai báo thời gian sống mặc định của một liên kết chia sẻ (tính bằng giây)
+pub const TTL: u64 = 7 * DAY;
+
+// Khai báo tên nhật ký liên kết trong đối tượng lưu trữ
+const LOG: &str = "links.log";
+
+// Khai báo một enum để đại diện cho thứ được chia sẻ qua liên kết
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    File(String), // Một tập tin trong đối tượng lưu trữ
+    Folder,       // Cả đối tượng lưu trữ (trừ các tập tin có quyền truy cập riêng Private)
+}
+
+// Khai báo một struct để chứa các tuỳ chọn khi tạo liên kết
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub ttl: u64,                 // Thời gian sống tính từ lúc tạo (tính bằng giây)
+    pub downloads: Option<u32>,   // Số lần tải tối đa (None nếu không giới hạn)
+    pub password: Option<String>, // Mật khẩu để mở liên kết (nếu có)
+}
+
+// Khai báo giá trị mặc định cho các tuỳ chọn: hết hạn sau TTL, không giới hạn lượt tải, không mật khẩu
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            ttl: TTL,
+            downloads: None,
+            password: None,
+        }
+    }
+}
+
+// Khai báo một struct để đại diện cho một liên kết chia sẻ
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+    pub id: u64,                       // Mã của liên kết
+    pub business: String,              // Doanh nghiệp sở hữu liên kết
+    pub creator: String,               // Người tạo dạng `tên@doanh nghiệp`
+    pub path: PathBuf,                 // Đường dẫn của đối tượng lưu trữ được chia sẻ
+    pub target: Target,                // Thứ được chia sẻ
+    pub created: u64,                  // Thời điểm tạo
+    pub expires: u64,                  // Thời điểm hết hạn
+    pub limit: Option<u32>,            // Số lần tải tối đa
+    pub downloads: u32,                // Số lần đã tải
+    pub revoked: bool,                 // Liên kết đã bị thu hồi
+    password: Option<Hash>,            // Mật khẩu đã băm (nếu có)
+    ciphers: BTreeMap<String, String>, // Khoá dữ liệu của từng tập tin được chia sẻ, bọc bằng khoá dẫn xuất từ mã liên kết (rỗng nếu không mã hoá)
+}
+
+// Khai báo các phương thức cho struct Link
+impl Link {
+    // Phương thức protected kiểm tra liên kết có cần mật khẩu hay không
+    pub fn protected(&self) -> bool {
+        self.password.is_some()
+    }
+}
+
+// Khai báo một struct để đại diện cho một lần dùng liên kết (thành công hoặc bị từ chối)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Use {
+    pub link: u64,      // Mã của liên kết
+    pub time: u64,      // Thời điểm dùng
+    pub name: String,   // Tập tin được tải (rỗng nếu chỉ liệt kê thư mục)
+    pub granted: bool,  // Lần dùng có được chấp nhận hay không
+    pub reason: String, // Lý do từ chối (rỗng nếu được chấp nhận)
+}
+
+// Khai báo một struct để đọc nội dung một tập tin qua liên kết (chỉ đọc)
+#[derive(Debug)]
+pub struct Download {
+    pub name: String, // Tên tập tin
+    pub size: u64,    // Kích thước nội dung (tính bằng byte)
+    reader: Reader,   // Luồng đọc tập tin (giải mã nếu đối tượng lưu trữ được mã hoá)
+}
+
+// Cho phép đọc nội dung tải về như một luồng
+impl Read for Download {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+// Khai báo một enum để đại diện cho một bản ghi trong nhật ký liên kết
+//
+// Nhật ký chỉ lưu giá trị băm của mã liên kết và mật khẩu nên người đọc được đối tượng lưu trữ cũng
+// không mở được liên kết.
+#[derive(Debug, Clone, PartialEq)]
+enum Record {
+    Create(String, Link), // Liên kết mới cùng băm của mã
+    Use(Use),             // Một lần dùng liên kết
+    Revoke(u64),          // Thu hồi liên kết
+}
+
+// Khai báo các phương thức cho enum Record
+impl Record {
+    // Phương thức render để ghi bản ghi thành một dòng, các trường cách nhau bằng tab
+    fn render(&self) -> String {
+        let fields: Vec<String> = match self {
+            Record::Create(key, link) => {
+                let (kind, name) = match &link.target {
+                    Target::File(name) => ("file", name.as_str()),
+                    Target::Folder => ("folder", ""),
+                };
+                vec![
+                    "create".into(),
+                    key.clone(),
+                    link.id.to_string(),
+                    escape(&link.business),
+                    escape(&link.creator),
+                    escape(&link.path.to_string_lossy()),
+                    kind.into(),
+                    escape(name),
+                    link.created.to_string(),
+                    link.expires.to_string(),
+                    link.limit.map_or(String::new(), |limit| limit.to_string()),
+                    link.password
+                        .as_ref()
+                        .map_or(String::new(), |hash| hash.to_string()),
+                    escape(
+                        &link
+                            .ciphers
+                            .iter()
+                            .map(|(name, wrapped)| format!("{}:{}", name, wrapped))
+                            .collect::<Vec<_>>()
+                            .join("/"),
+                    ),
+                ]
+            }
+            Record::Use(used) => vec![
+                "use".into(),
+                used.link.to_string(),
+                used.time.to_string(),
+                escape(&used.name),
+                (used.granted as u8).to_string(),
+                escape(&used.reason),
+            ],
+            Record::Revoke(id) => vec!["revoke".into(), id.to_string()],
+        };
+        format!("{}\n", fields.join("\t"))
+    }
+
+    // Phương thức parse để đọc lại một dòng do render tạo ra
+    fn parse(line: &str) -> Option<Record> {
+        let fields: Vec<String> = line.split('\t').map(unescape).collect();
+        let number = |i: usize| fields.get(i)?.parse::<u64>().ok();
+        let text = |i: usize| fields.get(i).cloned();
+        let record = match fields[0].as_str() {
+            "create" => {
+                let target = match text(6)?.as_str() {
+                    "file" => Target::File(text(7)?),
+                    "folder" => Target::Folder,
+                    _ => return None,
+                };
+                let limit = match text(10)?.as_str() {
+                    "" => None,
+                    limit => Some(limit.parse().ok()?),
+                };
+                let password = match text(11)?.as_str() {
+                    "" => None,
+                    hash => Some(hash.parse().ok()?),
+                };
+                Record::Create(
+                    text(1)?,
+                    Link {
+                        id: number(2)?,
+                        business: text(3)?,
+                        creator: text(4)?,
+                        path: PathBuf::from(text(5)?),
+                        target,
+                        created: number(8)?,
+                        expires: number(9)?,
+                        limit,
+                        downloads: 0,
+                        revoked: false,
+                        password,
+                        ciphers: ciphers(&text(12).unwrap_or_default()),
+                    },
+                )
+            }
+            "use" => Record::Use(Use {
+                link: number(1)?,
+                time: number(2)?,
+                name: text(3)?,
+                granted: number(4)? == 1,
+                reason: text(5)?,
+            }),
+            "revoke" => Record::Revoke(number(1)?),
+            _ => return None,
+        };
+        Some(record)
+    }
+}
+
+// Khai báo một struct để chứa các liên kết (được bảo vệ bởi khóa)
+#[derive(Debug, Default)]
+struct Data {
+    links: BTreeMap<u64, Link>,  // Các liên kết theo mã
+    keys: BTreeMap<String, u64>, // Mã liên kết theo băm của mã trao cho người nhận
+    uses: Vec<Use>,              // Các lần dùng theo thứ tự
+    next: u64,                   // Mã liên kết tiếp theo
+}
+
+// Khai báo một struct để tạo, mở và thu hồi liên kết chia sẻ tập tin
+//
+// Người nhận chỉ cần mã liên kết (và mật khẩu nếu có), không cần đăng nhập: liên kết chính là quyền
+// đọc, nên nó luôn có hạn, có thể giới hạn lượt tải và thu hồi được, và mọi lần dùng đều được ghi lại.
+#[derive(Debug)]
+pub struct Links {
+    iterations: u32,           // Số vòng lặp PBKDF2 cho mật khẩu của liên kết
+    audit: Option<Arc<Audit>>, // Nhật ký kiểm toán ghi lại việc tạo, dùng và thu hồi liên kết (nếu có)
+    storage: Mutex<Storage>,   // Nơi lưu nhật ký liên kết
+    data: Mutex<Data>,         // Các liên kết
+}
+
+// Khai báo các phương thức cho struct Links
+impl Links {
+    // Phương thức open để mở dịch vụ liên kết và đọc lại nhật ký liên kết
+    pub fn open(storage: Storage) -> io::Result<Links> {
+        Links::with_iterations(storage, ITERATIONS)
+    }
+
+    // Phương thức with_iterations để mở dịch vụ liên kết với số vòng lặp PBKDF2 cho trước
+    pub fn with_iterations(mut storage: Storage, iterations: u32) -> io::Result<Links> {
+        storage.serve()?;
+        let mut data = Data::default();
+        if storage.list()?.iter().any(|name| name == LOG) {
+            for (number, line) in storage.view(LOG)?.lines().enumerate() {
+                let record = Record::parse(line).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Invalid record in {} at line {}", LOG, number + 1),
+                    )
+                })?;
+                apply(&mut data, record);
+            }
+        }
+        Ok(Links {
+            iterations: iterations.max(1),
+            audit: None,
+            storage: Mutex::new(storage),
+            data: Mutex::new(data),
+        })
+    }
+
+    // Phương thức with_audit để ghi việc tạo, dùng và thu hồi liên kết vào nhật ký kiểm toán
+    pub fn with_audit(mut self, audit: Arc<Audit>) -> Links {
+        self.audit = Some(audit);
+        self
+    }
+
+    // Phương thức create để tạo liên kết tới một tập tin hoặc cả đối tượng lưu trữ mà người dùng đang
+    // đăng nhập được quyền ghi, trả về mã của liên kết và mã trao cho người nhận
+    pub fn create(
+        &self,
+        storage: &Storage,
+        target: Target,
+        options: &Options,
+        now: u64,
+    ) -> io::Result<(u64, String)> {
+        let principal = storage.require(Action::StorageWrite)?.clone();
+        if let Target::File(name) = &target {
+            if !shareable(&storage.path, name)? {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("File {} may not be shared by link", name),
+                ));
+            }
+        }
+        if options.ttl == 0 || options.downloads == Some(0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Share link must allow at least one download before it expires",
+            ));
+        }
+        let password = match &options.password {
+            Some(password) => Some(Hash::new(password, self.iterations)?),
+            None => None,
+        };
+        let token = hex(&crypto::key()?);
+        let resource = describe(&storage.path, &target);
+
+        // Với đối tượng lưu trữ được mã hoá, chỉ khoá dữ liệu của các tập tin được chia sẻ (chứ không phải khoá
+        // của cả đối tượng lưu trữ) được bọc lại, nên người giữ mã liên kết chỉ giải mã được các tập tin đó
+        let mut ciphers = BTreeMap::new();
+        if let Some(key) = storage.cipher()? {
+            let names = match &target {
+                Target::File(name) => vec![name.clone()],
+                Target::Folder => folder(&storage.path)?,
+            };
+            let wrapping = unlock(&token);
+            for name in names {
+                let data = vault::data_key(&storage.path, key, &name)?;
+                ciphers.insert(name.clone(), wrapping.wrap(&name, &data)?);
+            }
+        }
+
+        let mut data = self.data.lock().unwrap();
+        data.next += 1;
+        let link = Link {
+            id: data.next,
+            business: principal.business().to_string(),
+            creator: audit::actor(&principal),
+            path: storage.path.clone(),
+            target,
+            created: now,
+            expires: now + options.ttl,
+            limit: options.downloads,
+            downloads: 0,
+            revoked: false,
+            password,
+            ciphers,
+        };
+        let id = link.id;
+        let after = format!("link {} until {}", id, link.expires);
+        self.record(&mut data, Record::Create(key(&token), link))?;
+        drop(data);
+
+        self.audit(
+            &audit::actor(&principal),
+            "share_link",
+            &resource,
+            "",
+            &after,
+        )?;
+        Ok((id, token))
+    }
+
+    // Phương thức resolve để mở một tập tin qua liên kết và ghi nhận lần dùng
+    //
+    // Với liên kết tới cả đối tượng lưu trữ, `name` là tập tin cần tải; với liên kết tới một tập tin,
+    // `name` bỏ trống hoặc trùng với tên tập tin đó.
+    pub fn resolve(
+        &self,
+        token: &str,
+        name: Option<&str>,
+        password: Option<&str>,
+        now: u64,
+    ) -> io::Result<Download> {
+        let link = self.check(token, password, now)?;
+        let shared = match (&link.target, name) {
+            (Target::File(file), None) => Some(file.clone()),
+            (Target::File(file), Some(name)) => (file == name).then(|| file.clone()),
+            (Target::Folder, Some(name)) => shareable(&link.path, name)
+                .unwrap_or(false)
+                .then(|| name.to_string()),
+            (Target::Folder, None) => None,
+        };
+        let Some(name) = shared else {
+            let name = name.unwrap_or_default();
+            return Err(self.deny(&link, name, "File is not shared by this link", now));
+        };
+        let reader = match stream(&link, token, &name) {
+            Ok(reader) => reader,
+            Err(error) => {
+                self.refuse(&link, &name, &error.to_string(), now)?;
+                return Err(error);
+            }
+        };
+
+        // Kiểm tra lại liên kết và ghi nhận lần dùng dưới cùng một khoá để hai người không cùng lấy lượt cuối
+        let mut data = self.data.lock().unwrap();
+        let current = &data.links[&link.id];
+        let reason = if current.revoked {
+            "Share link was revoked"
+        } else if current
+            .limit
+            .is_some_and(|limit| current.downloads >= limit)
+        {
+            "Share link download limit reached"
+        } else {
+            ""
+        };
+        if !reason.is_empty() {
+            drop(data);
+            return Err(self.deny(&link, &name, reason, now));
+        }
+        self.used(&mut data, &link, &name, "", now)?;
+        Ok(Download {
+            name,
+            size: reader.size(),
+            reader,
+        })
+    }
+
+    // Phương thức list trả về các tập tin mở được qua một liên kết (không tính là lượt tải)
+    pub fn list(&self, token: &str, password: Option<&str>, now: u64) -> io::Result<Vec<String>> {
+        let link = self.check(token, password, now)?;
+        let names = match &link.target {
+            Target::File(name) => vec![name.clone()],
+            Target::Folder if vault::encrypted(&link.path) => {
+                link.ciphers.keys().cloned().collect()
+            }
+            Target::Folder => folder(&link.path)?,
+        };
+        let mut data = self.data.lock().unwrap();
+        self.used(&mut data, &link, "", "", now)?;
+        Ok(names)
+    }
+
+    // Phương thức revoke để thu hồi một liên kết của doanh nghiệp người dùng
+    pub fn revoke(&self, principal: &Principal, id: u64) -> io::Result<()> {
+        auth::authorize(principal, Action::StorageWrite, &format!("link {}", id))?;
+        let mut data = self.data.lock().unwrap();
+        let link = owned(&data, principal, id)?;
+        if link.revoked {
+            return Ok(());
+        }
+        let resource = describe(&link.path, &link.target);
+        self.record(&mut data, Record::Revoke(id))?;
+        drop(data);
+        let before = format!("link {}", id);
+        self.audit(
+            &audit::actor(principal),
+            "revoke_link",
+            &resource,
+            &before,
+            "revoked",
+        )
+    }
+
+    // Phương thức link trả về thông tin của một liên kết
+    pub fn link(&self, id: u64) -> io::Result<Link> {
+        self.data
+            .lock()
+            .unwrap()
+            .links
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| missing(id))
+    }
+
+    // Phương thức links trả về các liên kết của doanh nghiệp người dùng
+    pub fn links(&self, principal: &Principal) -> io::Result<Vec<Link>> {
+        auth::authorize(principal, Action::StorageRead, "links")?;
+        let data = self.data.lock().unwrap();
+        Ok(data
+            .links
+            .values()
+            .filter(|link| link.business == principal.business())
+            .cloned()
+            .collect())
+    }
+
+    // Phương thức uses trả về các lần dùng một liên kết của doanh nghiệp người dùng
+    pub fn uses(&self, principal: &Principal, id: u64) -> io::Result<Vec<Use>> {
+        auth::authorize(principal, Action::StorageRead, &format!("link {}", id))?;
+        let data = self.data.lock().unwrap();
+        owned(&data, principal, id)?;
+        Ok(data
+            .uses
+            .iter()
+            .filter(|used| used.link == id)
+            .cloned()
+            .collect())
+    }
+
+    // Phương thức check để tìm liên kết của một mã và kiểm tra hạn, thu hồi, mật khẩu và lượt tải
+    fn check(&self, token: &str, password: Option<&str>, now: u64) -> io::Result<Link> {
+        let link = {
+            let data = self.data.lock().unwrap();
+            let id = data
+                .keys
+                .get(&key(token))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Share link not found"))?;
+            data.links[id].clone()
+        };
+        if link.revoked {
+            return Err(self.deny(&link, "", "Share link was revoked", now));
+        }
+        if now >= link.expires {
+            return Err(self.deny(&link, "", "Share link has expired", now));
+        }
+        if link.limit.is_some_and(|limit| link.downloads >= limit) {
+            return Err(self.deny(&link, "", "Share link download limit reached", now));
+        }
+
+        // Kiểm tra mật khẩu ngoài khoá vì băm mật khẩu tốn thời gian
+        if let Some(hash) = &link.password {
+            if !password.is_some_and(|password| hash.verify(password)) {
+                return Err(self.deny(&link, "", "Invalid share link password", now));
+            }
+        }
+        Ok(link)
+    }
+
+    // Phương thức deny để ghi nhận một lần dùng bị từ chối và trả về lỗi từ chối tương ứng
+    fn deny(&self, link: &Link, name: &str, reason: &str, now: u64) -> io::Error {
+        match self.refuse(link, name, reason, now) {
+            Ok(()) => io::Error::new(io::ErrorKind::PermissionDenied, reason.to_string()),
+            Err(error) => error,
+        }
+    }
+
+    // Phương thức refuse để ghi nhận một lần dùng bị từ chối
+    fn refuse(&self, link: &Link, name: &str, reason: &str, now: u64) -> io::Result<()> {
+        let mut data = self.data.lock().unwrap();
+        self.used(&mut data, link, name, reason, now)
+    }
+
+    // Phương thức used để ghi một lần dùng vào nhật ký liên kết và nhật ký kiểm toán
+    fn used(
+        &self,
+        data: &mut Data,
+        link: &Link,
+        name: &str,
+        reason: &str,
+        now: u64,
+    ) -> io::Result<()> {
+        let used = Use {
+            link: link.id,
+            time: now,
+            name: name.to_string(),
+            granted: reason.is_empty(),
+            reason: reason.to_string(),
+        };
+        self.record(data, Record::Use(used))?;
+        let resource = match name {
+            "" => describe(&link.path, &link.target),
+            name => describe(&link.path, &Target::File(name.to_string())),
+        };
+        let after = match reason {
+            "" if name.is_empty() => "listed",
+            "" => "downloaded",
+            reason => reason,
+        };
+        let actor = format!("link {}", link.id);
+        self.audit(&actor, "use_link", &resource, "", after)
+    }
+
+    // Phương thức record để ghi bản ghi vào nhật ký rồi áp dụng nó vào trạng thái trong bộ nhớ
+    fn record(&self, data: &mut Data, record: Record) -> io::Result<()> {
+        self.storage
+            .lock()
+            .unwrap()
+            .append(LOG, record.render().as_bytes())?;
+        apply(data, record);
+        Ok(())
+    }
+
+    // Phương thức audit để ghi một thao tác vào nhật ký kiểm toán (nếu đã gắn)
+    fn audit(
+        &self,
+        actor: &str,
+        action: &str,
+        resource: &str,
+        before: &str,
+        after: &str,
+    ) -> io::Result<()> {
+        if let Some(audit) = &self.audit {
+            audit.record(actor, action, resource, before, after)?;
+        }
+        Ok(())
+    }
+}
+
+// Hàm apply áp dụng một bản ghi vào trạng thái của các liên kết
+fn apply(data: &mut Data, record: Record) {
+    match record {
+        Record::Create(key, link) => {
+            data.next = data.next.max(link.id);
+            data.keys.insert(key, link.id);
+            data.links.insert(link.id, link);
+        }
+        Record::Use(used) => {
+            // Chỉ lần tải thành công mới tính vào lượt tải (liệt kê thư mục thì không)
+            if used.granted && !used.name.is_empty() {
+                if let Some(link) = data.links.get_mut(&used.link) {
+                    link.downloads += 1;
+                }
+            }
+            data.uses.push(used);
+        }
+        Record::Revoke(id) => {
+            if let Some(link) = data.links.get_mut(&id) {
+                link.revoked = true;
+            }
+        }
+    }
+}
+
+// Hàm key trả về băm của mã liên kết (chỉ băm được lưu lại)
+fn key(token: &str) -> String {
+    hex(&sha256::digest(token.as_bytes()))
+}
+
+// Hàm stream mở tập tin được chia sẻ, giải mã bằng khoá dữ liệu mở được từ mã liên kết nếu cần
+fn stream(link: &Link, token: &str, name: &str) -> io::Result<Reader> {
+    let data = match link.ciphers.get(name) {
+        Some(wrapped) => Some(unlock(token).unwrap(name, wrapped)?),
+        None if vault::encrypted(&link.path) => {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("No key for {} in this share link", name),
+            ));
+        }
+        None => None,
+    };
+    pack::unsealed(&link.path, data, name)
+}
+
+// Hàm ciphers đọc lại các khoá dữ liệu đã bọc dạng `<tên>:<khoá đã bọc>` cách nhau bằng `/` (tên tập tin
+// được chia sẻ không chứa `/`; bản ghi cũ bọc cả khoá của đối tượng lưu trữ thì bị bỏ qua)
+fn ciphers(text: &str) -> BTreeMap<String, String> {
+    text.split('/')
+        .filter_map(|entry| {
+            let mut parts = entry.rsplitn(3, ':');
+            let (sealed, nonce, name) = (parts.next()?, parts.next()?, parts.next()?);
+            Some((name.to_string(), format!("{}:{}", nonce, sealed)))
+        })
+        .collect()
+}
+
+// Hàm folder trả về các tập tin chia sẻ được của một đối tượng lưu trữ (theo thứ tự bảng chữ cái)
+fn folder(path: &Path) -> io::Result<Vec<String>> {
+    let mut names = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let name = entry?.file_name().to_string_lossy().into_owned();
+        if shareable(path, &name)? {
+            names.push(name);
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+// Hàm unlock dẫn xuất khoá dùng để bọc khoá của đối tượng lưu trữ từ mã liên kết
+fn unlock(token: &str) -> Key {
+    Key::derive(token.as_bytes(), "share link")
+}
+
+// Hàm shareable kiểm tra một tập tin trong đối tượng lưu trữ có được chia sẻ qua liên kết hay không:
+// tập tin phải nằm ngay trong đối tượng lưu trữ và không có quyền truy cập riêng Private
+fn shareable(path: &Path, name: &str) -> io::Result<bool> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return Ok(false);
+    }
+    if !path.join(name).is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Source {} not found", path.join(name).display()),
+        ));
+    }
+    let marker = path.join(".access").join(name);
+    if marker.is_file() {
+        return Ok(fs::read_to_string(marker)?.trim().parse::<Access>()? != Access::Private);
+    }
+    Ok(true)
+}
+
+// Hàm owned trả về liên kết nếu nó thuộc doanh nghiệp của người dùng
+fn owned<'a>(data: &'a Data, principal: &Principal, id: u64) -> io::Result<&'a Link> {
+    data.links
+        .get(&id)
+        .filter(|link| link.business == principal.business())
+        .ok_or_else(|| missing(id))
+}
+
+// Hàm describe mô tả thứ được chia sẻ cho nhật ký kiểm toán
+fn describe(path: &Path, target: &Target) -> String {
+    match target {
+        Target::File(name) => format!("storage {}/{}", path.display(), name),
+        Target::Folder => format!("storage {}", path.display()),
+    }
+}
+
+// Hàm missing tạo lỗi io cho liên kết không tồn tại
+fn missing(id: u64) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("Link {} not found", id))
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::auth::role::Role;
+    use crate::auth::tests::principal;
+    use crate::storage::tests::{create_temp_storage, reopen};
+
+    // Khai báo một hàm để tạo dịch vụ liên kết tạm thời và đối tượng lưu trữ chứa tập tin được chia sẻ
+    fn links() -> (Links, Storage, PathBuf) {
+        let storage = create_temp_storage("links", Access::Private).unwrap();
+        let path = storage.path().to_path_buf();
+        let mut files = create_temp_storage("files", Access::Private).unwrap();
+        files.put("report.txt", b"Quarterly report").unwrap();
+        (Links::with_iterations(storage, 10).unwrap(), files, path)
+    }
+
+    // Khai báo một hàm để đọc hết nội dung tải về
+    fn read(mut download: Download) -> String {
+        let mut content = String::new();
+        download.read_to_string(&mut content).unwrap();
+        content
+    }
+
+    // Khai báo một hàm để kiểm tra hạn, lượt tải, mật khẩu và thu hồi của liên kết tới một tập tin
+    #[test]
+    fn test_file() {
+        let (links, files, path) = links();
+        let file = Target::File("report.txt".to_string());
+        let options = Options {
+            ttl: 100,
+            downloads: Some(2),
+            password: Some("open sesame".to_string()),
+        };
+        let (id, token) = links.create(&files, file.clone(), &options, 1000).unwrap();
+        assert!(links.link(id).unwrap().protected());
+
+        // Mật khẩu sai hoặc thiếu thì bị từ chối, mã lạ thì không tìm thấy
+        let error = links
+            .resolve(&token, None, Some("guess"), 1001)
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        assert!(links.resolve(&token, None, None, 1001).is_err());
+        let error = links.resolve("bogus", None, None, 1001).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+
+        // Tải được tập tin dưới dạng luồng chỉ đọc, nhưng không tải được tập tin khác
+        let download = links
+            .resolve(&token, None, Some("open sesame"), 1002)
+            .unwrap();
+        assert_eq!((download.name.as_str(), download.size), ("report.txt", 16));
+        assert_eq!(read(download), "Quarterly report");
+        assert!(links
+            .resolve(&token, Some(".access"), Some("open sesame"), 1003)
+            .is_err());
+
+        // Liên kết được ghi lại nên mở lại dịch vụ vẫn dùng được và giữ số lượt đã tải
+        drop(links);
+        let links = Links::with_iterations(reopen(&path).unwrap(), 10).unwrap();
+        assert_eq!(links.link(id).unwrap().downloads, 1);
+        links
+            .resolve(&token, Some("report.txt"), Some("open sesame"), 1004)
+            .unwrap();
+        let error = links
+            .resolve(&token, None, Some("open sesame"), 1005)
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Share link download limit reached");
+
+        // Liên kết hết hạn
+        let (_, token) = links
+            .create(&files, file.clone(), &Options::default(), 0)
+            .unwrap();
+        links.resolve(&token, None, None, TTL - 1).unwrap();
+        let error = links.resolve(&token, None, None, TTL).unwrap_err();
+        assert_eq!(error.to_string(), "Share link has expired");
+
+        // Thu hồi liên kết; doanh nghiệp khác không thu hồi được
+        let (other, token) = links.create(&files, file, &Options::default(), 0).unwrap();
+        assert!(links.revoke(&principal("beta"), other).is_err());
+        links.revoke(&principal("files"), other).unwrap();
+        let error = links.resolve(&token, None, None, 1).unwrap_err();
+        assert_eq!(error.to_string(), "Share link was revoked");
+
+        // Mọi lần dùng đều được ghi lại, kể cả khi bị từ chối
+        let uses = links.uses(&principal("files"), id).unwrap();
+        let granted: Vec<bool> = uses.iter().map(|used| used.granted).collect();
+        assert_eq!(granted, [false, false, true, false, true, false]);
+        assert_eq!(uses[0].reason, "Invalid share link password");
+        assert_eq!(uses[3].name, ".access");
+        assert!(links.uses(&principal("beta"), id).is_err());
+        assert_eq!(links.links(&principal("files")).unwrap().len(), 3);
+
+        fs::remove_dir_all(path).unwrap();
+        fs::remove_dir_all(files.path()).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra liên kết tới cả đối tượng lưu trữ
+    #[test]
+    fn test_folder() {
+        let (links, mut files, path) = links();
+        files.put("price.csv", b"sku,price").unwrap();
+        files.put("secret.txt", b"hidden").unwrap();
+        files
+            .set_file_access("secret.txt", Access::Private)
+            .unwrap();
+
+        // Nhân viên không được tạo liên kết, người chưa đăng nhập cũng vậy
+        let staff = Principal::new(1, "staff", "files", Role::Staff, u64::MAX);
+        files.login(&staff).unwrap();
+        assert!(links
+            .create(&files, Target::Folder, &Options::default(), 0)
+            .is_err());
+        files.logout();
+        assert!(links
+            .create(&files, Target::Folder, &Options::default(), 0)
+            .is_err());
+        files.login(&principal("files")).unwrap();
+        let private = Target::File("secret.txt".to_string());
+        assert!(links
+            .create(&files, private, &Options::default(), 0)
+            .is_err());
+
+        // Liệt kê và tải các tập tin, trừ tập tin có quyền truy cập riêng Private
+        let options = Options {
+            downloads: Some(2),
+            ..Options::default()
+        };
+        let (id, token) = links.create(&files, Target::Folder, &options, 0).unwrap();
+        assert_eq!(
+            links.list(&token, None, 1).unwrap(),
+            ["price.csv", "report.txt"]
+        );
+        let download = links.resolve(&token, Some("price.csv"), None, 2).unwrap();
+        assert_eq!(read(download), "sku,price");
+        assert!(links.resolve(&token, Some("secret.txt"), None, 3).is_err());
+        assert!(links.resolve(&token, Some("../x"), None, 3).is_err());
+        assert!(links.resolve(&token, None, None, 3).is_err());
+        links.resolve(&token, Some("report.txt"), None, 4).unwrap();
+
+        // Liệt kê không tính vào lượt tải, nhưng hết lượt thì không liệt kê được nữa
+        assert_eq!(links.link(id).unwrap().downloads, 2);
+        assert!(links.list(&token, None, 5).is_err());
+
+        fs::remove_dir_all(path).unwrap();
+        fs::remove_dir_all(files.path()).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra liên kết tới đối tượng lưu trữ được mã hoá
+    #[test]
+    fn test_encrypted() {
+        let (links, mut files, path) = links();
+        files.encrypt(&[7; 32]).unwrap();
+        let file = Target::File("report.txt".to_string());
+        let (id, token) = links.create(&files, file, &Options::default(), 0).unwrap();
+
+        // Người giữ mã liên kết đọc được nội dung đã giải mã mà không cần khoá chủ
+        let download = links.resolve(&token, None, None, 1).unwrap();
+        assert_eq!(download.size, 16);
+        assert_eq!(read(download), "Quarterly report");
+
+        // Liên kết chỉ giữ khoá dữ liệu của tập tin được chia sẻ, không phải khoá của cả đối tượng lưu trữ
+        let link = links.link(id).unwrap();
+        assert_eq!(link.ciphers.keys().collect::<Vec<_>>(), ["report.txt"]);
+        let wrapped = &link.ciphers["report.txt"];
+        assert!(unlock(&token).unwrap(vault::STORAGE, wrapped).is_err());
+
+        // Thay khoá của đối tượng lưu trữ không đổi khoá dữ liệu nên liên kết vẫn mở được
+        files.rotate_key(&[7; 32]).unwrap();
+        assert_eq!(
+            read(links.resolve(&token, None, None, 2).unwrap()),
+            "Quarterly report"
+        );
+
+        // Liên kết tới thư mục chỉ mở được các tập tin đã có lúc tạo liên kết
+        let (folder, token) = links
+            .create(&files, Target::Folder, &Options::default(), 2)
+            .unwrap();
+        files.put("later.txt", b"Added later").unwrap();
+        assert_eq!(links.list(&token, None, 2).unwrap(), ["report.txt"]);
+        assert!(links.resolve(&token, Some("later.txt"), None, 2).is_err());
+        let uses = links.uses(&principal("files"), folder).unwrap();
+        assert!(!uses[1].granted);
+
+        // Khoá dữ liệu đã bọc được đọc lại từ nhật ký
+        drop(links);
+        let links = Links::with_iterations(reopen(&path).unwrap(), 10).unwrap();
+        let download = links.resolve(&token, Some("report.txt"), None, 2).unwrap();
+        assert_eq!(read(download), "Quarterly report");
+        assert_eq!(links.link(id).unwrap().ciphers, link.ciphers);
+
+        // Đối tượng lưu trữ đang khoá thì không tạo được liên kết
+        files.lock();
+        let file = Target::File("report.txt".to_string());
+        assert!(links.create(&files, file, &Options::default(), 3).is_err());
+
+        fs::remove_dir_all(path).unwrap();
+        fs::remove_dir_all(files.path()).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra việc ghi liên kết vào nhật ký kiểm toán
+    #[test]
+    fn test_audit() {
+        let (audit, audit_path) = crate::audit::tests::audit();
+        let (links, files, path) = links();
+        let links = links.with_audit(audit.clone());
+        let file = Target::File("report.txt".to_string());
+        let (id, token) = links.create(&files, file, &Options::default(), 0).unwrap();
+        links.resolve(&token, None, None, 1).unwrap();
+        links.revoke(&principal("files"), id).unwrap();
+        assert!(links.resolve(&token, None, None, 2).is_err());
+
+        let entries = audit.entries().unwrap();
+        let actions: Vec<&str> = entries.iter().map(|entry| entry.action.as_str()).collect();
+        assert_eq!(
+            actions,
+            ["share_link", "use_link", "revoke_link", "use_link"]
+        );
+        assert_eq!(entries[0].actor, "tester@files");
+        assert_eq!(entries[1].actor, format!("link {}", id));
+        assert_eq!(entries[1].after, "downloaded");
+        assert_eq!(entries[3].after, "Share link was revoked");
+
+        fs::remove_dir_all(path).unwrap();
+        fs::remove_dir_all(files.path()).unwrap();
+        fs::remove_dir_all(audit_path).unwrap();
+    }
+}
@@ -0,0 +1,202 @@
+// Khai báo các thư viện cần thiết
+use std::fs;
+use std::io::{self, Cursor, Read};
+use std::path::Path;
+
+use super::vault::{self, Key};
+use super::{deflate, scrub};
+
+// Khai báo thư mục đánh dấu các tập tin đã nén (`.packed/<tên tập tin>` chứa kích thước gốc) và tên cờ
+// bật nén của đối tượng lưu trữ trong đó
+pub(crate) const PACKED: &str = ".packed";
+pub(crate) const STORAGE: &str = ".storage";
+
+// Khai báo kích thước nhỏ nhất để nén một tập tin (tập tin nhỏ hơn được lưu nguyên)
+pub const MIN: usize = 1024;
+
+// Khai báo các phần mở rộng của định dạng đã nén sẵn (nén lại thường không được gì)
+const COMPRESSED: &[&str] = &[
+    "7z", "avi", "br", "bz2", "docx", "gif", "gz", "jar", "jpeg", "jpg", "m4a", "mkv", "mov",
+    "mp3", "mp4", "odt", "ogg", "pdf", "png", "pptx", "rar", "tgz", "webm", "webp", "xlsx", "xz",
+    "zip", "zst",
+];
+
+// Khai báo một struct để đọc nội dung gốc của một tập tin (giải nén và giải mã nếu cần)
+#[derive(Debug)]
+pub struct Reader {
+    inner: Inner, // Cách đọc tuỳ theo tập tin có được nén hay không
+}
+
+// Khai báo một enum để phân biệt tập tin lưu nguyên (đọc thẳng từ vault) với tập tin đã nén (giải nén vào bộ nhớ)
+#[derive(Debug)]
+enum Inner {
+    Plain(vault::Reader),
+    Packed(Cursor<Vec<u8>>),
+}
+
+// Khai báo các phương thức cho struct Reader
+impl Reader {
+    // Phương thức size trả về kích thước nội dung gốc
+    pub fn size(&self) -> u64 {
+        match &self.inner {
+            Inner::Plain(reader) => reader.size(),
+            Inner::Packed(content) => content.get_ref().len() as u64,
+        }
+    }
+
+    // Phương thức seek để đọc tiếp từ một vị trí trong nội dung gốc
+    pub fn seek(&mut self, offset: u64) -> io::Result<()> {
+        match &mut self.inner {
+            Inner::Plain(reader) => reader.seek(offset),
+            Inner::Packed(content) => {
+                content.set_position(offset);
+                Ok(())
+            }
+        }
+    }
+}
+
+// Cho phép đọc nội dung như một luồng
+impl Read for Reader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match &mut self.inner {
+            Inner::Plain(reader) => reader.read(buf),
+            Inner::Packed(content) => content.read(buf),
+        }
+    }
+}
+
+// Hàm enabled kiểm tra đối tượng lưu trữ có bật nén hay không
+pub(crate) fn enabled(path: &Path) -> bool {
+    path.join(PACKED).join(STORAGE).is_file()
+}
+
+// Hàm enable bật nén cho đối tượng lưu trữ (các tập tin ghi sau đó mới được cân nhắc nén)
+pub(crate) fn enable(path: &Path) -> io::Result<()> {
+    let packed = path.join(PACKED);
+    if !packed.exists() {
+        fs::create_dir(&packed)?;
+    }
+    fs::write(packed.join(STORAGE), "deflate")
+}
+
+// Hàm packed kiểm tra một tập tin có được lưu ở dạng nén hay không
+pub(crate) fn packed(path: &Path, name: &str) -> bool {
+    path.join(PACKED).join(name).is_file()
+}
+
+// Hàm worth chọn tập tin đáng thử nén: đủ lớn và không phải định dạng đã nén sẵn
+pub(crate) fn worth(name: &str, content: &[u8]) -> bool {
+    let extension = Path::new(name)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+    content.len() >= MIN
+        && !extension.is_some_and(|extension| COMPRESSED.contains(&extension.as_str()))
+}
+
+// Hàm write ghi đè một tập tin; khi đối tượng lưu trữ bật nén, tập tin đáng thử được nén bằng gzip và chỉ
+// giữ dạng nén nếu nhỏ hơn ít nhất 1/8 (nén trước, mã hoá sau); mã kiểm tra của nội dung gốc được ghi lại
+pub(crate) fn write(path: &Path, key: Option<&Key>, name: &str, content: &[u8]) -> io::Result<()> {
+    let compressed = if enabled(path) && worth(name, content) {
+        Some(deflate::gzip(content)).filter(|packed| packed.len() * 8 <= content.len() * 7)
+    } else {
+        None
+    };
+    match compressed {
+        Some(packed) => {
+            vault::write(path, key, name, &packed)?;
+            fs::write(path.join(PACKED).join(name), content.len().to_string())?;
+        }
+        None => {
+            vault::write(path, key, name, content)?;
+            unmark(path, name)?;
+        }
+    }
+    scrub::record(path, key, name, content)
+}
+
+// Hàm append ghi thêm vào cuối một tập tin; tập tin đã nén được ghi thêm một thành phần gzip mới (gzip cho
+// phép nối nhiều thành phần), tập tin lưu nguyên vẫn lưu nguyên; mã kiểm tra được băm tiếp từ phần ghi thêm
+pub(crate) fn append(path: &Path, key: Option<&Key>, name: &str, content: &[u8]) -> io::Result<()> {
+    if packed(path, name) {
+        let size = logical(path, name)?;
+        vault::append(path, key, name, &deflate::gzip(content))?;
+        fs::write(
+            path.join(PACKED).join(name),
+            (size + content.len() as u64).to_string(),
+        )?;
+    } else {
+        vault::append(path, key, name, content)?;
+    }
+    scrub::extend(path, key, name, content)
+}
+
+// Hàm reader mở một tập tin để đọc nội dung gốc
+pub(crate) fn reader(path: &Path, key: Option<&Key>, name: &str) -> io::Result<Reader> {
+    unpack(path, name, vault::reader(path, key, name)?)
+}
+
+// Hàm unsealed mở một tập tin để đọc nội dung gốc bằng khoá dữ liệu đã mở sẵn của chính tập tin đó
+pub(crate) fn unsealed(path: &Path, data: Option<Key>, name: &str) -> io::Result<Reader> {
+    unpack(path, name, vault::unsealed(path, data, name)?)
+}
+
+// Hàm unpack giải nén nội dung đọc từ vault nếu tập tin được lưu ở dạng nén
+fn unpack(path: &Path, name: &str, mut plain: vault::Reader) -> io::Result<Reader> {
+    let inner = if packed(path, name) {
+        let mut packed = Vec::new();
+        plain.read_to_end(&mut packed)?;
+        let content = deflate::gunzip(&packed).map_err(|error| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Cannot decompress {}: {}", name, error),
+            )
+        })?;
+        Inner::Packed(Cursor::new(content))
+    } else {
+        Inner::Plain(plain)
+    };
+    Ok(Reader { inner })
+}
+
+// Hàm read đọc toàn bộ nội dung gốc của một tập tin
+pub(crate) fn read(path: &Path, key: Option<&Key>, name: &str) -> io::Result<Vec<u8>> {
+    let mut content = Vec::new();
+    reader(path, key, name)?.read_to_end(&mut content)?;
+    Ok(content)
+}
+
+// Hàm remove xoá dấu nén, mã kiểm tra và khoá dữ liệu của một tập tin (nếu có)
+pub(crate) fn remove(path: &Path, name: &str) -> io::Result<()> {
+    unmark(path, name)?;
+    scrub::forget(path, name)?;
+    vault::remove(path, name)
+}
+
+// Hàm logical trả về kích thước gốc của một tập tin mà không cần đọc nội dung (không cần mở khoá)
+pub(crate) fn logical(path: &Path, name: &str) -> io::Result<u64> {
+    let marker = path.join(PACKED).join(name);
+    if marker.is_file() {
+        return fs::read_to_string(marker)?.trim().parse().map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid size marker for {}", name),
+            )
+        });
+    }
+    let physical = fs::metadata(path.join(name))?.len();
+    if vault::encrypted(path) {
+        vault::size(physical)
+    } else {
+        Ok(physical)
+    }
+}
+
+// Hàm unmark xoá dấu nén của một tập tin (nếu có)
+fn unmark(path: &Path, name: &str) -> io::Result<()> {
+    let marker = path.join(PACKED).join(name);
+    if marker.is_file() {
+        fs::remove_file(marker)?;
+    }
+    Ok(())
+}
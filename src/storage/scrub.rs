@@ -0,0 +1,343 @@
+// Khai báo các thư viện cần thiết
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+use super::pack;
+use super::vault::Key;
+use super::Storage;
+use crate::clock;
+use crate::crypto::sha256::Sha256;
+use crate::crypto::{hex, unhex};
+use crate::xml::Writer;
+
+// Khai báo thư mục chứa mã kiểm tra của các tập tin (`.sums/<tên tập tin>` chứa kích thước và SHA-256 của nội
+// dung gốc, cùng trạng thái băm để ghi thêm mà không phải băm lại từ đầu; trạng thái được mã hoá bằng khoá
+// của đối tượng lưu trữ khi có)
+pub(crate) const SUMS: &str = ".sums";
+
+// Khai báo một struct để đại diện cho mã kiểm tra của nội dung gốc (trước khi nén và mã hoá) của một tập tin
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sum {
+    pub size: u64,    // Kích thước nội dung gốc
+    pub hash: String, // SHA-256 của nội dung gốc (thập lục phân)
+}
+
+// Khai báo một trait để lấy lại nội dung gốc của một tập tin từ nơi khác (ví dụ: bản sao hoặc lịch sử phiên bản)
+pub trait Replica {
+    // Phương thức fetch trả về nội dung gốc của tập tin, hoặc None nếu không có
+    fn fetch(&self, name: &str) -> io::Result<Option<Vec<u8>>>;
+}
+
+// Cho phép dùng một closure làm nguồn khôi phục
+impl<F: Fn(&str) -> io::Result<Option<Vec<u8>>>> Replica for F {
+    fn fetch(&self, name: &str) -> io::Result<Option<Vec<u8>>> {
+        self(name)
+    }
+}
+
+// Cho phép dùng một đối tượng lưu trữ khác (đã đăng nhập) làm bản sao
+impl Replica for Storage {
+    fn fetch(&self, name: &str) -> io::Result<Option<Vec<u8>>> {
+        if !self.path().join(name).is_file() {
+            return Ok(None);
+        }
+        let mut content = Vec::new();
+        self.reader(name)?.read_to_end(&mut content)?;
+        Ok(Some(content))
+    }
+}
+
+// Khai báo một enum để đại diện cho vấn đề tìm thấy ở một tập tin
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    // Có mã kiểm tra nhưng tập tin không còn
+    Missing,
+    // Có tập tin nhưng không có mã kiểm tra (ví dụ: ghi ngoài API)
+    Extra,
+    // Kích thước nội dung gốc khác với lúc ghi
+    Size {
+        expected: u64,
+        actual: u64,
+    },
+    // Nội dung khác lúc ghi (actual là None nếu không giải mã hoặc giải nén được)
+    Checksum {
+        expected: String,
+        actual: Option<String>,
+    },
+}
+
+// Khai báo các phương thức cho enum Problem
+impl Problem {
+    // Phương thức kind trả về tên ngắn của vấn đề (dùng trong báo cáo)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Problem::Missing => "missing",
+            Problem::Extra => "extra",
+            Problem::Size { .. } => "size",
+            Problem::Checksum { .. } => "checksum",
+        }
+    }
+}
+
+// Cho phép hiển thị vấn đề dưới dạng chuỗi
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::Missing => write!(f, "file is missing"),
+            Problem::Extra => write!(f, "no checksum recorded"),
+            Problem::Size { expected, actual } => {
+                write!(f, "size is {} bytes, expected {}", actual, expected)
+            }
+            Problem::Checksum {
+                expected,
+                actual: Some(actual),
+            } => write!(f, "checksum is {}, expected {}", actual, expected),
+            Problem::Checksum { expected, .. } => {
+                write!(f, "content is unreadable, expected checksum {}", expected)
+            }
+        }
+    }
+}
+
+// Khai báo một struct để đại diện cho một tập tin có vấn đề trong báo cáo
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub name: String,     // Tên tập tin
+    pub problem: Problem, // Vấn đề tìm thấy
+    pub repaired: bool,   // Đã khôi phục được từ bản sao hay chưa
+}
+
+// Khai báo một struct để đại diện cho báo cáo của một lần kiểm tra toàn bộ đối tượng lưu trữ
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub storage: String,        // Tên đối tượng lưu trữ
+    pub time: u64,              // Thời điểm kiểm tra
+    pub checked: usize,         // Số tập tin đã kiểm tra
+    pub findings: Vec<Finding>, // Các tập tin có vấn đề
+}
+
+// Khai báo các phương thức cho struct Report
+impl Report {
+    // Phương thức clean kiểm tra mọi tập tin đều nguyên vẹn (hoặc đã được khôi phục)
+    pub fn clean(&self) -> bool {
+        self.findings.iter().all(|finding| finding.repaired)
+    }
+
+    // Phương thức xml để xuất báo cáo dưới dạng XML
+    pub fn xml(&self) -> String {
+        let mut xml = Writer::new();
+        let checked = self.checked.to_string();
+        let clean = self.clean().to_string();
+        xml.open(
+            "ScrubReport",
+            &[
+                ("storage", &self.storage),
+                ("time", &clock::stamp(self.time)),
+                ("checked", &checked),
+                ("clean", &clean),
+            ],
+        );
+        for finding in &self.findings {
+            let repaired = finding.repaired.to_string();
+            xml.open(
+                "Finding",
+                &[
+                    ("name", &finding.name),
+                    ("problem", finding.problem.kind()),
+                    ("repaired", &repaired),
+                ],
+            );
+            match &finding.problem {
+                Problem::Size { expected, actual } => {
+                    xml.leaf("Expected", &[], &expected.to_string()).leaf(
+                        "Actual",
+                        &[],
+                        &actual.to_string(),
+                    );
+                }
+                Problem::Checksum { expected, actual } => {
+                    xml.leaf("Expected", &[], expected).leaf(
+                        "Actual",
+                        &[],
+                        actual.as_deref().unwrap_or("unreadable"),
+                    );
+                }
+                Problem::Missing | Problem::Extra => {}
+            }
+            xml.close();
+        }
+        xml.finish()
+    }
+}
+
+// Hàm digest tính mã kiểm tra của một luồng nội dung
+pub fn digest(reader: impl Read) -> io::Result<Sum> {
+    let mut hasher = Sha256::new();
+    feed(&mut hasher, reader)?;
+    Ok(Sum {
+        size: hasher.length(),
+        hash: hex(&hasher.finish()),
+    })
+}
+
+// Hàm feed băm tiếp toàn bộ một luồng nội dung
+fn feed(hasher: &mut Sha256, mut reader: impl Read) -> io::Result<()> {
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let count = reader.read(&mut buffer)?;
+        if count == 0 {
+            return Ok(());
+        }
+        hasher.update(&buffer[..count]);
+    }
+}
+
+// Hàm record ghi mã kiểm tra của nội dung vừa ghi vào một tập tin
+pub(crate) fn record(path: &Path, key: Option<&Key>, name: &str, content: &[u8]) -> io::Result<()> {
+    let mut hasher = Sha256::new();
+    hasher.update(content);
+    save(path, key, name, &hasher)
+}
+
+// Hàm extend cập nhật mã kiểm tra sau khi ghi thêm vào cuối một tập tin: băm tiếp phần ghi thêm từ trạng
+// thái đã lưu, hoặc tính lại từ toàn bộ nội dung nếu không có trạng thái dùng được (ví dụ: bản ghi cũ hoặc
+// sau khi thay khoá của đối tượng lưu trữ)
+pub(crate) fn extend(path: &Path, key: Option<&Key>, name: &str, content: &[u8]) -> io::Result<()> {
+    match state(path, key, name) {
+        Some(mut hasher) => {
+            hasher.update(content);
+            save(path, key, name, &hasher)
+        }
+        None => rehash(path, key, name),
+    }
+}
+
+// Hàm rehash tính lại mã kiểm tra từ nội dung hiện tại của một tập tin
+pub(crate) fn rehash(path: &Path, key: Option<&Key>, name: &str) -> io::Result<()> {
+    let mut hasher = Sha256::new();
+    feed(&mut hasher, pack::reader(path, key, name)?)?;
+    save(path, key, name, &hasher)
+}
+
+// Hàm reseal mã hoá trạng thái băm chưa mã hoá của một tập tin bằng khoá của đối tượng lưu trữ (khi bật mã hoá)
+pub(crate) fn reseal(path: &Path, key: &Key, name: &str) -> io::Result<()> {
+    match state(path, None, name) {
+        Some(hasher) => save(path, Some(key), name, &hasher),
+        None => Ok(()),
+    }
+}
+
+// Hàm state đọc trạng thái băm đã lưu của một tập tin (None nếu không có hoặc không mở được)
+fn state(path: &Path, key: Option<&Key>, name: &str) -> Option<Sha256> {
+    let text = fs::read_to_string(path.join(SUMS).join(name)).ok()?;
+    let mut fields = text.split_whitespace();
+    let size: u64 = fields.next()?.parse().ok()?;
+    let state = fields.nth(1)?;
+    let bytes = match key {
+        Some(key) => key.reveal(&label(name), state).ok()?,
+        None => unhex(state).ok()?,
+    };
+    Sha256::resume(&bytes).filter(|hasher| hasher.length() == size)
+}
+
+// Hàm label trả về nhãn dùng khi mã hoá trạng thái băm của một tập tin
+fn label(name: &str) -> String {
+    format!("{}/{}", SUMS, name)
+}
+
+// Hàm forget xoá mã kiểm tra của một tập tin (nếu có)
+pub(crate) fn forget(path: &Path, name: &str) -> io::Result<()> {
+    let sum = path.join(SUMS).join(name);
+    if sum.is_file() {
+        fs::remove_file(sum)?;
+    }
+    Ok(())
+}
+
+// Hàm sum đọc mã kiểm tra đã ghi của một tập tin
+pub(crate) fn sum(path: &Path, name: &str) -> io::Result<Option<Sum>> {
+    let file = path.join(SUMS).join(name);
+    if !file.is_file() {
+        return Ok(None);
+    }
+    let text = fs::read_to_string(file)?;
+    let mut fields = text.split_whitespace();
+    let (size, hash) = fields
+        .next()
+        .zip(fields.next())
+        .and_then(|(size, hash)| Some((size.parse().ok()?, hash.to_string())))
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid checksum record for {}", name),
+            )
+        })?;
+    Ok(Some(Sum { size, hash }))
+}
+
+// Hàm names trả về tên các tập tin có mã kiểm tra
+pub(crate) fn names(path: &Path) -> io::Result<Vec<String>> {
+    let sums = path.join(SUMS);
+    if !sums.is_dir() {
+        return Ok(Vec::new());
+    }
+    fs::read_dir(sums)?
+        .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+        .collect()
+}
+
+// Hàm check kiểm tra một tập tin với mã kiểm tra đã ghi: so kích thước trước (không cần đọc nội dung), rồi
+// mới so SHA-256 của nội dung gốc
+pub(crate) fn check(path: &Path, key: Option<&Key>, name: &str) -> io::Result<Option<Problem>> {
+    let exists = path.join(name).is_file();
+    let Some(expected) = sum(path, name)? else {
+        return Ok(exists.then_some(Problem::Extra));
+    };
+    if !exists {
+        return Ok(Some(Problem::Missing));
+    }
+    let unreadable = Problem::Checksum {
+        expected: expected.hash.clone(),
+        actual: None,
+    };
+    match pack::logical(path, name) {
+        Ok(actual) if actual != expected.size => {
+            return Ok(Some(Problem::Size {
+                expected: expected.size,
+                actual,
+            }))
+        }
+        Ok(_) => {}
+        Err(_) => return Ok(Some(unreadable)),
+    }
+    let actual = match pack::reader(path, key, name).and_then(digest) {
+        Ok(actual) => actual,
+        Err(_) => return Ok(Some(unreadable)),
+    };
+    if actual.hash != expected.hash {
+        return Ok(Some(Problem::Checksum {
+            expected: expected.hash,
+            actual: Some(actual.hash),
+        }));
+    }
+    Ok(None)
+}
+
+// Hàm save ghi mã kiểm tra của một tập tin cùng trạng thái băm (mã hoá khi có khoá)
+fn save(path: &Path, key: Option<&Key>, name: &str, hasher: &Sha256) -> io::Result<()> {
+    let sums = path.join(SUMS);
+    if !sums.exists() {
+        fs::create_dir(&sums)?;
+    }
+    let state = match key {
+        Some(key) => key.conceal(&label(name), &hasher.export())?,
+        None => hex(&hasher.export()),
+    };
+    let hash = hex(&hasher.clone().finish());
+    fs::write(
+        sums.join(name),
+        format!("{} {} {}\n", hasher.length(), hash, state),
+    )
+}
@@ -0,0 +1,438 @@
+// Khai báo các thư viện cần thiết
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::scrub::Replica;
+use super::vault::{self, Key};
+use super::{pack, scrub, Access};
+use crate::social::message::{escape, unescape};
+
+// Khai báo thư mục chứa các ảnh chụp, tên bản kê trong mỗi ảnh chụp và tên chính sách giữ ảnh chụp
+pub(crate) const SNAPSHOTS: &str = ".snapshots";
+const MANIFEST: &str = ".manifest";
+const RETENTION: &str = ".retention";
+
+// Khai báo các thư mục siêu dữ liệu theo từng tập tin được chép vào ảnh chụp, cùng tên dành cho thiết lập
+// của cả đối tượng lưu trữ trong mỗi thư mục (không được chép khi khôi phục)
+const META: [(&str, &str); 4] = [
+    (".access", ".access"),
+    (vault::KEYS, vault::STORAGE),
+    (pack::PACKED, pack::STORAGE),
+    (scrub::SUMS, ""),
+];
+
+// Khai báo một struct để đại diện cho một tập tin trong bản kê
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub name: String,           // Tên tập tin
+    pub size: u64,              // Kích thước nội dung gốc
+    pub hash: String,           // SHA-256 của nội dung gốc
+    pub access: Option<Access>, // Quyền truy cập riêng của tập tin (nếu có)
+}
+
+// Khai báo một struct để đại diện cho bản kê của một đối tượng lưu trữ tại một thời điểm
+#[derive(Debug, Clone, PartialEq)]
+pub struct Manifest {
+    pub name: String,        // Tên ảnh chụp (rỗng nếu là trạng thái hiện tại)
+    pub time: u64,           // Thời điểm lập bản kê
+    pub actor: String,       // Người lập bản kê
+    pub access: Access,      // Quyền truy cập của đối tượng lưu trữ
+    pub encrypted: bool,     // Đối tượng lưu trữ có được mã hoá hay không
+    pub entries: Vec<Entry>, // Các tập tin (xếp theo tên)
+}
+
+// Khai báo các phương thức cho struct Manifest
+impl Manifest {
+    // Phương thức entry trả về một tập tin trong bản kê theo tên
+    pub fn entry(&self, name: &str) -> Option<&Entry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    // Phương thức size trả về tổng kích thước gốc của các tập tin
+    pub fn size(&self) -> u64 {
+        self.entries.iter().map(|entry| entry.size).sum()
+    }
+
+    // Phương thức render để ghi bản kê thành các dòng phân cách bằng tab
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "snapshot\t{}\t{}\t{}\t{}\t{}\n",
+            escape(&self.name),
+            self.time,
+            escape(&self.actor),
+            self.access,
+            self.encrypted
+        );
+        for entry in &self.entries {
+            let access = entry.access.map(|a| a.to_string()).unwrap_or_default();
+            out.push_str(&format!(
+                "file\t{}\t{}\t{}\t{}\n",
+                escape(&entry.name),
+                entry.size,
+                entry.hash,
+                access
+            ));
+        }
+        out
+    }
+
+    // Phương thức parse để đọc lại bản kê do render tạo ra
+    pub fn parse(text: &str) -> io::Result<Manifest> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Invalid manifest");
+        let mut lines = text.lines();
+        let head: Vec<String> = lines
+            .next()
+            .ok_or_else(invalid)?
+            .split('\t')
+            .map(unescape)
+            .collect();
+        if head.len() != 6 || head[0] != "snapshot" {
+            return Err(invalid());
+        }
+        let mut manifest = Manifest {
+            name: head[1].clone(),
+            time: head[2].parse().map_err(|_| invalid())?,
+            actor: head[3].clone(),
+            access: head[4].parse()?,
+            encrypted: head[5] == "true",
+            entries: Vec::new(),
+        };
+        for line in lines {
+            let fields: Vec<String> = line.split('\t').map(unescape).collect();
+            if fields.len() != 5 || fields[0] != "file" {
+                return Err(invalid());
+            }
+            manifest.entries.push(Entry {
+                name: fields[1].clone(),
+                size: fields[2].parse().map_err(|_| invalid())?,
+                hash: fields[3].clone(),
+                access: match fields[4].as_str() {
+                    "" => None,
+                    access => Some(access.parse()?),
+                },
+            });
+        }
+        Ok(manifest)
+    }
+}
+
+// Khai báo một enum để đại diện cho một thay đổi giữa hai bản kê
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added(String),          // Tập tin mới
+    Removed(String),        // Tập tin bị xoá
+    Modified(String),       // Tập tin đổi nội dung hoặc quyền truy cập riêng
+    Access(Access, Access), // Quyền truy cập của đối tượng lưu trữ thay đổi (trước, sau)
+}
+
+// Hàm diff so sánh hai bản kê và trả về các thay đổi (quyền truy cập trước, sau đó các tập tin theo tên)
+pub fn diff(from: &Manifest, to: &Manifest) -> Vec<Change> {
+    let mut changes = Vec::new();
+    if from.access != to.access {
+        changes.push(Change::Access(from.access, to.access));
+    }
+    let mut names: Vec<&str> = from
+        .entries
+        .iter()
+        .chain(&to.entries)
+        .map(|entry| entry.name.as_str())
+        .collect();
+    names.sort();
+    names.dedup();
+    for name in names {
+        match (from.entry(name), to.entry(name)) {
+            (None, Some(_)) => changes.push(Change::Added(name.to_string())),
+            (Some(_), None) => changes.push(Change::Removed(name.to_string())),
+            (Some(a), Some(b)) if a != b => changes.push(Change::Modified(name.to_string())),
+            _ => {}
+        }
+    }
+    changes
+}
+
+// Khai báo một struct để đại diện cho chính sách giữ ảnh chụp: một ảnh chụp được giữ nếu nằm trong `last`
+// ảnh chụp mới nhất hoặc được chụp trong vòng `within` giây; không đặt giới hạn nào thì giữ tất cả
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Retention {
+    pub last: Option<usize>, // Số ảnh chụp mới nhất được giữ
+    pub within: Option<u64>, // Tuổi tối đa (giây) của ảnh chụp được giữ
+}
+
+// Khai báo các phương thức cho struct Retention
+impl Retention {
+    // Phương thức expired trả về tên các ảnh chụp hết hạn theo chính sách tại thời điểm `now`
+    pub fn expired(&self, snapshots: &[Manifest], now: u64) -> Vec<String> {
+        if self.last.is_none() && self.within.is_none() {
+            return Vec::new();
+        }
+        let mut newest: Vec<&Manifest> = snapshots.iter().collect();
+        newest.sort_by(|a, b| (b.time, &b.name).cmp(&(a.time, &a.name)));
+        newest
+            .iter()
+            .enumerate()
+            .filter(|(index, snapshot)| {
+                let recent = self.last.is_some_and(|last| *index < last);
+                let young = self
+                    .within
+                    .is_some_and(|within| now.saturating_sub(snapshot.time) <= within);
+                !recent && !young
+            })
+            .map(|(_, snapshot)| snapshot.name.clone())
+            .collect()
+    }
+
+    // Phương thức render để ghi chính sách thành một dòng (`-` nếu không giới hạn)
+    pub(crate) fn render(&self) -> String {
+        let field = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+        format!(
+            "{}\t{}\n",
+            field(self.last.map(|last| last.to_string())),
+            field(self.within.map(|within| within.to_string()))
+        )
+    }
+
+    // Phương thức parse để đọc lại chính sách do render tạo ra
+    pub(crate) fn parse(text: &str) -> io::Result<Retention> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Invalid retention policy");
+        let (last, within) = text.trim().split_once('\t').ok_or_else(invalid)?;
+        Ok(Retention {
+            last: match last {
+                "-" => None,
+                last => Some(last.parse().map_err(|_| invalid())?),
+            },
+            within: match within {
+                "-" => None,
+                within => Some(within.parse().map_err(|_| invalid())?),
+            },
+        })
+    }
+}
+
+// Khai báo một struct để đọc nội dung của một ảnh chụp (dùng được làm nguồn khôi phục cho `Storage::verify`)
+#[derive(Debug)]
+pub struct Snapshot {
+    manifest: Manifest, // Bản kê của ảnh chụp
+    dir: PathBuf,       // Thư mục của ảnh chụp
+    key: Option<Key>,   // Khoá của đối tượng lưu trữ (nếu được mã hoá)
+}
+
+// Khai báo các phương thức cho struct Snapshot
+impl Snapshot {
+    // Phương thức manifest trả về bản kê của ảnh chụp
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    // Phương thức read để đọc nội dung gốc của một tập tin trong ảnh chụp
+    pub fn read(&self, name: &str) -> io::Result<Vec<u8>> {
+        if self.manifest.entry(name).is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not in snapshot {}", name, self.manifest.name),
+            ));
+        }
+        pack::read(&self.dir, self.key.as_ref(), name)
+    }
+}
+
+// Cho phép dùng một ảnh chụp làm nguồn khôi phục tập tin bị xoá hoặc bị thay thế (tập tin bị sửa tại chỗ ngoài
+// API thì ảnh chụp cũng hỏng theo vì dùng chung liên kết cứng; verify sẽ không dùng nội dung sai mã kiểm tra)
+impl Replica for Snapshot {
+    fn fetch(&self, name: &str) -> io::Result<Option<Vec<u8>>> {
+        match self.manifest.entry(name) {
+            Some(_) => self.read(name).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+// Hàm check kiểm tra tên của một ảnh chụp mới: không rỗng, không bắt đầu bằng dấu chấm, không chứa dấu gạch
+// chéo và chưa được dùng
+pub(crate) fn check(path: &Path, name: &str) -> io::Result<()> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid snapshot name: {}", name),
+        ));
+    }
+    if path.join(SNAPSHOTS).join(name).exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("Snapshot {} already exists", name),
+        ));
+    }
+    Ok(())
+}
+
+// Hàm take chụp các tập tin trong bản kê: tập tin dữ liệu được liên kết cứng (vì mọi thao tác ghi đều thay
+// tập tin bằng tập tin mới nên ảnh chụp không bị sửa theo), siêu dữ liệu nhỏ được chép; ảnh chụp được dựng
+// trong một thư mục tạm rồi mới đổi tên nên không bao giờ dở dang
+pub(crate) fn take(path: &Path, manifest: &Manifest) -> io::Result<()> {
+    let root = path.join(SNAPSHOTS);
+    if !root.exists() {
+        fs::create_dir(&root)?;
+    }
+    let partial = root.join(format!(".{}.partial", manifest.name));
+    if partial.exists() {
+        fs::remove_dir_all(&partial)?;
+    }
+    fs::create_dir(&partial)?;
+    for entry in &manifest.entries {
+        link(&path.join(&entry.name), &partial.join(&entry.name))?;
+    }
+    for (meta, reserved) in META {
+        copy(&path.join(meta), &partial.join(meta), reserved)?;
+    }
+    fs::write(partial.join(MANIFEST), manifest.render())?;
+    fs::rename(partial, root.join(&manifest.name))
+}
+
+// Hàm load đọc bản kê của một ảnh chụp
+pub(crate) fn load(path: &Path, name: &str) -> io::Result<Manifest> {
+    let manifest = path.join(SNAPSHOTS).join(name).join(MANIFEST);
+    if name.starts_with('.') || name.contains(['/', '\\']) || !manifest.is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Snapshot {} not found", name),
+        ));
+    }
+    Manifest::parse(&fs::read_to_string(manifest)?)
+}
+
+// Hàm open mở một ảnh chụp để đọc
+pub(crate) fn open(path: &Path, name: &str, key: Option<&Key>) -> io::Result<Snapshot> {
+    Ok(Snapshot {
+        manifest: load(path, name)?,
+        dir: path.join(SNAPSHOTS).join(name),
+        key: key.cloned(),
+    })
+}
+
+// Hàm list trả về bản kê của các ảnh chụp, cũ nhất trước
+pub(crate) fn list(path: &Path) -> io::Result<Vec<Manifest>> {
+    let mut snapshots = Vec::new();
+    for dir in dirs(path)? {
+        let name = dir.file_name().unwrap().to_string_lossy().into_owned();
+        snapshots.push(load(path, &name)?);
+    }
+    snapshots.sort_by(|a, b| (a.time, &a.name).cmp(&(b.time, &b.name)));
+    Ok(snapshots)
+}
+
+// Hàm dirs trả về thư mục của các ảnh chụp đã hoàn tất
+pub(crate) fn dirs(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let root = path.join(SNAPSHOTS);
+    if !root.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut dirs = Vec::new();
+    for entry in fs::read_dir(root)? {
+        let entry = entry?;
+        if entry.path().is_dir() && !entry.file_name().to_string_lossy().starts_with('.') {
+            dirs.push(entry.path());
+        }
+    }
+    Ok(dirs)
+}
+
+// Hàm restore thay các tập tin hiện tại bằng các tập tin của ảnh chụp (liên kết cứng, không chép dữ liệu);
+// các thiết lập của cả đối tượng lưu trữ (khoá, bật nén) được giữ nguyên
+pub(crate) fn restore(path: &Path, manifest: &Manifest) -> io::Result<()> {
+    let dir = path.join(SNAPSHOTS).join(&manifest.name);
+    clear(path)?;
+    for (meta, reserved) in META {
+        copy(&dir.join(meta), &path.join(meta), reserved)?;
+    }
+    for entry in &manifest.entries {
+        link(&dir.join(&entry.name), &path.join(&entry.name))?;
+    }
+    Ok(())
+}
+
+// Hàm clear xoá các tập tin và siêu dữ liệu theo từng tập tin của đối tượng lưu trữ, giữ lại các thiết lập của
+// cả đối tượng lưu trữ và các ảnh chụp
+pub(crate) fn clear(path: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.path().is_file() {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    for (meta, reserved) in META {
+        let current = path.join(meta);
+        if current.is_dir() {
+            for entry in fs::read_dir(&current)? {
+                let entry = entry?;
+                if entry.file_name() != reserved && entry.path().is_file() {
+                    fs::remove_file(entry.path())?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// Hàm remove xoá một ảnh chụp
+pub(crate) fn remove(path: &Path, name: &str) -> io::Result<()> {
+    load(path, name)?;
+    fs::remove_dir_all(path.join(SNAPSHOTS).join(name))
+}
+
+// Hàm retention đọc chính sách giữ ảnh chụp (mặc định giữ tất cả)
+pub(crate) fn retention(path: &Path) -> io::Result<Retention> {
+    let file = path.join(SNAPSHOTS).join(RETENTION);
+    if !file.is_file() {
+        return Ok(Retention::default());
+    }
+    Retention::parse(&fs::read_to_string(file)?)
+}
+
+// Hàm set_retention ghi chính sách giữ ảnh chụp
+pub(crate) fn set_retention(path: &Path, retention: &Retention) -> io::Result<()> {
+    let root = path.join(SNAPSHOTS);
+    if !root.exists() {
+        fs::create_dir(&root)?;
+    }
+    fs::write(root.join(RETENTION), retention.render())
+}
+
+// Hàm recover xoá các ảnh chụp dở dang và hoàn tất các lần thay khoá dở dang trong ảnh chụp
+pub(crate) fn recover(path: &Path) -> io::Result<()> {
+    let root = path.join(SNAPSHOTS);
+    if !root.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(&root)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().ends_with(".partial") {
+            fs::remove_dir_all(entry.path())?;
+        }
+    }
+    for dir in dirs(path)? {
+        vault::recover(&dir)?;
+    }
+    Ok(())
+}
+
+// Hàm link tạo liên kết cứng tới một tập tin (chép nếu hệ thống tập tin không hỗ trợ)
+fn link(from: &Path, to: &Path) -> io::Result<()> {
+    fs::hard_link(from, to).or_else(|_| fs::copy(from, to).map(|_| ()))
+}
+
+// Hàm copy chép các tập tin trong một thư mục siêu dữ liệu (trừ tên dành riêng) sang thư mục khác
+fn copy(from: &Path, to: &Path, reserved: &str) -> io::Result<()> {
+    if !from.is_dir() {
+        return Ok(());
+    }
+    if !to.exists() {
+        fs::create_dir(to)?;
+    }
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        if entry.file_name() != reserved && entry.path().is_file() {
+            fs::copy(entry.path(), to.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
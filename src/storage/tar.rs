@@ -0,0 +1,230 @@
+// Khai báo các thư viện cần thiết
+use std::io::{self, Read, Write};
+
+// Khai báo kích thước một khối của tar (mỗi đầu mục và phần nội dung được làm tròn lên bội số của khối)
+const BLOCK: usize = 512;
+
+// Khai báo độ dài tối đa của tên một mục (trường name của ustar, không dùng trường prefix)
+const NAME: usize = 100;
+
+// Khai báo kích thước tối đa của một mục (11 chữ số bát phân trong trường size)
+const MAX_SIZE: u64 = 0o77777777777;
+
+// Khai báo một struct để ghi một luồng tar theo định dạng ustar (POSIX.1-1988), đọc được bằng `tar` thông thường
+#[derive(Debug)]
+pub struct Writer<W: Write> {
+    out: W, // Luồng đích
+}
+
+// Khai báo các phương thức cho struct Writer
+impl<W: Write> Writer<W> {
+    // Phương thức new để tạo một bộ ghi tar vào luồng cho trước
+    pub fn new(out: W) -> Writer<W> {
+        Writer { out }
+    }
+
+    // Phương thức file để ghi một tập tin thường với tên, thời điểm sửa và nội dung cho trước
+    pub fn file(&mut self, name: &str, mtime: u64, content: &[u8]) -> io::Result<()> {
+        if name.is_empty() || name.len() > NAME {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid archive member name: {}", name),
+            ));
+        }
+        if content.len() as u64 > MAX_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Archive member {} is too large", name),
+            ));
+        }
+        self.out
+            .write_all(&header(name, content.len() as u64, mtime))?;
+        self.out.write_all(content)?;
+        self.out
+            .write_all(&[0; BLOCK][..padding(content.len() as u64)])
+    }
+
+    // Phương thức finish để ghi hai khối rỗng đánh dấu cuối luồng và trả lại luồng đích
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.write_all(&[0; 2 * BLOCK])?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+// Khai báo một struct để đọc lần lượt các tập tin thường trong một luồng tar (các loại mục khác được bỏ qua)
+#[derive(Debug)]
+pub struct Reader<R: Read> {
+    input: R,   // Luồng nguồn
+    done: bool, // Đã gặp khối đánh dấu cuối luồng hay chưa
+}
+
+// Khai báo các phương thức cho struct Reader
+impl<R: Read> Reader<R> {
+    // Phương thức new để tạo một bộ đọc tar từ luồng cho trước
+    pub fn new(input: R) -> Reader<R> {
+        Reader { input, done: false }
+    }
+
+    // Phương thức entry trả về tên và nội dung của tập tin tiếp theo, hoặc None khi hết luồng
+    pub fn entry(&mut self) -> io::Result<Option<(String, Vec<u8>)>> {
+        while !self.done {
+            let mut block = [0; BLOCK];
+            self.input.read_exact(&mut block)?;
+            if block.iter().all(|&byte| byte == 0) {
+                self.done = true;
+                break;
+            }
+            let stored = octal(&block[148..156])?;
+            let actual: u64 = block
+                .iter()
+                .enumerate()
+                .map(|(i, &byte)| {
+                    if (148..156).contains(&i) {
+                        32
+                    } else {
+                        byte as u64
+                    }
+                })
+                .sum();
+            if stored != actual {
+                return Err(corrupt("header checksum mismatch"));
+            }
+            let size = octal(&block[124..136])?;
+            let mut content = Vec::new();
+            (&mut self.input).take(size).read_to_end(&mut content)?;
+            if (content.len() as u64) < size {
+                return Err(corrupt("unexpected end of archive"));
+            }
+            io::copy(
+                &mut (&mut self.input).take(padding(size) as u64),
+                &mut io::sink(),
+            )?;
+            if matches!(block[156], b'0' | 0) {
+                let mut name = field(&block[0..100]);
+                let prefix = field(&block[345..500]);
+                if &block[257..262] == b"ustar" && !prefix.is_empty() {
+                    name = format!("{}/{}", prefix, name);
+                }
+                return Ok(Some((name, content)));
+            }
+        }
+        Ok(None)
+    }
+}
+
+// Hàm header tạo đầu mục ustar của một tập tin thường
+fn header(name: &str, size: u64, mtime: u64) -> [u8; BLOCK] {
+    let mut block = [0; BLOCK];
+    block[..name.len()].copy_from_slice(name.as_bytes());
+    block[100..108].copy_from_slice(b"0000644\0");
+    block[108..116].copy_from_slice(b"0000000\0");
+    block[116..124].copy_from_slice(b"0000000\0");
+    block[124..136].copy_from_slice(format!("{:011o}\0", size).as_bytes());
+    block[136..148].copy_from_slice(format!("{:011o}\0", mtime.min(MAX_SIZE)).as_bytes());
+    block[148..156].copy_from_slice(b"        ");
+    block[156] = b'0';
+    block[257..263].copy_from_slice(b"ustar\0");
+    block[263..265].copy_from_slice(b"00");
+    let sum: u32 = block.iter().map(|&byte| byte as u32).sum();
+    block[148..156].copy_from_slice(format!("{:06o}\0 ", sum).as_bytes());
+    block
+}
+
+// Hàm padding tính số byte đệm để nội dung tròn khối
+fn padding(size: u64) -> usize {
+    (BLOCK - (size as usize % BLOCK)) % BLOCK
+}
+
+// Hàm octal đọc một trường số bát phân (kết thúc bằng NUL hoặc khoảng trắng)
+fn octal(field: &[u8]) -> io::Result<u64> {
+    let text: String = field
+        .iter()
+        .take_while(|&&byte| byte != 0)
+        .map(|&byte| byte as char)
+        .collect();
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(text, 8).map_err(|_| corrupt("invalid numeric field"))
+}
+
+// Hàm field đọc một trường chuỗi (kết thúc bằng NUL)
+fn field(field: &[u8]) -> String {
+    let end = field
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+// Hàm corrupt tạo lỗi cho luồng tar hỏng
+fn corrupt(reason: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Corrupt archive: {}", reason),
+    )
+}
+
+// Khai báo module kiểm thử
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use std::fs;
+    use std::process::Command;
+
+    // Khai báo một hàm để kiểm tra việc ghi rồi đọc lại một luồng tar
+    #[test]
+    fn test_round_trip() {
+        let mut writer = Writer::new(Vec::new());
+        writer.file("a.txt", 1_700_000_000, b"Alpha").unwrap();
+        writer.file("dir/empty", 0, b"").unwrap();
+        writer.file("big.bin", 0, &[7; 1500]).unwrap();
+        assert!(writer.file(&"x".repeat(101), 0, b"").is_err());
+        let archive = writer.finish().unwrap();
+        assert_eq!(archive.len() % BLOCK, 0);
+
+        let mut reader = Reader::new(&archive[..]);
+        assert_eq!(
+            reader.entry().unwrap(),
+            Some(("a.txt".to_string(), b"Alpha".to_vec()))
+        );
+        assert_eq!(
+            reader.entry().unwrap(),
+            Some(("dir/empty".to_string(), Vec::new()))
+        );
+        assert_eq!(
+            reader.entry().unwrap(),
+            Some(("big.bin".to_string(), vec![7; 1500]))
+        );
+        assert_eq!(reader.entry().unwrap(), None);
+
+        // Đầu mục bị sửa thì bị phát hiện nhờ mã kiểm tra
+        let mut broken = archive.clone();
+        broken[0] = b'b';
+        assert_eq!(
+            Reader::new(&broken[..]).entry().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    // Khai báo một hàm để kiểm tra luồng tar đọc được bằng công cụ tar của hệ thống (nếu có)
+    #[test]
+    fn test_compatibility() {
+        let mut writer = Writer::new(Vec::new());
+        writer
+            .file("rim/hello.txt", 1_700_000_000, b"Hello, tar!\n")
+            .unwrap();
+        let archive = writer.finish().unwrap();
+        let path = std::env::temp_dir().join(format!("rim-tar-{}.tar", std::process::id()));
+        fs::write(&path, archive).unwrap();
+        let output = Command::new("tar").arg("-xOf").arg(&path).output();
+        fs::remove_file(&path).unwrap();
+        if let Ok(output) = output {
+            assert!(output.status.success());
+            assert_eq!(output.stdout, b"Hello, tar!\n");
+        }
+    }
+}
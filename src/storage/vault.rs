@@ -0,0 +1,625 @@
+// Khai báo các thư viện cần thiết
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+use crate::crypto::{self, chacha, hex, sha256, unhex};
+
+// Khai báo kích thước bản rõ của một khối mã hoá (tính bằng byte)
+pub const CHUNK: usize = 64 * 1024;
+
+// Khai báo kích thước nonce và phần thêm vào mỗi khối (nonce ở đầu, tag ở cuối)
+const NONCE: usize = 12;
+const OVERHEAD: u64 = (NONCE + chacha::TAG) as u64;
+
+// Khai báo kích thước trên đĩa của một khối đầy
+const SEALED: u64 = CHUNK as u64 + OVERHEAD;
+
+// Khai báo thư mục chứa các khoá đã bọc và tên khoá của đối tượng lưu trữ trong đó
+pub(crate) const KEYS: &str = ".keys";
+pub(crate) const STORAGE: &str = ".storage";
+
+// Khai báo tên tập tin chứa khoá của đối tượng lưu trữ (đã bọc) trong lúc các tập tin hiện có đang được mã hoá
+const PENDING: &str = ".pending";
+
+// Khai báo thư mục tạm để ghi nội dung mới trước khi thay cho tập tin cũ
+const TMP: &str = ".tmp";
+
+// Khai báo thư mục (trong thư mục tạm) chứa nội dung mã hoá đã ghi xong, chờ được đưa vào chỗ cùng khoá của nó
+const READY: &str = ".ready";
+
+// Khai báo một struct để giữ một khoá 32 byte (không in ra khi debug)
+#[derive(Clone, PartialEq)]
+pub(crate) struct Key([u8; 32]);
+
+// Khai báo cách hiển thị khoá khi debug mà không lộ nội dung
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Key(..)")
+    }
+}
+
+// Khai báo các phương thức cho struct Key
+impl Key {
+    // Phương thức new để tạo một khoá ngẫu nhiên
+    pub(crate) fn new() -> io::Result<Key> {
+        Ok(Key(crypto::key()?))
+    }
+
+    // Phương thức raw để dùng một khoá có sẵn (ví dụ: khoá chủ do người gọi giữ)
+    pub(crate) fn raw(bytes: &[u8; 32]) -> Key {
+        Key(*bytes)
+    }
+
+    // Phương thức derive để dẫn xuất một khoá từ một bí mật và nhãn (HMAC-SHA256)
+    pub(crate) fn derive(secret: &[u8], label: &str) -> Key {
+        Key(sha256::hmac(secret, label.as_bytes()))
+    }
+
+    // Phương thức wrap để bọc một khoá khác bằng khoá này, gắn với nhãn để không đổi chỗ được
+    pub(crate) fn wrap(&self, label: &str, key: &Key) -> io::Result<String> {
+        self.conceal(label, &key.0)
+    }
+
+    // Phương thức unwrap để mở một khoá đã được bọc bằng khoá này với cùng nhãn
+    pub(crate) fn unwrap(&self, label: &str, text: &str) -> io::Result<Key> {
+        let key = self.reveal(label, text)?;
+        Ok(Key(key.try_into().map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid wrapped key for {}", label),
+            )
+        })?))
+    }
+
+    // Phương thức conceal để mã hoá một đoạn dữ liệu ngắn bằng khoá này, gắn với nhãn (dạng `nonce:bản mã`)
+    pub(crate) fn conceal(&self, label: &str, data: &[u8]) -> io::Result<String> {
+        let mut nonce = [0; NONCE];
+        crypto::random(&mut nonce)?;
+        let sealed = chacha::seal(&self.0, &nonce, label.as_bytes(), data);
+        Ok(format!("{}:{}", hex(&nonce), hex(&sealed)))
+    }
+
+    // Phương thức reveal để giải mã một đoạn dữ liệu do `conceal` tạo với cùng nhãn
+    pub(crate) fn reveal(&self, label: &str, text: &str) -> io::Result<Vec<u8>> {
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid sealed value for {}", label),
+            )
+        };
+        let (nonce, sealed) = text.trim().split_once(':').ok_or_else(invalid)?;
+        let nonce: [u8; NONCE] = unhex(nonce)?.try_into().map_err(|_| invalid())?;
+        chacha::open(&self.0, &nonce, label.as_bytes(), &unhex(sealed)?).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("Wrong key for {}", label),
+            )
+        })
+    }
+}
+
+// Khai báo một struct để đọc nội dung một tập tin trong đối tượng lưu trữ như một luồng, giải mã từng
+// khối khi đối tượng lưu trữ được mã hoá
+#[derive(Debug)]
+pub struct Reader {
+    file: File,       // Tập tin được mở chỉ để đọc
+    key: Option<Key>, // Khoá dữ liệu của tập tin (None nếu không mã hoá)
+    physical: u64,    // Kích thước trên đĩa
+    size: u64,        // Kích thước nội dung
+    index: u64,       // Khối tiếp theo cần đọc
+    buffer: Vec<u8>,  // Bản rõ của khối hiện tại
+    position: usize,  // Vị trí đã đọc trong khối hiện tại
+}
+
+// Khai báo các phương thức cho struct Reader
+impl Reader {
+    // Phương thức size trả về kích thước nội dung (sau khi giải mã)
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    // Phương thức seek để đọc tiếp từ một vị trí trong nội dung (chỉ giải mã khối chứa vị trí đó)
+    pub fn seek(&mut self, offset: u64) -> io::Result<()> {
+        let Some(key) = self.key.clone() else {
+            self.file.seek(SeekFrom::Start(offset))?;
+            return Ok(());
+        };
+        self.buffer.clear();
+        self.position = 0;
+        if offset >= self.size {
+            self.index = chunks(self.physical);
+            return Ok(());
+        }
+        self.index = offset / CHUNK as u64;
+        self.file.seek(SeekFrom::Start(self.index * SEALED))?;
+        self.load(&key)?;
+        self.position = (offset % CHUNK as u64) as usize;
+        Ok(())
+    }
+
+    // Phương thức load để đọc và giải mã khối tiếp theo
+    fn load(&mut self, key: &Key) -> io::Result<()> {
+        let last = self.index + 1 == chunks(self.physical);
+        let length = if last {
+            self.physical - self.index * SEALED
+        } else {
+            SEALED
+        };
+        let mut sealed = vec![0; length as usize];
+        self.file.read_exact(&mut sealed)?;
+        self.buffer = open(key, self.index, last, &sealed)?;
+        self.position = 0;
+        self.index += 1;
+        Ok(())
+    }
+}
+
+// Cho phép đọc nội dung như một luồng
+impl Read for Reader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Some(key) = self.key.clone() else {
+            return self.file.read(buf);
+        };
+        while self.position == self.buffer.len() {
+            if self.index >= chunks(self.physical) {
+                return Ok(0);
+            }
+            self.load(&key)?;
+        }
+        let count = buf.len().min(self.buffer.len() - self.position);
+        buf[..count].copy_from_slice(&self.buffer[self.position..self.position + count]);
+        self.position += count;
+        Ok(count)
+    }
+}
+
+// Hàm reader mở một tập tin trong đối tượng lưu trữ để đọc, dùng khoá của đối tượng lưu trữ (nếu có)
+// để mở khoá dữ liệu của tập tin
+pub(crate) fn reader(path: &Path, key: Option<&Key>, name: &str) -> io::Result<Reader> {
+    let data = match key {
+        Some(key) => Some(data_key(path, key, name)?),
+        None => None,
+    };
+    unsealed(path, data, name)
+}
+
+// Hàm unsealed mở một tập tin để đọc bằng khoá dữ liệu đã mở sẵn của chính tập tin đó (ví dụ: khoá mở
+// được từ liên kết chia sẻ), không cần khoá của đối tượng lưu trữ
+pub(crate) fn unsealed(path: &Path, data: Option<Key>, name: &str) -> io::Result<Reader> {
+    let file = File::open(path.join(name))?;
+    let physical = file.metadata()?.len();
+    let (key, size) = match data {
+        Some(data) => (Some(data), size(physical)?),
+        None => (None, physical),
+    };
+    Ok(Reader {
+        file,
+        key,
+        physical,
+        size,
+        index: 0,
+        buffer: Vec::new(),
+        position: 0,
+    })
+}
+
+// Hàm write ghi đè một tập tin trong đối tượng lưu trữ; khi có khoá, tập tin được mã hoá bằng một khoá
+// dữ liệu mới (bọc bằng khoá của đối tượng lưu trữ trong `.keys/<tên tập tin>`)
+//
+// Khoá mới và bản mã được ghi vào `.tmp` trước; bản mã được chuyển sang `.tmp/.ready` khi cả hai đã ghi
+// xong, rồi mới lần lượt đổi tên khoá và bản mã vào chỗ. Nếu dừng trước đó, `recover` bỏ các tập tin tạm
+// và giữ nguyên khoá cùng nội dung cũ; nếu dừng sau đó, `recover` đưa nốt khoá và bản mã mới vào chỗ.
+pub(crate) fn write(path: &Path, key: Option<&Key>, name: &str, content: &[u8]) -> io::Result<()> {
+    let Some(key) = key else {
+        return replace(path, name, content);
+    };
+    let data = Key::new()?;
+    let tmp = path.join(TMP);
+    for dir in [path.join(KEYS), tmp.join(KEYS), tmp.join(READY)] {
+        if !dir.exists() {
+            fs::create_dir_all(&dir)?;
+        }
+    }
+    fs::write(tmp.join(KEYS).join(name), key.wrap(name, &data)?)?;
+    fs::write(tmp.join(name), seal(&data, 0, content)?)?;
+    fs::rename(tmp.join(name), tmp.join(READY).join(name))?;
+    settle(path, name)
+}
+
+// Hàm settle đưa khoá mới (nếu còn trong `.tmp`) rồi bản mã đã sẵn sàng của một tập tin vào chỗ
+fn settle(path: &Path, name: &str) -> io::Result<()> {
+    let tmp = path.join(TMP);
+    let fresh = tmp.join(KEYS).join(name);
+    if fresh.exists() {
+        fs::rename(fresh, path.join(KEYS).join(name))?;
+    }
+    fs::rename(tmp.join(READY).join(name), path.join(name))
+}
+
+// Hàm replace ghi nội dung mới vào một tập tin tạm rồi đổi tên thay cho tập tin cũ, nên tập tin cũ (và các
+// liên kết cứng tới nó, ví dụ trong ảnh chụp) không bị sửa
+fn replace(path: &Path, name: &str, content: &[u8]) -> io::Result<()> {
+    let tmp = path.join(TMP);
+    if !tmp.exists() {
+        fs::create_dir(&tmp)?;
+    }
+    fs::write(tmp.join(name), content)?;
+    fs::rename(tmp.join(name), path.join(name))
+}
+
+// Hàm unshare tách một tập tin có liên kết cứng khác thành bản sao riêng trước khi sửa tại chỗ
+fn unshare(path: &Path, name: &str) -> io::Result<()> {
+    let dest = path.join(name);
+    if dest.exists() && fs::metadata(&dest)?.nlink() > 1 {
+        replace(path, name, &fs::read(&dest)?)?;
+    }
+    Ok(())
+}
+
+// Hàm append ghi thêm vào cuối một tập tin; khi mã hoá, chỉ khối cuối được mã hoá lại
+pub(crate) fn append(path: &Path, key: Option<&Key>, name: &str, content: &[u8]) -> io::Result<()> {
+    let dest = path.join(name);
+    unshare(path, name)?;
+    let Some(key) = key else {
+        let mut file = OpenOptions::new().create(true).append(true).open(&dest)?;
+        return file.write_all(content);
+    };
+    if !dest.exists() {
+        return write(path, Some(key), name, content);
+    }
+
+    // Giải mã khối cuối, cắt nó khỏi tập tin rồi ghi lại cùng nội dung mới (mỗi khối có nonce mới)
+    let data = data_key(path, key, name)?;
+    let mut file = OpenOptions::new().read(true).write(true).open(&dest)?;
+    let physical = file.metadata()?.len();
+    let last = chunks(physical) - 1;
+    file.seek(SeekFrom::Start(last * SEALED))?;
+    let mut sealed = Vec::new();
+    file.read_to_end(&mut sealed)?;
+    let mut tail = open(&data, last, true, &sealed)?;
+    tail.extend_from_slice(content);
+    file.set_len(last * SEALED)?;
+    file.seek(SeekFrom::Start(last * SEALED))?;
+    file.write_all(&seal(&data, last, &tail)?)?;
+    file.flush()
+}
+
+// Hàm remove xoá khoá dữ liệu của một tập tin (nếu có)
+pub(crate) fn remove(path: &Path, name: &str) -> io::Result<()> {
+    let wrapped = path.join(KEYS).join(name);
+    if wrapped.is_file() {
+        fs::remove_file(wrapped)?;
+    }
+    Ok(())
+}
+
+// Hàm encrypted kiểm tra đối tượng lưu trữ có được mã hoá (hoặc đang được mã hoá) hay không
+pub(crate) fn encrypted(path: &Path) -> bool {
+    path.join(KEYS).join(STORAGE).is_file() || pending(path)
+}
+
+// Hàm pending kiểm tra việc mã hoá các tập tin hiện có đã bắt đầu nhưng chưa hoàn tất
+pub(crate) fn pending(path: &Path) -> bool {
+    path.join(KEYS).join(PENDING).is_file()
+}
+
+// Hàm sealed kiểm tra một tập tin đã được mã hoá (đã có khoá dữ liệu) hay chưa
+pub(crate) fn sealed(path: &Path, name: &str) -> bool {
+    path.join(KEYS).join(name).is_file()
+}
+
+// Hàm unlock mở khoá của đối tượng lưu trữ bằng khoá chủ
+pub(crate) fn unlock(path: &Path, master: &Key) -> io::Result<Key> {
+    let name = if pending(path) { PENDING } else { STORAGE };
+    master.unwrap(STORAGE, &fs::read_to_string(path.join(KEYS).join(name))?)
+}
+
+// Hàm begin ghi khoá của đối tượng lưu trữ (đã bọc bằng khoá chủ) như một khoá đang chờ, trước khi các
+// tập tin hiện có được mã hoá, để một lần mã hoá bị dừng giữa chừng có thể được tiếp tục bằng khoá đó
+pub(crate) fn begin(path: &Path, master: &Key, key: &Key) -> io::Result<()> {
+    let tmp = path.join(TMP);
+    for dir in [path.join(KEYS), tmp.clone()] {
+        if !dir.exists() {
+            fs::create_dir_all(&dir)?;
+        }
+    }
+    fs::write(tmp.join(PENDING), master.wrap(STORAGE, key)?)?;
+    fs::rename(tmp.join(PENDING), path.join(KEYS).join(PENDING))
+}
+
+// Hàm commit đánh dấu việc mã hoá các tập tin hiện có đã hoàn tất bằng cách đổi khoá đang chờ thành
+// khoá của đối tượng lưu trữ
+pub(crate) fn commit(path: &Path) -> io::Result<()> {
+    let keys = path.join(KEYS);
+    fs::rename(keys.join(PENDING), keys.join(STORAGE))
+}
+
+// Hàm enclose bọc khoá của đối tượng lưu trữ bằng khoá chủ và ghi lại
+pub(crate) fn enclose(path: &Path, master: &Key, key: &Key) -> io::Result<()> {
+    let keys = path.join(KEYS);
+    if !keys.exists() {
+        fs::create_dir(&keys)?;
+    }
+    let name = if pending(path) { PENDING } else { STORAGE };
+    fs::write(keys.join(name), master.wrap(STORAGE, key)?)
+}
+
+// Hàm rotate thay khoá của đối tượng lưu trữ: mọi khoá dữ liệu được bọc lại bằng khoá mới, còn nội dung
+// các tập tin giữ nguyên
+//
+// Các khoá bọc lại được ghi vào `.keys.new` rồi mới thay cho `.keys`, nên nếu dừng giữa chừng thì
+// `recover` khi mở lại sẽ giữ nguyên bộ khoá cũ hoặc dùng trọn bộ khoá mới.
+pub(crate) fn rotate(path: &Path, master: &Key, old: &Key, new: &Key) -> io::Result<()> {
+    let keys = path.join(KEYS);
+    let fresh = path.join(format!("{}.new", KEYS));
+    if fresh.exists() {
+        fs::remove_dir_all(&fresh)?;
+    }
+    fs::create_dir(&fresh)?;
+    for entry in fs::read_dir(&keys)? {
+        let name = entry?.file_name().to_string_lossy().into_owned();
+        if name == STORAGE || name == PENDING {
+            continue;
+        }
+        let data = old.unwrap(&name, &fs::read_to_string(keys.join(&name))?)?;
+        fs::write(fresh.join(&name), new.wrap(&name, &data)?)?;
+    }
+    fs::write(fresh.join(STORAGE), master.wrap(STORAGE, new)?)?;
+
+    let stale = path.join(format!("{}.old", KEYS));
+    fs::rename(&keys, &stale)?;
+    fs::rename(&fresh, &keys)?;
+    fs::remove_dir_all(stale)
+}
+
+// Hàm recover hoàn tất hoặc huỷ một lần thay khoá hay ghi tập tin mã hoá bị dừng giữa chừng và xoá các
+// tập tin tạm còn sót
+pub(crate) fn recover(path: &Path) -> io::Result<()> {
+    let keys = path.join(KEYS);
+    let fresh = path.join(format!("{}.new", KEYS));
+    let stale = path.join(format!("{}.old", KEYS));
+    if !keys.exists() && stale.exists() {
+        // `.keys` đã được đổi tên: bộ khoá mới đã ghi xong nên dùng nó
+        fs::rename(&fresh, &keys)?;
+    }
+
+    // Bản mã trong `.tmp/.ready` đã ghi xong cùng khoá của nó nên được đưa nốt vào chỗ
+    let ready = path.join(TMP).join(READY);
+    if ready.is_dir() {
+        for entry in fs::read_dir(&ready)? {
+            settle(path, &entry?.file_name().to_string_lossy())?;
+        }
+    }
+    for leftover in [fresh, stale, path.join(TMP)] {
+        if leftover.exists() {
+            fs::remove_dir_all(leftover)?;
+        }
+    }
+    Ok(())
+}
+
+// Hàm data_key mở khoá dữ liệu của một tập tin
+pub(crate) fn data_key(path: &Path, key: &Key, name: &str) -> io::Result<Key> {
+    let wrapped = fs::read_to_string(path.join(KEYS).join(name)).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Missing data key for {}", name),
+        )
+    })?;
+    key.unwrap(name, &wrapped)
+}
+
+// Hàm seal mã hoá nội dung thành các khối bắt đầu từ khối `start` (nội dung rỗng vẫn có một khối để
+// không cắt bỏ được toàn bộ tập tin mà không bị phát hiện)
+fn seal(key: &Key, start: u64, content: &[u8]) -> io::Result<Vec<u8>> {
+    let count = content.len().div_ceil(CHUNK).max(1);
+    let mut out = Vec::with_capacity(content.len() + count * OVERHEAD as usize);
+    for i in 0..count {
+        let chunk = &content[(i * CHUNK).min(content.len())..((i + 1) * CHUNK).min(content.len())];
+        let mut nonce = [0; NONCE];
+        crypto::random(&mut nonce)?;
+        let aad = aad(start + i as u64, i + 1 == count);
+        out.extend_from_slice(&nonce);
+        out.extend(chacha::seal(&key.0, &nonce, &aad, chunk));
+    }
+    Ok(out)
+}
+
+// Hàm open giải mã một khối; khối bị sửa, đổi chỗ hoặc tập tin bị cắt bớt đều bị phát hiện
+fn open(key: &Key, index: u64, last: bool, sealed: &[u8]) -> io::Result<Vec<u8>> {
+    if sealed.len() < OVERHEAD as usize {
+        return Err(truncated());
+    }
+    let (nonce, body) = sealed.split_at(NONCE);
+    let nonce: [u8; NONCE] = nonce.try_into().unwrap();
+    chacha::open(&key.0, &nonce, &aad(index, last), body).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Encrypted chunk {} failed authentication", index),
+        )
+    })
+}
+
+// Hàm aad tạo dữ liệu phụ của một khối: số thứ tự và cờ khối cuối
+fn aad(index: u64, last: bool) -> [u8; 9] {
+    let mut aad = [0; 9];
+    aad[..8].copy_from_slice(&index.to_le_bytes());
+    aad[8] = last as u8;
+    aad
+}
+
+// Hàm chunks trả về số khối của một tập tin đã mã hoá
+fn chunks(physical: u64) -> u64 {
+    physical.div_ceil(SEALED)
+}
+
+// Hàm size trả về kích thước nội dung của một tập tin đã mã hoá từ kích thước trên đĩa
+pub(crate) fn size(physical: u64) -> io::Result<u64> {
+    let full = physical / SEALED;
+    match physical % SEALED {
+        0 if full > 0 => Ok(full * CHUNK as u64),
+        rest if rest >= OVERHEAD => Ok(full * CHUNK as u64 + rest - OVERHEAD),
+        _ => Err(truncated()),
+    }
+}
+
+// Hàm truncated tạo lỗi cho tập tin mã hoá bị cắt bớt
+fn truncated() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "Encrypted file is truncated")
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+    use crate::storage::tests::tempdir;
+
+    // Khai báo một hàm để đọc toàn bộ nội dung của một tập tin trong đối tượng lưu trữ
+    fn read(path: &Path, key: Option<&Key>, name: &str) -> io::Result<Vec<u8>> {
+        let mut content = Vec::new();
+        reader(path, key, name)?.read_to_end(&mut content)?;
+        Ok(content)
+    }
+
+    // Khai báo một hàm để tạo nội dung thử nghiệm dài hơn hai khối
+    fn content() -> Vec<u8> {
+        (0..2 * CHUNK + 100).map(|i| (i % 251) as u8).collect()
+    }
+
+    // Khai báo một hàm để kiểm tra việc bọc và mở khoá
+    #[test]
+    fn test_wrap() {
+        let master = Key::new().unwrap();
+        let key = Key::new().unwrap();
+        let wrapped = master.wrap("a.txt", &key).unwrap();
+        assert_eq!(master.unwrap("a.txt", &wrapped).unwrap(), key);
+        assert!(master.unwrap("b.txt", &wrapped).is_err());
+        let error = Key::new().unwrap().unwrap("a.txt", &wrapped).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        assert_eq!(format!("{:?}", key), "Key(..)");
+    }
+
+    // Khai báo một hàm để kiểm tra mã hoá theo khối, đọc theo vị trí và ghi thêm
+    #[test]
+    fn test_chunks() {
+        let path = tempdir().unwrap();
+        let key = Key::new().unwrap();
+        let content = content();
+        write(&path, Some(&key), "data.bin", &content).unwrap();
+        let physical = fs::metadata(path.join("data.bin")).unwrap().len();
+        assert_eq!(physical, content.len() as u64 + 3 * OVERHEAD);
+        assert_eq!(size(physical).unwrap(), content.len() as u64);
+        assert_eq!(read(&path, Some(&key), "data.bin").unwrap(), content);
+
+        // Đọc một đoạn vắt qua ranh giới hai khối
+        let mut reader = reader(&path, Some(&key), "data.bin").unwrap();
+        reader.seek(CHUNK as u64 - 10).unwrap();
+        let mut range = vec![0; 20];
+        reader.read_exact(&mut range).unwrap();
+        assert_eq!(range, content[CHUNK - 10..CHUNK + 10]);
+        reader.seek(content.len() as u64).unwrap();
+        assert_eq!(reader.read(&mut range).unwrap(), 0);
+
+        // Ghi thêm làm đầy khối cuối rồi sang khối mới
+        let mut expected = content.clone();
+        let more = vec![7; CHUNK];
+        append(&path, Some(&key), "data.bin", &more).unwrap();
+        expected.extend_from_slice(&more);
+        assert_eq!(read(&path, Some(&key), "data.bin").unwrap(), expected);
+
+        // Nội dung rỗng vẫn có một khối; tập tin không mã hoá được đọc nguyên vẹn
+        write(&path, Some(&key), "empty", b"").unwrap();
+        assert_eq!(read(&path, Some(&key), "empty").unwrap(), b"");
+        append(&path, None, "plain.txt", b"Hello").unwrap();
+        assert_eq!(read(&path, None, "plain.txt").unwrap(), b"Hello");
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra việc ghi đè bị dừng giữa chừng được huỷ hoặc hoàn tất khi mở lại
+    #[test]
+    fn test_recover() {
+        let path = tempdir().unwrap();
+        let key = Key::new().unwrap();
+        write(&path, Some(&key), "data.bin", b"old").unwrap();
+        let keys = path.join(KEYS).join("data.bin");
+        let tmp = path.join(TMP);
+
+        // Dừng khi bản mã trong `.tmp` chưa sẵn sàng: giữ nguyên khoá và nội dung cũ
+        fs::create_dir_all(tmp.join(KEYS)).unwrap();
+        fs::write(tmp.join(KEYS).join("data.bin"), b"partial key").unwrap();
+        fs::write(tmp.join("data.bin"), b"partial").unwrap();
+        recover(&path).unwrap();
+        assert_eq!(read(&path, Some(&key), "data.bin").unwrap(), b"old");
+        assert!(!tmp.exists());
+
+        // Đã sẵn sàng, và dừng trước hoặc sau khi đổi tên khoá: hoàn tất với nội dung mới
+        for moved in [false, true] {
+            let data = Key::new().unwrap();
+            fs::create_dir_all(tmp.join(READY)).unwrap();
+            fs::create_dir_all(tmp.join(KEYS)).unwrap();
+            let wrapped = key.wrap("data.bin", &data).unwrap();
+            match moved {
+                true => fs::write(&keys, wrapped).unwrap(),
+                false => fs::write(tmp.join(KEYS).join("data.bin"), wrapped).unwrap(),
+            }
+            let sealed = seal(&data, 0, b"new").unwrap();
+            fs::write(tmp.join(READY).join("data.bin"), sealed).unwrap();
+            recover(&path).unwrap();
+            assert_eq!(read(&path, Some(&key), "data.bin").unwrap(), b"new");
+            assert!(!tmp.exists());
+            write(&path, Some(&key), "data.bin", b"old").unwrap();
+        }
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    // Khai báo một hàm để kiểm tra việc phát hiện nội dung bị sửa, cắt bớt hoặc đổi chỗ
+    #[test]
+    fn test_tamper() {
+        let path = tempdir().unwrap();
+        let key = Key::new().unwrap();
+        let content = content();
+        write(&path, Some(&key), "data.bin", &content).unwrap();
+        let sealed = fs::read(path.join("data.bin")).unwrap();
+        let rejected = |bytes: &[u8]| {
+            fs::write(path.join("data.bin"), bytes).unwrap();
+            read(&path, Some(&key), "data.bin").is_err()
+        };
+
+        // Sửa một byte
+        let mut edited = sealed.clone();
+        edited[SEALED as usize + 40] ^= 1;
+        assert!(rejected(&edited));
+
+        // Cắt bỏ khối cuối (khối còn lại không có cờ khối cuối) hoặc cắt giữa khối
+        assert!(rejected(&sealed[..2 * SEALED as usize]));
+        assert!(rejected(&sealed[..sealed.len() - 5]));
+        assert!(rejected(&sealed[..5]));
+
+        // Đổi chỗ hai khối đầu
+        let block = SEALED as usize;
+        let swapped = [
+            &sealed[block..2 * block],
+            &sealed[..block],
+            &sealed[2 * block..],
+        ]
+        .concat();
+        assert!(rejected(&swapped));
+
+        // Khoá dữ liệu của tập tin khác không mở được
+        write(&path, Some(&key), "other.bin", b"x").unwrap();
+        fs::copy(
+            path.join(KEYS).join("other.bin"),
+            path.join(KEYS).join("data.bin"),
+        )
+        .unwrap();
+        assert!(rejected(&sealed));
+
+        fs::remove_dir_all(path).unwrap();
+    }
+}
@@ -0,0 +1,128 @@
+// Khai báo một struct để ghi tài liệu XML (thụt lề hai dấu cách, tự đóng các thẻ còn mở)
+#[derive(Debug)]
+pub struct Writer {
+    out: String,        // Nội dung tài liệu đã ghi
+    stack: Vec<String>, // Các thẻ đang mở
+}
+
+// Khai báo các phương thức cho struct Writer
+impl Writer {
+    // Phương thức new để tạo một tài liệu mới bắt đầu bằng khai báo XML
+    pub fn new() -> Writer {
+        Writer {
+            out: "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n".to_string(),
+            stack: Vec::new(),
+        }
+    }
+
+    // Phương thức open để mở một thẻ mới với các thuộc tính cho trước
+    pub fn open(&mut self, tag: &str, attrs: &[(&str, &str)]) -> &mut Writer {
+        self.indent();
+        self.out.push('<');
+        self.out.push_str(tag);
+        self.attrs(attrs);
+        self.out.push_str(">\n");
+        self.stack.push(tag.to_string());
+        self
+    }
+
+    // Phương thức leaf để ghi một thẻ chỉ chứa văn bản (ví dụ: <Id>42</Id>)
+    pub fn leaf(&mut self, tag: &str, attrs: &[(&str, &str)], text: &str) -> &mut Writer {
+        self.indent();
+        self.out.push('<');
+        self.out.push_str(tag);
+        self.attrs(attrs);
+        self.out.push('>');
+        self.out.push_str(&escape(text));
+        self.out.push_str("</");
+        self.out.push_str(tag);
+        self.out.push_str(">\n");
+        self
+    }
+
+    // Phương thức close để đóng thẻ đang mở gần nhất
+    pub fn close(&mut self) -> &mut Writer {
+        if let Some(tag) = self.stack.pop() {
+            self.indent();
+            self.out.push_str("</");
+            self.out.push_str(&tag);
+            self.out.push_str(">\n");
+        }
+        self
+    }
+
+    // Phương thức finish để đóng tất cả các thẻ còn mở và trả về tài liệu
+    pub fn finish(mut self) -> String {
+        while !self.stack.is_empty() {
+            self.close();
+        }
+        self.out
+    }
+
+    // Phương thức indent để thụt lề theo độ sâu hiện tại
+    fn indent(&mut self) {
+        for _ in 0..self.stack.len() {
+            self.out.push_str("  ");
+        }
+    }
+
+    // Phương thức attrs để ghi danh sách thuộc tính của thẻ
+    fn attrs(&mut self, attrs: &[(&str, &str)]) {
+        for (key, value) in attrs {
+            self.out
+                .push_str(&format!(" {}=\"{}\"", key, escape(value)));
+        }
+    }
+}
+
+// Khai báo giá trị mặc định cho struct Writer
+impl Default for Writer {
+    fn default() -> Writer {
+        Writer::new()
+    }
+}
+
+// Hàm escape thay thế các ký tự đặc biệt của XML
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// Khai báo một module để chứa các unit test
+#[cfg(test)]
+mod tests {
+    // Nhập các thư viện cần thiết
+    use super::*;
+
+    // Khai báo một hàm để kiểm tra việc ghi tài liệu XML
+    #[test]
+    fn test_writer() {
+        // Ghi một tài liệu có thẻ lồng nhau và ký tự đặc biệt
+        let mut writer = Writer::new();
+        writer
+            .open("Doc", &[("xmlns", "urn:x")])
+            .leaf("Amt", &[("Ccy", "USD")], "1.00")
+            .leaf("Nm", &[], "A & <B>");
+        let xml = writer.finish();
+
+        // Kiểm tra nội dung tài liệu
+        assert_eq!(
+            xml,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <Doc xmlns=\"urn:x\">\n  \
+             <Amt Ccy=\"USD\">1.00</Amt>\n  \
+             <Nm>A &amp; &lt;B&gt;</Nm>\n\
+             </Doc>\n"
+        );
+    }
+}
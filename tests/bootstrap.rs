@@ -0,0 +1,90 @@
+// Khai báo các thư viện cần thiết
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use rim::audit::Audit;
+use rim::auth::session::Sessions;
+use rim::auth::Auth;
+use rim::clock;
+use rim::event::Bus;
+use rim::social::message::Messages;
+use rim::storage::{Access, Storage};
+
+// Khai báo một hàm để tạo đường dẫn tạm thời duy nhất cho một đối tượng lưu trữ
+fn temp(name: &str) -> String {
+    let path: PathBuf = std::env::temp_dir().join(format!("rim-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&path);
+    path.to_str().unwrap().to_string()
+}
+
+// Khai báo một hàm để kiểm tra việc khởi động các dịch vụ chỉ bằng API công khai:
+// dịch vụ mở đối tượng lưu trữ chưa ai đăng nhập, và người dùng đăng nhập bằng phiên do Auth cấp
+#[test]
+fn test_bootstrap() {
+    let bus = Arc::new(Bus::new());
+    let (sessions_path, audit_path) = (temp("sessions"), temp("audit"));
+    let (users_path, files_path) = (temp("users"), temp("files"));
+    let messages_path = temp("messages");
+
+    // Các dịch vụ tự đăng nhập vào đối tượng lưu trữ của mình
+    let storage = Storage::new(&sessions_path, "server", Access::Private).unwrap();
+    let sessions = Arc::new(Sessions::open(storage, bus.clone()).unwrap());
+    let storage = Storage::new(&audit_path, "server", Access::Private).unwrap();
+    let audit = Arc::new(Audit::open(storage, &[5; 32]).unwrap());
+    let storage = Storage::new(&users_path, "server", Access::Private).unwrap();
+    let auth = Auth::open(storage, bus.clone(), sessions)
+        .unwrap()
+        .with_audit(audit.clone());
+
+    // Người dùng đầu tiên là chủ doanh nghiệp và nhận phiên khi đăng nhập
+    let now = clock::now();
+    auth.register(None, "alpha", "an", "correct horse").unwrap();
+    let session = auth.login("alpha", "an", "correct horse", now).unwrap();
+    let principal = auth.session(&session.token, now).unwrap();
+
+    // Người dùng tiếp theo chỉ được thêm bởi người quản lý vai trò của doanh nghiệp
+    assert!(auth
+        .register(None, "alpha", "binh", "battery staple")
+        .is_err());
+    auth.register(Some(&principal), "alpha", "binh", "battery staple")
+        .unwrap();
+
+    // Đối tượng lưu trữ của doanh nghiệp chỉ dùng được sau khi đăng nhập bằng phiên đó
+    let mut files = Storage::new(&files_path, "alpha", Access::Private).unwrap();
+    assert!(files.list().is_err());
+    files.login(&principal).unwrap();
+    files.put("hello.txt", b"Hello").unwrap();
+    assert_eq!(files.list().unwrap(), ["hello.txt"]);
+
+    // Dịch vụ tin nhắn hoạt động với người dùng đã xác thực
+    let storage = Storage::new(&messages_path, "server", Access::Private).unwrap();
+    let messages = Messages::open(storage, bus.clone()).unwrap();
+    let id = messages.direct(&principal, "beta").unwrap();
+    assert_eq!(messages.send(&principal, id, "Hi", &[]).unwrap(), 1);
+
+    // Mở lại dịch vụ phiên và người dùng từ đối tượng lưu trữ mà không cần ai đăng nhập
+    drop(auth);
+    let storage = Storage::open(&sessions_path).unwrap();
+    let sessions = Arc::new(Sessions::open(storage, bus.clone()).unwrap());
+    assert_eq!(sessions.validate(&session.token, now).unwrap(), principal);
+    let storage = Storage::open(&users_path).unwrap();
+    let auth = Auth::open(storage, bus, sessions).unwrap();
+    assert!(auth.login("alpha", "an", "correct horse", now).is_ok());
+    assert_eq!(
+        audit.verify().unwrap(),
+        audit.entries().unwrap().len() as u64
+    );
+
+    // Xoá các thư mục tạm thời
+    drop((auth, audit, messages, files));
+    for path in [
+        sessions_path,
+        audit_path,
+        users_path,
+        files_path,
+        messages_path,
+    ] {
+        fs::remove_dir_all(path).unwrap();
+    }
+}